@@ -0,0 +1,39 @@
+use mmb_domain::order::snapshot::Amount;
+use mmb_utils::DateTime;
+
+use crate::balance::manager::balance_request::BalanceRequest;
+
+/// Balance value changed by a mutation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BalanceAuditTarget {
+    VirtualBalance,
+    ReservedAmount,
+}
+
+/// Operation of balance manager that caused balance mutation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BalanceChangeCause {
+    Reserve,
+    Unreserve,
+    UnreserveCompensation,
+    ReservationPriceUpdate,
+    Transfer,
+    Fill,
+    Commission,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceAuditRecord {
+    pub target: BalanceAuditTarget,
+    pub request: BalanceRequest,
+    pub delta: Amount,
+    pub resulting_value: Amount,
+    pub time: DateTime,
+    pub cause: BalanceChangeCause,
+}
+
+/// Receiver of every virtual balance and reserved amount mutation in order of applying,
+/// so the exact sequence of balance changes can be reconstructed
+pub trait BalanceAuditSink: Send + Sync {
+    fn record(&self, record: BalanceAuditRecord);
+}
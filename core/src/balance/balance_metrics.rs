@@ -0,0 +1,32 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+pub const RESERVED_AMOUNT: &str = "reserved_amount";
+pub const POSITION: &str = "position";
+pub const VIRTUAL_BALANCE_DIFF: &str = "virtual_balance_diff";
+
+/// Labeled value of a gauge, e.g. `reserved_amount{exchange="Binance_0",pair="btc/usdt",currency="btc"} 5`.
+/// Serialization into the format of specific monitoring system is up to exporter
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct MetricSample {
+    pub name: &'static str,
+    pub labels: Vec<(&'static str, String)>,
+    pub value: Decimal,
+}
+
+impl MetricSample {
+    pub fn new(name: &'static str, labels: Vec<(&'static str, String)>, value: Decimal) -> Self {
+        Self {
+            name,
+            labels,
+            value,
+        }
+    }
+
+    pub fn label(&self, label_name: &str) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|(name, _)| *name == label_name)
+            .map(|(_, value)| value.as_str())
+    }
+}
@@ -10,7 +10,10 @@ use mmb_utils::{nothing_to_do, DateTime};
 use mockall_double::double;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use thiserror::Error;
 
+use crate::balance::balance_audit::{BalanceAuditSink, BalanceAuditTarget, BalanceChangeCause};
+use crate::balance::balance_metrics::{self, MetricSample};
 use crate::balance::balance_position_model::BalancePositionModel;
 use crate::balance::manager::approved_part::ApprovedPart;
 use crate::balance::manager::balance_position_by_fill_amount::BalancePositionByFillAmount;
@@ -24,19 +27,79 @@ use crate::balance::{
 };
 use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
 use crate::exchanges::general::exchange::Exchange;
-use crate::explanation::{Explanation, OptionExplanationAddReasonExt};
+use crate::explanation::{Explanation, ExplanationStep, OptionExplanationAddReasonExt};
 use crate::misc::reserve_parameters::ReserveParameters;
 use crate::misc::service_value_tree::ServiceValueTree;
 #[double]
 use crate::misc::time::time_manager;
 use crate::service_configuration::configuration_descriptor::ConfigurationDescriptor;
-use mmb_domain::exchanges::symbol::{BeforeAfter, Symbol};
+use mmb_domain::events::ExchangeBalancesAndPositions;
+use mmb_domain::exchanges::symbol::{BeforeAfter, DerivativeKind, Symbol};
 use mmb_domain::market::{CurrencyCode, CurrencyPair, ExchangeAccountId, MarketAccountId};
 use mmb_domain::order::snapshot::ReservationId;
 use mmb_domain::order::snapshot::{ClientOrderFillId, ClientOrderId, OrderSide};
+use mmb_domain::position::{DerivativePosition, LiquidationDistance};
 
 use super::balance_reservation_preset::BalanceReservationPreset;
 
+#[derive(Debug, Error)]
+pub enum TransferError {
+    #[error("Source reservation {0} is not found")]
+    SourceNotFound(ReservationId),
+    #[error("Destination reservation {0} is not found")]
+    DestNotFound(ReservationId),
+    #[error(
+        "Reservations {src_reservation_id} and {dst_reservation_id} are from different sources"
+    )]
+    IncompatibleReservations {
+        src_reservation_id: ReservationId,
+        dst_reservation_id: ReservationId,
+    },
+    #[error(
+        "Insufficient amount to transfer {amount} from reservation {reservation_id}: {reason}"
+    )]
+    InsufficientBalance {
+        reservation_id: ReservationId,
+        amount: Amount,
+        reason: String,
+    },
+    #[error("Available balance for destination reservation {0} is not found")]
+    AvailableBalanceNotFound(ReservationId),
+}
+
+/// Difference between balance tracked by bot and balance reported by exchange
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BalanceDiscrepancy {
+    pub exchange_account_id: ExchangeAccountId,
+    pub currency_code: CurrencyCode,
+    /// Exchange balance with virtual diffs and not approved reservations
+    pub expected: Amount,
+    pub reported: Amount,
+}
+
+impl BalanceDiscrepancy {
+    pub fn difference(&self) -> Amount {
+        self.reported - self.expected
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BalanceReconciliation {
+    /// Max allowed relative difference between expected and reported balances
+    pub tolerance: Decimal,
+    /// Replace exchange balance in `VirtualBalanceHolder` so that expected balance matches reported one
+    pub correct_exchange_balance: bool,
+}
+
+impl Default for BalanceReconciliation {
+    fn default() -> Self {
+        Self {
+            tolerance: dec!(0.001),
+            correct_exchange_balance: false,
+        }
+    }
+}
+
 pub(super) struct CanReserveResult {
     can_reserve: bool,
     preset: BalanceReservationPreset,
@@ -50,13 +113,19 @@ pub(crate) struct BalanceReservationManager {
     pub currency_pair_to_symbol_converter: Arc<CurrencyPairToSymbolConverter>,
     reserved_amount_in_amount_currency: ServiceValueTree,
     amount_limits_in_amount_currency: ServiceValueTree,
+    /// Absolute position caps which can't be crossed by any reservation, unlike soft amount limits
+    hard_position_caps: HashMap<MarketAccountId, Amount>,
 
     position_by_fill_amount_in_amount_currency: BalancePositionByFillAmount,
+    /// Last derivative positions received from exchanges
+    derivative_positions: HashMap<ExchangeAccountId, Vec<DerivativePosition>>,
 
     pub virtual_balance_holder: VirtualBalanceHolder,
     pub balance_reservation_storage: BalanceReservationStorage,
 
     pub(crate) is_call_from_clone: bool,
+    log_balance_changes: bool,
+    reconciliation: BalanceReconciliation,
 }
 
 impl BalanceReservationManager {
@@ -65,13 +134,119 @@ impl BalanceReservationManager {
             currency_pair_to_symbol_converter: currency_pair_to_symbol_converter.clone(),
             reserved_amount_in_amount_currency: ServiceValueTree::default(),
             amount_limits_in_amount_currency: ServiceValueTree::default(),
+            hard_position_caps: HashMap::new(),
             position_by_fill_amount_in_amount_currency: BalancePositionByFillAmount::default(),
+            derivative_positions: HashMap::new(),
             virtual_balance_holder: VirtualBalanceHolder::new(
                 currency_pair_to_symbol_converter.exchanges_by_id().clone(),
             ),
             balance_reservation_storage: BalanceReservationStorage::new(),
             is_call_from_clone: false,
+            log_balance_changes: true,
+            reconciliation: BalanceReconciliation::default(),
+        }
+    }
+
+    pub fn log_balance_changes(&self) -> bool {
+        self.log_balance_changes
+    }
+
+    /// Enable or disable high-frequency logging of balance changes on reservations and transfers.
+    /// Accounting itself doesn't depend on this flag
+    pub fn set_log_balance_changes(&mut self, log_balance_changes: bool) {
+        self.log_balance_changes = log_balance_changes;
+        self.virtual_balance_holder
+            .set_log_balance_changes(log_balance_changes);
+    }
+
+    /// Set receiver of records about every virtual balance and reserved amount change
+    pub fn set_audit_sink(&mut self, audit_sink: Option<Arc<dyn BalanceAuditSink>>) {
+        self.virtual_balance_holder.set_audit_sink(audit_sink);
+    }
+
+    pub fn set_reconciliation(&mut self, reconciliation: BalanceReconciliation) {
+        self.reconciliation = reconciliation;
+    }
+
+    /// Compare balances tracked for exchange account with balances reported by exchange
+    /// and return ones which differ more than reconciliation tolerance
+    pub fn reconcile_with_exchange(
+        &mut self,
+        exchange_account_id: ExchangeAccountId,
+        event: &ExchangeBalancesAndPositions,
+    ) -> Vec<BalanceDiscrepancy> {
+        let balance_diffs = self
+            .virtual_balance_holder
+            .get_virtual_balance_diffs()
+            .get_as_balances();
+        let reservations = self
+            .balance_reservation_storage
+            .get_all_raw_reservations()
+            .values()
+            .filter(|x| x.exchange_account_id == exchange_account_id)
+            .collect_vec();
+
+        let mut discrepancies = Vec::new();
+        for exchange_balance in &event.balances {
+            let currency_code = exchange_balance.currency_code;
+            let exchange_balance_in_holder = match self
+                .virtual_balance_holder
+                .get_raw_exchange_balances()
+                .get(&exchange_account_id)
+                .and_then(|x| x.get(&currency_code))
+            {
+                Some(balance) => *balance,
+                // balance of currency isn't tracked, so there is nothing to compare with
+                None => continue,
+            };
+
+            let balance_diff: Amount = balance_diffs
+                .iter()
+                .filter(|(request, _)| {
+                    request.exchange_account_id == exchange_account_id
+                        && request.currency_code == currency_code
+                })
+                .map(|(_, diff)| *diff)
+                .sum();
+
+            let not_approved_reserved: Amount = reservations
+                .iter()
+                .filter(|x| x.reservation_currency_code == currency_code)
+                .map(|x| {
+                    let not_approved_amount_cost = x
+                        .get_proportional_cost_amount(x.not_approved_amount)
+                        .with_expect(|| format!("failed to get cost of reservation {x:?}"));
+                    x.convert_in_reservation_currency(not_approved_amount_cost)
+                })
+                .sum();
+
+            let discrepancy = BalanceDiscrepancy {
+                exchange_account_id,
+                currency_code,
+                expected: exchange_balance_in_holder + balance_diff + not_approved_reserved,
+                reported: exchange_balance.balance,
+            };
+
+            let max_difference = self.reconciliation.tolerance
+                * discrepancy.expected.abs().max(discrepancy.reported.abs());
+            if discrepancy.difference().abs() <= max_difference {
+                continue;
+            }
+
+            log::warn!("Balance discrepancy found: {discrepancy:?}");
+
+            if self.reconciliation.correct_exchange_balance {
+                self.virtual_balance_holder.add_exchange_balance(
+                    exchange_account_id,
+                    currency_code,
+                    discrepancy.difference(),
+                );
+            }
+
+            discrepancies.push(discrepancy);
         }
+
+        discrepancies
     }
 
     pub fn exchanges_by_id(&self) -> &HashMap<ExchangeAccountId, Arc<Exchange>> {
@@ -90,6 +265,13 @@ impl BalanceReservationManager {
         self.sync_reservation_amounts();
     }
 
+    /// Virtual balance diffs reflect fills not yet settled in exchange balances,
+    /// so they should be restored together with reservations
+    pub fn restore_virtual_balance_diffs(&mut self, diffs: &ServiceValueTree) {
+        self.virtual_balance_holder
+            .set_virtual_balance_diffs(diffs.clone());
+    }
+
     pub fn sync_reservation_amounts(&mut self) {
         fn make_balance_request(reservation: &BalanceReservation) -> BalanceRequest {
             BalanceRequest::new(
@@ -206,17 +388,27 @@ impl BalanceReservationManager {
 
         let old_balance = self.get_available_balance(&balance_params, true, &mut None);
 
-        log::info!("VirtualBalanceHolder {}", old_balance);
+        if self.log_balance_changes {
+            tracing::info!("VirtualBalanceHolder {}", old_balance);
+        }
 
         self.unreserve_not_approved_part(reservation_id, client_or_order_id, amount_to_unreserve)
             .context("failed unreserve not approved part")?;
 
         let reservation = self.get_reservation_expected(reservation_id);
         let balance_request = BalanceRequest::from_reservation(reservation);
-        self.add_reserved_amount(&balance_request, reservation_id, -amount_to_unreserve, true)?;
+        self.add_reserved_amount(
+            &balance_request,
+            reservation_id,
+            -amount_to_unreserve,
+            true,
+            BalanceChangeCause::Unreserve,
+        )?;
 
         let new_balance = self.get_available_balance(&balance_params, true, &mut None);
-        log::info!("VirtualBalanceHolder {}", new_balance);
+        if self.log_balance_changes {
+            tracing::info!("VirtualBalanceHolder {}", new_balance);
+        }
 
         let mut reservation = self.get_reservation_expected(reservation_id).clone();
         if reservation.unreserved_amount < dec!(0)
@@ -253,6 +445,7 @@ impl BalanceReservationManager {
                     &mut self.reserved_amount_in_amount_currency,
                     amount_diff_in_amount_currency,
                     true,
+                    BalanceChangeCause::UnreserveCompensation,
                 )?;
             }
 
@@ -357,27 +550,127 @@ impl BalanceReservationManager {
         explanation: &mut Option<Explanation>,
     ) -> Option<Amount> {
         let currency_code = symbol.get_trade_code(side, BeforeAfter::Before);
+        let balance_in_currency_code = self.get_raw_virtual_balance(
+            configuration_descriptor,
+            exchange_account_id,
+            symbol.clone(),
+            currency_code,
+            price,
+            explanation,
+        );
+
+        self.apply_available_balance_adjustments(
+            configuration_descriptor,
+            exchange_account_id,
+            symbol,
+            side,
+            price,
+            balance_in_currency_code,
+            include_free_amount,
+            is_leveraged,
+            explanation,
+        )
+    }
+
+    /// Available balances for buy and sell sides respectively.
+    /// Virtual balance is requested once if both sides are traded in the same currency
+    pub fn try_get_available_balance_both_sides(
+        &self,
+        configuration_descriptor: ConfigurationDescriptor,
+        exchange_account_id: ExchangeAccountId,
+        symbol: Arc<Symbol>,
+        price: Price,
+    ) -> (Option<Amount>, Option<Amount>) {
+        let buy_currency_code = symbol.get_trade_code(OrderSide::Buy, BeforeAfter::Before);
+        let sell_currency_code = symbol.get_trade_code(OrderSide::Sell, BeforeAfter::Before);
+
+        let get_raw_balance = |currency_code| {
+            self.get_raw_virtual_balance(
+                configuration_descriptor,
+                exchange_account_id,
+                symbol.clone(),
+                currency_code,
+                price,
+                &mut None,
+            )
+        };
+        let buy_raw_balance = get_raw_balance(buy_currency_code);
+        let sell_raw_balance = if sell_currency_code == buy_currency_code {
+            buy_raw_balance
+        } else {
+            get_raw_balance(sell_currency_code)
+        };
+
+        let get_available_balance = |side, raw_balance| {
+            self.apply_available_balance_adjustments(
+                configuration_descriptor,
+                exchange_account_id,
+                symbol.clone(),
+                side,
+                price,
+                raw_balance,
+                true,
+                false,
+                &mut None,
+            )
+        };
+
+        (
+            get_available_balance(OrderSide::Buy, buy_raw_balance),
+            get_available_balance(OrderSide::Sell, sell_raw_balance),
+        )
+    }
+
+    fn get_raw_virtual_balance(
+        &self,
+        configuration_descriptor: ConfigurationDescriptor,
+        exchange_account_id: ExchangeAccountId,
+        symbol: Arc<Symbol>,
+        currency_code: CurrencyCode,
+        price: Price,
+        explanation: &mut Option<Explanation>,
+    ) -> Option<Amount> {
         let request = BalanceRequest::new(
             configuration_descriptor,
             exchange_account_id,
             symbol.currency_pair(),
             currency_code,
         );
-        let balance_in_currency_code = self.virtual_balance_holder.get_virtual_balance(
-            &request,
-            symbol.clone(),
-            Some(price),
-            explanation,
+        self.virtual_balance_holder
+            .get_virtual_balance(&request, symbol, Some(price), explanation)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn apply_available_balance_adjustments(
+        &self,
+        configuration_descriptor: ConfigurationDescriptor,
+        exchange_account_id: ExchangeAccountId,
+        symbol: Arc<Symbol>,
+        side: OrderSide,
+        price: Price,
+        balance_in_currency_code: Option<Amount>,
+        include_free_amount: bool,
+        is_leveraged: bool,
+        explanation: &mut Option<Explanation>,
+    ) -> Option<Amount> {
+        let currency_code = symbol.get_trade_code(side, BeforeAfter::Before);
+        let request = BalanceRequest::new(
+            configuration_descriptor,
+            exchange_account_id,
+            symbol.currency_pair(),
+            currency_code,
         );
 
         explanation
             .with_reason(|| format!("balance_in_currency_code_raw = {balance_in_currency_code:?}"));
 
         let mut balance_in_currency_code = balance_in_currency_code?;
+        explanation.add_step(ExplanationStep::RawBalance(balance_in_currency_code));
 
         let leverage = self.get_leverage(exchange_account_id, symbol.currency_pair());
 
         explanation.with_reason(|| format!("leverage = {leverage:?}"));
+        explanation.add_step(ExplanationStep::Leverage(leverage));
 
         if symbol.is_derivative {
             if include_free_amount {
@@ -396,8 +689,8 @@ impl BalanceReservationManager {
                         free_amount_in_amount_currency_code,
                         price,
                     );
-                free_amount_in_currency_code /= leverage;
-                free_amount_in_currency_code *= symbol.amount_multiplier;
+                free_amount_in_currency_code =
+                    symbol.position_to_margin(free_amount_in_currency_code, leverage);
 
                 explanation.with_reason(|| {
                     format!(
@@ -405,6 +698,7 @@ impl BalanceReservationManager {
                         free_amount_in_currency_code
                     )
                 });
+                explanation.add_step(ExplanationStep::FreeAmount(free_amount_in_currency_code));
 
                 balance_in_currency_code += free_amount_in_currency_code;
 
@@ -427,6 +721,7 @@ impl BalanceReservationManager {
                     balance_in_currency_code
                 )
             });
+            explanation.add_step(ExplanationStep::AfterUntouchable(balance_in_currency_code));
         }
         if self
             .amount_limits_in_amount_currency
@@ -442,6 +737,7 @@ impl BalanceReservationManager {
                 leverage,
                 explanation,
             );
+            explanation.add_step(ExplanationStep::AfterLimit(balance_in_currency_code));
         }
 
         explanation.with_reason(|| {
@@ -450,8 +746,8 @@ impl BalanceReservationManager {
 
         // isLeveraged is used when we need to know how much funds we can use for orders
         if is_leveraged {
-            balance_in_currency_code *= leverage;
-            balance_in_currency_code /= symbol.amount_multiplier;
+            balance_in_currency_code =
+                symbol.margin_to_position(balance_in_currency_code, leverage);
 
             explanation.with_reason(|| format!("balance_in_currency_code with leverage and multiplier: {balance_in_currency_code}"));
         }
@@ -549,8 +845,7 @@ impl BalanceReservationManager {
         });
 
         //AmountLimit is applied to full amount
-        balance_in_currency_code *= leverage;
-        balance_in_currency_code /= symbol.amount_multiplier;
+        balance_in_currency_code = symbol.margin_to_position(balance_in_currency_code, leverage);
         explanation.with_reason(|| {
             format!(
                 "balance_in_currency_code with leverage and multiplier: {balance_in_currency_code}"
@@ -582,8 +877,8 @@ impl BalanceReservationManager {
         });
 
         //converting back to pure balance
-        limited_balance_in_currency_code /= leverage;
-        limited_balance_in_currency_code *= symbol.amount_multiplier;
+        limited_balance_in_currency_code =
+            symbol.position_to_margin(limited_balance_in_currency_code, leverage);
         explanation.with_reason(|| {
             format!("limited_balance_in_currency_code without leverage and multiplier: {limited_balance_in_currency_code}")
         });
@@ -609,14 +904,25 @@ impl BalanceReservationManager {
         exchange_account_id: ExchangeAccountId,
         currency_pair: CurrencyPair,
     ) -> Decimal {
-        *self
-            .exchanges_by_id()
+        self.try_get_leverage(exchange_account_id, currency_pair)
+            .expect("failed to get leverage")
+    }
+
+    /// Leverage can be unknown yet for a derivative pair if fill is received before leverage is fetched
+    pub fn try_get_leverage(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+    ) -> Result<Decimal> {
+        self.exchanges_by_id()
             .get(&exchange_account_id)
-            .with_expect(|| format!("failed to get exchange {exchange_account_id}"))
+            .with_context(|| format!("failed to get exchange {exchange_account_id}"))?
             .leverage_by_currency_pair
             .get(&currency_pair)
-            .as_deref()
-            .expect("failed to get leverage")
+            .map(|leverage| *leverage)
+            .with_context(|| {
+                format!("Leverage for {currency_pair} on {exchange_account_id} is unknown yet")
+            })
     }
 
     fn get_position_values(
@@ -637,7 +943,7 @@ impl BalanceReservationManager {
             .amount_limits_in_amount_currency
             .get_by_balance_request(&request);
 
-        let position = self.get_position(exchange_account_id, symbol.currency_pair(), side);
+        let position = self.position_by_symbol(exchange_account_id, &symbol, side);
 
         BalancePositionModel {
             position,
@@ -645,20 +951,32 @@ impl BalanceReservationManager {
         }
     }
 
+    /// Returns an error if position can't be determined because symbol for `currency_pair` is unknown.
+    /// It shouldn't be treated as zero position, because callers would underestimate the risk
     pub fn get_position(
         &self,
         exchange_account_id: ExchangeAccountId,
         currency_pair: CurrencyPair,
         side: OrderSide,
-    ) -> Decimal {
+    ) -> Result<Decimal> {
         let symbol = self
             .currency_pair_to_symbol_converter
-            .get_symbol(exchange_account_id, currency_pair);
+            .try_get_symbol(exchange_account_id, currency_pair)
+            .context("Unable to determine position")?;
 
+        Ok(self.position_by_symbol(exchange_account_id, &symbol, side))
+    }
+
+    fn position_by_symbol(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+        symbol: &Symbol,
+        side: OrderSide,
+    ) -> Decimal {
         let currency_code = symbol.get_trade_code(side, BeforeAfter::Before);
         let mut position_in_amount_currency = self
             .position_by_fill_amount_in_amount_currency
-            .get(exchange_account_id, currency_pair)
+            .get(exchange_account_id, symbol.currency_pair())
             .unwrap_or(dec!(0));
 
         match (
@@ -722,32 +1040,62 @@ impl BalanceReservationManager {
         reserved_amount_in_amount_currency: &mut ServiceValueTree,
         amount_diff_in_amount_currency: Amount,
         update_balance: bool,
+        cause: BalanceChangeCause,
     ) -> Result<()> {
-        if update_balance {
+        // global reservation indicator
+        let res_amount_request = BalanceRequest::new(
+            request.configuration_descriptor,
+            request.exchange_account_id,
+            request.currency_pair,
+            reservation.reservation_currency_code,
+        );
+
+        // all amounts are validated before any mutation so balances aren't changed partially on overflow
+        let new_unreserved_amount = reservation
+            .unreserved_amount
+            .checked_add(amount_diff_in_amount_currency)
+            .with_context(|| format!("Overflow on adding {amount_diff_in_amount_currency} to unreserved amount of {reservation:?}"))?;
+        let reserved_amount = reserved_amount_in_amount_currency
+            .get_by_balance_request(&res_amount_request)
+            .unwrap_or_default();
+        reserved_amount
+            .checked_add(amount_diff_in_amount_currency)
+            .with_context(|| format!("Overflow on adding {amount_diff_in_amount_currency} to reserved amount {reserved_amount} for {res_amount_request:?}"))?;
+        let cost = if update_balance {
             let cost = reservation
                 .get_proportional_cost_amount(amount_diff_in_amount_currency)
                 .with_context(|| format!("Failed to get proportional cost amount form {reservation:?} with {amount_diff_in_amount_currency}"))?;
+            Some(cost)
+        } else {
+            None
+        };
 
+        if let Some(cost) = cost {
             virtual_balance_holder.add_balance_by_symbol(
                 request,
                 reservation.symbol.clone(),
                 -cost,
                 reservation.price,
+                cause,
             );
         }
 
-        reservation.unreserved_amount += amount_diff_in_amount_currency;
+        reservation.unreserved_amount = new_unreserved_amount;
 
-        // global reservation indicator
-        let res_amount_request = BalanceRequest::new(
-            request.configuration_descriptor,
-            request.exchange_account_id,
-            request.currency_pair,
-            reservation.reservation_currency_code,
+        reserved_amount_in_amount_currency
+            .add_by_request(&res_amount_request, amount_diff_in_amount_currency)
+            .context("Failed to update reserved amount")?;
+
+        virtual_balance_holder.audit(
+            BalanceAuditTarget::ReservedAmount,
+            &res_amount_request,
+            amount_diff_in_amount_currency,
+            reserved_amount_in_amount_currency
+                .get_by_balance_request(&res_amount_request)
+                .unwrap_or_default(),
+            cause,
         );
 
-        reserved_amount_in_amount_currency
-            .add_by_request(&res_amount_request, amount_diff_in_amount_currency);
         Ok(())
     }
 
@@ -757,6 +1105,7 @@ impl BalanceReservationManager {
         reservation_id: ReservationId,
         amount_diff_in_amount_currency: Amount,
         update_balance: bool,
+        cause: BalanceChangeCause,
     ) -> Result<()> {
         BalanceReservationManager::add_reserved_amount_by_reservation(
             balance_request,
@@ -766,6 +1115,7 @@ impl BalanceReservationManager {
             &mut self.reserved_amount_in_amount_currency,
             amount_diff_in_amount_currency,
             update_balance,
+            cause,
         )
     }
 
@@ -775,12 +1125,14 @@ impl BalanceReservationManager {
         reservation_id: ReservationId,
         amount_diff_in_amount_currency: Amount,
         update_balance: bool,
+        cause: BalanceChangeCause,
     ) {
         self.add_reserved_amount(
             balance_request,
             reservation_id,
             amount_diff_in_amount_currency,
             update_balance,
+            cause,
         )
         .with_expect(|| format!("failed to add reserved amount {balance_request:?} {reservation_id} {amount_diff_in_amount_currency}"));
     }
@@ -829,6 +1181,123 @@ impl BalanceReservationManager {
         Ok(())
     }
 
+    pub fn update_derivative_positions(
+        &mut self,
+        exchange_account_id: ExchangeAccountId,
+        positions: &[DerivativePosition],
+    ) {
+        let _ = self
+            .derivative_positions
+            .insert(exchange_account_id, positions.to_vec());
+    }
+
+    /// Sum of unrealized PnL over all derivative positions on exchange. PnL of each position is
+    /// calculated according to kind of its symbol: in quote currency for linear contracts and
+    /// in base currency for inverse ones.
+    /// Positions for which `mark_lookup` returns no price or symbol isn't found are skipped
+    pub fn total_unrealized_pnl(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+        mark_lookup: impl Fn(&CurrencyPair) -> Option<Price>,
+    ) -> Amount {
+        self.derivative_positions
+            .get(&exchange_account_id)
+            .map(|positions| {
+                positions
+                    .iter()
+                    .filter_map(|position| {
+                        let mark_price = mark_lookup(&position.currency_pair)?;
+                        let symbol = self
+                            .currency_pair_to_symbol_converter
+                            .try_get_symbol(exchange_account_id, position.currency_pair)
+                            .ok()?;
+                        Some(position.unrealized_pnl(mark_price, symbol.is_inverse()))
+                    })
+                    .sum()
+            })
+            .unwrap_or(dec!(0))
+    }
+
+    /// Local estimate of the adverse mark price move which liquidates derivative position on `currency_pair`.
+    /// Equity is exchange balance with unrealized PnL of the position in margin currency: quote currency
+    /// for linear contracts and base currency for inverse ones. It's considered exhausted when only
+    /// untouchable part of position margin (notional divided by leverage) is left.
+    /// Complements liquidation price received from exchange.
+    /// Returns `None` if there is no position, balance is unknown or short inverse position
+    /// can't be liquidated by any price
+    pub fn liquidation_distance(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+        mark_price: Price,
+    ) -> Option<LiquidationDistance> {
+        if mark_price <= dec!(0) {
+            return None;
+        }
+
+        let position = self
+            .derivative_positions
+            .get(&exchange_account_id)?
+            .iter()
+            .find(|x| x.currency_pair == currency_pair && !x.position.is_zero())?;
+
+        let symbol = self
+            .currency_pair_to_symbol_converter
+            .try_get_symbol(exchange_account_id, currency_pair)
+            .ok()?;
+        let is_inverse = symbol.is_inverse();
+        let margin_currency_code = match is_inverse {
+            true => symbol.base_currency_code,
+            false => symbol.quote_currency_code,
+        };
+        let balance = self.virtual_balance_holder.get_exchange_balance(
+            exchange_account_id,
+            symbol.clone(),
+            margin_currency_code,
+            Some(mark_price),
+        )?;
+
+        let equity = balance + position.unrealized_pnl(mark_price, is_inverse);
+        let position_amount = position.position.abs();
+        let margin = match is_inverse {
+            true => position_amount / mark_price / position.leverage,
+            false => position_amount * mark_price / position.leverage,
+        };
+        let maintenance_margin = Self::get_untouchable_amount(symbol, margin);
+        let free_equity = dec!(0).max(equity - maintenance_margin);
+
+        let (price_distance, liquidation_price) = match is_inverse {
+            false => {
+                let price_distance = free_equity / position_amount;
+                let liquidation_price = match position.get_side() {
+                    OrderSide::Buy => dec!(0).max(mark_price - price_distance),
+                    OrderSide::Sell => mark_price + price_distance,
+                };
+                (price_distance, liquidation_price)
+            }
+            true => {
+                // PnL of inverse position changes by `position * (1 / mark_price - 1 / price)`
+                let inverse_price_distance = free_equity / position_amount;
+                let inverse_liquidation_price = match position.get_side() {
+                    OrderSide::Buy => Decimal::ONE / mark_price + inverse_price_distance,
+                    OrderSide::Sell => Decimal::ONE / mark_price - inverse_price_distance,
+                };
+                if inverse_liquidation_price <= dec!(0) {
+                    return None;
+                }
+
+                let liquidation_price = Decimal::ONE / inverse_liquidation_price;
+                ((liquidation_price - mark_price).abs(), liquidation_price)
+            }
+        };
+
+        Some(LiquidationDistance {
+            price_distance,
+            percent: price_distance / mark_price * dec!(100),
+            liquidation_price,
+        })
+    }
+
     pub fn get_last_position_change_before_period(
         &self,
         market_account_id: &MarketAccountId,
@@ -838,6 +1307,204 @@ impl BalanceReservationManager {
             .get_last_position_change_before_period(market_account_id, start_of_period)
     }
 
+    pub fn get_position_changes_in_period(
+        &self,
+        market_account_id: &MarketAccountId,
+        from: DateTime,
+        to: DateTime,
+    ) -> Vec<PositionChange> {
+        self.position_by_fill_amount_in_amount_currency
+            .get_position_changes_in_period(market_account_id, from, to)
+    }
+
+    /// Current reserved amounts, positions and virtual balance diffs as labeled gauges for monitoring.
+    /// Values of different configuration descriptors are summed up
+    pub fn collect_metrics(&self) -> Vec<MetricSample> {
+        let mut reserved_amounts = HashMap::new();
+        for (request, amount) in self.reserved_amount_in_amount_currency.get_as_balances() {
+            *reserved_amounts
+                .entry((
+                    request.exchange_account_id,
+                    request.currency_pair,
+                    request.currency_code,
+                ))
+                .or_insert(dec!(0)) += amount;
+        }
+
+        let mut balance_diffs = HashMap::new();
+        for (request, amount) in self
+            .virtual_balance_holder
+            .get_virtual_balance_diffs()
+            .get_as_balances()
+        {
+            *balance_diffs
+                .entry((request.exchange_account_id, request.currency_code))
+                .or_insert(dec!(0)) += amount;
+        }
+
+        let reserved_amount_samples = reserved_amounts.into_iter().map(
+            |((exchange_account_id, currency_pair, currency_code), amount)| {
+                MetricSample::new(
+                    balance_metrics::RESERVED_AMOUNT,
+                    vec![
+                        ("exchange", exchange_account_id.to_string()),
+                        ("pair", currency_pair.to_string()),
+                        ("currency", currency_code.to_string()),
+                    ],
+                    amount,
+                )
+            },
+        );
+
+        let position_samples = self
+            .position_by_fill_amount_in_amount_currency
+            .get_all()
+            .iter()
+            .map(|(market_account_id, position)| {
+                MetricSample::new(
+                    balance_metrics::POSITION,
+                    vec![
+                        (
+                            "exchange",
+                            market_account_id.exchange_account_id.to_string(),
+                        ),
+                        ("pair", market_account_id.currency_pair.to_string()),
+                    ],
+                    *position,
+                )
+            });
+
+        let balance_diff_samples =
+            balance_diffs
+                .into_iter()
+                .map(|((exchange_account_id, currency_code), amount)| {
+                    MetricSample::new(
+                        balance_metrics::VIRTUAL_BALANCE_DIFF,
+                        vec![
+                            ("exchange", exchange_account_id.to_string()),
+                            ("currency", currency_code.to_string()),
+                        ],
+                        amount,
+                    )
+                });
+
+        reserved_amount_samples
+            .chain(position_samples)
+            .chain(balance_diff_samples)
+            .sorted()
+            .collect_vec()
+    }
+
+    pub fn position_flip_count(
+        &self,
+        market_account_id: &MarketAccountId,
+        window: chrono::Duration,
+    ) -> usize {
+        self.position_by_fill_amount_in_amount_currency
+            .position_flip_count(market_account_id, time_manager::now() - window)
+    }
+
+    /// Reservations that expire within `window` from now (already expired ones included),
+    /// so they can be extended before being swept
+    pub fn reservations_expiring_within(&self, window: chrono::Duration) -> Vec<ReservationId> {
+        let deadline = time_manager::now() + window;
+        self.balance_reservation_storage
+            .get_all_raw_reservations()
+            .iter()
+            .filter(|(_, reservation)| {
+                reservation
+                    .expires_at
+                    .is_some_and(|expires_at| expires_at <= deadline)
+            })
+            .map(|(reservation_id, _)| *reservation_id)
+            .sorted()
+            .collect_vec()
+    }
+
+    /// Unreserve rest of expired reservations. Reservations with approved parts are kept
+    /// because their orders are still working. Returns ids of unreserved reservations
+    pub fn unreserve_expired(&mut self) -> Result<Vec<ReservationId>> {
+        let now = time_manager::now();
+        let expired = self
+            .balance_reservation_storage
+            .get_all_raw_reservations()
+            .iter()
+            .filter(|(_, reservation)| {
+                reservation.approved_parts.is_empty()
+                    && reservation
+                        .expires_at
+                        .is_some_and(|expires_at| expires_at <= now)
+            })
+            .map(|(reservation_id, reservation)| (*reservation_id, reservation.unreserved_amount))
+            .sorted()
+            .collect_vec();
+
+        for &(reservation_id, amount) in &expired {
+            log::info!("Unreserving expired reservation {reservation_id}");
+            self.unreserve(reservation_id, amount, &None)?;
+        }
+
+        Ok(expired
+            .into_iter()
+            .map(|(reservation_id, _)| reservation_id)
+            .collect_vec())
+    }
+
+    /// Merge reservations with the same balance request, side, price and expiration time into
+    /// the one with the smallest id, so repeated identical reservations don't fragment the storage.
+    /// Reservations with approved parts are skipped because their ids are already referenced by orders.
+    /// Ids of merged reservations become invalid. Returns count of removed reservations
+    pub fn compact_reservations(&mut self) -> usize {
+        let groups = self
+            .balance_reservation_storage
+            .get_all_raw_reservations()
+            .iter()
+            .filter(|(_, reservation)| reservation.approved_parts.is_empty())
+            .map(|(&reservation_id, reservation)| {
+                let request = BalanceRequest::new(
+                    reservation.configuration_descriptor,
+                    reservation.exchange_account_id,
+                    reservation.symbol.currency_pair(),
+                    reservation.reservation_currency_code,
+                );
+                let key = (
+                    request,
+                    reservation.order_side,
+                    reservation.price,
+                    reservation.expires_at,
+                );
+                (key, reservation_id)
+            })
+            .into_group_map();
+
+        let mut merged_count = 0;
+        for reservation_ids in groups.into_values() {
+            let mut reservation_ids = reservation_ids.into_iter().sorted();
+            let target_id = match reservation_ids.next() {
+                Some(target_id) => target_id,
+                None => continue,
+            };
+
+            for reservation_id in reservation_ids {
+                let merged = self
+                    .balance_reservation_storage
+                    .get_expected(reservation_id)
+                    .clone();
+                let target = self.balance_reservation_storage.get_mut_expected(target_id);
+                target.amount += merged.amount;
+                target.taken_free_amount += merged.taken_free_amount;
+                target.cost += merged.cost;
+                target.unreserved_amount += merged.unreserved_amount;
+                target.not_approved_amount += merged.not_approved_amount;
+
+                self.balance_reservation_storage.remove(reservation_id);
+                merged_count += 1;
+            }
+        }
+
+        merged_count
+    }
+
     pub fn get_fill_amount_position_percent(
         &self,
         configuration_descriptor: ConfigurationDescriptor,
@@ -855,6 +1522,23 @@ impl BalanceReservationManager {
         dec!(1).min(dec!(0).max(position.position / limit))
     }
 
+    /// Same as `get_fill_amount_position_percent` but keeps the sign of the position,
+    /// so the result is in [-1, 1]. Returns `None` if the limit is not set or equals zero
+    pub fn get_signed_fill_amount_position_percent(
+        &self,
+        configuration_descriptor: ConfigurationDescriptor,
+        exchange_account_id: ExchangeAccountId,
+        symbol: Arc<Symbol>,
+        side: OrderSide,
+    ) -> Option<Decimal> {
+        let position =
+            self.get_position_values(configuration_descriptor, exchange_account_id, symbol, side);
+
+        let limit = position.limit.filter(|limit| !limit.is_zero())?;
+
+        Some((position.position / limit).clamp(dec!(-1), dec!(1)))
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn handle_position_fill_amount_change(
         &mut self,
@@ -866,7 +1550,13 @@ impl BalanceReservationManager {
         configuration_descriptor: ConfigurationDescriptor,
         exchange_account_id: ExchangeAccountId,
         symbol: Arc<Symbol>,
-    ) -> (Amount, CurrencyCode) {
+    ) -> Result<(Amount, CurrencyCode)> {
+        // leverage should be checked before any balance change to not apply fill partially
+        let leverage = match symbol.is_derivative {
+            true => Some(self.try_get_leverage(exchange_account_id, symbol.currency_pair())?),
+            false => None,
+        };
+
         let mut change_amount_in_currency = dec!(0);
 
         let currency_code = symbol.get_trade_code(side, before_after);
@@ -883,6 +1573,7 @@ impl BalanceReservationManager {
                 symbol.clone(),
                 -fill_amount,
                 price,
+                BalanceChangeCause::Fill,
             );
 
             change_amount_in_currency =
@@ -903,14 +1594,15 @@ impl BalanceReservationManager {
                     (free_amount, (free_amount - move_amount).abs())
                 };
 
-                let leverage = self.get_leverage(exchange_account_id, symbol.currency_pair());
+                let leverage = leverage.expect("leverage should be known for derivative");
                 let diff_in_amount_currency =
-                    (add_amount - sub_amount) / leverage * symbol.amount_multiplier;
+                    symbol.position_to_margin(add_amount - sub_amount, leverage);
                 self.virtual_balance_holder.add_balance_by_symbol(
                     &request,
                     symbol.clone(),
                     diff_in_amount_currency,
                     price,
+                    BalanceChangeCause::Fill,
                 );
 
                 change_amount_in_currency = symbol.convert_amount_from_amount_currency_code(
@@ -919,8 +1611,7 @@ impl BalanceReservationManager {
                     price,
                 );
 
-                // reversed derivative
-                if symbol.amount_currency_code == symbol.base_currency_code() {
+                if symbol.derivative_kind == Some(DerivativeKind::Linear) {
                     position_change.inverse_sign();
                 }
             }
@@ -934,7 +1625,7 @@ impl BalanceReservationManager {
             );
             self.validate_position_and_limits(&request);
         }
-        (change_amount_in_currency, currency_code)
+        Ok((change_amount_in_currency, currency_code))
     }
 
     fn validate_position_and_limits(&self, request: &BalanceRequest) {
@@ -1011,8 +1702,8 @@ impl BalanceReservationManager {
         configuration_descriptor: ConfigurationDescriptor,
         exchange_account_id: ExchangeAccountId,
         symbol: Arc<Symbol>,
-    ) {
-        let leverage = self.get_leverage(exchange_account_id, symbol.currency_pair());
+    ) -> Result<()> {
+        let leverage = self.try_get_leverage(exchange_account_id, symbol.currency_pair())?;
         if !symbol.is_derivative || symbol.balance_currency_code == Some(commission_currency_code) {
             let request = BalanceRequest::new(
                 configuration_descriptor,
@@ -1021,8 +1712,11 @@ impl BalanceReservationManager {
                 commission_currency_code,
             );
             let res_commission_amount = commission_amount / leverage;
-            self.virtual_balance_holder
-                .add_balance(&request, -res_commission_amount);
+            self.virtual_balance_holder.add_balance(
+                &request,
+                -res_commission_amount,
+                BalanceChangeCause::Commission,
+            );
         } else {
             let request = BalanceRequest::new(
                 configuration_descriptor,
@@ -1041,8 +1735,11 @@ impl BalanceReservationManager {
                 symbol,
                 -res_commission_amount_in_amount_currency,
                 price,
+                BalanceChangeCause::Commission,
             );
         }
+
+        Ok(())
     }
 
     pub fn approve_reservation(
@@ -1088,33 +1785,65 @@ impl BalanceReservationManager {
         Ok(())
     }
 
+    /// Move `amount` from source reservation to destination one.
+    /// If source and destination are the same reservation, `amount` of approved part of `client_order_id`
+    /// is returned to not approved amount of the reservation, so it can be approved by another order.
+    /// Return `Ok(false)` if the transfer is declined: there is nothing to transfer or there won't be
+    /// enough balance for destination reservation with another price on derivative market.
+    /// Return `Err` on internal errors: one of reservations was already removed, reservations are
+    /// for different exchange accounts, symbols or sides, source reservation doesn't have `amount`
+    /// or available balance for destination reservation with another price on derivative market
+    /// can't be calculated
     pub fn try_transfer_reservation(
         &mut self,
         src_reservation_id: ReservationId,
         dst_reservation_id: ReservationId,
         amount: Amount,
         client_order_id: &Option<ClientOrderId>,
-    ) -> bool {
-        let src_reservation = self.get_reservation_expected(src_reservation_id);
+    ) -> Result<bool, TransferError> {
+        let src_reservation = self
+            .get_reservation(src_reservation_id)
+            .ok_or(TransferError::SourceNotFound(src_reservation_id))?;
 
-        let dst_reservation = self.get_reservation_expected(dst_reservation_id);
+        let dst_reservation = self
+            .get_reservation(dst_reservation_id)
+            .ok_or(TransferError::DestNotFound(dst_reservation_id))?;
 
         if src_reservation.configuration_descriptor != dst_reservation.configuration_descriptor
             || src_reservation.exchange_account_id != dst_reservation.exchange_account_id
             || src_reservation.symbol != dst_reservation.symbol
             || src_reservation.order_side != dst_reservation.order_side
         {
-            panic!("Reservations {src_reservation:?} and {dst_reservation:?} are from different sources");
+            log::error!("Reservations {src_reservation:?} and {dst_reservation:?} are from different sources");
+            return Err(TransferError::IncompatibleReservations {
+                src_reservation_id,
+                dst_reservation_id,
+            });
         }
 
         let amount_to_move = src_reservation
             .symbol
-            .round_to_remove_amount_precision_error_expected(amount);
+            .round_to_remove_amount_precision_error(amount);
         if amount_to_move.is_zero() {
             log::warn!(
                 "Can't transfer zero amount from {src_reservation_id} to {dst_reservation_id}"
             );
-            return false;
+            return Ok(false);
+        }
+
+        Self::check_transferable_amount(
+            src_reservation_id,
+            src_reservation,
+            amount_to_move,
+            client_order_id,
+        )?;
+
+        if src_reservation_id == dst_reservation_id {
+            return Ok(self.release_approved_amount(
+                src_reservation_id,
+                amount_to_move,
+                client_order_id,
+            ));
         }
 
         if src_reservation.price != dst_reservation.price {
@@ -1137,12 +1866,10 @@ impl BalanceReservationManager {
                         false,
                         &mut None,
                     )
-                    .with_expect(|| {
-                        format!("failed to get available balance for {dst_reservation:?}")
-                    });
+                    .ok_or(TransferError::AvailableBalanceNotFound(dst_reservation_id))?;
                 if available_balance + balance_diff_amount < dec!(0) {
-                    log::warn!("Can't transfer {amount_to_move} because there will be insufficient balance ({src_reservation_id} => {dst_reservation_id})");
-                    return false;
+                    log::warn!("Can't transfer {amount_to_move} because there will be insufficient balance {available_balance} < {} ({src_reservation_id} => {dst_reservation_id})", -balance_diff_amount);
+                    return Ok(false);
                 }
             }
         }
@@ -1154,9 +1881,91 @@ impl BalanceReservationManager {
             amount_to_move,
             client_order_id,
         );
+        Ok(true)
+    }
+
+    fn release_approved_amount(
+        &mut self,
+        reservation_id: ReservationId,
+        amount: Amount,
+        client_order_id: &Option<ClientOrderId>,
+    ) -> bool {
+        let client_order_id = match client_order_id {
+            Some(client_order_id) => client_order_id,
+            None => {
+                log::warn!("Can't transfer not approved amount {amount} within {reservation_id}");
+                return false;
+            }
+        };
+
+        let reservation = self.get_mut_reservation_expected(reservation_id);
+        let approved_part = reservation
+            .approved_parts
+            .get(client_order_id)
+            .expect("approved part should be checked before transfer");
+        let new_approved_amount = approved_part.unreserved_amount - amount;
+        if reservation.is_amount_within_symbol_margin_error(new_approved_amount) {
+            let _ = reservation.approved_parts.remove(client_order_id);
+        } else {
+            let approved_part = reservation
+                .approved_parts
+                .get_mut(client_order_id)
+                .expect("approved part should be checked before transfer");
+            approved_part.unreserved_amount = new_approved_amount;
+            approved_part.amount -= amount;
+        }
+        reservation.not_approved_amount += amount;
+
+        log::info!(
+            "Released {amount} of approved part for order {client_order_id} in {reservation_id}"
+        );
         true
     }
 
+    fn check_transferable_amount(
+        src_reservation_id: ReservationId,
+        src_reservation: &BalanceReservation,
+        amount_to_move: Amount,
+        client_order_id: &Option<ClientOrderId>,
+    ) -> Result<(), TransferError> {
+        let insufficient_balance = |reason: String| TransferError::InsufficientBalance {
+            reservation_id: src_reservation_id,
+            amount: amount_to_move,
+            reason,
+        };
+
+        let new_unreserved_amount = src_reservation.unreserved_amount - amount_to_move;
+        if new_unreserved_amount < dec!(0)
+            && !src_reservation.is_amount_within_symbol_margin_error(new_unreserved_amount)
+        {
+            return Err(insufficient_balance(format!(
+                "unreserved amount is {}",
+                src_reservation.unreserved_amount
+            )));
+        }
+
+        if let Some(client_order_id) = client_order_id {
+            let approved_part = src_reservation
+                .approved_parts
+                .get(client_order_id)
+                .ok_or_else(|| {
+                    insufficient_balance(format!("there is no approved part for {client_order_id}"))
+                })?;
+
+            let new_approved_amount = approved_part.unreserved_amount - amount_to_move;
+            if new_approved_amount < dec!(0)
+                && !src_reservation.is_amount_within_symbol_margin_error(new_approved_amount)
+            {
+                return Err(insufficient_balance(format!(
+                    "unreserved amount of approved part for {client_order_id} is {}",
+                    approved_part.unreserved_amount
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
     fn transfer_amount(
         &mut self,
         src_reservation_id: ReservationId,
@@ -1166,7 +1975,9 @@ impl BalanceReservationManager {
     ) {
         let src_reservation = self.get_reservation_expected(src_reservation_id);
         let new_src_unreserved_amount = src_reservation.unreserved_amount - amount_to_move;
-        log::info!("trying to update src unreserved amount for transfer: {src_reservation:?} {new_src_unreserved_amount} {client_order_id:?}");
+        if self.log_balance_changes {
+            tracing::info!("trying to update src unreserved amount for transfer: {src_reservation:?} {new_src_unreserved_amount} {client_order_id:?}");
+        }
         let src_cost_diff = self.update_unreserved_amount_for_transfer(
             src_reservation_id,
             new_src_unreserved_amount,
@@ -1177,7 +1988,9 @@ impl BalanceReservationManager {
 
         let dst_reservation = self.get_reservation_expected(dst_reservation_id);
         let new_dst_unreserved_amount = dst_reservation.unreserved_amount + amount_to_move;
-        log::info!("trying to update dst unreserved amount for transfer: {dst_reservation:?} {new_dst_unreserved_amount} {client_order_id:?}");
+        if self.log_balance_changes {
+            tracing::info!("trying to update dst unreserved amount for transfer: {dst_reservation:?} {new_dst_unreserved_amount} {client_order_id:?}");
+        }
         let _ = self.update_unreserved_amount_for_transfer(
             dst_reservation_id,
             new_dst_unreserved_amount,
@@ -1252,6 +2065,7 @@ impl BalanceReservationManager {
             reservation_id,
             reservation_amount_diff,
             false,
+            BalanceChangeCause::Transfer,
         );
         let reservation = self.get_mut_reservation_expected(reservation_id);
 
@@ -1270,6 +2084,7 @@ impl BalanceReservationManager {
             buff_symbol,
             -cost_diff,
             buff_price,
+            BalanceChangeCause::Transfer,
         );
         let reservation = self.get_mut_reservation_expected(reservation_id);
 
@@ -1345,7 +2160,7 @@ impl BalanceReservationManager {
             reserve_parameters.symbol.currency_pair(),
             can_reserve_result.preset.reservation_currency_code,
         );
-        let reservation = BalanceReservation::new(
+        let mut reservation = BalanceReservation::new(
             reserve_parameters.configuration_descriptor,
             reserve_parameters.exchange_account_id,
             reserve_parameters.symbol.clone(),
@@ -1358,6 +2173,11 @@ impl BalanceReservationManager {
             can_reserve_result.preset.cost_in_amount_currency_code,
             can_reserve_result.preset.reservation_currency_code,
         );
+        reservation.expires_at = reserve_parameters.ttl.map(|ttl| {
+            time_manager::now()
+                + chrono::Duration::from_std(ttl)
+                    .expect("reservation ttl should fit chrono::Duration")
+        });
 
         let reservation_id = ReservationId::generate();
         log::info!(
@@ -1378,6 +2198,7 @@ impl BalanceReservationManager {
             reservation_id,
             reserve_parameters.amount,
             true,
+            BalanceChangeCause::Reserve,
         );
 
         log::info!("Reserved successfully");
@@ -1453,15 +2274,19 @@ impl BalanceReservationManager {
             reservation_currency_code,
         );
 
-        let limit = match self
+        let limit = self
             .amount_limits_in_amount_currency
-            .get_by_balance_request(&request)
-        {
-            Some(limit) => limit,
-            None => {
-                return (true, None);
-            }
-        };
+            .get_by_balance_request(&request);
+        let hard_position_cap = self
+            .hard_position_caps
+            .get(&MarketAccountId::new(
+                request.exchange_account_id,
+                request.currency_pair,
+            ))
+            .cloned();
+        if limit.is_none() && hard_position_cap.is_none() {
+            return (true, None);
+        }
 
         let reserved_amount = self
             .reserved_amount_in_amount_currency
@@ -1470,15 +2295,33 @@ impl BalanceReservationManager {
         let new_reserved_amount = reserved_amount + reserve_parameters.amount;
 
         // The sign depends on reserve_parameters.order_side look comment for this function
-        let position = self.get_position(
+        let position = match self.get_position(
             request.exchange_account_id,
             request.currency_pair,
             reserve_parameters.order_side,
-        );
+        ) {
+            Ok(position) => position,
+            Err(error) => {
+                // without known position we can't check the limit, so deny reservation to be on the safe side
+                log::error!("Can't reserve with limit {limit:?} and hard position cap {hard_position_cap:?} for {request:?}: {error:?}");
+                return (false, None);
+            }
+        };
 
         let potential_position = position + new_reserved_amount;
 
         let potential_position_abs = potential_position.abs();
+        if let Some(hard_position_cap) = hard_position_cap {
+            if potential_position_abs > hard_position_cap {
+                // hard cap can't be crossed even while moving toward the limit
+                return (false, Some(potential_position));
+            }
+        }
+
+        let limit = match limit {
+            Some(limit) => limit,
+            None => return (true, Some(potential_position)),
+        };
         if potential_position_abs <= limit {
             // position is within limit range
             return (true, Some(potential_position));
@@ -1655,6 +2498,7 @@ impl BalanceReservationManager {
             reservation_id,
             reservation_amount_diff,
             true,
+            BalanceChangeCause::ReservationPriceUpdate,
         )
         .with_expect(|| {
             format!(
@@ -1729,4 +2573,86 @@ impl BalanceReservationManager {
                 .set_by_balance_request(&request, limit);
         }
     }
+
+    /// Set absolute cap of position on currency pair. Unlike target amount limit, no reservation
+    /// can make position bigger than the cap by absolute value, even reducing one
+    pub fn set_hard_position_cap(
+        &mut self,
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+        cap: Amount,
+    ) {
+        self.hard_position_caps.insert(
+            MarketAccountId::new(exchange_account_id, currency_pair),
+            cap,
+        );
+    }
+
+    pub fn clear_hard_position_cap(
+        &mut self,
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+    ) {
+        let _ = self
+            .hard_position_caps
+            .remove(&MarketAccountId::new(exchange_account_id, currency_pair));
+    }
+
+    /// Remove target amount limit previously set by `set_target_amount_limit`
+    pub fn clear_target_amount_limit(
+        &mut self,
+        configuration_descriptor: ConfigurationDescriptor,
+        exchange_account_id: ExchangeAccountId,
+        symbol: Arc<Symbol>,
+    ) {
+        for currency_code in [symbol.base_currency_code, symbol.quote_currency_code()] {
+            let request = BalanceRequest::new(
+                configuration_descriptor,
+                exchange_account_id,
+                symbol.currency_pair(),
+                currency_code,
+            );
+            let _ = self
+                .amount_limits_in_amount_currency
+                .remove_by_balance_request(&request);
+        }
+    }
+
+    /// Set target amount limits for several currency pairs at once.
+    /// Limits are applied only if metadata of every currency pair is known, otherwise nothing is changed
+    pub fn set_target_amount_limits(
+        &mut self,
+        configuration_descriptor: ConfigurationDescriptor,
+        limits: HashMap<(ExchangeAccountId, CurrencyPair), Amount>,
+    ) -> Result<()> {
+        let mut unknown_pairs = Vec::new();
+        let mut resolved_limits = Vec::with_capacity(limits.len());
+        for ((exchange_account_id, currency_pair), limit) in limits {
+            match self
+                .currency_pair_to_symbol_converter
+                .try_get_symbol(exchange_account_id, currency_pair)
+            {
+                Ok(symbol) => resolved_limits.push((exchange_account_id, symbol, limit)),
+                Err(_) => unknown_pairs.push(format!("{exchange_account_id} {currency_pair}")),
+            }
+        }
+
+        if !unknown_pairs.is_empty() {
+            bail!(
+                "Unable to set target amount limits because of unknown currency pairs: {}",
+                unknown_pairs.iter().sorted().join(", ")
+            );
+        }
+
+        for (exchange_account_id, symbol, limit) in resolved_limits {
+            self.set_target_amount_limit(
+                configuration_descriptor,
+                exchange_account_id,
+                symbol,
+                limit,
+            );
+        }
+
+        Ok(())
+    }
 }
@@ -1,6 +1,6 @@
 use mockall_double::double;
 
-use mmb_domain::exchanges::symbol::Symbol;
+use mmb_domain::exchanges::symbol::{DerivativeKind, Symbol};
 use mmb_domain::order::fill::OrderFill;
 use mmb_domain::order::snapshot::{OrderSide, OrderSnapshot};
 use std::sync::Arc;
@@ -57,8 +57,8 @@ impl BalanceChangesCalculator {
         let order_side = order.header.side;
         let exchange_account_id = order.header.exchange_account_id;
 
-        let (new_base_amount, new_quote_amount) = if !symbol.is_derivative {
-            match order_side {
+        let (new_base_amount, new_quote_amount) = match symbol.derivative_kind {
+            None => match order_side {
                 OrderSide::Sell => (
                     -filled_amount,
                     symbol.convert_amount_from_amount_currency_code(
@@ -75,56 +75,43 @@ impl BalanceChangesCalculator {
                         price,
                     ),
                 ),
-            }
-        } else {
-            let balance_currency_code = symbol
-                .balance_currency_code
-                .expect("Balance currency code isn't set");
-
-            if balance_currency_code == symbol.base_currency_code {
-                match order_side {
-                    OrderSide::Sell => (
-                        symbol.convert_amount_from_amount_currency_code(
-                            symbol.base_currency_code(),
-                            -filled_amount,
-                            price,
-                        ) - commission_amount,
-                        filled_amount,
-                    ),
-                    OrderSide::Buy => (
-                        symbol.convert_amount_from_amount_currency_code(
-                            symbol.base_currency_code(),
-                            filled_amount,
-                            price,
-                        ) - commission_amount,
-                        -filled_amount,
-                    ),
-                }
-            } else if balance_currency_code == symbol.quote_currency_code {
-                match order_side {
-                    OrderSide::Sell => (
+            },
+            Some(DerivativeKind::Inverse) => match order_side {
+                OrderSide::Sell => (
+                    symbol.convert_amount_from_amount_currency_code(
+                        symbol.base_currency_code(),
                         -filled_amount,
-                        symbol.convert_amount_from_amount_currency_code(
-                            symbol.quote_currency_code(),
-                            filled_amount,
-                            price,
-                        ) - commission_amount,
-                    ),
-                    OrderSide::Buy => (
+                        price,
+                    ) - commission_amount,
+                    filled_amount,
+                ),
+                OrderSide::Buy => (
+                    symbol.convert_amount_from_amount_currency_code(
+                        symbol.base_currency_code(),
                         filled_amount,
-                        symbol.convert_amount_from_amount_currency_code(
-                            symbol.quote_currency_code(),
-                            -filled_amount,
-                            price,
-                        ) - commission_amount,
-                    ),
-                }
-            } else {
-                panic!(
-                    "BalanceChangesCalculator::get_balance_changes_calculator_results: balance_currency_code({}) is wrong.",
-                    balance_currency_code
-                )
-            }
+                        price,
+                    ) - commission_amount,
+                    -filled_amount,
+                ),
+            },
+            Some(DerivativeKind::Linear) => match order_side {
+                OrderSide::Sell => (
+                    -filled_amount,
+                    symbol.convert_amount_from_amount_currency_code(
+                        symbol.quote_currency_code(),
+                        filled_amount,
+                        price,
+                    ) - commission_amount,
+                ),
+                OrderSide::Buy => (
+                    filled_amount,
+                    symbol.convert_amount_from_amount_currency_code(
+                        symbol.quote_currency_code(),
+                        -filled_amount,
+                        price,
+                    ) - commission_amount,
+                ),
+            },
         };
 
         let base_currency_code_request = BalanceRequest::new(
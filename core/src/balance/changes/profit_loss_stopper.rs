@@ -413,7 +413,7 @@ pub(crate) mod test {
             .balance_manager
             .lock()
             .expect_get_position()
-            .returning(|_, _, _| dec!(0));
+            .returning(|_, _, _| Ok(dec!(0)));
 
         context
             .balance_change_usd_periodic_calculator
@@ -477,7 +477,7 @@ pub(crate) mod test {
             .balance_manager
             .lock()
             .expect_get_position()
-            .returning(|_, _, _| dec!(0));
+            .returning(|_, _, _| Ok(dec!(0)));
 
         context
             .balance_change_usd_periodic_calculator
@@ -554,7 +554,7 @@ pub(crate) mod test {
             .balance_manager
             .lock()
             .expect_get_position()
-            .returning(|_, _, _| dec!(0));
+            .returning(|_, _, _| Ok(dec!(0)));
 
         context
             .balance_change_usd_periodic_calculator
@@ -639,7 +639,7 @@ pub(crate) mod test {
             .balance_manager
             .lock()
             .expect_get_position()
-            .returning(|_, _, _| dec!(0));
+            .returning(|_, _, _| Ok(dec!(0)));
 
         context
             .balance_change_usd_periodic_calculator
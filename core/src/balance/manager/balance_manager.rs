@@ -1,7 +1,11 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
-use crate::balance::balance_reservation_manager::BalanceReservationManager;
+use crate::balance::balance_audit::BalanceAuditSink;
+use crate::balance::balance_metrics::MetricSample;
+use crate::balance::balance_reservation_manager::{
+    BalanceDiscrepancy, BalanceReconciliation, BalanceReservationManager, TransferError,
+};
 use crate::balance::changes::balance_changes_service::BalanceChangesService;
 use crate::balance::manager::balance_reservation::BalanceReservation;
 use crate::balance::manager::balances::Balances;
@@ -20,7 +24,7 @@ use mmb_domain::order::snapshot::Amount;
 use mmb_domain::order::snapshot::{
     ClientOrderId, OrderSide, OrderSnapshot, OrderStatus, OrderType, ReservationId,
 };
-use mmb_domain::position::DerivativePosition;
+use mmb_domain::position::{DerivativePosition, LiquidationDistance};
 
 use anyhow::{bail, Context, Result};
 use futures::future::join_all;
@@ -42,6 +46,7 @@ use mmb_utils::cancellation_token::CancellationToken;
 #[cfg(test)]
 use mockall::automock;
 use serde::Serialize;
+use tokio::sync::Notify;
 /// The entity for getting information about account balances for selected exchanges
 #[derive(Clone)]
 pub struct BalanceManager {
@@ -52,6 +57,10 @@ pub struct BalanceManager {
     position_differs_times_in_row_by_exchange_id:
         HashMap<ExchangeAccountId, HashMap<CurrencyPair, u32>>,
     event_recorder: Option<Arc<EventRecorder>>,
+    /// Fills which can't be applied yet because leverage of derivative pair is unknown
+    fills_waiting_for_leverage: Vec<(ConfigurationDescriptor, OrderSnapshot, OrderFill)>,
+    /// Notified after every change of balances, so waiting for free balance doesn't need polling
+    balance_changed: Arc<Notify>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -77,6 +86,8 @@ impl BalanceManager {
             balance_changes_service: None,
             position_differs_times_in_row_by_exchange_id: Default::default(),
             event_recorder,
+            fills_waiting_for_leverage: Vec::new(),
+            balance_changed: Arc::new(Notify::new()),
         }))
     }
 
@@ -96,11 +107,8 @@ impl BalanceManager {
         }
 
         if let Some(virtual_diff_balances) = &balances.virtual_diff_balances {
-            for (request, diff) in virtual_diff_balances.get_as_balances() {
-                self.balance_reservation_manager
-                    .virtual_balance_holder
-                    .add_balance(&request, diff);
-            }
+            self.balance_reservation_manager
+                .restore_virtual_balance_diffs(virtual_diff_balances);
         }
 
         if let (Some(balance_reservations_by_reservation_id), Some(_)) = (
@@ -127,6 +135,25 @@ impl BalanceManager {
             .get_reservation_ids()
     }
 
+    pub fn reservations_expiring_within(&self, window: chrono::Duration) -> Vec<ReservationId> {
+        self.balance_reservation_manager
+            .reservations_expiring_within(window)
+    }
+
+    /// Sweep expired reservations, see `BalanceReservationManager::unreserve_expired`
+    pub fn unreserve_expired(&mut self) -> Result<Vec<ReservationId>> {
+        let expired = self.balance_reservation_manager.unreserve_expired()?;
+        if !expired.is_empty() {
+            self.save_balances();
+        }
+        Ok(expired)
+    }
+
+    /// Merge identical reservations which aren't approved yet. Returns count of removed reservations
+    pub fn compact_reservations(&mut self) -> usize {
+        self.balance_reservation_manager.compact_reservations()
+    }
+
     #[cfg(test)]
     pub(crate) fn restore_balance_state_with_reservations_handling(
         &mut self,
@@ -173,6 +200,8 @@ impl BalanceManager {
     }
 
     fn save_balances(&mut self) {
+        self.balance_changed.notify_waiters();
+
         match &self.event_recorder {
             None => {}
             Some(event_recorder) => {
@@ -355,6 +384,10 @@ impl BalanceManager {
             let _ = filtered_exchange_balances.entry(currency).or_default();
         }
 
+        if let Some(positions) = &balances_and_positions.positions {
+            self.balance_reservation_manager
+                .update_derivative_positions(exchange_account_id, positions);
+        }
         self.restore_fill_amount_position(exchange_account_id, &balances_and_positions.positions)?;
 
         let reservations_by_exchange_account_id = self
@@ -432,6 +465,7 @@ impl BalanceManager {
         let this_locked = this.lock();
         let balances = this_locked.get_balances();
         let event_recorder = this_locked.event_recorder.clone();
+        let log_balance_changes = this_locked.log_balance_changes();
         let exchanges_by_id = this_locked.balance_reservation_manager.exchanges_by_id();
         let new_balance_manager = Self::new(
             CurrencyPairToSymbolConverter::new(exchanges_by_id.clone()),
@@ -441,12 +475,44 @@ impl BalanceManager {
 
         let mut new_bm_lock = new_balance_manager.lock();
         new_bm_lock.restore_balance_state(&balances, true);
+        new_bm_lock.set_log_balance_changes(log_balance_changes);
         new_bm_lock.balance_reservation_manager.is_call_from_clone = true;
         drop(new_bm_lock);
 
         new_balance_manager
     }
 
+    /// Try to reserve balance and if it isn't enough wait for balance changes and retry until success or `timeout`.
+    /// Returns `None` if balance wasn't reserved within `timeout`
+    pub async fn reserve_when_available(
+        this: &Arc<Mutex<Self>>,
+        reserve_parameters: &ReserveParameters,
+        timeout: std::time::Duration,
+    ) -> Result<Option<ReservationId>> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let balance_changed = this.lock().balance_changed.clone();
+
+        loop {
+            // should be created before reservation attempt to not miss changes happened right after it
+            let notified = balance_changed.notified();
+
+            if let Some(reservation_id) = this.lock().try_reserve(reserve_parameters, &mut None) {
+                return Ok(Some(reservation_id));
+            }
+
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                log::info!(
+                    "Unable to reserve {} {} for {} on {} within {timeout:?}",
+                    reserve_parameters.amount,
+                    reserve_parameters.symbol.currency_pair(),
+                    reserve_parameters.order_side,
+                    reserve_parameters.exchange_account_id,
+                );
+                return Ok(None);
+            }
+        }
+    }
+
     pub fn clone_and_subtract_not_approved_data(
         this: Arc<Mutex<Self>>,
         orders: Option<&mut dyn Iterator<Item = &'_ OrderRef>>,
@@ -543,24 +609,138 @@ impl BalanceManager {
         self.order_was_filled_with_fill(configuration_descriptor, order_snapshot, order_fill)
     }
 
+    pub fn get_signed_fill_amount_position_percent(
+        &self,
+        configuration_descriptor: ConfigurationDescriptor,
+        exchange_account_id: ExchangeAccountId,
+        symbol: Arc<Symbol>,
+        side: OrderSide,
+    ) -> Option<Decimal> {
+        self.balance_reservation_manager
+            .get_signed_fill_amount_position_percent(
+                configuration_descriptor,
+                exchange_account_id,
+                symbol,
+                side,
+            )
+    }
+
     pub fn order_was_filled_with_fill(
         &mut self,
         configuration_descriptor: ConfigurationDescriptor,
         order_snapshot: &OrderSnapshot,
         order_fill: &OrderFill,
+    ) {
+        self.retry_fills_waiting_for_leverage();
+        self.try_apply_order_fill(configuration_descriptor, order_snapshot, order_fill);
+    }
+
+    /// Compare tracked balances with balances reported by exchange, see `BalanceReservationManager::reconcile_with_exchange`
+    pub fn reconcile_with_exchange(
+        &mut self,
+        exchange_account_id: ExchangeAccountId,
+        balances_and_positions: &ExchangeBalancesAndPositions,
+    ) -> Vec<BalanceDiscrepancy> {
+        let discrepancies = self
+            .balance_reservation_manager
+            .reconcile_with_exchange(exchange_account_id, balances_and_positions);
+
+        if !discrepancies.is_empty() {
+            self.save_balances();
+        }
+
+        discrepancies
+    }
+
+    pub fn set_balance_reconciliation(&mut self, reconciliation: BalanceReconciliation) {
+        self.balance_reservation_manager
+            .set_reconciliation(reconciliation);
+    }
+
+    /// Apply balance change of settlement fill (e.g. funding) which has zero price,
+    /// so it's added to exchange balance as is
+    pub fn settlement_was_received(
+        &mut self,
+        exchange_account_id: ExchangeAccountId,
+        currency_code: CurrencyCode,
+        amount: Amount,
+    ) {
+        self.balance_reservation_manager
+            .virtual_balance_holder
+            .add_exchange_balance(exchange_account_id, currency_code, amount);
+
+        self.save_balances();
+    }
+
+    /// Commission of already applied fill was updated by more prioritized source,
+    /// so applied commission is returned and updated one is charged instead
+    pub fn fill_commission_was_updated(
+        &mut self,
+        exchange_account_id: ExchangeAccountId,
+        applied_commission_currency_code: CurrencyCode,
+        applied_commission_amount: Amount,
+        commission_currency_code: CurrencyCode,
+        commission_amount: Amount,
+    ) {
+        let virtual_balance_holder = &mut self.balance_reservation_manager.virtual_balance_holder;
+        virtual_balance_holder.add_exchange_balance(
+            exchange_account_id,
+            applied_commission_currency_code,
+            applied_commission_amount,
+        );
+        virtual_balance_holder.add_exchange_balance(
+            exchange_account_id,
+            commission_currency_code,
+            -commission_amount,
+        );
+
+        self.save_balances();
+    }
+
+    /// Apply fills which were postponed because leverage was unknown when they were received.
+    /// Fills which still can't be applied stay postponed
+    pub fn retry_fills_waiting_for_leverage(&mut self) {
+        for (configuration_descriptor, order_snapshot, order_fill) in
+            std::mem::take(&mut self.fills_waiting_for_leverage)
+        {
+            self.try_apply_order_fill(configuration_descriptor, &order_snapshot, &order_fill);
+        }
+    }
+
+    pub fn fills_waiting_for_leverage_count(&self) -> usize {
+        self.fills_waiting_for_leverage.len()
+    }
+
+    fn try_apply_order_fill(
+        &mut self,
+        configuration_descriptor: ConfigurationDescriptor,
+        order_snapshot: &OrderSnapshot,
+        order_fill: &OrderFill,
     ) {
         let exchange_account_id = order_snapshot.header.exchange_account_id;
         let symbol = self
             .balance_reservation_manager
             .currency_pair_to_symbol_converter
             .get_symbol(exchange_account_id, order_snapshot.header.currency_pair);
-        self.handle_order_fill(
+        if let Err(error) = self.handle_order_fill(
             configuration_descriptor,
             exchange_account_id,
             symbol,
             order_snapshot,
             order_fill,
-        );
+        ) {
+            log::warn!(
+                "Fill {:?} of order {} is postponed: {error:?}",
+                order_fill.trade_id(),
+                order_snapshot.header.client_order_id
+            );
+            self.fills_waiting_for_leverage.push((
+                configuration_descriptor,
+                order_snapshot.clone(),
+                order_fill.clone(),
+            ));
+            return;
+        }
         self.save_balances();
 
         if let Some(balance_changes_service) = &self.balance_changes_service {
@@ -579,7 +759,11 @@ impl BalanceManager {
         symbol: Arc<Symbol>,
         order_snapshot: &OrderSnapshot,
         order_fill: &OrderFill,
-    ) {
+    ) -> Result<()> {
+        // fill is applied by several balance changes, so they shouldn't be started without leverage
+        self.balance_reservation_manager
+            .try_get_leverage(exchange_account_id, symbol.currency_pair())?;
+
         let (amount_in_before_trade_currency_code, currency_code_before_trade) = self
             .balance_reservation_manager
             .handle_position_fill_amount_change(
@@ -591,7 +775,7 @@ impl BalanceManager {
                 configuration_descriptor,
                 exchange_account_id,
                 symbol.clone(),
-            );
+            )?;
 
         let (amount_in_after_trade_currency_code, currency_code_after_trade) = self
             .balance_reservation_manager
@@ -604,7 +788,7 @@ impl BalanceManager {
                 configuration_descriptor,
                 exchange_account_id,
                 symbol.clone(),
-            );
+            )?;
 
         self.balance_reservation_manager
             .handle_position_fill_amount_change_commission(
@@ -616,7 +800,7 @@ impl BalanceManager {
                 configuration_descriptor,
                 exchange_account_id,
                 symbol.clone(),
-            );
+            )?;
 
         self.update_last_order_fill(
             exchange_account_id,
@@ -648,6 +832,8 @@ impl BalanceManager {
             currency_code_after_trade,
             amount_in_after_trade_currency_code
         );
+
+        Ok(())
     }
 
     fn update_last_order_fill(
@@ -675,13 +861,19 @@ impl BalanceManager {
 
         if order_snapshot.status() == OrderStatus::Canceled {
             if let Some(reservation_id) = order_snapshot.header.reservation_id {
-                if self.get_reservation(reservation_id).is_some() {
-                    self.balance_reservation_manager
-                        .cancel_approved_reservation(
-                            reservation_id,
-                            &order_snapshot.header.client_order_id,
-                        );
-                    self.save_balances();
+                if let Some(reservation) = self.get_reservation(reservation_id) {
+                    // approved part of replaced order is handed over to the new order on replacing
+                    if reservation
+                        .approved_parts
+                        .contains_key(&order_snapshot.header.client_order_id)
+                    {
+                        self.balance_reservation_manager
+                            .cancel_approved_reservation(
+                                reservation_id,
+                                &order_snapshot.header.client_order_id,
+                            );
+                        self.save_balances();
+                    }
                 }
             }
         }
@@ -751,17 +943,17 @@ impl BalanceManager {
         dst_reservation_id: ReservationId,
         amount: Amount,
         client_order_id: &Option<ClientOrderId>,
-    ) -> bool {
+    ) -> Result<bool, TransferError> {
         if !self.balance_reservation_manager.try_transfer_reservation(
             src_reservation_id,
             dst_reservation_id,
             amount,
             client_order_id,
-        ) {
-            return false;
+        )? {
+            return Ok(false);
         }
         self.save_balances();
-        true
+        Ok(true)
     }
 
     pub fn try_update_reservation(
@@ -846,12 +1038,52 @@ impl BalanceManager {
             .get_exchange_balance(exchange_account_id, symbol, currency_code, None)
     }
 
+    /// Sum of unrealized PnL over derivative positions on exchange, pairs without mark price are skipped
+    pub fn total_unrealized_pnl(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+        mark_lookup: impl Fn(&CurrencyPair) -> Option<Price>,
+    ) -> Amount {
+        self.balance_reservation_manager
+            .total_unrealized_pnl(exchange_account_id, mark_lookup)
+    }
+
+    /// Local estimate of the adverse mark price move which liquidates derivative position.
+    /// See `BalanceReservationManager::liquidation_distance`
+    pub fn liquidation_distance(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+        mark_price: Price,
+    ) -> Option<LiquidationDistance> {
+        self.balance_reservation_manager.liquidation_distance(
+            exchange_account_id,
+            currency_pair,
+            mark_price,
+        )
+    }
+
+    /// Reserved amounts, positions and virtual balance diffs as labeled samples for exporting to monitoring
+    pub fn collect_metrics(&self) -> Vec<MetricSample> {
+        self.balance_reservation_manager.collect_metrics()
+    }
+
     pub fn get_all_virtual_balance_diffs(&self) -> &ServiceValueTree {
         self.balance_reservation_manager
             .virtual_balance_holder
             .get_virtual_balance_diffs()
     }
 
+    /// Virtual balances of all currencies held on exchange account
+    pub fn get_virtual_balances(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+    ) -> HashMap<CurrencyCode, Amount> {
+        self.balance_reservation_manager
+            .virtual_balance_holder
+            .get_virtual_balances(exchange_account_id)
+    }
+
     pub fn get_leveraged_balance_in_amount_currency_code(
         &self,
         configuration_descriptor: ConfigurationDescriptor,
@@ -933,6 +1165,23 @@ impl BalanceManager {
         )
     }
 
+    /// Available balances for buy and sell sides respectively
+    pub fn get_balance_both_sides(
+        &self,
+        configuration_descriptor: ConfigurationDescriptor,
+        exchange_account_id: ExchangeAccountId,
+        symbol: Arc<Symbol>,
+        price: Price,
+    ) -> (Option<Amount>, Option<Amount>) {
+        self.balance_reservation_manager
+            .try_get_available_balance_both_sides(
+                configuration_descriptor,
+                exchange_account_id,
+                symbol,
+                price,
+            )
+    }
+
     pub fn get_balance_by_reserve_parameters(
         &self,
         reserve_parameters: &ReserveParameters,
@@ -980,10 +1229,73 @@ impl BalanceManager {
         );
     }
 
+    pub fn clear_target_amount_limit(
+        &mut self,
+        configuration_descriptor: ConfigurationDescriptor,
+        exchange_account_id: ExchangeAccountId,
+        symbol: Arc<Symbol>,
+    ) {
+        self.balance_reservation_manager.clear_target_amount_limit(
+            configuration_descriptor,
+            exchange_account_id,
+            symbol,
+        );
+    }
+
+    /// See `BalanceReservationManager::set_hard_position_cap`
+    pub fn set_hard_position_cap(
+        &mut self,
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+        cap: Amount,
+    ) {
+        self.balance_reservation_manager.set_hard_position_cap(
+            exchange_account_id,
+            currency_pair,
+            cap,
+        );
+    }
+
+    pub fn clear_hard_position_cap(
+        &mut self,
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+    ) {
+        self.balance_reservation_manager
+            .clear_hard_position_cap(exchange_account_id, currency_pair);
+    }
+
+    /// Set target amount limits for several currency pairs at once.
+    /// Returns error without applying any limit if metadata of some currency pair is unknown
+    pub fn set_target_amount_limits(
+        &mut self,
+        configuration_descriptor: ConfigurationDescriptor,
+        limits: HashMap<(ExchangeAccountId, CurrencyPair), Amount>,
+    ) -> Result<()> {
+        self.balance_reservation_manager
+            .set_target_amount_limits(configuration_descriptor, limits)
+    }
+
     pub fn set_balance_changes_service(&mut self, service: Arc<BalanceChangesService>) {
         self.balance_changes_service = Some(service);
     }
 
+    pub fn log_balance_changes(&self) -> bool {
+        self.balance_reservation_manager.log_balance_changes()
+    }
+
+    /// Enable or disable info logs which are written on every balance change.
+    /// In active trading these logs are too frequent, so they can be disabled in production
+    pub fn set_log_balance_changes(&mut self, log_balance_changes: bool) {
+        self.balance_reservation_manager
+            .set_log_balance_changes(log_balance_changes);
+    }
+
+    /// Set receiver of records about every balance mutation, e.g. for tamper-evident ledger
+    pub fn set_audit_sink(&mut self, audit_sink: Option<Arc<dyn BalanceAuditSink>>) {
+        self.balance_reservation_manager.set_audit_sink(audit_sink);
+    }
+
     pub async fn update_balances_for_exchanges(
         this: Arc<Mutex<Self>>,
         cancellation_token: CancellationToken,
@@ -1052,15 +1364,36 @@ impl BalanceManager {
             .get_last_position_change_before_period(market_account_id, start_of_period)
     }
 
+    /// Position changes within `[from, to]` sorted by time, e.g. for plotting position over time
+    pub fn get_position_changes_in_period(
+        &self,
+        market_account_id: &MarketAccountId,
+        from: DateTime,
+        to: DateTime,
+    ) -> Vec<PositionChange> {
+        self.balance_reservation_manager
+            .get_position_changes_in_period(market_account_id, from, to)
+    }
+
     pub fn get_position(
         &self,
         exchange_account_id: ExchangeAccountId,
         currency_pair: CurrencyPair,
         side: OrderSide,
-    ) -> Decimal {
+    ) -> Result<Decimal> {
         self.balance_reservation_manager
             .get_position(exchange_account_id, currency_pair, side)
     }
+
+    /// Number of position flips (long to short and vice versa) by fills for the last `window`
+    pub fn position_flip_count(
+        &self,
+        market_account_id: &MarketAccountId,
+        window: chrono::Duration,
+    ) -> usize {
+        self.balance_reservation_manager
+            .position_flip_count(market_account_id, window)
+    }
 }
 
 impl_mock_initializer!(MockBalanceManager);
@@ -17,6 +17,14 @@ pub struct BalancePositionByFillAmount {
 
     /// MarketAccountId -> AmountInAmountCurrency
     position_changes: HashMap<MarketAccountId, Vec<PositionChange>>,
+
+    /// MarketAccountId -> last non-zero position, so a flip through zero position can be detected
+    #[serde(skip)]
+    last_non_zero_positions: HashMap<MarketAccountId, Decimal>,
+
+    /// MarketAccountId -> times when position moved to the side opposite to the last non-zero position
+    #[serde(skip)]
+    position_flip_times: HashMap<MarketAccountId, Vec<DateTime>>,
 }
 
 impl BalancePositionByFillAmount {
@@ -30,6 +38,10 @@ impl BalancePositionByFillAmount {
             .cloned()
     }
 
+    pub fn get_all(&self) -> &HashMap<MarketAccountId, Decimal> {
+        &self.position_by_fill_amount
+    }
+
     pub(crate) fn set(
         &mut self,
         exchange_account_id: ExchangeAccountId,
@@ -86,6 +98,20 @@ impl BalancePositionByFillAmount {
             } else {
                 log::warn!("PositionChanges for key {key:?} not found");
             }
+
+            let is_flip = self
+                .last_non_zero_positions
+                .get(&key)
+                .is_some_and(|last_position| {
+                    !new_position.is_zero()
+                        && last_position.is_sign_positive() != new_position.is_sign_positive()
+                });
+            if is_flip {
+                self.position_flip_times.entry(key).or_default().push(now);
+            }
+        }
+        if !new_position.is_zero() {
+            self.last_non_zero_positions.insert(key, new_position);
         }
         self.position_by_fill_amount.insert(key, new_position);
     }
@@ -140,4 +166,41 @@ impl BalancePositionByFillAmount {
         );
         None
     }
+
+    /// Position changes with `change_time` within `[from, to]` sorted by `change_time`
+    pub fn get_position_changes_in_period(
+        &self,
+        market_account_id: &MarketAccountId,
+        from: DateTime,
+        to: DateTime,
+    ) -> Vec<PositionChange> {
+        let mut position_changes: Vec<_> = self
+            .position_changes
+            .get(market_account_id)
+            .into_iter()
+            .flatten()
+            .filter(|x| from <= x.change_time && x.change_time <= to)
+            .cloned()
+            .collect();
+        position_changes.sort_by_key(|x| x.change_time);
+        position_changes
+    }
+
+    /// Number of fills which moved the position to the side opposite to the last non-zero position
+    /// since `start_of_period`. Closing the position by one fill and opening the opposite one
+    /// by a later fill is counted as a flip too
+    pub fn position_flip_count(
+        &self,
+        market_account_id: &MarketAccountId,
+        start_of_period: DateTime,
+    ) -> usize {
+        self.position_flip_times
+            .get(market_account_id)
+            .map_or(0, |flip_times| {
+                flip_times
+                    .iter()
+                    .filter(|&&flip_time| flip_time >= start_of_period)
+                    .count()
+            })
+    }
 }
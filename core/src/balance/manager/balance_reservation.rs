@@ -10,6 +10,7 @@ use mmb_domain::order::snapshot::Amount;
 use mmb_domain::order::snapshot::ClientOrderId;
 use mmb_domain::order::snapshot::OrderSide;
 use mmb_domain::order::snapshot::Price;
+use mmb_utils::DateTime;
 use serde::Serialize;
 
 use anyhow::{bail, Result};
@@ -34,6 +35,9 @@ pub struct BalanceReservation {
     /// Not approved amount in AmountCurrencyCode
     pub not_approved_amount: Amount,
     pub approved_parts: HashMap<ClientOrderId, ApprovedPart>,
+
+    /// Time after which the reservation may be swept. `None` means it never expires
+    pub expires_at: Option<DateTime>,
 }
 
 impl BalanceReservation {
@@ -62,6 +66,7 @@ impl BalanceReservation {
             unreserved_amount: dec!(0),
             not_approved_amount: amount,
             approved_parts: HashMap::new(),
+            expires_at: None,
         }
     }
 
@@ -77,7 +82,7 @@ impl BalanceReservation {
     }
 
     pub fn is_amount_within_symbol_margin_error(&self, amount: Amount) -> bool {
-        amount.abs() <= self.symbol.get_amount_tick() * dec!(0.01)
+        amount.abs() <= self.symbol.get_amount_margin_error()
     }
 
     pub(crate) fn convert_in_reservation_currency(
@@ -91,3 +96,53 @@ impl BalanceReservation {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mmb_domain::exchanges::symbol::Precision;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(dec!(0.01), false)]
+    #[case(dec!(1), true)]
+    fn residual_within_margin_error_by_symbol_tolerance(
+        #[case] amount_margin_error_in_ticks: Decimal,
+        #[case] expected_within_margin_error: bool,
+    ) {
+        let mut symbol = Symbol::new(
+            false,
+            "ETH".into(),
+            "ETH".into(),
+            "BTC".into(),
+            "BTC".into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            "ETH".into(),
+            Some("BTC".into()),
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0.001) },
+        );
+        symbol.amount_margin_error_in_ticks = amount_margin_error_in_ticks;
+
+        let reservation = BalanceReservation::new(
+            ConfigurationDescriptor::new("LiquidityGenerator".into(), "ETH/BTC".into()),
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            Arc::new(symbol),
+            OrderSide::Sell,
+            dec!(0.2),
+            dec!(1),
+            dec!(0),
+            dec!(0),
+            "ETH".into(),
+        );
+
+        assert_eq!(
+            reservation.is_amount_within_symbol_margin_error(dec!(0.0005)),
+            expected_within_margin_error
+        );
+    }
+}
@@ -220,23 +220,29 @@ impl BalanceManagerDerivative {
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
+    use std::sync::Arc;
 
     use chrono::Utc;
+    use mmb_domain::events::{ExchangeBalance, ExchangeBalancesAndPositions};
+    use mmb_domain::exchanges::symbol::{BeforeAfter, DerivativeKind, Precision, Symbol};
     use mmb_domain::order::snapshot::{Amount, Price};
+    use mmb_domain::position::{DerivativePosition, LiquidationDistance};
     use mmb_utils::hashmap;
     use mmb_utils::logger::init_logger;
     use rstest::rstest;
     use rust_decimal::Decimal;
     use rust_decimal_macros::dec;
 
+    use crate::balance::balance_reservation_manager::{BalanceReservationManager, TransferError};
     use crate::balance::manager::balance_manager::BalanceManager;
     use crate::balance::manager::tests::balance_manager_base::BalanceManagerBase;
-    use crate::explanation::Explanation;
+    use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
+    use crate::explanation::{Explanation, ExplanationStep};
     use crate::infrastructure::init_lifetime_manager;
     use mmb_domain::market::CurrencyCode;
 
     use mmb_domain::order::pool::OrdersPool;
-    use mmb_domain::order::snapshot::{OrderSide, OrderStatus, ReservationId};
+    use mmb_domain::order::snapshot::{OrderSide, OrderStatus, OrderType, ReservationId};
 
     use super::BalanceManagerDerivative;
 
@@ -395,6 +401,39 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn reservation_explanation_should_contain_balance_steps() {
+        init_logger();
+        let test_object =
+            create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(100), false);
+
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Sell,
+            BalanceManagerDerivative::price(),
+            dec!(5),
+        );
+        let mut explanation = Some(Explanation::default());
+        let _ = test_object
+            .balance_manager()
+            .try_reserve(&reserve_parameters, &mut explanation)
+            .expect("in test");
+
+        let explanation = explanation.expect("in test");
+        assert!(
+            explanation.steps().starts_with(&[
+                ExplanationStep::RawBalance(dec!(100)),
+                ExplanationStep::Leverage(dec!(1)),
+                ExplanationStep::AfterUntouchable(dec!(95)),
+            ]),
+            "{:?}",
+            explanation.steps()
+        );
+        assert!(!explanation
+            .steps()
+            .iter()
+            .any(|step| matches!(step, ExplanationStep::AfterLimit(_))));
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     pub async fn reservation_should_use_balance_currency_reversed() {
         init_logger();
@@ -545,11 +584,14 @@ mod tests {
             .order_was_filled(configuration_descriptor, &order);
 
         assert_eq!(
-            test_object.balance_manager().get_position(
-                test_object.balance_manager_base.exchange_account_id_1,
-                test_object.balance_manager_base.symbol().currency_pair(),
-                order_side,
-            ),
+            test_object
+                .balance_manager()
+                .get_position(
+                    test_object.balance_manager_base.exchange_account_id_1,
+                    test_object.balance_manager_base.symbol().currency_pair(),
+                    order_side,
+                )
+                .expect("in test"),
             expected_position
         );
     }
@@ -841,11 +883,14 @@ mod tests {
         );
 
         assert_eq!(
-            test_object.balance_manager().get_position(
-                exchange_account_id,
-                test_object.balance_manager_base.symbol().currency_pair(),
-                OrderSide::Sell
-            ),
+            test_object
+                .balance_manager()
+                .get_position(
+                    exchange_account_id,
+                    test_object.balance_manager_base.symbol().currency_pair(),
+                    OrderSide::Sell
+                )
+                .expect("in test"),
             dec!(-1)
         );
 
@@ -954,11 +999,14 @@ mod tests {
         );
 
         assert_eq!(
-            test_object.balance_manager().get_position(
-                exchange_account_id,
-                test_object.balance_manager_base.symbol().currency_pair(),
-                OrderSide::Sell
-            ),
+            test_object
+                .balance_manager()
+                .get_position(
+                    exchange_account_id,
+                    test_object.balance_manager_base.symbol().currency_pair(),
+                    OrderSide::Sell
+                )
+                .expect("in test"),
             -amount
         );
 
@@ -1065,11 +1113,14 @@ mod tests {
         );
 
         assert_eq!(
-            test_object.balance_manager().get_position(
-                exchange_account_id,
-                test_object.balance_manager_base.symbol().currency_pair(),
-                OrderSide::Buy
-            ),
+            test_object
+                .balance_manager()
+                .get_position(
+                    exchange_account_id,
+                    test_object.balance_manager_base.symbol().currency_pair(),
+                    OrderSide::Buy
+                )
+                .expect("in test"),
             dec!(-1)
         );
 
@@ -1178,11 +1229,14 @@ mod tests {
         );
 
         assert_eq!(
-            test_object.balance_manager().get_position(
-                exchange_account_id,
-                test_object.balance_manager_base.symbol().currency_pair(),
-                OrderSide::Buy
-            ),
+            test_object
+                .balance_manager()
+                .get_position(
+                    exchange_account_id,
+                    test_object.balance_manager_base.symbol().currency_pair(),
+                    OrderSide::Buy
+                )
+                .expect("in test"),
             -amount
         );
 
@@ -1273,11 +1327,14 @@ mod tests {
             .expect("in test");
 
         assert_eq!(
-            test_object.balance_manager().get_position(
-                exchange_account_id,
-                test_object.balance_manager_base.symbol().currency_pair(),
-                OrderSide::Buy
-            ),
+            test_object
+                .balance_manager()
+                .get_position(
+                    exchange_account_id,
+                    test_object.balance_manager_base.symbol().currency_pair(),
+                    OrderSide::Buy
+                )
+                .expect("in test"),
             dec!(1)
         );
 
@@ -1432,11 +1489,14 @@ mod tests {
             .expect("in test");
 
         assert_eq!(
-            test_object.balance_manager().get_position(
-                exchange_account_id,
-                test_object.balance_manager_base.symbol().currency_pair(),
-                OrderSide::Buy
-            ),
+            test_object
+                .balance_manager()
+                .get_position(
+                    exchange_account_id,
+                    test_object.balance_manager_base.symbol().currency_pair(),
+                    OrderSide::Buy
+                )
+                .expect("in test"),
             amount
         );
 
@@ -1590,11 +1650,14 @@ mod tests {
             .expect("in test");
 
         assert_eq!(
-            test_object.balance_manager().get_position(
-                exchange_account_id,
-                test_object.balance_manager_base.symbol().currency_pair(),
-                OrderSide::Buy
-            ),
+            test_object
+                .balance_manager()
+                .get_position(
+                    exchange_account_id,
+                    test_object.balance_manager_base.symbol().currency_pair(),
+                    OrderSide::Buy
+                )
+                .expect("in test"),
             dec!(-1)
         );
 
@@ -1751,11 +1814,14 @@ mod tests {
             .expect("in test");
 
         assert_eq!(
-            test_object.balance_manager().get_position(
-                exchange_account_id,
-                test_object.balance_manager_base.symbol().currency_pair(),
-                OrderSide::Buy
-            ),
+            test_object
+                .balance_manager()
+                .get_position(
+                    exchange_account_id,
+                    test_object.balance_manager_base.symbol().currency_pair(),
+                    OrderSide::Buy
+                )
+                .expect("in test"),
             -amount
         );
 
@@ -2000,6 +2066,85 @@ mod tests {
         );
     }
 
+    fn reserve_for_transfer_with_different_prices(
+        test_object: &BalanceManagerDerivative,
+    ) -> (ReservationId, ReservationId) {
+        let side = OrderSide::Sell;
+        let src_reserve_parameters =
+            test_object
+                .balance_manager_base
+                .create_reserve_parameters(side, dec!(5), dec!(1));
+        let src_reservation_id = test_object
+            .balance_manager()
+            .try_reserve(&src_reserve_parameters, &mut None)
+            .expect("in test");
+
+        let dst_reserve_parameters =
+            test_object
+                .balance_manager_base
+                .create_reserve_parameters(side, dec!(0.1), dec!(0.1));
+        let dst_reservation_id = test_object
+            .balance_manager()
+            .try_reserve(&dst_reserve_parameters, &mut None)
+            .expect("in test");
+
+        (src_reservation_id, dst_reservation_id)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn transfer_reservation_different_price_declined_on_insufficient_balance() {
+        init_logger();
+        let test_object = create_eth_btc_test_obj(dec!(5), dec!(5), false);
+        let (src_reservation_id, dst_reservation_id) =
+            reserve_for_transfer_with_different_prices(&test_object);
+
+        // destination reservation with lower price requires 10 ETH for 1 BTC while the whole balance is 5 ETH
+        let transfer_result = test_object.balance_manager().try_transfer_reservation(
+            src_reservation_id,
+            dst_reservation_id,
+            dec!(1),
+            &None,
+        );
+        assert!(matches!(transfer_result, Ok(false)));
+
+        let balance_manager = test_object.balance_manager();
+        assert_eq!(
+            balance_manager
+                .get_reservation_expected(src_reservation_id)
+                .unreserved_amount,
+            dec!(1)
+        );
+        assert_eq!(
+            balance_manager
+                .get_reservation_expected(dst_reservation_id)
+                .unreserved_amount,
+            dec!(0.1)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn transfer_reservation_different_price_without_available_balance() {
+        init_logger();
+        let test_object = create_eth_btc_test_obj(dec!(5), dec!(5), false);
+        let (src_reservation_id, dst_reservation_id) =
+            reserve_for_transfer_with_different_prices(&test_object);
+
+        // reservations are restored in balance manager which didn't receive exchange balances yet
+        let balances = test_object.balance_manager().get_balances();
+        let restored_test_object = BalanceManagerDerivative::new(false);
+        restored_test_object
+            .balance_manager()
+            .restore_balance_state(&balances, false);
+
+        let transfer_result = restored_test_object
+            .balance_manager()
+            .try_transfer_reservation(src_reservation_id, dst_reservation_id, dec!(1), &None);
+        assert!(matches!(
+            transfer_result,
+            Err(TransferError::AvailableBalanceNotFound(id)) if id == dst_reservation_id
+        ));
+    }
+
     #[rstest]
     #[ignore] // Transfer
     #[case(dec!(25), dec!(0.2), dec!(3), dec!(0.5), dec!(2) ,dec!(2) )] // Optimistic case: price1 < price2
@@ -2067,12 +2212,14 @@ mod tests {
             Some(balance_2)
         );
 
-        assert!(balance_manager.try_transfer_reservation(
-            reservation_id_1,
-            reservation_id_2,
-            amount_to_transfer,
-            &None
-        ));
+        assert!(balance_manager
+            .try_transfer_reservation(
+                reservation_id_1,
+                reservation_id_2,
+                amount_to_transfer,
+                &None
+            )
+            .expect("in test"));
 
         let add = reservation_1.convert_in_reservation_currency(amount_to_transfer);
         let sub = reservation_2.convert_in_reservation_currency(amount_to_transfer);
@@ -2149,12 +2296,15 @@ mod tests {
             Some(balance_2)
         );
 
-        assert!(test_object.balance_manager().try_transfer_reservation(
-            reservation_id_1,
-            reservation_id_2,
-            amount_to_transfer,
-            &None
-        ));
+        assert!(test_object
+            .balance_manager()
+            .try_transfer_reservation(
+                reservation_id_1,
+                reservation_id_2,
+                amount_to_transfer,
+                &None
+            )
+            .expect("in test"));
 
         assert_eq!(
             test_object
@@ -2200,12 +2350,10 @@ mod tests {
             Some(expected_balance)
         );
 
-        assert!(test_object.balance_manager().try_transfer_reservation(
-            reservation_id_1,
-            reservation_id_2,
-            dec!(2),
-            &None
-        ));
+        assert!(test_object
+            .balance_manager()
+            .try_transfer_reservation(reservation_id_1, reservation_id_2, dec!(2), &None)
+            .expect("in test"));
         assert_eq!(
             test_object
                 .balance_manager()
@@ -2287,11 +2435,14 @@ mod tests {
             .order_was_filled(configuration_descriptor, &order);
 
         assert_eq!(
-            test_object.balance_manager().get_position(
-                test_object.balance_manager_base.exchange_account_id_1,
-                test_object.balance_manager_base.symbol().currency_pair(),
-                OrderSide::Buy,
-            ),
+            test_object
+                .balance_manager()
+                .get_position(
+                    test_object.balance_manager_base.exchange_account_id_1,
+                    test_object.balance_manager_base.symbol().currency_pair(),
+                    OrderSide::Buy,
+                )
+                .expect("in test"),
             dec!(1)
         );
         test_object
@@ -2323,12 +2474,10 @@ mod tests {
             Some(dec!(0.5))
         );
 
-        assert!(test_object.balance_manager().try_transfer_reservation(
-            reservation_id_1,
-            reservation_id_2,
-            dec!(2),
-            &None
-        ));
+        assert!(test_object
+            .balance_manager()
+            .try_transfer_reservation(reservation_id_1, reservation_id_2, dec!(2), &None)
+            .expect("in test"));
 
         assert_eq!(
             test_object
@@ -2414,6 +2563,210 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn total_unrealized_pnl_over_two_positions() {
+        init_logger();
+        let test_object =
+            create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(25), false);
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+
+        let base = BalanceManagerBase::bnb();
+        let quote = BalanceManagerBase::btc();
+        let second_symbol = Arc::new(Symbol::new(
+            true,
+            base.as_str().into(),
+            base,
+            quote.as_str().into(),
+            quote,
+            None,
+            None,
+            None,
+            None,
+            None,
+            base,
+            Some(quote),
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0.001) },
+        ));
+        let _ = test_object.exchanges_by_id[&exchange_account_id]
+            .symbols
+            .insert(second_symbol.currency_pair(), second_symbol.clone());
+
+        let first_currency_pair = test_object.balance_manager_base.symbol().currency_pair();
+        let second_currency_pair = second_symbol.currency_pair();
+        test_object
+            .balance_manager()
+            .update_exchange_balance(
+                exchange_account_id,
+                &ExchangeBalancesAndPositions {
+                    balances: vec![ExchangeBalance {
+                        currency_code: BalanceManagerBase::eth(),
+                        balance: dec!(25),
+                    }],
+                    positions: Some(vec![
+                        DerivativePosition::new(
+                            first_currency_pair,
+                            dec!(10),
+                            dec!(0.2),
+                            dec!(0),
+                            dec!(1),
+                        ),
+                        DerivativePosition::new(
+                            second_currency_pair,
+                            dec!(-100),
+                            dec!(0.02),
+                            dec!(0),
+                            dec!(1),
+                        ),
+                    ]),
+                    event_time: None,
+                },
+            )
+            .expect("in test");
+
+        let mark_prices = hashmap![
+            first_currency_pair => dec!(0.25),
+            second_currency_pair => dec!(0.01)
+        ];
+        // inverse ETH/BTC: 10 * (1 / 0.2 - 1 / 0.25), linear BNB/BTC: (-100) * (0.01 - 0.02)
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .total_unrealized_pnl(exchange_account_id, |currency_pair| mark_prices
+                    .get(currency_pair)
+                    .copied()),
+            dec!(11)
+        );
+
+        // pair without mark price is skipped
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .total_unrealized_pnl(exchange_account_id, |currency_pair| {
+                    (*currency_pair == first_currency_pair).then_some(dec!(0.25))
+                }),
+            dec!(10)
+        );
+    }
+
+    #[rstest]
+    #[case(dec!(100), dec!(0.2), dec!(0.1025))]
+    #[case(dec!(-100), dec!(0.3), dec!(0.3975))]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn liquidation_distance_of_linear_position(
+        #[case] position: Amount,
+        #[case] entry_price: Price,
+        #[case] expected_liquidation_price: Price,
+    ) {
+        init_logger();
+        let is_reversed = true;
+        let test_object =
+            create_test_obj_by_currency_code(BalanceManagerBase::btc(), dec!(10), is_reversed);
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+        let currency_pair = test_object.balance_manager_base.symbol().currency_pair();
+
+        test_object
+            .balance_manager()
+            .update_exchange_balance(
+                exchange_account_id,
+                &ExchangeBalancesAndPositions {
+                    balances: vec![ExchangeBalance {
+                        currency_code: BalanceManagerBase::btc(),
+                        balance: dec!(10),
+                    }],
+                    positions: Some(vec![DerivativePosition::new(
+                        currency_pair,
+                        position,
+                        entry_price,
+                        dec!(0),
+                        dec!(5),
+                    )]),
+                    event_time: None,
+                },
+            )
+            .expect("in test");
+
+        // equity: 10 + 5 (PnL) = 15
+        // untouchable margin: 5% of 100 * 0.25 / 5 = 0.25
+        // distance: (15 - 0.25) / 100 = 0.1475
+        let liquidation_distance = test_object
+            .balance_manager()
+            .liquidation_distance(exchange_account_id, currency_pair, dec!(0.25))
+            .expect("in test");
+        assert_eq!(liquidation_distance.price_distance, dec!(0.1475));
+        assert_eq!(liquidation_distance.percent, dec!(59));
+        assert_eq!(
+            liquidation_distance.liquidation_price,
+            expected_liquidation_price
+        );
+
+        let unknown_exchange_account_id = test_object.balance_manager_base.exchange_account_id_2;
+        assert_eq!(
+            test_object.balance_manager().liquidation_distance(
+                unknown_exchange_account_id,
+                currency_pair,
+                dec!(0.25)
+            ),
+            None
+        );
+    }
+
+    // equity in ETH: balance + position * (1 / 0.2 - 1 / 0.25)
+    // untouchable margin: 5% of 100 / 0.25 / 5 = 4
+    // long: 1 / (1 / 0.25 + (4 + 100 - 4) / 100) = 0.2
+    // short: 1 / (1 / 0.25 - (304 - 100 - 4) / 100) = 0.5
+    #[rstest]
+    #[case(dec!(100), dec!(4), dec!(0.05), dec!(20), dec!(0.2))]
+    #[case(dec!(-100), dec!(304), dec!(0.25), dec!(100), dec!(0.5))]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn liquidation_distance_of_inverse_position(
+        #[case] position: Amount,
+        #[case] balance: Amount,
+        #[case] expected_price_distance: Price,
+        #[case] expected_percent: Decimal,
+        #[case] expected_liquidation_price: Price,
+    ) {
+        init_logger();
+        let test_object =
+            create_test_obj_by_currency_code(BalanceManagerBase::eth(), balance, false);
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+        let currency_pair = test_object.balance_manager_base.symbol().currency_pair();
+
+        test_object
+            .balance_manager()
+            .update_exchange_balance(
+                exchange_account_id,
+                &ExchangeBalancesAndPositions {
+                    balances: vec![ExchangeBalance {
+                        currency_code: BalanceManagerBase::eth(),
+                        balance,
+                    }],
+                    positions: Some(vec![DerivativePosition::new(
+                        currency_pair,
+                        position,
+                        dec!(0.2),
+                        dec!(0),
+                        dec!(5),
+                    )]),
+                    event_time: None,
+                },
+            )
+            .expect("in test");
+
+        let liquidation_distance = test_object
+            .balance_manager()
+            .liquidation_distance(exchange_account_id, currency_pair, dec!(0.25))
+            .expect("in test");
+        assert_eq!(
+            liquidation_distance,
+            LiquidationDistance {
+                price_distance: expected_price_distance,
+                percent: expected_percent,
+                liquidation_price: expected_liquidation_price,
+            }
+        );
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     pub async fn update_exchange_balance_should_use_cost_for_balance_filter_when_partially_free_cost(
     ) {
@@ -3691,6 +4044,54 @@ mod tests {
         );
     }
 
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn can_not_reserve_when_moving_to_the_limit_over_hard_position_cap(
+        #[case] is_reversed: bool,
+    ) {
+        init_logger();
+        let mut test_object = create_test_obj_by_currency_code_and_symbol_currency_pair(
+            BalanceManagerBase::eth(),
+            dec!(1000),
+            Some(dec!(450)),
+            is_reversed,
+            Some(dec!(610)),
+        );
+
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+        let symbol = test_object.balance_manager_base.symbol();
+        test_object
+            .exchanges_by_id
+            .get_mut(&exchange_account_id)
+            .expect("in test")
+            .leverage_by_currency_pair
+            .insert(symbol.currency_pair(), dec!(3));
+        test_object.balance_manager().set_hard_position_cap(
+            exchange_account_id,
+            symbol.currency_pair(),
+            dec!(500),
+        );
+
+        // position 610 is reduced to 580 which is still over hard cap
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Sell,
+            dec!(9570),
+            dec!(30),
+        );
+        assert!(!test_object
+            .balance_manager()
+            .can_reserve(&reserve_parameters, &mut None));
+
+        test_object
+            .balance_manager()
+            .clear_hard_position_cap(exchange_account_id, symbol.currency_pair());
+        assert!(test_object
+            .balance_manager()
+            .can_reserve(&reserve_parameters, &mut None));
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     pub async fn get_leveraged_balance_in_amount_currency_code_balance_is_more_than_limit_long_position(
     ) {
@@ -4387,4 +4788,208 @@ mod tests {
             .try_reserve(&reserve_parameters, &mut None,)
             .is_none());
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn fill_amount_change_without_leverage_returns_error() {
+        init_logger();
+        let test_object =
+            create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(100), false);
+
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+        let symbol = test_object.balance_manager_base.symbol();
+        test_object
+            .exchanges_by_id
+            .get(&exchange_account_id)
+            .expect("in test")
+            .leverage_by_currency_pair
+            .remove(&symbol.currency_pair());
+
+        let mut balance_reservation_manager = BalanceReservationManager::new(
+            CurrencyPairToSymbolConverter::new(test_object.exchanges_by_id.clone()),
+        );
+        let configuration_descriptor = test_object.balance_manager_base.configuration_descriptor;
+
+        assert!(balance_reservation_manager
+            .handle_position_fill_amount_change(
+                OrderSide::Buy,
+                BeforeAfter::Before,
+                &None,
+                dec!(1),
+                dec!(0.1),
+                configuration_descriptor,
+                exchange_account_id,
+                symbol.clone(),
+            )
+            .is_err());
+        assert!(balance_reservation_manager
+            .handle_position_fill_amount_change_commission(
+                BalanceManagerBase::eth(),
+                dec!(0.01),
+                BalanceManagerBase::btc(),
+                dec!(0.001),
+                dec!(0.1),
+                configuration_descriptor,
+                exchange_account_id,
+                symbol,
+            )
+            .is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn fill_without_leverage_applied_when_leverage_is_known() {
+        init_logger();
+        let mut test_object =
+            create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(100), false);
+
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+        let symbol = test_object.balance_manager_base.symbol();
+        let exchange = test_object
+            .exchanges_by_id
+            .get(&exchange_account_id)
+            .expect("in test");
+        exchange.setup_balance_manager(
+            test_object
+                .balance_manager_base
+                .balance_manager
+                .clone()
+                .expect("in test"),
+        );
+        exchange
+            .leverage_by_currency_pair
+            .remove(&symbol.currency_pair());
+
+        let mut order = test_object
+            .balance_manager_base
+            .create_order(OrderSide::Buy, ReservationId::generate());
+        order.add_fill(BalanceManagerDerivative::create_order_fill(
+            dec!(0.1),
+            dec!(1),
+            dec!(0.1),
+            dec!(0),
+            false,
+        ));
+
+        let configuration_descriptor = test_object.balance_manager_base.configuration_descriptor;
+        test_object
+            .balance_manager()
+            .order_was_filled(configuration_descriptor, &order);
+
+        let get_position = || {
+            test_object
+                .balance_manager()
+                .get_position(exchange_account_id, symbol.currency_pair(), OrderSide::Buy)
+                .expect("in test")
+        };
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .fills_waiting_for_leverage_count(),
+            1
+        );
+        assert_eq!(get_position(), dec!(0));
+
+        exchange.set_leverage(symbol.currency_pair(), dec!(1));
+
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .fills_waiting_for_leverage_count(),
+            0
+        );
+        assert_eq!(get_position(), dec!(1));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn close_position_order_for_long_position() {
+        init_logger();
+        let mut test_object =
+            create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(100), false);
+
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+        let currency_pair = test_object.balance_manager_base.symbol().currency_pair();
+        let exchange = test_object
+            .exchanges_by_id
+            .get(&exchange_account_id)
+            .expect("in test");
+        exchange.setup_balance_manager(
+            test_object
+                .balance_manager_base
+                .balance_manager
+                .clone()
+                .expect("in test"),
+        );
+
+        assert!(exchange
+            .build_close_position_order(currency_pair)
+            .expect("in test")
+            .is_none());
+
+        let mut order = test_object
+            .balance_manager_base
+            .create_order(OrderSide::Buy, ReservationId::generate());
+        order.add_fill(BalanceManagerDerivative::create_order_fill(
+            dec!(0.1),
+            dec!(1),
+            dec!(0.1),
+            dec!(0),
+            false,
+        ));
+        let configuration_descriptor = test_object.balance_manager_base.configuration_descriptor;
+        test_object
+            .balance_manager()
+            .order_was_filled(configuration_descriptor, &order);
+
+        let header = exchange
+            .build_close_position_order(currency_pair)
+            .expect("in test")
+            .expect("in test");
+
+        assert_eq!(header.side, OrderSide::Sell);
+        assert_eq!(header.amount, dec!(1));
+        assert_eq!(header.order_type, OrderType::Market);
+        assert_eq!(header.currency_pair, currency_pair);
+        assert!(header.reduce_only);
+    }
+
+    #[rstest]
+    #[case::inverse(false, DerivativeKind::Inverse, BalanceManagerBase::eth(), dec!(13.3))]
+    #[case::linear(true, DerivativeKind::Linear, BalanceManagerBase::btc(), dec!(332500))]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn leveraged_balance_depends_on_derivative_kind(
+        #[case] is_reversed: bool,
+        #[case] expected_kind: DerivativeKind,
+        #[case] balance_currency_code: CurrencyCode,
+        #[case] expected_balance: Amount,
+    ) {
+        init_logger();
+        // same balance, leverage and price for both pairs, linear one also has contract size 0.001
+        let mut test_object =
+            create_test_obj_by_currency_code(balance_currency_code, dec!(10), is_reversed);
+
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+        let symbol = test_object.balance_manager_base.symbol();
+        assert_eq!(symbol.derivative_kind, Some(expected_kind));
+        test_object
+            .exchanges_by_id
+            .get_mut(&exchange_account_id)
+            .expect("in test")
+            .leverage_by_currency_pair
+            .insert(symbol.currency_pair(), BalanceManagerDerivative::leverage());
+
+        for side in [OrderSide::Buy, OrderSide::Sell] {
+            let balance = test_object
+                .balance_manager()
+                .get_leveraged_balance_in_amount_currency_code(
+                    test_object.balance_manager_base.configuration_descriptor,
+                    side,
+                    exchange_account_id,
+                    symbol.clone(),
+                    BalanceManagerDerivative::price(),
+                    &mut None,
+                )
+                .expect("in test");
+
+            assert_eq!(balance, expected_balance, "side {side:?}");
+        }
+    }
 }
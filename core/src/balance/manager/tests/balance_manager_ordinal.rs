@@ -144,6 +144,7 @@ mod tests {
     use std::time::Duration;
 
     use chrono::Utc;
+    use itertools::Itertools;
     use mmb_domain::market::CurrencyCode;
     use mmb_domain::order::snapshot::{Amount, Price};
     use mmb_utils::hashmap;
@@ -152,14 +153,24 @@ mod tests {
     use rstest::rstest;
     use rust_decimal::Decimal;
     use rust_decimal_macros::dec;
+    use tracing_subscriber::layer::SubscriberExt;
 
+    use crate::balance::balance_audit::{
+        BalanceAuditRecord, BalanceAuditSink, BalanceAuditTarget, BalanceChangeCause,
+    };
+    use crate::balance::balance_metrics;
+    use crate::balance::balance_reservation_manager::{
+        BalanceDiscrepancy, BalanceReconciliation, TransferError,
+    };
     use crate::balance::manager::balance_manager::BalanceManager;
     use crate::balance::manager::position_change::PositionChange;
     use crate::balance::manager::tests::balance_manager_base::BalanceManagerBase;
     use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
-    use crate::misc::reserve_parameters::ReserveParameters;
+    use crate::exchanges::general::test_helper::get_test_exchange_with_symbol_and_id;
+    use crate::misc::reserve_parameters::{ReserveAmountKind, ReserveParameters};
+    use mmb_domain::events::{ExchangeBalance, ExchangeBalancesAndPositions};
     use mmb_domain::exchanges::symbol::{Precision, Symbol};
-    use mmb_domain::market::{ExchangeAccountId, MarketAccountId};
+    use mmb_domain::market::{CurrencyPair, ExchangeAccountId, MarketAccountId};
     use mmb_domain::order::pool::OrdersPool;
     use mmb_domain::order::snapshot::{
         ClientOrderFillId, ClientOrderId, OrderSide, OrderSnapshot, OrderStatus, ReservationId,
@@ -411,6 +422,41 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn get_balance_both_sides_returns_quote_and_base_balances() {
+        init_logger();
+        let test_object = create_eth_btc_test_obj(dec!(0.5), dec!(0.1));
+        let configuration_descriptor = test_object.balance_manager_base.configuration_descriptor;
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+        let symbol = test_object.balance_manager_base.symbol();
+
+        let balance_manager = test_object.balance_manager();
+        let both_sides = balance_manager.get_balance_both_sides(
+            configuration_descriptor,
+            exchange_account_id,
+            symbol.clone(),
+            dec!(1),
+        );
+        assert_eq!(both_sides, (Some(dec!(0.5)), Some(dec!(0.1))));
+
+        let get_balance_by_side = |side| {
+            balance_manager.get_balance_by_side(
+                configuration_descriptor,
+                exchange_account_id,
+                symbol.clone(),
+                side,
+                dec!(1),
+            )
+        };
+        assert_eq!(
+            both_sides,
+            (
+                get_balance_by_side(OrderSide::Buy),
+                get_balance_by_side(OrderSide::Sell)
+            )
+        );
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     pub async fn can_reserve_buy_not_enough_balance() {
         init_logger();
@@ -1355,6 +1401,210 @@ mod tests {
         assert_eq!(reservation.unreserved_amount, dec!(1));
     }
 
+    /// Collects messages of log events emitted while it's set as default subscriber
+    #[derive(Default, Clone)]
+    struct LogMessagesCapture {
+        messages: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct MessageVisitor<'a>(&'a mut Vec<String>);
+
+    impl tracing::field::Visit for MessageVisitor<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                self.0.push(format!("{value:?}"));
+            }
+        }
+    }
+
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for LogMessagesCapture {
+        fn on_event(
+            &self,
+            event: &tracing::Event<'_>,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            event.record(&mut MessageVisitor(&mut self.messages.lock()));
+        }
+    }
+
+    fn count_virtual_balance_holder_messages(capture: &LogMessagesCapture) -> usize {
+        capture
+            .messages
+            .lock()
+            .iter()
+            .filter(|message| message.contains("VirtualBalanceHolder"))
+            .count()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn unreserve_buy_with_disabled_balance_changes_logging() {
+        init_logger();
+        let test_object = create_test_obj_by_currency_code(BalanceManagerBase::btc(), dec!(1));
+        assert!(test_object.balance_manager().log_balance_changes());
+
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Buy,
+            dec!(0.2),
+            dec!(5),
+        );
+
+        let capture = LogMessagesCapture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let reservation_id = tracing::subscriber::with_default(subscriber, || {
+            let reservation_id = test_object
+                .balance_manager()
+                .try_reserve(&reserve_parameters, &mut None)
+                .expect("in test");
+
+            test_object
+                .balance_manager()
+                .unreserve(reservation_id, dec!(1))
+                .expect("in test");
+
+            reservation_id
+        });
+        // make sure capture works, otherwise absence of messages below proves nothing
+        assert!(count_virtual_balance_holder_messages(&capture) > 0);
+
+        test_object.balance_manager().set_log_balance_changes(false);
+        assert!(!test_object.balance_manager().log_balance_changes());
+
+        let capture = LogMessagesCapture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            test_object
+                .balance_manager()
+                .unreserve(reservation_id, dec!(3))
+                .expect("in test");
+        });
+        assert_eq!(count_virtual_balance_holder_messages(&capture), 0);
+
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_balance_by_reserve_parameters(&reserve_parameters),
+            Some(dec!(0.8))
+        );
+
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_reservation_expected(reservation_id)
+                .unreserved_amount,
+            dec!(1)
+        );
+
+        let cloned_balance_manager = BalanceManager::custom_clone(
+            test_object
+                .balance_manager_base
+                .balance_manager
+                .clone()
+                .expect("in test"),
+        );
+        assert!(!cloned_balance_manager.lock().log_balance_changes());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn can_reserve_with_limit_denied_when_symbol_is_unknown() {
+        init_logger();
+        let mut test_object = create_test_obj_by_currency_code(BalanceManagerBase::btc(), dec!(1));
+
+        let base = BalanceManagerBase::bnb();
+        let quote = BalanceManagerBase::btc();
+        let unknown_symbol = Arc::new(Symbol::new(
+            false,
+            base.as_str().into(),
+            base,
+            quote.as_str().into(),
+            quote,
+            None,
+            None,
+            None,
+            None,
+            None,
+            base,
+            Some(quote),
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0.001) },
+        ));
+        test_object
+            .balance_manager_base
+            .set_symbol(unknown_symbol.clone());
+
+        let configuration_descriptor = test_object.balance_manager_base.configuration_descriptor;
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+
+        // leverage is known, so only position of the unknown symbol can deny reservation
+        let (_, exchanges_by_id) = BalanceManagerOrdinal::create_balance_manager_ctor_parameters();
+        exchanges_by_id
+            .get(&exchange_account_id)
+            .expect("in test")
+            .leverage_by_currency_pair
+            .insert(unknown_symbol.currency_pair(), dec!(1));
+        let balance_manager =
+            BalanceManager::new(CurrencyPairToSymbolConverter::new(exchanges_by_id), None);
+        BalanceManagerBase::update_balance(
+            &mut balance_manager.lock(),
+            exchange_account_id,
+            hashmap![BalanceManagerBase::btc() => dec!(1)],
+        );
+        test_object
+            .balance_manager_base
+            .set_balance_manager(balance_manager);
+        test_object.balance_manager().set_target_amount_limit(
+            configuration_descriptor,
+            exchange_account_id,
+            unknown_symbol,
+            dec!(10),
+        );
+
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Buy,
+            dec!(0.2),
+            dec!(1),
+        );
+
+        assert!(test_object
+            .balance_manager()
+            .get_position(
+                exchange_account_id,
+                reserve_parameters.symbol.currency_pair(),
+                OrderSide::Buy
+            )
+            .is_err());
+        assert!(!test_object
+            .balance_manager()
+            .can_reserve(&reserve_parameters, &mut None));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn collect_metrics_for_just_created_reservation() {
+        init_logger();
+        let test_object = create_test_obj_by_currency_code(BalanceManagerBase::btc(), dec!(1));
+
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Buy,
+            dec!(0.2),
+            dec!(5),
+        );
+        let _ = test_object
+            .balance_manager()
+            .try_reserve(&reserve_parameters, &mut None)
+            .expect("in test");
+
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+        let metrics = test_object.balance_manager().collect_metrics();
+        let reserved_amount_sample = metrics
+            .iter()
+            .find(|sample| {
+                sample.name == balance_metrics::RESERVED_AMOUNT
+                    && sample.label("exchange") == Some(exchange_account_id.to_string().as_str())
+            })
+            .expect("reserved amount sample should exist");
+
+        assert_eq!(reserved_amount_sample.value, dec!(5));
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     pub async fn unreserve_sell() {
         init_logger();
@@ -1582,12 +1832,15 @@ mod tests {
             Some(balance_2)
         );
 
-        assert!(test_object.balance_manager().try_transfer_reservation(
-            reservation_id_1,
-            reservation_id_2,
-            amount_to_transfer,
-            &None
-        ));
+        assert!(test_object
+            .balance_manager()
+            .try_transfer_reservation(
+                reservation_id_1,
+                reservation_id_2,
+                amount_to_transfer,
+                &None
+            )
+            .expect("in test"));
         assert_eq!(
             test_object
                 .balance_manager()
@@ -1656,12 +1909,15 @@ mod tests {
             Some(balance_2)
         );
 
-        assert!(test_object.balance_manager().try_transfer_reservation(
-            reservation_id_1,
-            reservation_id_2,
-            amount_to_transfer,
-            &None
-        ));
+        assert!(test_object
+            .balance_manager()
+            .try_transfer_reservation(
+                reservation_id_1,
+                reservation_id_2,
+                amount_to_transfer,
+                &None
+            )
+            .expect("in test"));
         assert_eq!(
             test_object
                 .balance_manager()
@@ -1709,12 +1965,10 @@ mod tests {
             .try_reserve(&reserve_parameters_2, &mut None)
             .expect("in test");
 
-        assert!(test_object.balance_manager().try_transfer_reservation(
-            reservation_id_1,
-            reservation_id_2,
-            dec!(2),
-            &None
-        ));
+        assert!(test_object
+            .balance_manager()
+            .try_transfer_reservation(reservation_id_1, reservation_id_2, dec!(2), &None)
+            .expect("in test"));
 
         assert_eq!(
             test_object
@@ -1763,12 +2017,10 @@ mod tests {
             .try_reserve(&reserve_parameters_2, &mut None)
             .expect("in test");
 
-        assert!(test_object.balance_manager().try_transfer_reservation(
-            reservation_id_1,
-            reservation_id_2,
-            dec!(3),
-            &None
-        ));
+        assert!(test_object
+            .balance_manager()
+            .try_transfer_reservation(reservation_id_1, reservation_id_2, dec!(3), &None)
+            .expect("in test"));
 
         assert_eq!(
             test_object
@@ -1792,7 +2044,7 @@ mod tests {
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    pub async fn transfer_reservations_amount_more_than_we_have_should_do_nothing_and_panic() {
+    pub async fn transfer_reservations_amount_more_than_we_have_should_do_nothing_and_fail() {
         init_logger();
         let test_object = Arc::new(Mutex::new(create_test_obj_by_currency_code(
             BalanceManagerBase::eth(),
@@ -1819,20 +2071,14 @@ mod tests {
             .balance_manager()
             .try_reserve(&reserve_parameters_2, &mut None)
             .expect("in test");
-        let balance_manager_cloned = Mutex::new(test_object.lock().balance_manager().clone());
-
-        let handle = std::thread::spawn(move || {
-            balance_manager_cloned.lock().try_transfer_reservation(
-                reservation_id_1,
-                reservation_id_2,
-                dec!(5),
-                &None,
-            );
-        });
-
-        if handle.join().is_ok() {
-            panic!();
-        }
+        let transfer_result = test_object
+            .lock()
+            .balance_manager()
+            .try_transfer_reservation(reservation_id_1, reservation_id_2, dec!(5), &None);
+        assert!(matches!(
+            transfer_result,
+            Err(TransferError::InsufficientBalance { .. })
+        ));
 
         assert_eq!(
             test_object
@@ -1917,12 +2163,10 @@ mod tests {
             .unreserve(reservation_id_1, dec!(1))
             .expect("in test");
 
-        assert!(test_object.balance_manager().try_transfer_reservation(
-            reservation_id_1,
-            reservation_id_2,
-            dec!(1),
-            &None
-        ));
+        assert!(test_object
+            .balance_manager()
+            .try_transfer_reservation(reservation_id_1, reservation_id_2, dec!(1), &None)
+            .expect("in test"));
 
         assert_eq!(
             test_object
@@ -2003,12 +2247,14 @@ mod tests {
             amount
         );
 
-        assert!(balance_manager.try_transfer_reservation(
-            reservation_id_1,
-            reservation_id_2,
-            dec!(2),
-            &Some(order.header.client_order_id.clone())
-        ));
+        assert!(balance_manager
+            .try_transfer_reservation(
+                reservation_id_1,
+                reservation_id_2,
+                dec!(2),
+                &Some(order.header.client_order_id.clone())
+            )
+            .expect("in test"));
 
         assert_eq!(
             balance_manager.get_balance_by_reserve_parameters(&reserve_parameters_1),
@@ -2047,7 +2293,6 @@ mod tests {
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    #[should_panic]
     pub async fn transfer_reservations_amount_more_thane_we_have() {
         init_logger();
         let mut test_object = create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(5));
@@ -2097,16 +2342,19 @@ mod tests {
             reservation.amount
         );
 
-        balance_manager.try_transfer_reservation(
+        let transfer_result = balance_manager.try_transfer_reservation(
             reservation_id_1,
             reservation_id_2,
             dec!(4),
             &None,
         );
+        assert!(matches!(
+            transfer_result,
+            Err(TransferError::InsufficientBalance { .. })
+        ));
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    #[should_panic]
     pub async fn transfer_reservations_amount_more_than_we_have_by_approve_client_order_id() {
         init_logger();
         let mut test_object = create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(5));
@@ -2152,16 +2400,19 @@ mod tests {
             dec!(1)
         );
 
-        balance_manager.try_transfer_reservation(
+        let transfer_result = balance_manager.try_transfer_reservation(
             reservation_id_1,
             reservation_id_2,
             dec!(2),
             &Some(order.header.client_order_id),
         );
+        assert!(matches!(
+            transfer_result,
+            Err(TransferError::InsufficientBalance { .. })
+        ));
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    #[should_panic]
     pub async fn transfer_reservations_unknown_client_order_id() {
         init_logger();
         let test_object = create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(5));
@@ -2185,70 +2436,263 @@ mod tests {
             .balance_manager()
             .try_reserve(&reserve_parameters_2, &mut None)
             .expect("in test");
-        test_object.balance_manager().try_transfer_reservation(
+        let transfer_result = test_object.balance_manager().try_transfer_reservation(
             reservation_id_1,
             reservation_id_2,
             dec!(2),
             &Some(ClientOrderId::new("unknown_id".into())),
         );
+        assert!(matches!(
+            transfer_result,
+            Err(TransferError::InsufficientBalance { .. })
+        ));
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    pub async fn transfer_reservations_amount_partial_approve_with_multiple_orders() {
+    pub async fn transfer_reservations_unknown_reservation_id() {
         init_logger();
-        let mut test_object = create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(5));
+        let test_object = create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(5));
 
-        let reserve_parameters_1 = test_object.balance_manager_base.create_reserve_parameters(
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
             OrderSide::Sell,
             dec!(0.2),
             dec!(3),
         );
-        let reservation_id_1 = test_object
-            .balance_manager()
-            .try_reserve(&reserve_parameters_1, &mut None)
-            .expect("in test");
-
-        let reserve_parameters_2 = test_object.balance_manager_base.create_reserve_parameters(
-            OrderSide::Sell,
-            dec!(0.2),
-            dec!(2),
-        );
-        let reservation_id_2 = test_object
+        let reservation_id = test_object
             .balance_manager()
-            .try_reserve(&reserve_parameters_2, &mut None)
+            .try_reserve(&reserve_parameters, &mut None)
             .expect("in test");
+        let unknown_reservation_id = ReservationId::generate();
 
-        let order_1 = test_object
-            .balance_manager_base
-            .create_order(OrderSide::Sell, ReservationId::generate());
-        let order_2 = test_object
-            .balance_manager_base
-            .create_order(OrderSide::Sell, ReservationId::generate());
-
-        test_object.balance_manager().approve_reservation(
-            reservation_id_1,
-            &order_1.header.client_order_id,
+        let transfer_result = test_object.balance_manager().try_transfer_reservation(
+            unknown_reservation_id,
+            reservation_id,
             dec!(1),
+            &None,
         );
+        assert!(matches!(
+            transfer_result,
+            Err(TransferError::SourceNotFound(id)) if id == unknown_reservation_id
+        ));
 
-        test_object.balance_manager().approve_reservation(
-            reservation_id_1,
-            &order_2.header.client_order_id,
-            dec!(2),
+        let transfer_result = test_object.balance_manager().try_transfer_reservation(
+            reservation_id,
+            unknown_reservation_id,
+            dec!(1),
+            &None,
         );
-
-        assert!(test_object.balance_manager().try_transfer_reservation(
-            reservation_id_1,
-            reservation_id_2,
-            dec!(2),
-            &Some(order_2.header.client_order_id.clone()),
+        assert!(matches!(
+            transfer_result,
+            Err(TransferError::DestNotFound(id)) if id == unknown_reservation_id
         ));
 
         assert_eq!(
             test_object
                 .balance_manager()
-                .get_balance_by_reserve_parameters(&reserve_parameters_1),
-            Some(dec!(0))
+                .get_reservation_expected(reservation_id)
+                .unreserved_amount,
+            dec!(3)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn transfer_reservations_with_different_sides() {
+        init_logger();
+        let test_object = create_eth_btc_test_obj(dec!(5), dec!(5));
+
+        let sell_reservation_id = test_object
+            .balance_manager()
+            .try_reserve(
+                &test_object.balance_manager_base.create_reserve_parameters(
+                    OrderSide::Sell,
+                    dec!(0.2),
+                    dec!(2),
+                ),
+                &mut None,
+            )
+            .expect("in test");
+        let buy_reservation_id = test_object
+            .balance_manager()
+            .try_reserve(
+                &test_object.balance_manager_base.create_reserve_parameters(
+                    OrderSide::Buy,
+                    dec!(0.2),
+                    dec!(2),
+                ),
+                &mut None,
+            )
+            .expect("in test");
+
+        let transfer_result = test_object.balance_manager().try_transfer_reservation(
+            sell_reservation_id,
+            buy_reservation_id,
+            dec!(1),
+            &None,
+        );
+        assert!(matches!(
+            transfer_result,
+            Err(TransferError::IncompatibleReservations {
+                src_reservation_id,
+                dst_reservation_id,
+            }) if src_reservation_id == sell_reservation_id && dst_reservation_id == buy_reservation_id
+        ));
+
+        let balance_manager = test_object.balance_manager();
+        assert_eq!(
+            balance_manager
+                .get_reservation_expected(sell_reservation_id)
+                .unreserved_amount,
+            dec!(2)
+        );
+        assert_eq!(
+            balance_manager
+                .get_reservation_expected(buy_reservation_id)
+                .unreserved_amount,
+            dec!(2)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn transfer_within_reservation_releases_approved_part() {
+        init_logger();
+        let test_object = create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(5));
+
+        let reservation_id = test_object
+            .balance_manager()
+            .try_reserve(
+                &test_object.balance_manager_base.create_reserve_parameters(
+                    OrderSide::Sell,
+                    dec!(0.2),
+                    dec!(2),
+                ),
+                &mut None,
+            )
+            .expect("in test");
+        let client_order_id = ClientOrderId::unique_id();
+        test_object.balance_manager().approve_reservation(
+            reservation_id,
+            &client_order_id,
+            dec!(2),
+        );
+
+        let transfer_result = test_object.balance_manager().try_transfer_reservation(
+            reservation_id,
+            reservation_id,
+            dec!(2),
+            &Some(client_order_id.clone()),
+        );
+        assert!(matches!(transfer_result, Ok(true)));
+
+        let balance_manager = test_object.balance_manager();
+        let reservation = balance_manager.get_reservation_expected(reservation_id);
+        assert_eq!(reservation.unreserved_amount, dec!(2));
+        assert_eq!(reservation.not_approved_amount, dec!(2));
+        assert!(!reservation.approved_parts.contains_key(&client_order_id));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn transfer_reservations_from_removed_reservation() {
+        init_logger();
+        let test_object = create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(5));
+
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Sell,
+            dec!(0.2),
+            dec!(2),
+        );
+        let src_reservation_id = test_object
+            .balance_manager()
+            .try_reserve(&reserve_parameters, &mut None)
+            .expect("in test");
+        let dst_reservation_id = test_object
+            .balance_manager()
+            .try_reserve(&reserve_parameters, &mut None)
+            .expect("in test");
+
+        test_object
+            .balance_manager()
+            .unreserve(src_reservation_id, dec!(2))
+            .expect("in test");
+
+        let transfer_result = test_object.balance_manager().try_transfer_reservation(
+            src_reservation_id,
+            dst_reservation_id,
+            dec!(1),
+            &None,
+        );
+        assert!(matches!(
+            transfer_result,
+            Err(TransferError::SourceNotFound(id)) if id == src_reservation_id
+        ));
+
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_reservation_expected(dst_reservation_id)
+                .unreserved_amount,
+            dec!(2)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn transfer_reservations_amount_partial_approve_with_multiple_orders() {
+        init_logger();
+        let mut test_object = create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(5));
+
+        let reserve_parameters_1 = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Sell,
+            dec!(0.2),
+            dec!(3),
+        );
+        let reservation_id_1 = test_object
+            .balance_manager()
+            .try_reserve(&reserve_parameters_1, &mut None)
+            .expect("in test");
+
+        let reserve_parameters_2 = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Sell,
+            dec!(0.2),
+            dec!(2),
+        );
+        let reservation_id_2 = test_object
+            .balance_manager()
+            .try_reserve(&reserve_parameters_2, &mut None)
+            .expect("in test");
+
+        let order_1 = test_object
+            .balance_manager_base
+            .create_order(OrderSide::Sell, ReservationId::generate());
+        let order_2 = test_object
+            .balance_manager_base
+            .create_order(OrderSide::Sell, ReservationId::generate());
+
+        test_object.balance_manager().approve_reservation(
+            reservation_id_1,
+            &order_1.header.client_order_id,
+            dec!(1),
+        );
+
+        test_object.balance_manager().approve_reservation(
+            reservation_id_1,
+            &order_2.header.client_order_id,
+            dec!(2),
+        );
+
+        assert!(test_object
+            .balance_manager()
+            .try_transfer_reservation(
+                reservation_id_1,
+                reservation_id_2,
+                dec!(2),
+                &Some(order_2.header.client_order_id.clone()),
+            )
+            .expect("in test"));
+
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_balance_by_reserve_parameters(&reserve_parameters_1),
+            Some(dec!(0))
         );
 
         let balance_manager = test_object.balance_manager();
@@ -2335,12 +2779,15 @@ mod tests {
             dec!(1),
         );
 
-        assert!(test_object.balance_manager().try_transfer_reservation(
-            reservation_id_1,
-            reservation_id_2,
-            dec!(2),
-            &Some(order_2.header.client_order_id.clone()),
-        ));
+        assert!(test_object
+            .balance_manager()
+            .try_transfer_reservation(
+                reservation_id_1,
+                reservation_id_2,
+                dec!(2),
+                &Some(order_2.header.client_order_id.clone()),
+            )
+            .expect("in test"));
 
         assert_eq!(
             test_object
@@ -4385,6 +4832,63 @@ mod tests {
             .can_reserve(&reserve_parameters, &mut None));
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn restored_state_keeps_available_balance() {
+        init_logger();
+        let mut test_object = create_eth_btc_test_obj(dec!(1), dec!(2));
+        let (_, exchanges_by_id) = BalanceManagerOrdinal::create_balance_manager_ctor_parameters();
+
+        let price = dec!(0.2);
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Buy,
+            price,
+            dec!(2),
+        );
+        test_object
+            .balance_manager()
+            .try_reserve(&reserve_parameters, &mut None)
+            .expect("in test");
+
+        let mut order = test_object
+            .balance_manager_base
+            .create_order(OrderSide::Sell, ReservationId::generate());
+        order.add_fill(BalanceManagerOrdinal::create_order_fill(
+            price,
+            dec!(1),
+            dec!(0.2),
+        ));
+        test_object.balance_manager().order_was_filled(
+            test_object.balance_manager_base.configuration_descriptor,
+            &order,
+        );
+
+        let balances = test_object.balance_manager().get_balances();
+        let restored_balance_manager =
+            BalanceManager::new(CurrencyPairToSymbolConverter::new(exchanges_by_id), None);
+        restored_balance_manager
+            .lock()
+            .restore_balance_state(&balances, true);
+
+        for side in [OrderSide::Buy, OrderSide::Sell] {
+            let get_balance_by_side = |balance_manager: &BalanceManager| {
+                balance_manager.get_balance_by_side(
+                    test_object.balance_manager_base.configuration_descriptor,
+                    test_object.balance_manager_base.exchange_account_id_1,
+                    test_object.balance_manager_base.symbol(),
+                    side,
+                    price,
+                )
+            };
+
+            let original_balance = get_balance_by_side(&test_object.balance_manager());
+            assert!(original_balance.is_some());
+            assert_eq!(
+                get_balance_by_side(&restored_balance_manager.lock()),
+                original_balance
+            );
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     pub async fn restore_state_ctor() {
         init_logger();
@@ -5285,39 +5789,845 @@ mod tests {
         );
     }
 
-    fn order_was_filled(
-        test_object: &mut BalanceManagerOrdinal,
-        order: &mut OrderSnapshot,
-    ) -> ClientOrderFillId {
-        let order_fill_id = ClientOrderFillId::unique_id();
-        order
-            .fills
-            .fills
-            .first_mut()
-            .expect("in test")
-            .set_client_order_fill_id(order_fill_id.clone());
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn position_flip_count_by_alternating_fills() {
+        init_logger();
+        let mut test_object = create_eth_btc_test_obj(dec!(10), dec!(0));
 
-        let configuration_descriptor = test_object.balance_manager_base.configuration_descriptor;
-        let order_fill = order.fills.fills.first().expect("in test");
-        test_object.balance_manager().order_was_filled_with_fill(
-            configuration_descriptor,
-            order,
-            order_fill,
+        let market_account_id = MarketAccountId::new(
+            test_object.balance_manager_base.exchange_account_id_1,
+            test_object.balance_manager_base.symbol().currency_pair(),
         );
+        let window = chrono::Duration::seconds(60);
 
-        order_fill_id
-    }
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .position_flip_count(&market_account_id, window),
+            0
+        );
 
-    fn check_position(test_object: &BalanceManagerOrdinal, position: Decimal) {
-        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+        let price = dec!(0.2);
+        let mut create_order_with_fill = |side, amount| {
+            let mut order = test_object
+                .balance_manager_base
+                .create_order(side, ReservationId::generate());
+            order.add_fill(BalanceManagerOrdinal::create_order_fill_with_time(
+                price,
+                amount,
+                dec!(2.5),
+                test_object.now,
+            ));
+            order
+        };
+        let mut sell_5 = create_order_with_fill(OrderSide::Sell, dec!(5));
+        let mut buy_10 = create_order_with_fill(OrderSide::Buy, dec!(10));
+        let mut sell_5_to_close = create_order_with_fill(OrderSide::Sell, dec!(5));
+        let mut sell_5_to_open = create_order_with_fill(OrderSide::Sell, dec!(5));
+        let mut another_buy_10 = create_order_with_fill(OrderSide::Buy, dec!(10));
+        let mut buy_1 = create_order_with_fill(OrderSide::Buy, dec!(1));
+
+        order_was_filled(&mut test_object, &mut sell_5);
+        check_position(&test_object, dec!(-5));
 
-        let currency_pair = test_object.balance_manager_base.symbol().currency_pair();
-        let amount_position = test_object.balance_manager().get_position(
-            exchange_account_id,
-            currency_pair,
-            OrderSide::Buy,
+        test_object.timer_add_second();
+        order_was_filled(&mut test_object, &mut buy_10);
+        check_position(&test_object, dec!(5));
+
+        // long -> flat -> short is a flip too
+        test_object.timer_add_second();
+        order_was_filled(&mut test_object, &mut sell_5_to_close);
+        check_position(&test_object, dec!(0));
+
+        test_object.timer_add_second();
+        order_was_filled(&mut test_object, &mut sell_5_to_open);
+        check_position(&test_object, dec!(-5));
+
+        test_object.timer_add_second();
+        order_was_filled(&mut test_object, &mut another_buy_10);
+        check_position(&test_object, dec!(5));
+
+        test_object.timer_add_second();
+        order_was_filled(&mut test_object, &mut buy_1);
+        check_position(&test_object, dec!(6));
+
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .position_flip_count(&market_account_id, window),
+            3
+        );
+        // only the last flip happened within the last second
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .position_flip_count(&market_account_id, chrono::Duration::seconds(1)),
+            1
         );
+    }
 
-        assert_eq!(position, amount_position);
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn reservations_expiring_within_window() {
+        init_logger();
+        let test_object = create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(10));
+
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Sell,
+            dec!(0.2),
+            dec!(1),
+        );
+        let reserve_with_expiration = |expires_in_secs: Option<i64>| {
+            let mut balance_manager = test_object.balance_manager();
+            let reservation_id = balance_manager
+                .try_reserve(&reserve_parameters, &mut None)
+                .expect("in test");
+            balance_manager
+                .get_mut_reservation_expected(reservation_id)
+                .expires_at =
+                expires_in_secs.map(|secs| test_object.now + chrono::Duration::seconds(secs));
+            reservation_id
+        };
+
+        let already_expired = reserve_with_expiration(Some(-5));
+        let expiring_soon = reserve_with_expiration(Some(10));
+        let expiring_at_window_end = reserve_with_expiration(Some(30));
+        let _expiring_later = reserve_with_expiration(Some(31));
+        let _without_expiration = reserve_with_expiration(None);
+
+        let mut expected = vec![already_expired, expiring_soon, expiring_at_window_end];
+        expected.sort();
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .reservations_expiring_within(chrono::Duration::seconds(30)),
+            expected
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn unreserve_expired_reservations() {
+        init_logger();
+        let test_object = create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(10));
+
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Sell,
+            dec!(0.2),
+            dec!(1),
+        );
+        let reserve = |reserve_parameters: ReserveParameters| {
+            test_object
+                .balance_manager()
+                .try_reserve(&reserve_parameters, &mut None)
+                .expect("in test")
+        };
+
+        let expired = reserve(reserve_parameters.clone().with_ttl(Duration::ZERO));
+        let expired_approved = reserve(reserve_parameters.clone().with_ttl(Duration::ZERO));
+        let not_expired = reserve(reserve_parameters.clone().with_ttl(Duration::from_secs(60)));
+        let without_ttl = reserve(reserve_parameters.clone());
+
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_reservation_expected(not_expired)
+                .expires_at,
+            Some(test_object.now + chrono::Duration::seconds(60))
+        );
+
+        test_object.balance_manager().approve_reservation(
+            expired_approved,
+            &ClientOrderId::unique_id(),
+            dec!(1),
+        );
+
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .unreserve_expired()
+                .expect("in test"),
+            vec![expired]
+        );
+
+        let mut expected = vec![expired_approved, not_expired, without_ttl];
+        expected.sort();
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_reservation_ids()
+                .into_iter()
+                .sorted()
+                .collect_vec(),
+            expected
+        );
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_balance_by_reserve_parameters(&reserve_parameters),
+            Some(dec!(7))
+        );
+    }
+
+    #[rstest]
+    #[case::half_long(OrderSide::Buy, dec!(0.5))]
+    #[case::half_short(OrderSide::Sell, dec!(-0.5))]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn signed_fill_amount_position_percent(
+        #[case] fill_side: OrderSide,
+        #[case] expected_percent: Decimal,
+    ) {
+        init_logger();
+        let mut test_object = create_eth_btc_test_obj(dec!(10), dec!(10));
+
+        let configuration_descriptor = test_object.balance_manager_base.configuration_descriptor;
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+        let symbol = test_object.balance_manager_base.symbol();
+        let get_percent = |test_object: &BalanceManagerOrdinal| {
+            test_object
+                .balance_manager()
+                .get_signed_fill_amount_position_percent(
+                    configuration_descriptor,
+                    exchange_account_id,
+                    symbol.clone(),
+                    OrderSide::Buy,
+                )
+        };
+
+        test_object.balance_manager().set_target_amount_limit(
+            configuration_descriptor,
+            exchange_account_id,
+            symbol.clone(),
+            dec!(0),
+        );
+        assert_eq!(get_percent(&test_object), None);
+
+        test_object.balance_manager().set_target_amount_limit(
+            configuration_descriptor,
+            exchange_account_id,
+            symbol.clone(),
+            dec!(10),
+        );
+
+        let mut order = test_object
+            .balance_manager_base
+            .create_order(fill_side, ReservationId::generate());
+        order.add_fill(BalanceManagerOrdinal::create_order_fill_with_time(
+            dec!(0.2),
+            dec!(5),
+            dec!(1),
+            test_object.now,
+        ));
+        order_was_filled(&mut test_object, &mut order);
+
+        assert_eq!(get_percent(&test_object), Some(expected_percent));
+    }
+
+    fn order_was_filled(
+        test_object: &mut BalanceManagerOrdinal,
+        order: &mut OrderSnapshot,
+    ) -> ClientOrderFillId {
+        let order_fill_id = ClientOrderFillId::unique_id();
+        order
+            .fills
+            .fills
+            .first_mut()
+            .expect("in test")
+            .set_client_order_fill_id(order_fill_id.clone());
+
+        let configuration_descriptor = test_object.balance_manager_base.configuration_descriptor;
+        let order_fill = order.fills.fills.first().expect("in test");
+        test_object.balance_manager().order_was_filled_with_fill(
+            configuration_descriptor,
+            order,
+            order_fill,
+        );
+
+        order_fill_id
+    }
+
+    fn check_position(test_object: &BalanceManagerOrdinal, position: Decimal) {
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+
+        let currency_pair = test_object.balance_manager_base.symbol().currency_pair();
+        let amount_position = test_object
+            .balance_manager()
+            .get_position(exchange_account_id, currency_pair, OrderSide::Buy)
+            .expect("in test");
+
+        assert_eq!(position, amount_position);
+    }
+
+    #[derive(Default)]
+    struct TestAuditSink {
+        records: Mutex<Vec<BalanceAuditRecord>>,
+    }
+
+    impl BalanceAuditSink for TestAuditSink {
+        fn record(&self, record: BalanceAuditRecord) {
+            self.records.lock().push(record);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn reserve_and_unreserve_audit_records() {
+        init_logger();
+        let test_object = create_test_obj_by_currency_code(BalanceManagerBase::btc(), dec!(1));
+
+        let audit_sink = Arc::new(TestAuditSink::default());
+        test_object
+            .balance_manager()
+            .set_audit_sink(Some(audit_sink.clone()));
+
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Buy,
+            dec!(0.2),
+            dec!(5),
+        );
+        let reservation_id = test_object
+            .balance_manager()
+            .try_reserve(&reserve_parameters, &mut None)
+            .expect("in test");
+        test_object
+            .balance_manager()
+            .unreserve(reservation_id, dec!(5))
+            .expect("in test");
+
+        let records = audit_sink.records.lock();
+        let actual = records
+            .iter()
+            .map(|x| (x.target, x.cause, x.delta, x.resulting_value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            actual,
+            vec![
+                (
+                    BalanceAuditTarget::VirtualBalance,
+                    BalanceChangeCause::Reserve,
+                    dec!(-1),
+                    dec!(-1)
+                ),
+                (
+                    BalanceAuditTarget::ReservedAmount,
+                    BalanceChangeCause::Reserve,
+                    dec!(5),
+                    dec!(5)
+                ),
+                (
+                    BalanceAuditTarget::VirtualBalance,
+                    BalanceChangeCause::Unreserve,
+                    dec!(1),
+                    dec!(0)
+                ),
+                (
+                    BalanceAuditTarget::ReservedAmount,
+                    BalanceChangeCause::Unreserve,
+                    dec!(-5),
+                    dec!(0)
+                ),
+            ]
+        );
+
+        for target in [
+            BalanceAuditTarget::VirtualBalance,
+            BalanceAuditTarget::ReservedAmount,
+        ] {
+            let delta_sum: Decimal = records
+                .iter()
+                .filter(|x| x.target == target)
+                .map(|x| x.delta)
+                .sum();
+            assert_eq!(delta_sum, dec!(0));
+        }
+        assert!(records.windows(2).all(|x| x[0].time <= x[1].time));
+    }
+
+    #[rstest]
+    #[case::base(ReserveAmountKind::Base, dec!(5))]
+    #[case::quote(ReserveAmountKind::Quote, dec!(1))]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn reserve_sell_with_amount_kind(
+        #[case] amount_kind: ReserveAmountKind,
+        #[case] amount: Amount,
+    ) {
+        init_logger();
+        let test_object = create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(10));
+
+        let reserve_parameters = ReserveParameters::new_with_amount_kind(
+            test_object.balance_manager_base.configuration_descriptor,
+            test_object.balance_manager_base.exchange_account_id_1,
+            test_object.balance_manager_base.symbol(),
+            OrderSide::Sell,
+            dec!(0.2),
+            amount,
+            amount_kind,
+        );
+        assert_eq!(reserve_parameters.amount, dec!(5));
+
+        let reservation_id = test_object
+            .balance_manager()
+            .try_reserve(&reserve_parameters, &mut None)
+            .expect("in test");
+
+        let balance_manager = test_object.balance_manager();
+        let reservation = balance_manager.get_reservation_expected(reservation_id);
+        assert_eq!(
+            reservation.reservation_currency_code,
+            BalanceManagerBase::eth()
+        );
+        assert_eq!(reservation.amount, dec!(5));
+        assert_eq!(reservation.unreserved_amount, dec!(5));
+        drop(balance_manager);
+
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_balance_by_reserve_parameters(&reserve_parameters),
+            Some(dec!(5))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn reserve_when_available_after_reservation_freed() {
+        init_logger();
+        let test_object = create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(10));
+        let balance_manager = test_object
+            .balance_manager_base
+            .balance_manager
+            .clone()
+            .expect("in test");
+
+        let first_reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Sell,
+            dec!(0.2),
+            dec!(10),
+        );
+        let first_reservation_id = balance_manager
+            .lock()
+            .try_reserve(&first_reserve_parameters, &mut None)
+            .expect("in test");
+
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Sell,
+            dec!(0.2),
+            dec!(5),
+        );
+        assert!(!balance_manager
+            .lock()
+            .can_reserve(&reserve_parameters, &mut None));
+
+        let unreserve_handle = tokio::spawn({
+            let balance_manager = balance_manager.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                balance_manager
+                    .lock()
+                    .unreserve_rest(first_reservation_id)
+                    .expect("in test");
+            }
+        });
+
+        let reservation_id = BalanceManager::reserve_when_available(
+            &balance_manager,
+            &reserve_parameters,
+            Duration::from_secs(1),
+        )
+        .await
+        .expect("in test")
+        .expect("reservation should be made after balance was freed");
+        unreserve_handle.await.expect("in test");
+
+        let balance_manager = balance_manager.lock();
+        assert_eq!(
+            balance_manager
+                .get_reservation_expected(reservation_id)
+                .unreserved_amount,
+            dec!(5)
+        );
+        assert!(balance_manager
+            .get_reservation(first_reservation_id)
+            .is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn reconcile_with_exchange_reports_discrepancy() {
+        init_logger();
+        let test_object = create_eth_btc_test_obj(dec!(1), dec!(10));
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Sell,
+            dec!(0.2),
+            dec!(5),
+        );
+        test_object
+            .balance_manager()
+            .try_reserve(&reserve_parameters, &mut None)
+            .expect("in test");
+        // not approved reservation is subtracted from exchange balance
+        BalanceManagerBase::update_balance(
+            &mut test_object.balance_manager(),
+            exchange_account_id,
+            hashmap![BalanceManagerBase::btc() => dec!(1), BalanceManagerBase::eth() => dec!(10)],
+        );
+
+        // BTC balance on exchange is 1% more than expected
+        let exchange_balances = ExchangeBalancesAndPositions {
+            balances: vec![
+                ExchangeBalance {
+                    currency_code: BalanceManagerBase::btc(),
+                    balance: dec!(1.01),
+                },
+                ExchangeBalance {
+                    currency_code: BalanceManagerBase::eth(),
+                    balance: dec!(10),
+                },
+            ],
+            positions: None,
+            event_time: None,
+        };
+
+        let discrepancies = test_object
+            .balance_manager()
+            .reconcile_with_exchange(exchange_account_id, &exchange_balances);
+        assert_eq!(
+            discrepancies,
+            vec![BalanceDiscrepancy {
+                exchange_account_id,
+                currency_code: BalanceManagerBase::btc(),
+                expected: dec!(1),
+                reported: dec!(1.01),
+            }]
+        );
+        assert_eq!(discrepancies[0].difference(), dec!(0.01));
+
+        test_object
+            .balance_manager()
+            .set_balance_reconciliation(BalanceReconciliation {
+                correct_exchange_balance: true,
+                ..BalanceReconciliation::default()
+            });
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .reconcile_with_exchange(exchange_account_id, &exchange_balances)
+                .len(),
+            1
+        );
+        assert!(test_object
+            .balance_manager()
+            .reconcile_with_exchange(exchange_account_id, &exchange_balances)
+            .is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn set_target_amount_limits_for_several_pairs() {
+        init_logger();
+        let mut test_object = BalanceManagerOrdinal::new();
+
+        let symbol = test_object.balance_manager_base.symbol();
+        let exchange_account_ids = (0..3)
+            .map(|account_number| {
+                ExchangeAccountId::new(BalanceManagerBase::exchange_id().as_str(), account_number)
+            })
+            .collect_vec();
+        let exchanges_by_id = exchange_account_ids
+            .iter()
+            .map(|&exchange_account_id| {
+                let exchange =
+                    get_test_exchange_with_symbol_and_id(symbol.clone(), exchange_account_id).0;
+                (exchange_account_id, exchange)
+            })
+            .collect();
+        test_object
+            .balance_manager_base
+            .set_balance_manager(BalanceManager::new(
+                CurrencyPairToSymbolConverter::new(exchanges_by_id),
+                None,
+            ));
+
+        for &exchange_account_id in &exchange_account_ids {
+            BalanceManagerBase::update_balance(
+                &mut test_object.balance_manager(),
+                exchange_account_id,
+                hashmap![BalanceManagerBase::btc() => dec!(10)],
+            );
+        }
+
+        let configuration_descriptor = test_object.balance_manager_base.configuration_descriptor;
+        let currency_pair = BalanceManagerBase::currency_pair();
+        let limits = [dec!(1), dec!(2), dec!(3)];
+        test_object
+            .balance_manager()
+            .set_target_amount_limits(
+                configuration_descriptor,
+                exchange_account_ids
+                    .iter()
+                    .zip(limits)
+                    .map(|(&exchange_account_id, limit)| {
+                        ((exchange_account_id, currency_pair), limit)
+                    })
+                    .collect(),
+            )
+            .expect("in test");
+
+        let price = dec!(0.2);
+        for (&exchange_account_id, limit) in exchange_account_ids.iter().zip(limits) {
+            let reserve_parameters = ReserveParameters::new(
+                configuration_descriptor,
+                exchange_account_id,
+                symbol.clone(),
+                OrderSide::Buy,
+                price,
+                limit,
+            );
+            assert_eq!(
+                test_object
+                    .balance_manager()
+                    .get_balance_by_reserve_parameters(&reserve_parameters),
+                Some(limit * price)
+            );
+
+            let over_limit_parameters = ReserveParameters::new(
+                configuration_descriptor,
+                exchange_account_id,
+                symbol.clone(),
+                OrderSide::Buy,
+                price,
+                limit + dec!(1),
+            );
+            assert!(test_object
+                .balance_manager()
+                .try_reserve(&over_limit_parameters, &mut None)
+                .is_none());
+            assert!(test_object
+                .balance_manager()
+                .try_reserve(&reserve_parameters, &mut None)
+                .is_some());
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn set_target_amount_limits_with_unknown_pair_applies_nothing() {
+        init_logger();
+        let test_object = create_test_obj_by_currency_code(BalanceManagerBase::btc(), dec!(10));
+
+        let configuration_descriptor = test_object.balance_manager_base.configuration_descriptor;
+        let exchange_account_id = test_object.balance_manager_base.exchange_account_id_1;
+        let unknown_pair =
+            CurrencyPair::from_codes(BalanceManagerBase::bnb(), BalanceManagerBase::btc());
+        let error = test_object
+            .balance_manager()
+            .set_target_amount_limits(
+                configuration_descriptor,
+                hashmap![
+                    (exchange_account_id, BalanceManagerBase::currency_pair()) => dec!(1),
+                    (exchange_account_id, unknown_pair) => dec!(1)
+                ],
+            )
+            .expect_err("in test");
+        assert!(error.to_string().contains(unknown_pair.as_str()));
+
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Buy,
+            dec!(0.2),
+            dec!(2),
+        );
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_balance_by_reserve_parameters(&reserve_parameters),
+            Some(dec!(10))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn clear_target_amount_limit_removes_limit() {
+        init_logger();
+        let test_object = create_test_obj_by_currency_code_with_limit(
+            BalanceManagerBase::btc(),
+            dec!(1),
+            Some(dec!(2)),
+        );
+
+        let price = dec!(0.2);
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Buy,
+            price,
+            dec!(2),
+        );
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_balance_by_reserve_parameters(&reserve_parameters),
+            Some(dec!(2) * price)
+        );
+
+        test_object.balance_manager().clear_target_amount_limit(
+            test_object.balance_manager_base.configuration_descriptor,
+            test_object.balance_manager_base.exchange_account_id_1,
+            test_object.balance_manager_base.symbol(),
+        );
+
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_balance_by_reserve_parameters(&reserve_parameters),
+            Some(dec!(1))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn get_position_changes_in_period_returns_sub_window() {
+        init_logger();
+        let mut test_object = create_eth_btc_test_obj(dec!(10), dec!(0));
+
+        let market_account_id = MarketAccountId::new(
+            test_object.balance_manager_base.exchange_account_id_1,
+            test_object.balance_manager_base.symbol().currency_pair(),
+        );
+
+        let price = dec!(0.2);
+        let mut create_order_with_fill = |side, amount| {
+            let mut order = test_object
+                .balance_manager_base
+                .create_order(side, ReservationId::generate());
+            order.add_fill(BalanceManagerOrdinal::create_order_fill_with_time(
+                price,
+                amount,
+                dec!(2.5),
+                test_object.now,
+            ));
+            order
+        };
+        let mut sell_5 = create_order_with_fill(OrderSide::Sell, dec!(5));
+        let mut sell_10 = create_order_with_fill(OrderSide::Sell, dec!(10));
+        let mut buy_10 = create_order_with_fill(OrderSide::Buy, dec!(10));
+
+        let _order_fill_id_1 = order_was_filled(&mut test_object, &mut sell_5);
+        test_object.timer_add_second();
+        let order_fill_id_2 = order_was_filled(&mut test_object, &mut buy_10);
+        test_object.timer_add_second();
+        let order_fill_id_3 = order_was_filled(&mut test_object, &mut sell_10);
+        test_object.timer_add_second();
+        let _order_fill_id_4 = order_was_filled(&mut test_object, &mut buy_10);
+        check_position(&test_object, dec!(5));
+
+        let seconds = |x| test_object.now + chrono::Duration::seconds(x);
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_position_changes_in_period(&market_account_id, seconds(1), seconds(2)),
+            vec![
+                PositionChange::new(order_fill_id_2, seconds(1), dec!(0.5)),
+                PositionChange::new(order_fill_id_3, seconds(2), dec!(0.5)),
+            ]
+        );
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_position_changes_in_period(&market_account_id, seconds(0), seconds(3))
+                .len(),
+            4
+        );
+        assert!(test_object
+            .balance_manager()
+            .get_position_changes_in_period(&market_account_id, seconds(4), seconds(5))
+            .is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn compact_identical_reservations() {
+        init_logger();
+        let test_object = create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(10));
+
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Sell,
+            dec!(0.2),
+            dec!(1),
+        );
+        let reservation_ids = (0..3)
+            .map(|_| {
+                test_object
+                    .balance_manager()
+                    .try_reserve(&reserve_parameters, &mut None)
+                    .expect("in test")
+            })
+            .collect_vec();
+        let balance_before_compaction = test_object
+            .balance_manager()
+            .get_balance_by_reserve_parameters(&reserve_parameters);
+        assert_eq!(balance_before_compaction, Some(dec!(7)));
+
+        assert_eq!(test_object.balance_manager().compact_reservations(), 2);
+
+        let target_id = *reservation_ids.iter().min().expect("in test");
+        assert_eq!(
+            test_object.balance_manager().get_reservation_ids(),
+            vec![target_id]
+        );
+        let balance_manager = test_object.balance_manager();
+        let reservation = balance_manager.get_reservation_expected(target_id);
+        assert_eq!(reservation.amount, dec!(3));
+        assert_eq!(reservation.unreserved_amount, dec!(3));
+        assert_eq!(reservation.not_approved_amount, dec!(3));
+        assert_eq!(
+            balance_manager.get_balance_by_reserve_parameters(&reserve_parameters),
+            balance_before_compaction
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    pub async fn unreserve_rest_by_merged_reservation_id_fails() {
+        init_logger();
+        let test_object = create_test_obj_by_currency_code(BalanceManagerBase::eth(), dec!(10));
+
+        let reserve_parameters = test_object.balance_manager_base.create_reserve_parameters(
+            OrderSide::Sell,
+            dec!(0.2),
+            dec!(1),
+        );
+        let reservation_ids = (0..3)
+            .map(|_| {
+                test_object
+                    .balance_manager()
+                    .try_reserve(&reserve_parameters, &mut None)
+                    .expect("in test")
+            })
+            .collect_vec();
+        let (target_id, merged_id, approved_id) =
+            (reservation_ids[0], reservation_ids[1], reservation_ids[2]);
+        test_object.balance_manager().approve_reservation(
+            approved_id,
+            &ClientOrderId::unique_id(),
+            dec!(1),
+        );
+
+        // reservation with approved part isn't merged
+        assert_eq!(test_object.balance_manager().compact_reservations(), 1);
+
+        assert!(test_object
+            .balance_manager()
+            .unreserve_rest(merged_id)
+            .is_err());
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_balance_by_reserve_parameters(&reserve_parameters),
+            Some(dec!(7))
+        );
+
+        test_object
+            .balance_manager()
+            .unreserve_rest(target_id)
+            .expect("in test");
+        assert_eq!(
+            test_object.balance_manager().get_reservation_ids(),
+            vec![approved_id]
+        );
+        assert_eq!(
+            test_object
+                .balance_manager()
+                .get_balance_by_reserve_parameters(&reserve_parameters),
+            Some(dec!(9))
+        );
     }
 }
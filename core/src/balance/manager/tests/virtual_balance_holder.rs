@@ -93,6 +93,7 @@ mod tests {
     use rust_decimal::Decimal;
     use rust_decimal_macros::dec;
 
+    use crate::balance::balance_audit::BalanceChangeCause;
     use crate::balance::manager::balance_request::BalanceRequest;
 
     use super::VirtualBalanceHolderTests;
@@ -103,9 +104,11 @@ mod tests {
         balance_to_add: Decimal,
         expect_balance: Option<Decimal>,
     ) {
-        test_obj
-            .virtual_balance_holder
-            .add_balance(balance_request, balance_to_add);
+        test_obj.virtual_balance_holder.add_balance(
+            balance_request,
+            balance_to_add,
+            BalanceChangeCause::Fill,
+        );
         assert_eq!(
             test_obj.virtual_balance_holder.get_virtual_balance(
                 balance_request,
@@ -1,7 +1,10 @@
+pub mod balance_audit;
+pub mod balance_metrics;
 pub(crate) mod balance_position_model;
 pub(crate) mod balance_reservation_manager;
 pub(crate) mod balance_reservation_preset;
 pub(crate) mod balance_reservation_storage;
 pub(crate) mod changes;
 pub mod manager;
+pub mod position_flip_guard;
 pub(crate) mod virtual_balance_holder;
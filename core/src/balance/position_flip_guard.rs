@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use chrono::Duration;
+use mmb_domain::market::MarketAccountId;
+use mockall_double::double;
+use parking_lot::Mutex;
+
+#[double]
+use crate::balance::manager::balance_manager::BalanceManager;
+#[double]
+use crate::exchanges::exchange_blocker::ExchangeBlocker;
+
+use crate::exchanges::{block_reasons::POSITION_FLIPS_EXCEEDED, exchange_blocker::BlockType};
+
+/// Blocks exchange account when position on the market flips between long and short
+/// more than `max_flips` times during `window`. It's a guard against strategies which churn the position
+pub struct PositionFlipGuard {
+    max_flips: usize,
+    window: Duration,
+    target_market_account_id: MarketAccountId,
+    exchange_blocker: Arc<ExchangeBlocker>,
+    balance_manager: Arc<Mutex<BalanceManager>>,
+}
+
+impl PositionFlipGuard {
+    pub fn new(
+        max_flips: usize,
+        window: Duration,
+        target_market_account_id: MarketAccountId,
+        exchange_blocker: Arc<ExchangeBlocker>,
+        balance_manager: Arc<Mutex<BalanceManager>>,
+    ) -> Self {
+        Self {
+            max_flips,
+            window,
+            target_market_account_id,
+            exchange_blocker,
+            balance_manager,
+        }
+    }
+
+    /// Engage block if flips limit is exceeded and release it when flips count is back within the limit.
+    /// Should be called after fills of orders on target market
+    pub fn check(&self) {
+        let flip_count = self
+            .balance_manager
+            .lock()
+            .position_flip_count(&self.target_market_account_id, self.window);
+
+        let exchange_account_id = self.target_market_account_id.exchange_account_id;
+        let is_blocked = self
+            .exchange_blocker
+            .is_blocked_by_reason(exchange_account_id, POSITION_FLIPS_EXCEEDED);
+
+        if flip_count > self.max_flips {
+            if is_blocked {
+                return;
+            }
+
+            log::warn!(
+                "Position on {:?} flipped {} times for {} that exceeded {}",
+                self.target_market_account_id,
+                flip_count,
+                self.window,
+                self.max_flips
+            );
+
+            self.exchange_blocker.block(
+                exchange_account_id,
+                POSITION_FLIPS_EXCEEDED,
+                BlockType::Manual,
+            );
+        } else if is_blocked {
+            log::warn!(
+                "Position on {:?} flipped {} times for {} that is within {}",
+                self.target_market_account_id,
+                flip_count,
+                self.window,
+                self.max_flips
+            );
+
+            self.exchange_blocker
+                .unblock(exchange_account_id, POSITION_FLIPS_EXCEEDED);
+        }
+    }
+}
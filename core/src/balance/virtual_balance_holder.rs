@@ -1,15 +1,21 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::balance::balance_audit::{
+    BalanceAuditRecord, BalanceAuditSink, BalanceAuditTarget, BalanceChangeCause,
+};
 use crate::balance::manager::balance_request::BalanceRequest;
 use crate::exchanges::general::exchange::Exchange;
 use crate::explanation::{Explanation, OptionExplanationAddReasonExt};
 use crate::misc::service_value_tree::ServiceValueTree;
+#[double]
+use crate::misc::time::time_manager;
 use mmb_domain::exchanges::symbol::Symbol;
 use mmb_domain::market::ExchangeAccountId;
 
 use mmb_domain::market::CurrencyCode;
 use mmb_domain::order::snapshot::{Amount, Price};
+use mockall_double::double;
 use rust_decimal_macros::dec;
 
 type BalanceByExchangeId = HashMap<ExchangeAccountId, HashMap<CurrencyCode, Amount>>;
@@ -18,6 +24,8 @@ type BalanceByExchangeId = HashMap<ExchangeAccountId, HashMap<CurrencyCode, Amou
 pub(crate) struct VirtualBalanceHolder {
     balance_by_exchange_id: BalanceByExchangeId,
     balance_diff: ServiceValueTree,
+    log_balance_changes: bool,
+    audit_sink: Option<Arc<dyn BalanceAuditSink>>,
 }
 
 impl VirtualBalanceHolder {
@@ -30,6 +38,38 @@ impl VirtualBalanceHolder {
         Self {
             balance_by_exchange_id,
             balance_diff: ServiceValueTree::default(),
+            log_balance_changes: true,
+            audit_sink: None,
+        }
+    }
+
+    /// Enable or disable logging of every balance change (it happens on each reservation and fill)
+    pub fn set_log_balance_changes(&mut self, log_balance_changes: bool) {
+        self.log_balance_changes = log_balance_changes;
+    }
+
+    pub fn set_audit_sink(&mut self, audit_sink: Option<Arc<dyn BalanceAuditSink>>) {
+        self.audit_sink = audit_sink;
+    }
+
+    /// Pass record to audit sink if it's set
+    pub fn audit(
+        &self,
+        target: BalanceAuditTarget,
+        request: &BalanceRequest,
+        delta: Amount,
+        resulting_value: Amount,
+        cause: BalanceChangeCause,
+    ) {
+        if let Some(audit_sink) = &self.audit_sink {
+            audit_sink.record(BalanceAuditRecord {
+                target,
+                request: request.clone(),
+                delta,
+                resulting_value,
+                time: time_manager::now(),
+                cause,
+            });
         }
     }
 
@@ -41,11 +81,13 @@ impl VirtualBalanceHolder {
         self.balance_by_exchange_id
             .insert(exchange_account_id, balances_by_currency_code.clone());
 
-        log::info!(
-            "VirtualBalanceHolder::update_balances {} {:?}",
-            exchange_account_id,
-            balances_by_currency_code
-        );
+        if self.log_balance_changes {
+            tracing::info!(
+                "VirtualBalanceHolder::update_balances {} {:?}",
+                exchange_account_id,
+                balances_by_currency_code
+            );
+        }
 
         let all_diffs = self.balance_diff.get_as_balances();
         for currency_code in balances_by_currency_code.keys() {
@@ -55,17 +97,33 @@ impl VirtualBalanceHolder {
                 {
                     self.balance_diff
                         .set_by_balance_request(balance_request, dec!(0));
-                    log::info!(
-                        "VirtualBalanceHolder::update_balances Reset {} {}",
-                        balance_request.exchange_account_id,
-                        balance_request.currency_code
-                    );
+                    if self.log_balance_changes {
+                        tracing::info!(
+                            "VirtualBalanceHolder::update_balances Reset {} {}",
+                            balance_request.exchange_account_id,
+                            balance_request.currency_code
+                        );
+                    }
                 }
             }
         }
     }
 
-    pub fn add_balance(&mut self, balance_request: &BalanceRequest, balance_to_add: Amount) {
+    /// Replace all balance diffs, e.g. by ones saved with balance state before restart
+    pub fn set_virtual_balance_diffs(&mut self, balance_diffs: ServiceValueTree) {
+        if self.log_balance_changes {
+            tracing::info!("VirtualBalanceHolder::set_virtual_balance_diffs {balance_diffs:?}");
+        }
+
+        self.balance_diff = balance_diffs;
+    }
+
+    pub fn add_balance(
+        &mut self,
+        balance_request: &BalanceRequest,
+        balance_to_add: Amount,
+        cause: BalanceChangeCause,
+    ) {
         let current_diff_value = self
             .balance_diff
             .get_by_balance_request(balance_request)
@@ -75,23 +133,56 @@ impl VirtualBalanceHolder {
         self.balance_diff
             .set_by_balance_request(balance_request, new_value);
 
-        log::info!(
-            "VirtualBalanceHolder::add_balance {} {} {} {} {} {}",
-            balance_request.exchange_account_id,
-            balance_request.currency_pair,
-            balance_request.currency_code,
-            current_diff_value,
+        if self.log_balance_changes {
+            tracing::info!(
+                "VirtualBalanceHolder::add_balance {} {} {} {} {} {}",
+                balance_request.exchange_account_id,
+                balance_request.currency_pair,
+                balance_request.currency_code,
+                current_diff_value,
+                balance_to_add,
+                new_value
+            );
+        }
+
+        self.audit(
+            BalanceAuditTarget::VirtualBalance,
+            balance_request,
             balance_to_add,
-            new_value
+            new_value,
+            cause,
         );
     }
 
+    /// Change balance received from exchange, e.g. by settlement which doesn't belong to any order
+    pub fn add_exchange_balance(
+        &mut self,
+        exchange_account_id: ExchangeAccountId,
+        currency_code: CurrencyCode,
+        balance_to_add: Amount,
+    ) {
+        let balance = self
+            .balance_by_exchange_id
+            .entry(exchange_account_id)
+            .or_default()
+            .entry(currency_code)
+            .or_insert(dec!(0));
+        *balance += balance_to_add;
+
+        if self.log_balance_changes {
+            tracing::info!(
+                "VirtualBalanceHolder::add_exchange_balance {exchange_account_id} {currency_code} {balance_to_add} {balance}"
+            );
+        }
+    }
+
     pub fn add_balance_by_symbol(
         &mut self,
         request: &BalanceRequest,
         symbol: Arc<Symbol>,
         diff_in_amount_currency: Amount,
         price: Price,
+        cause: BalanceChangeCause,
     ) {
         if !symbol.is_derivative {
             let diff_in_request_currency = symbol.convert_amount_from_amount_currency_code(
@@ -99,7 +190,7 @@ impl VirtualBalanceHolder {
                 diff_in_amount_currency,
                 price,
             );
-            self.add_balance(request, diff_in_request_currency);
+            self.add_balance(request, diff_in_request_currency, cause);
         } else {
             let balance_currency_code_request = BalanceRequest::new(
                 request.configuration_descriptor,
@@ -117,6 +208,7 @@ impl VirtualBalanceHolder {
             self.add_balance(
                 &balance_currency_code_request,
                 diff_in_balance_currency_code,
+                cause,
             );
         }
     }
@@ -220,6 +312,27 @@ impl VirtualBalanceHolder {
         &self.balance_diff
     }
 
+    /// Exchange balances with applied diffs for all currencies of exchange account.
+    /// Diffs are summed over all configuration descriptors and currency pairs
+    pub fn get_virtual_balances(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+    ) -> HashMap<CurrencyCode, Amount> {
+        let mut balances = self
+            .balance_by_exchange_id
+            .get(&exchange_account_id)
+            .cloned()
+            .unwrap_or_default();
+
+        for (request, diff) in self.balance_diff.get_as_balances() {
+            if request.exchange_account_id == exchange_account_id {
+                *balances.entry(request.currency_code).or_default() += diff;
+            }
+        }
+
+        balances
+    }
+
     pub fn has_real_balance_on_exchange(&self, exchange_account_id: ExchangeAccountId) -> bool {
         self.balance_by_exchange_id
             .get(&exchange_account_id)
@@ -0,0 +1,50 @@
+use crate::database::events::recorder::EventRecorder;
+use anyhow::Result;
+use mmb_domain::order::fill::OrderFill;
+use mmb_domain::order::snapshot::OrderSnapshot;
+use parking_lot::Mutex;
+
+/// Storage for orders and fills which can be replaced to check what is recorded, e.g. in tests
+pub trait DataRecorder: Send + Sync {
+    fn save_order(&self, order: &OrderSnapshot) -> Result<()>;
+    fn save_fill(&self, fill: &OrderFill) -> Result<()>;
+}
+
+impl DataRecorder for EventRecorder {
+    fn save_order(&self, order: &OrderSnapshot) -> Result<()> {
+        self.save(&mut order.clone())
+    }
+
+    fn save_fill(&self, fill: &OrderFill) -> Result<()> {
+        self.save(fill.clone())
+    }
+}
+
+/// Keeps recorded orders and fills in memory in order of saving
+#[derive(Default)]
+pub struct InMemoryDataRecorder {
+    orders: Mutex<Vec<OrderSnapshot>>,
+    fills: Mutex<Vec<OrderFill>>,
+}
+
+impl InMemoryDataRecorder {
+    pub fn orders(&self) -> Vec<OrderSnapshot> {
+        self.orders.lock().clone()
+    }
+
+    pub fn fills(&self) -> Vec<OrderFill> {
+        self.fills.lock().clone()
+    }
+}
+
+impl DataRecorder for InMemoryDataRecorder {
+    fn save_order(&self, order: &OrderSnapshot) -> Result<()> {
+        self.orders.lock().push(order.clone());
+        Ok(())
+    }
+
+    fn save_fill(&self, fill: &OrderFill) -> Result<()> {
+        self.fills.lock().push(fill.clone());
+        Ok(())
+    }
+}
@@ -1 +1,2 @@
+pub mod data_recorder;
 pub mod events;
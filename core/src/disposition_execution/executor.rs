@@ -36,9 +36,7 @@ use mmb_domain::market::{ExchangeAccountId, MarketAccountId};
 use mmb_domain::order::event::OrderEventType;
 use mmb_domain::order::pool::OrderRef;
 use mmb_domain::order::snapshot::{Amount, Price, UserOrder};
-use mmb_domain::order::snapshot::{
-    ClientOrderId, OrderHeader, OrderSide, OrderSnapshot, OrderStatus,
-};
+use mmb_domain::order::snapshot::{OrderHeader, OrderSide, OrderSnapshot, OrderStatus};
 use mmb_utils::cancellation_token::CancellationToken;
 
 static DISPOSITION_EXECUTOR: &str = "DispositionExecutor";
@@ -246,6 +244,17 @@ impl DispositionExecutor {
                             cloned_order.header.client_order_id
                         );
                     }
+                    OrderEventType::FilledAfterCancel { ref cloned_order } => {
+                        // order was already finished for strategy, so only balances are updated
+                        log::warn!(
+                            "Order {} was filled after cancellation",
+                            cloned_order.header.client_order_id
+                        );
+                        self.engine_ctx.balance_manager.lock().order_was_filled(
+                            self.strategy.configuration_descriptor(),
+                            cloned_order,
+                        );
+                    }
                     OrderEventType::CancelOrderSucceeded => {
                         let client_order_id = order.client_order_id();
                         log::trace!("Started handling event CancelOrderSucceeded {client_order_id} in DispositionExecutor");
@@ -616,7 +625,7 @@ impl DispositionExecutor {
             );
         }
 
-        let new_client_order_id = ClientOrderId::unique_id();
+        let new_client_order_id = self.exchange().generate_client_order_id();
 
         let requests_group_id = self.engine_ctx.timeout_manager.try_reserve_group(
             self.exchange_account_id,
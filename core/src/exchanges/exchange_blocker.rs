@@ -830,6 +830,27 @@ impl ExchangeBlocker {
         log::trace!("ExchangeBlocker::stop_blocker() started");
         self.events_processor.stop_processing().await;
     }
+
+    /// Remove all blockers at once releasing all `wait_unblock` waiters and stop events processing.
+    /// Processing is stopped before removing, so pending internal events are dropped together with
+    /// events channel and can't refer to removed blockers. Handlers aren't called for forced unblocking.
+    pub async fn force_unblock_all_and_stop(&self) {
+        log::trace!("ExchangeBlocker::force_unblock_all_and_stop() started");
+        self.events_processor.stop_processing().await;
+
+        for (exchange_account_id, blockers) in self.blockers.write().iter_mut() {
+            for (reason, blocker) in blockers.drain() {
+                if let Timeout::InProgress { in_progress } = &*blocker.timeout.lock() {
+                    in_progress.timer_handle.abort();
+                }
+
+                blocker.unblocked_notify.notify_waiters();
+                log::trace!("Forced unblock {exchange_account_id} {reason} in ExchangeBlocker");
+            }
+        }
+
+        log::trace!("ExchangeBlocker::force_unblock_all_and_stop() finished");
+    }
 }
 
 impl_mock_initializer!(MockExchangeBlocker);
@@ -1148,6 +1169,53 @@ mod tests {
         with_timeout(Duration::from_millis(100), exchange_blocker.stop_blocker()).await;
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[timeout(120_000)]
+    async fn force_unblock_all_and_stop_releases_waiters() {
+        let _ = init_lifetime_manager();
+        let exchange_blocker = &exchange_blocker();
+
+        exchange_blocker.block(exchange_account_id(), "reason1".into(), Manual);
+        exchange_blocker.block(
+            exchange_account_id(),
+            "reason2".into(),
+            Timed(Duration::from_secs(60)),
+        );
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        let _ = spawn_future_ok(
+            "Run wait_unblock in force_unblock_all_and_stop_releases_waiters test",
+            SpawnFutureFlags::DENY_CANCELLATION | SpawnFutureFlags::STOP_BY_TOKEN,
+            {
+                let exchange_blocker = exchange_blocker.clone();
+                async move {
+                    exchange_blocker
+                        .wait_unblock(exchange_account_id(), CancellationToken::new())
+                        .await;
+                    tx.send_expected(());
+                }
+            },
+        );
+
+        sleep(Duration::from_millis(10)).await;
+        assert!(rx.try_recv().is_err());
+
+        with_timeout(
+            Duration::from_millis(100),
+            exchange_blocker.force_unblock_all_and_stop(),
+        )
+        .await;
+
+        with_timeout(Duration::from_secs(1), rx.recv()).await;
+        assert!(!exchange_blocker.is_blocked(exchange_account_id()));
+
+        // blocking still applies after stop, but unblocking isn't finished because events aren't processed anymore
+        exchange_blocker.block(exchange_account_id(), "reason1".into(), Manual);
+        exchange_blocker.unblock(exchange_account_id(), "reason1".into());
+        sleep(Duration::from_millis(1)).await;
+        assert!(exchange_blocker.is_blocked(exchange_account_id()));
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     #[timeout(120_000)]
     async fn block_with_handler_after_stop() {
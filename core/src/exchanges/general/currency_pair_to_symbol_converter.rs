@@ -1,11 +1,14 @@
 #[cfg(test)]
 use crate::MOCK_MUTEX;
+use anyhow::{Context, Result};
 use mmb_utils::impl_mock_initializer;
 use mmb_utils::infrastructure::WithExpect;
 #[cfg(test)]
 use mockall::automock;
 
+use dashmap::DashMap;
 use std::collections::HashMap;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use crate::exchanges::general::exchange::Exchange;
@@ -16,12 +19,18 @@ use mmb_domain::market::ExchangeAccountId;
 #[derive(Clone)]
 pub struct CurrencyPairToSymbolConverter {
     exchanges_by_id: HashMap<ExchangeAccountId, Arc<Exchange>>,
+    /// Read-through cache of symbols because they are requested on hot paths (e.g. getting position).
+    /// Cached symbol is valid while `symbols_version` of exchange is the same as on caching
+    symbols_cache: DashMap<(ExchangeAccountId, CurrencyPair), (u64, Arc<Symbol>)>,
 }
 
 #[cfg_attr(test, automock)]
 impl CurrencyPairToSymbolConverter {
     pub fn new(exchanges_by_id: HashMap<ExchangeAccountId, Arc<Exchange>>) -> Arc<Self> {
-        Arc::new(Self { exchanges_by_id })
+        Arc::new(Self {
+            exchanges_by_id,
+            symbols_cache: DashMap::new(),
+        })
     }
 
     pub(crate) fn get_symbol(
@@ -38,15 +47,133 @@ impl CurrencyPairToSymbolConverter {
                     exchange_account_id
                 )
             });
-        exchange
-            .get_symbol(currency_pair)
+        self.get_symbol_cached(exchange, currency_pair)
             .expect("failed to get currency pair")
     }
 
+    pub(crate) fn try_get_symbol(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+        currency_pair: CurrencyPair,
+    ) -> Result<Arc<Symbol>> {
+        let exchange = self
+            .exchanges_by_id
+            .get(&exchange_account_id)
+            .with_context(|| format!("Failed to get exchange by id: {exchange_account_id}"))?;
+        self.get_symbol_cached(exchange, currency_pair)
+    }
+
     #[allow(dead_code)]
     pub(crate) fn exchanges_by_id(&self) -> &HashMap<ExchangeAccountId, Arc<Exchange>> {
         &self.exchanges_by_id
     }
 }
 
+impl CurrencyPairToSymbolConverter {
+    fn get_symbol_cached(
+        &self,
+        exchange: &Exchange,
+        currency_pair: CurrencyPair,
+    ) -> Result<Arc<Symbol>> {
+        let key = (exchange.exchange_account_id, currency_pair);
+        let symbols_version = exchange.symbols_version.load(Ordering::SeqCst);
+        if let Some(cached) = self.symbols_cache.get(&key) {
+            let (cached_version, symbol) = cached.value();
+            if *cached_version == symbols_version {
+                return Ok(symbol.clone());
+            }
+        }
+
+        let symbol = exchange.get_symbol(currency_pair)?;
+        let _ = self
+            .symbols_cache
+            .insert(key, (symbols_version, symbol.clone()));
+        Ok(symbol)
+    }
+}
+
 impl_mock_initializer!(MockCurrencyPairToSymbolConverter);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use mmb_domain::exchanges::symbol::{Precision, Symbol};
+    use mmb_domain::market::{CurrencyPair, ExchangeAccountId};
+    use rust_decimal_macros::dec;
+
+    use crate::exchanges::general::features::OpenOrdersType;
+    use crate::exchanges::general::test_helper::{get_test_exchange_with_client, TestClient};
+
+    use super::CurrencyPairToSymbolConverter;
+
+    fn create_symbol() -> Arc<Symbol> {
+        Arc::new(Symbol::new(
+            false,
+            "PHB".into(),
+            "PHB".into(),
+            "BTC".into(),
+            "BTC".into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            "PHB".into(),
+            None,
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0) },
+        ))
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn symbols_cache_invalidated_on_refresh() {
+        let symbol = create_symbol();
+        let rebuilt_symbol = create_symbol();
+        let exchange_client = TestClient {
+            build_all_symbols: Some(Box::new({
+                let rebuilt_symbol = rebuilt_symbol.clone();
+                move || Ok(vec![rebuilt_symbol.clone()])
+            })),
+            ..TestClient::default()
+        };
+        let exchange_account_id = ExchangeAccountId::new("local_exchange_account_id", 0);
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            symbol.clone(),
+            exchange_account_id,
+            exchange_client,
+            OpenOrdersType::AllCurrencyPair,
+        );
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+
+        let converter = CurrencyPairToSymbolConverter::new(HashMap::from([(
+            exchange_account_id,
+            exchange.clone(),
+        )]));
+
+        let first = converter.get_symbol(exchange_account_id, currency_pair);
+        assert!(Arc::ptr_eq(&first, &symbol));
+
+        // symbol replaced on exchange without refreshing, so converter keeps returning cached one
+        let _ = exchange.symbols.insert(currency_pair, create_symbol());
+        let cached = converter
+            .try_get_symbol(exchange_account_id, currency_pair)
+            .expect("in test");
+        assert!(Arc::ptr_eq(&cached, &symbol));
+
+        exchange
+            .refresh_symbol_metadata(currency_pair)
+            .await
+            .expect("in test");
+
+        let refreshed = converter
+            .try_get_symbol(exchange_account_id, currency_pair)
+            .expect("in test");
+        assert!(Arc::ptr_eq(&refreshed, &rebuilt_symbol));
+        assert!(Arc::ptr_eq(
+            &converter.get_symbol(exchange_account_id, currency_pair),
+            &rebuilt_symbol
+        ));
+    }
+}
@@ -3,21 +3,30 @@ use crate::balance::manager::balance_manager::BalanceManager;
 use crate::connectivity::{
     websocket_open, ConnectivityError, WebSocketParams, WebSocketRole, WsSender,
 };
+use crate::database::data_recorder::DataRecorder;
 use crate::database::events::recorder::EventRecorder;
 use crate::exchanges::block_reasons::WEBSOCKET_DISCONNECTED;
 use crate::exchanges::exchange_blocker::{BlockType, ExchangeBlocker};
-use crate::exchanges::general::features::ExchangeFeatures;
+use crate::exchanges::general::features::{ExchangeFeatures, RestFillsType};
+use crate::exchanges::general::handlers::handle_order_filled::FillEvent;
 use crate::exchanges::general::order::cancel::CancelOrderResult;
 use crate::exchanges::general::order::create::CreateOrderResult;
 use crate::exchanges::general::request_type::RequestType;
+use crate::exchanges::general::trades_arrival::TradesArrival;
+use crate::exchanges::timeouts::requests_timeout_manager::RequestBudget;
 use crate::exchanges::timeouts::requests_timeout_manager_factory::RequestTimeoutArguments;
 use crate::exchanges::timeouts::timeout_manager::TimeoutManager;
 use crate::exchanges::traits::{ExchangeClient, ExchangeError};
+use crate::exchanges::websocket_disconnects_guard::WebsocketDisconnectsGuard;
 use crate::infrastructure::spawn_future;
 use crate::lifecycle::app_lifetime_manager::AppLifetimeManager;
-use crate::misc::time::time_manager;
+use crate::misc::time::{time_manager, Clock, SystemClock};
 use crate::orders::buffered_fills::buffered_canceled_orders_manager::BufferedCanceledOrdersManager;
 use crate::orders::buffered_fills::buffered_fills_manager::BufferedFillsManager;
+use crate::services::usd_convertion::price_source_service::PriceSourceService;
+use crate::settings::{
+    ConvertedCommissionRounding, FillTypeMismatchReaction, WebsocketDisconnectsLimit,
+};
 use anyhow::{bail, Context, Result};
 use dashmap::DashMap;
 use function_name::named;
@@ -25,8 +34,9 @@ use futures::future::join_all;
 use itertools::Itertools;
 use mmb_database::impl_event;
 use mmb_domain::events::{
-    BalanceUpdateEvent, ExchangeBalancesAndPositions, ExchangeEvent, LiquidationPriceEvent,
-    MetricsEvent, MetricsEventInfo, MetricsEventInfoBase, MetricsEventType, MetricsTime, Trade,
+    BalanceUpdateEvent, ExchangeBalancesAndPositions, ExchangeEvent, LatestBalanceUpdates,
+    LiquidationPriceEvent, MetricsEvent, MetricsEventInfo, MetricsEventInfoBase, MetricsEventType,
+    MetricsTimeSource, Trade,
 };
 use mmb_domain::exchanges::commission::Commission;
 use mmb_domain::exchanges::symbol::Symbol;
@@ -37,25 +47,30 @@ use mmb_domain::order::event::OrderEvent;
 use mmb_domain::order::event::OrderEventType;
 use mmb_domain::order::pool::OrderRef;
 use mmb_domain::order::pool::OrdersPool;
-use mmb_domain::order::snapshot::OrderSide;
 use mmb_domain::order::snapshot::{Amount, Price};
 use mmb_domain::order::snapshot::{ClientOrderId, ExchangeOrderId};
+use mmb_domain::order::snapshot::{OrderHeader, OrderRole, OrderSide, OrderStatus, UserOrder};
 use mmb_domain::position::{ActivePosition, ClosedPosition, DerivativePosition};
 use mmb_utils::cancellation_token::CancellationToken;
 use mmb_utils::infrastructure::{SpawnFutureFlags, WithExpect};
 use mmb_utils::send_expected::SendExpectedByRef;
 use mmb_utils::{nothing_to_do, DateTime};
 use parking_lot::Mutex;
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
 use serde::Serialize;
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::ops::DerefMut;
-use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Weak};
 use std::time::Duration;
-use tokio::sync::{broadcast, oneshot};
+use tokio::sync::{broadcast, oneshot, watch};
 use tokio::time::sleep;
 
+/// Strategy name of orders built by exchange itself to close positions
+pub const CLOSE_POSITION_STRATEGY_NAME: &str = "ClosePosition";
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum RequestResult<T> {
     Success(T),
@@ -83,6 +98,24 @@ pub struct OrderBookTop {
     pub bid: Option<PriceLevel>,
 }
 
+/// Value of exchange account balances in USD
+#[derive(Debug, Default)]
+pub struct PortfolioValue {
+    pub total: Amount,
+    /// Currencies with non-zero balance which can't be converted to USD
+    pub skipped_currencies: Vec<CurrencyCode>,
+}
+
+/// State of websocket connection of exchange account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Disconnected,
+    Connecting,
+    /// Connection is restored, orders state is synchronizing with exchange after the gap
+    Resyncing,
+    Connected,
+}
+
 #[derive(Serialize)]
 struct LiquidationPrice(Price);
 impl_event!(LiquidationPrice, "liquidation_prices");
@@ -90,6 +123,8 @@ impl_event!(LiquidationPrice, "liquidation_prices");
 pub struct Exchange {
     pub exchange_account_id: ExchangeAccountId,
     pub symbols: DashMap<CurrencyPair, Arc<Symbol>>,
+    /// Incremented each time `symbols` are replaced, so symbols cached outside of exchange can be invalidated
+    pub(crate) symbols_version: AtomicU64,
     /// Actualised orders data for active order and some late cached orders
     pub orders: Arc<OrdersPool>,
     pub currencies: Mutex<Vec<CurrencyCode>>,
@@ -112,6 +147,11 @@ pub struct Exchange {
     pub(crate) balance_manager: Mutex<Option<Weak<Mutex<BalanceManager>>>>,
     pub(super) buffered_fills_manager: Mutex<BufferedFillsManager>,
     pub(super) buffered_canceled_orders_manager: Mutex<BufferedCanceledOrdersManager>,
+    /// Fills of orders taken over manually, they are applied after the order is unfrozen
+    pub(super) frozen_orders: DashMap<ClientOrderId, Vec<FillEvent>>,
+    /// Fills aren't applied while processing is paused, they are buffered in `buffered_fills_manager`
+    pub(super) fill_processing_enabled: AtomicBool,
+    pub(super) trades_arrival: TradesArrival,
     // It allows to send and receive notification about event in websocket channel
     // Websocket event is main source detecting order creation result
     // Rest response using only for unsuccessful operations as error
@@ -133,11 +173,27 @@ pub struct Exchange {
     exchange_blocker: Weak<ExchangeBlocker>,
     ws_sender: Mutex<Option<WsSender>>,
     auto_reconnect: AtomicBool,
+    was_connected: AtomicBool,
+    connection_state: watch::Sender<ConnectionState>,
 
     // Temporary fix before integration ExchangeBlocker to wait_order_finish/wait_cancel_order fallbacks #641
     timeout: Duration,
-    // Equal 0 by default in case if we cannot get exchange server time
-    server_time_latency: AtomicI64,
+    // Offset equals 0 by default in case if we cannot get exchange server time
+    metrics_time_source: MetricsTimeSource,
+    client_order_id_prefix: Mutex<Option<String>>,
+    websocket_disconnects_guard: Mutex<Option<WebsocketDisconnectsGuard>>,
+    pub(super) fill_type_mismatch_reaction: Mutex<FillTypeMismatchReaction>,
+    pub(super) commissionless_fill_role: Mutex<Option<OrderRole>>,
+    pub(super) was_commissionless_fill_logged: AtomicBool,
+    pub(super) converted_commission_rounding: Mutex<ConvertedCommissionRounding>,
+    pub(super) amount_margin_error_in_ticks: Mutex<Option<Decimal>>,
+    pub(super) price_source_service: Mutex<Option<Arc<PriceSourceService>>>,
+    cancel_ack_latencies: Mutex<VecDeque<i64>>,
+    latest_balance_updates: Mutex<LatestBalanceUpdates>,
+    clock: Mutex<Arc<dyn Clock>>,
+    data_recorder: Mutex<Arc<dyn DataRecorder>>,
+    /// Reference to itself for background futures started by the exchange
+    pub(super) exchange_weak: Weak<Exchange>,
     pub event_recorder: Arc<EventRecorder>,
 }
 
@@ -177,6 +233,7 @@ impl Exchange {
                 timeout_manager,
                 commission,
                 symbols: Default::default(),
+                symbols_version: AtomicU64::new(0),
                 currencies: Default::default(),
                 order_book_top: Default::default(),
                 wait_cancel_order: DashMap::new(),
@@ -192,9 +249,27 @@ impl Exchange {
                 buffered_fills_manager: Default::default(),
                 exchange_blocker,
                 buffered_canceled_orders_manager: Default::default(),
+                frozen_orders: DashMap::new(),
+                fill_processing_enabled: AtomicBool::new(true),
+                trades_arrival: Default::default(),
                 auto_reconnect: AtomicBool::new(false),
+                was_connected: AtomicBool::new(false),
+                connection_state: watch::channel(ConnectionState::Disconnected).0,
                 timeout,
-                server_time_latency: Default::default(),
+                metrics_time_source: Default::default(),
+                client_order_id_prefix: Mutex::new(None),
+                websocket_disconnects_guard: Mutex::new(None),
+                fill_type_mismatch_reaction: Mutex::new(FillTypeMismatchReaction::default()),
+                commissionless_fill_role: Mutex::new(None),
+                was_commissionless_fill_logged: AtomicBool::new(false),
+                converted_commission_rounding: Mutex::new(ConvertedCommissionRounding::default()),
+                amount_margin_error_in_ticks: Mutex::new(None),
+                price_source_service: Mutex::new(None),
+                cancel_ack_latencies: Mutex::new(VecDeque::new()),
+                latest_balance_updates: Mutex::new(LatestBalanceUpdates::default()),
+                clock: Mutex::new(Arc::new(SystemClock)),
+                data_recorder: Mutex::new(event_recorder.clone()),
+                exchange_weak: e.clone(),
                 event_recorder,
             }
         })
@@ -284,6 +359,9 @@ impl Exchange {
             return;
         }
 
+        self.connection_state
+            .send_replace(ConnectionState::Connecting);
+
         let callback_outcome = self.exchange_client.on_connecting();
         if let Err(error) = callback_outcome {
             log::warn!(
@@ -314,6 +392,9 @@ impl Exchange {
             self.exchange_account_id
         );
 
+        self.connection_state
+            .send_replace(ConnectionState::Disconnected);
+
         self.exchange_client
             .on_disconnected()
             .unwrap_or_else(|err| {
@@ -329,6 +410,10 @@ impl Exchange {
                 WEBSOCKET_DISCONNECTED,
                 BlockType::Manual,
             );
+
+            if self.engage_kill_switch_if_needed(&x) {
+                return;
+            }
         }
 
         // auto reconnect
@@ -349,6 +434,44 @@ impl Exchange {
         spawn_future(&action, SpawnFutureFlags::STOP_BY_TOKEN, future);
     }
 
+    /// Stop reconnecting and flatten positions if websocket disconnects too often.
+    /// Return true if kill switch was engaged
+    fn engage_kill_switch_if_needed(
+        self: &Arc<Self>,
+        exchange_blocker: &Arc<ExchangeBlocker>,
+    ) -> bool {
+        let is_engaged = match self.websocket_disconnects_guard.lock().as_mut() {
+            Some(guard) => guard.on_disconnected(
+                self.exchange_account_id,
+                time_manager::now(),
+                exchange_blocker,
+            ),
+            None => false,
+        };
+
+        if !is_engaged {
+            return false;
+        }
+
+        self.auto_reconnect.store(false, Ordering::SeqCst);
+
+        if self.exchange_client.get_settings().is_margin_trading {
+            let action = format!(
+                "Close active positions of {} by kill switch",
+                self.exchange_account_id
+            );
+            let future = self
+                .clone()
+                .close_active_positions(self.lifetime_manager.stop_token());
+            spawn_future(&action, SpawnFutureFlags::STOP_BY_TOKEN, async move {
+                future.await;
+                Ok(())
+            });
+        }
+
+        true
+    }
+
     fn maybe_log_websocket_message(&self, msg: &str) {
         if self.exchange_client.should_log_message(msg) {
             log::info!("Websocket message from {}: {msg}", self.exchange_account_id);
@@ -383,7 +506,7 @@ impl Exchange {
                     SpawnFutureFlags::STOP_BY_TOKEN,
                     Self::reader_future(Arc::downgrade(self), reader),
                 );
-                self.on_connected();
+                self.on_connection_established().await;
                 Ok(())
             }
             Err(e) => {
@@ -393,6 +516,139 @@ impl Exchange {
         }
     }
 
+    async fn on_connection_established(&self) {
+        self.on_connected();
+
+        if self.was_connected.swap(true, Ordering::SeqCst) {
+            if let Err(error) = self.resync_after_reconnect().await {
+                log::error!(
+                    "Failed to resync orders after reconnect on {}: {error:?}",
+                    self.exchange_account_id
+                );
+            }
+        }
+
+        self.connection_state
+            .send_replace(ConnectionState::Connected);
+    }
+
+    /// Websocket channels are resubscribed by connecting itself because they are part of websocket params,
+    /// so here only orders are synchronized to reconcile fills missed while websocket was disconnected
+    async fn resync_after_reconnect(&self) -> Result<()> {
+        self.connection_state
+            .send_replace(ConnectionState::Resyncing);
+
+        log::info!(
+            "Resyncing orders after reconnect on {}",
+            self.exchange_account_id
+        );
+
+        let _ = self
+            .get_open_orders(true)
+            .await
+            .context("Failed to get open orders after reconnect")?;
+
+        let active_orders = self
+            .orders
+            .not_finished
+            .iter()
+            .filter(|order| {
+                order.status() == OrderStatus::Created && order.exchange_order_id().is_some()
+            })
+            .map(|order| order.clone())
+            .collect_vec();
+
+        match self.features.rest_fills_features.fills_type {
+            RestFillsType::None => nothing_to_do(),
+            RestFillsType::MyTrades => {
+                let orders_by_currency_pair = active_orders
+                    .into_iter()
+                    .into_group_map_by(|x| x.currency_pair());
+                for (currency_pair, orders) in orders_by_currency_pair {
+                    self.reconcile_missed_trades(currency_pair, &orders).await?;
+                }
+            }
+            RestFillsType::GetOrderInfo => {
+                for order in active_orders {
+                    let symbol = self.get_symbol(order.currency_pair())?;
+                    let _ = self
+                        .check_order_fills_using_request_type(
+                            &order,
+                            &symbol,
+                            RequestType::GetOrderInfo,
+                            None,
+                            self.lifetime_manager.stop_token(),
+                        )
+                        .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn reconcile_missed_trades(
+        &self,
+        currency_pair: CurrencyPair,
+        orders: &[OrderRef],
+    ) -> Result<()> {
+        let symbol = self.get_symbol(currency_pair)?;
+
+        self.timeout_manager
+            .reserve_when_available(
+                self.exchange_account_id,
+                RequestType::GetMyTrades,
+                None,
+                self.lifetime_manager.stop_token(),
+            )
+            .await
+            .into_result()?;
+
+        let my_trades = match self.exchange_client.get_my_trades(&symbol, None).await {
+            RequestResult::Success(my_trades) => my_trades,
+            RequestResult::Error(error) => bail!(
+                "Failed to get trades for {currency_pair} on {} after reconnect: {error:?}",
+                self.exchange_account_id
+            ),
+        };
+
+        for order_trade in my_trades {
+            let order = orders
+                .iter()
+                .find(|x| x.exchange_order_id().as_ref() == Some(&order_trade.exchange_order_id));
+            let order = match order {
+                Some(order) => order,
+                None => continue,
+            };
+
+            let trade_id = Some(&order_trade.trade_id);
+            let is_fill_exists =
+                order.fn_ref(|o| o.fills.fills.iter().any(|fill| fill.trade_id() == trade_id));
+            if is_fill_exists {
+                continue;
+            }
+
+            log::info!(
+                "Reconciling fill {} of order {} missed while disconnected on {}",
+                order_trade.trade_id,
+                order.client_order_id(),
+                self.exchange_account_id
+            );
+            self.handle_order_filled_for_rest_fallback(order, &order_trade);
+        }
+
+        Ok(())
+    }
+
+    pub fn connection_state(&self) -> ConnectionState {
+        *self.connection_state.borrow()
+    }
+
+    /// Subscribe to changes of websocket connection state
+    pub fn subscribe_connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.connection_state.subscribe()
+    }
+
     /// Read websocket messages and forward to upstream callbacks
     async fn reader_future(
         instance: Weak<Self>,
@@ -514,6 +770,202 @@ impl Exchange {
         Ok(())
     }
 
+    /// Set prefix of client order ids for orders created by bot.
+    /// Only ASCII alphanumeric characters are kept and prefix is shortened to leave space for unique part of id
+    pub fn set_client_order_id_prefix(&self, prefix: Option<String>) {
+        // space for unique part of id which is u64 number
+        const UNIQUE_PART_MAX_LEN: usize = 20;
+
+        let prefix = prefix.map(|prefix| {
+            let max_prefix_len = self
+                .exchange_client
+                .max_client_order_id_len()
+                .saturating_sub(UNIQUE_PART_MAX_LEN);
+            let sanitized: String = prefix
+                .chars()
+                .filter(|x| x.is_ascii_alphanumeric())
+                .take(max_prefix_len)
+                .collect();
+
+            if sanitized != prefix {
+                log::warn!(
+                    "Client order id prefix {prefix} was changed to {sanitized} to satisfy limits of {}",
+                    self.exchange_account_id
+                );
+            }
+
+            sanitized
+        });
+
+        *self.client_order_id_prefix.lock() = prefix.filter(|x| !x.is_empty());
+    }
+
+    /// Set limit of websocket disconnects after which exchange account is blocked and positions are closed
+    pub fn set_websocket_disconnects_limit(&self, limit: Option<&WebsocketDisconnectsLimit>) {
+        *self.websocket_disconnects_guard.lock() = limit.map(WebsocketDisconnectsGuard::new);
+    }
+
+    pub fn set_fill_type_mismatch_reaction(&self, reaction: FillTypeMismatchReaction) {
+        *self.fill_type_mismatch_reaction.lock() = reaction;
+    }
+
+    pub fn set_commissionless_fill_role(&self, role: Option<OrderRole>) {
+        *self.commissionless_fill_role.lock() = role;
+    }
+
+    pub fn set_converted_commission_rounding(&self, rounding: ConvertedCommissionRounding) {
+        *self.converted_commission_rounding.lock() = rounding;
+    }
+
+    /// Override of `Symbol::amount_margin_error_in_ticks` for all symbols of the exchange.
+    /// Applied when symbols are built or refreshed
+    pub fn set_amount_margin_error_in_ticks(&self, amount_margin_error_in_ticks: Option<Decimal>) {
+        *self.amount_margin_error_in_ticks.lock() = amount_margin_error_in_ticks;
+    }
+
+    /// Price source for converting commission when there are no top prices for direct conversion
+    pub fn set_price_source_service(&self, price_source_service: Option<Arc<PriceSourceService>>) {
+        *self.price_source_service.lock() = price_source_service;
+    }
+
+    /// Clock used for time of fills and order statuses set while handling fills
+    pub fn set_clock(&self, clock: Arc<dyn Clock>) {
+        *self.clock.lock() = clock;
+    }
+
+    pub(crate) fn now(&self) -> DateTime {
+        self.clock.lock().now()
+    }
+
+    /// Recorder of orders and fills received while handling fills
+    pub fn set_data_recorder(&self, data_recorder: Arc<dyn DataRecorder>) {
+        *self.data_recorder.lock() = data_recorder;
+    }
+
+    pub(crate) fn data_recorder(&self) -> Arc<dyn DataRecorder> {
+        self.data_recorder.lock().clone()
+    }
+
+    /// Unique client order id with configured prefix
+    pub fn generate_client_order_id(&self) -> ClientOrderId {
+        match self.client_order_id_prefix.lock().as_deref() {
+            Some(prefix) => ClientOrderId::unique_id_with_prefix(prefix),
+            None => ClientOrderId::unique_id(),
+        }
+    }
+
+    /// Check whether order was created by bot. Without configured prefix all orders are considered as own
+    pub fn is_own_client_order_id(&self, client_order_id: &ClientOrderId) -> bool {
+        match self.client_order_id_prefix.lock().as_deref() {
+            Some(prefix) => client_order_id.as_str().starts_with(prefix),
+            None => true,
+        }
+    }
+
+    /// Rough probability that resting order `distance_ticks` away from mid price is filled within `horizon`.
+    /// Fills are modeled as Poisson arrival of opposite side trades with intensity decaying
+    /// exponentially with distance. Returns `None` if no trades were received for `currency_pair`
+    pub fn maker_fill_probability(
+        &self,
+        currency_pair: CurrencyPair,
+        side: OrderSide,
+        distance_ticks: u32,
+        horizon: Duration,
+    ) -> Option<Decimal> {
+        const FILL_INTENSITY_DECAY_PER_TICK: Decimal = dec!(0.5);
+
+        let trades_rate = self.trades_arrival.rate_per_second(
+            currency_pair,
+            side.change_side(),
+            time_manager::now(),
+        )?;
+
+        let distance_decay = (FILL_INTENSITY_DECAY_PER_TICK * Decimal::from(distance_ticks))
+            .checked_exp()
+            .map_or(dec!(0), |exp| dec!(1) / exp);
+        let horizon_secs = Decimal::from(horizon.as_millis() as u64) / dec!(1000);
+        let expected_fills_count = trades_rate * horizon_secs * distance_decay;
+
+        let no_fill_probability = expected_fills_count
+            .checked_exp()
+            .map_or(dec!(0), |exp| dec!(1) / exp);
+        Some(dec!(1) - no_fill_probability)
+    }
+
+    /// Sum of non-zero virtual balances of exchange account converted to USD by `price_source`.
+    /// Currencies without conversion path are skipped
+    pub async fn portfolio_value_usd(&self, price_source: &PriceSourceService) -> Result<Amount> {
+        Ok(self
+            .calculate_portfolio_value_usd(price_source)
+            .await?
+            .total)
+    }
+
+    /// Same as `portfolio_value_usd`, but also returns currencies skipped as not convertible to USD
+    pub async fn calculate_portfolio_value_usd(
+        &self,
+        price_source: &PriceSourceService,
+    ) -> Result<PortfolioValue> {
+        let balance_manager = self
+            .balance_manager
+            .lock()
+            .as_ref()
+            .and_then(|balance_manager| balance_manager.upgrade())
+            .context("BalanceManager should be initialized for calculating portfolio value")?;
+        let balances = balance_manager
+            .lock()
+            .get_virtual_balances(self.exchange_account_id);
+
+        let usd = "USD".into();
+        let usdt = "USDT".into();
+        let usd_currency_code = self
+            .currencies
+            .lock()
+            .iter()
+            .find(|&&x| x == usdt || x == usd)
+            .cloned()
+            .unwrap_or(usd);
+
+        let mut portfolio_value = PortfolioValue::default();
+        for (currency_code, balance) in balances {
+            if balance.is_zero() {
+                continue;
+            }
+
+            if currency_code == usd_currency_code {
+                portfolio_value.total += balance;
+                continue;
+            }
+
+            let usd_amount = match price_source.market_ids_for(currency_code, usd_currency_code) {
+                None => None,
+                Some(_) => {
+                    price_source
+                        .convert_amount(
+                            currency_code,
+                            usd_currency_code,
+                            balance,
+                            self.lifetime_manager.stop_token(),
+                        )
+                        .await?
+                }
+            };
+
+            match usd_amount {
+                Some(usd_amount) => portfolio_value.total += usd_amount,
+                None => {
+                    log::warn!(
+                        "Balance {balance} {currency_code} on {} is skipped in portfolio value: can't convert it to {usd_currency_code}",
+                        self.exchange_account_id
+                    );
+                    portfolio_value.skipped_currencies.push(currency_code);
+                }
+            }
+        }
+
+        Ok(portfolio_value)
+    }
+
     pub async fn cancel_opened_orders(
         self: Arc<Self>,
         cancellation_token: CancellationToken,
@@ -527,6 +979,11 @@ impl Exchange {
                 );
             }
             Ok(orders) => {
+                let orders = orders
+                    .into_iter()
+                    .filter(|x| self.is_own_client_order_id(&x.client_order_id))
+                    .collect_vec();
+
                 tokio::select! {
                     _ = self.cancel_orders(orders.clone(), cancellation_token.clone()) => nothing_to_do(),
                     _ = cancellation_token.when_cancelled() => {
@@ -676,24 +1133,58 @@ impl Exchange {
 
     fn update_positions_leverage(&self, positions: &[DerivativePosition]) {
         for position in positions {
-            if let Some(mut leverage) = self
-                .leverage_by_currency_pair
-                .get_mut(&position.currency_pair)
-            {
-                *leverage.value_mut() = position.leverage;
+            if self.symbols.contains_key(&position.currency_pair) {
+                self.set_leverage(position.currency_pair, position.leverage);
             }
         }
     }
 
+    /// Set leverage of currency pair and apply fills postponed by `BalanceManager` while leverage was unknown
+    pub fn set_leverage(&self, currency_pair: CurrencyPair, leverage: Decimal) {
+        self.leverage_by_currency_pair
+            .insert(currency_pair, leverage);
+
+        let balance_manager = self
+            .balance_manager
+            .lock()
+            .as_ref()
+            .and_then(|balance_manager| balance_manager.upgrade());
+        if let Some(balance_manager) = balance_manager {
+            balance_manager.lock().retry_fills_waiting_for_leverage();
+        }
+    }
+
+    /// Snapshot older than already handled one is ignored and the latest snapshot is returned instead,
+    /// because snapshots can arrive out of sequence
     fn handle_balances_and_positions(
         &self,
         balances_and_positions: ExchangeBalancesAndPositions,
     ) -> ExchangeBalancesAndPositions {
+        let balance_update = BalanceUpdateEvent {
+            exchange_account_id: self.exchange_account_id,
+            balances_and_positions: balances_and_positions.clone(),
+            // fallback to receive time if exchange doesn't report time of the snapshot
+            event_time: balances_and_positions
+                .event_time
+                .unwrap_or_else(time_manager::now),
+        };
+
+        let mut latest_balance_updates = self.latest_balance_updates.lock();
+        if !latest_balance_updates.apply(balance_update.clone()) {
+            if let Some(latest) = latest_balance_updates.get(self.exchange_account_id) {
+                log::warn!(
+                    "Ignoring balance snapshot for {} at {} because it isn't newer than snapshot at {}",
+                    self.exchange_account_id,
+                    balance_update.event_time,
+                    latest.event_time
+                );
+                return latest.balances_and_positions.clone();
+            }
+        }
+        drop(latest_balance_updates);
+
         self.events_channel
-            .send_expected(ExchangeEvent::BalanceUpdate(BalanceUpdateEvent {
-                exchange_account_id: self.exchange_account_id,
-                balances_and_positions: balances_and_positions.clone(),
-            }));
+            .send_expected(ExchangeEvent::BalanceUpdate(balance_update));
 
         if let Some(positions) = &balances_and_positions.positions {
             for position_info in positions {
@@ -808,36 +1299,153 @@ impl Exchange {
     }
 
     pub fn update_server_time_latency(&self, latency: i64) {
-        self.server_time_latency.store(latency, Ordering::SeqCst)
+        self.metrics_time_source.set_local_time_offset(latency)
     }
 
     fn handle_metrics(&self, event_info: &MetricsEventInfo) {
-        let local_time_offset = match event_info.base.event_type() {
+        let metrics_event = match event_info.base.event_type() {
             MetricsEventType::TradeEvent | MetricsEventType::OrderBookEvent => {
-                self.server_time_latency.load(Ordering::SeqCst)
+                MetricsEvent::from_info(&event_info.base, &self.metrics_time_source)
             }
             MetricsEventType::MlPrediction
             | MetricsEventType::OrderFromCreateToFill
-            | MetricsEventType::TradeToMl => 0,
-            MetricsEventType::OrderLifeCycle(_) => unimplemented!(),
+            | MetricsEventType::OrderLifeCycle(_)
+            | MetricsEventType::CancelAckLatency
+            | MetricsEventType::TradeToMl => MetricsEvent::new(&event_info.base, 0),
         };
 
-        self.save_metrics(&event_info.base, local_time_offset);
+        self.save_metrics(metrics_event);
     }
 
-    pub(super) fn save_metrics(
-        &self,
-        metrics_event_info: &MetricsEventInfoBase,
-        local_time_offset: MetricsTime,
-    ) {
-        let metrics_event = MetricsEvent::new(metrics_event_info, local_time_offset);
+    fn save_metrics(&self, metrics_event: MetricsEvent) {
+        let event_type = metrics_event.event_type();
+        self.event_recorder
+            .save(metrics_event)
+            .with_expect(|| format!("Failure save metrics event {event_type:?}"));
+    }
 
-        self.event_recorder.save(metrics_event).with_expect(|| {
-            format!(
-                "Failure save metrics event {:?}",
-                metrics_event_info.event_type()
-            )
+    /// Save metrics of order processing and also send it to events channel,
+    /// so that subscribers can observe latencies of orders without database
+    pub(super) fn emit_order_metrics(&self, metrics_event_info: &MetricsEventInfoBase) {
+        let metrics_event = MetricsEvent::new(metrics_event_info, 0);
+        self.save_metrics(metrics_event.clone());
+
+        self.events_channel
+            .send_expected(ExchangeEvent::Metrics(metrics_event));
+    }
+
+    /// Emit `OrderLifeCycle` metrics for the last status change of order.
+    /// Latency is the time which order spent in the previous status
+    pub(super) fn emit_order_lifecycle_metrics(&self, order: &OrderRef) {
+        let last_change = order.fn_ref(|x| {
+            x.status_history
+                .last_change_with_previous_time(x.props.init_time)
         });
+
+        if let Some((status, previous_status_time, status_time)) = last_change {
+            self.emit_order_metrics(&MetricsEventInfoBase::new(
+                previous_status_time.timestamp_millis(),
+                status_time.timestamp_millis(),
+                MetricsEventType::OrderLifeCycle(status),
+            ));
+        }
+    }
+
+    /// Emit `CancelAckLatency` metrics for order which was just canceled after `Canceling` status
+    pub(super) fn emit_cancel_ack_latency_metrics(&self, order: &OrderRef) {
+        const MAX_CANCEL_ACK_LATENCIES_COUNT: usize = 100;
+
+        let last_change = order.fn_ref(|x| {
+            x.status_history
+                .last_change_with_previous_time(x.props.init_time)
+        });
+
+        if let Some((OrderStatus::Canceled, cancel_sent_time, cancel_ack_time)) = last_change {
+            {
+                let mut cancel_ack_latencies = self.cancel_ack_latencies.lock();
+                if cancel_ack_latencies.len() == MAX_CANCEL_ACK_LATENCIES_COUNT {
+                    let _ = cancel_ack_latencies.pop_front();
+                }
+                cancel_ack_latencies
+                    .push_back((cancel_ack_time - cancel_sent_time).num_milliseconds());
+            }
+
+            self.emit_order_metrics(&MetricsEventInfoBase::new(
+                cancel_sent_time.timestamp_millis(),
+                cancel_ack_time.timestamp_millis(),
+                MetricsEventType::CancelAckLatency,
+            ));
+        }
+    }
+
+    /// Average latency of last order cancellations in milliseconds
+    pub fn average_cancel_ack_latency(&self) -> Option<i64> {
+        let cancel_ack_latencies = self.cancel_ack_latencies.lock();
+        if cancel_ack_latencies.is_empty() {
+            return None;
+        }
+
+        Some(cancel_ack_latencies.iter().sum::<i64>() / cancel_ack_latencies.len() as i64)
+    }
+
+    /// Reduce only market order which flattens current position by `currency_pair`.
+    /// Returns `None` if there is no position
+    pub fn build_close_position_order(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> Result<Option<OrderHeader>> {
+        let symbol = self.get_symbol(currency_pair)?;
+        if !symbol.is_derivative {
+            bail!(
+                "Unable to build close position order for non-derivative {currency_pair} on {}",
+                self.exchange_account_id
+            );
+        }
+
+        let balance_manager = self
+            .balance_manager
+            .lock()
+            .as_ref()
+            .and_then(|balance_manager| balance_manager.upgrade())
+            .context("BalanceManager should be initialized before closing positions")?;
+
+        // position by buy side is positive for long position and negative for short one
+        let position = balance_manager.lock().get_position(
+            self.exchange_account_id,
+            currency_pair,
+            OrderSide::Buy,
+        )?;
+
+        if position.is_zero() {
+            return Ok(None);
+        }
+
+        let side = match position.is_sign_positive() {
+            true => OrderSide::Sell,
+            false => OrderSide::Buy,
+        };
+
+        let header = OrderHeader::with_user_order(
+            self.generate_client_order_id(),
+            self.exchange_account_id,
+            currency_pair,
+            side,
+            position.abs(),
+            UserOrder::market(),
+            None,
+            None,
+            CLOSE_POSITION_STRATEGY_NAME.to_owned(),
+        )
+        .with_reduce_only();
+
+        Ok(Some(header))
+    }
+
+    /// Requests budget of rate limiter for the exchange account, so strategies can pace
+    /// order placement before requests get throttled
+    pub fn remaining_request_budget(&self) -> RequestBudget {
+        self.timeout_manager
+            .get_request_budget(self.exchange_account_id)
     }
 }
 
@@ -859,3 +1467,226 @@ fn print_warn(
 ) {
     log::warn!("Failed to {fn_name} for {exchange_account_id} on retry {retry_attempt}: {error:?}");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
+    use crate::exchanges::general::features::OpenOrdersType;
+    use crate::exchanges::general::order::get_order_trades::OrderTrade;
+    use crate::exchanges::general::test_helper::{
+        get_test_exchange, get_test_exchange_with_client_and_fills_type, TestClient,
+    };
+    use crate::services::usd_convertion::price_source_service::test::PriceSourceServiceTestBase;
+    use chrono::Utc;
+    use mmb_domain::events::{ExchangeBalance, ExchangeBalancesAndPositions, TradeId};
+    use mmb_domain::exchanges::symbol::Precision;
+    use mmb_domain::order::fill::OrderFillType;
+    use mmb_domain::order_book_data;
+    use mmb_utils::hashmap;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn older_balance_snapshot_does_not_override_newer_one() {
+        let (exchange, _event_receiver) = get_test_exchange(false);
+
+        let balances_and_positions = |balance, event_time| ExchangeBalancesAndPositions {
+            balances: vec![ExchangeBalance {
+                currency_code: "BTC".into(),
+                balance,
+            }],
+            positions: None,
+            event_time: Some(event_time),
+        };
+        let now = Utc::now();
+
+        let newer = exchange.handle_balances_and_positions(balances_and_positions(dec!(2), now));
+        assert_eq!(newer.balances[0].balance, dec!(2));
+
+        let older = exchange.handle_balances_and_positions(balances_and_positions(
+            dec!(1),
+            now - chrono::Duration::seconds(1),
+        ));
+        assert_eq!(older.balances[0].balance, dec!(2));
+        assert_eq!(older.event_time, Some(now));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn remaining_request_budget_decreases_after_requests() {
+        let (exchange, _event_receiver) = get_test_exchange(false);
+
+        let initial_budget = exchange.remaining_request_budget();
+        assert_eq!(initial_budget.refill_in, Duration::ZERO);
+
+        let requests_count = 3;
+        for _ in 0..requests_count {
+            assert!(exchange
+                .timeout_manager
+                .try_reserve_instant(exchange.exchange_account_id, RequestType::CreateOrder));
+        }
+
+        let budget = exchange.remaining_request_budget();
+        assert_eq!(
+            budget.available_now,
+            initial_budget.available_now - requests_count
+        );
+        assert!(budget.refill_in > Duration::ZERO);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn missed_fill_reconciled_after_reconnect() {
+        let currency_pair = CurrencyPair::from_codes("ETH".into(), "BTC".into());
+        let exchange_order_id = ExchangeOrderId::from("missed_fill_order");
+        let missed_trades = Arc::new(Mutex::new(Vec::new()));
+
+        let exchange_client = TestClient {
+            open_orders_by_currency_pair: Some(Box::new(|_| Ok(vec![]))),
+            get_my_trades: Some(Box::new({
+                let missed_trades = missed_trades.clone();
+                move |_| RequestResult::Success(missed_trades.lock().drain(..).collect())
+            })),
+            ..TestClient::default()
+        };
+        let symbol = Arc::new(Symbol::new(
+            false,
+            "ETH".into(),
+            "ETH".into(),
+            "BTC".into(),
+            "BTC".into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            "ETH".into(),
+            Some("BTC".into()),
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0.001) },
+        ));
+        let (exchange, _event_receiver) = get_test_exchange_with_client_and_fills_type(
+            symbol,
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            exchange_client,
+            OpenOrdersType::OneCurrencyPair,
+            RestFillsType::MyTrades,
+        );
+        let mut connection_state = exchange.subscribe_connection_state();
+
+        exchange.on_connection_established().await;
+        assert_eq!(exchange.connection_state(), ConnectionState::Connected);
+
+        let order_header = OrderHeader::with_user_order(
+            ClientOrderId::unique_id(),
+            exchange.exchange_account_id,
+            currency_pair,
+            OrderSide::Buy,
+            dec!(5),
+            UserOrder::limit(dec!(0.2)),
+            None,
+            None,
+            "FromTest".to_owned(),
+        );
+        let order = exchange
+            .orders
+            .add_simple_initial(&order_header, time_manager::now(), None);
+        order.fn_mut(|order| {
+            order.props.exchange_order_id = Some(exchange_order_id.clone());
+            order.set_status(OrderStatus::Created, time_manager::now());
+        });
+        exchange
+            .orders
+            .cache_by_exchange_id
+            .insert(exchange_order_id.clone(), order.clone());
+
+        exchange.on_disconnected();
+        assert_eq!(exchange.connection_state(), ConnectionState::Disconnected);
+
+        // fill happened while websocket was disconnected
+        missed_trades.lock().push(OrderTrade::new(
+            exchange_order_id,
+            TradeId::Number(1),
+            time_manager::now(),
+            dec!(0.2),
+            dec!(2),
+            OrderRole::Maker,
+            "BTC".into(),
+            None,
+            Some(dec!(0)),
+            OrderFillType::UserTrade,
+        ));
+        assert_eq!(order.filled_amount(), dec!(0));
+
+        exchange.on_connection_established().await;
+
+        assert_eq!(order.filled_amount(), dec!(2));
+        assert_eq!(exchange.connection_state(), ConnectionState::Connected);
+        assert!(connection_state.has_changed().expect("in test"));
+        assert_eq!(
+            *connection_state.borrow_and_update(),
+            ConnectionState::Connected
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn portfolio_value_usd_skips_not_convertible_currencies() {
+        let (exchange, _event_receiver) = get_test_exchange(false);
+        let exchange_account_id = exchange.exchange_account_id;
+        let btc = CurrencyCode::new("BTC");
+        let phb = CurrencyCode::new("PHB");
+        let usd = CurrencyCode::new("USD");
+
+        let balance_manager = BalanceManager::new(
+            CurrencyPairToSymbolConverter::new(hashmap![exchange_account_id => exchange.clone()]),
+            None,
+        );
+        exchange.setup_balance_manager(balance_manager.clone());
+        balance_manager
+            .lock()
+            .update_exchange_balance(
+                exchange_account_id,
+                &ExchangeBalancesAndPositions {
+                    balances: vec![
+                        ExchangeBalance {
+                            currency_code: btc,
+                            balance: dec!(2),
+                        },
+                        ExchangeBalance {
+                            currency_code: phb,
+                            balance: dec!(100),
+                        },
+                    ],
+                    positions: None,
+                    event_time: None,
+                },
+            )
+            .expect("in test");
+
+        // there is no price source chain for PHB -> USD
+        let price_source_service = PriceSourceServiceTestBase::create_service_with_snapshot(
+            btc,
+            usd,
+            CurrencyPair::from_codes(btc, usd),
+            order_book_data![
+                dec!(25010) => dec!(1),
+                ;
+                dec!(24990) => dec!(1),
+            ]
+            .to_orderbook_snapshot(Utc::now()),
+        );
+
+        let portfolio_value = exchange
+            .calculate_portfolio_value_usd(&price_source_service)
+            .await
+            .expect("in test");
+
+        // middle price of BTC/USD is 25000
+        assert_eq!(portfolio_value.total, dec!(50000));
+        assert_eq!(portfolio_value.skipped_currencies, vec![phb]);
+        assert_eq!(
+            exchange
+                .portfolio_value_usd(&price_source_service)
+                .await
+                .expect("in test"),
+            dec!(50000)
+        );
+    }
+}
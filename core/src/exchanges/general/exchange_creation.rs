@@ -79,6 +79,12 @@ pub async fn create_exchange(
         event_recorder,
     );
 
+    exchange.set_client_order_id_prefix(user_settings.client_order_id_prefix.clone());
+    exchange.set_websocket_disconnects_limit(user_settings.websocket_disconnects_limit.as_ref());
+    exchange.set_fill_type_mismatch_reaction(user_settings.fill_type_mismatch_reaction);
+    exchange.set_commissionless_fill_role(user_settings.commissionless_fill_role);
+    exchange.set_converted_commission_rounding(user_settings.converted_commission_rounding);
+    exchange.set_amount_margin_error_in_ticks(user_settings.amount_margin_error_in_ticks);
     exchange.build_symbols(&user_settings.currency_pairs).await;
     exchange.exchange_client.initialized(exchange.clone()).await;
 
@@ -1,14 +1,16 @@
+use anyhow::{Context, Result};
 use dashmap::DashMap;
 use itertools::Itertools;
 use mmb_domain::market::CurrencyCode;
 use mmb_utils::infrastructure::WithExpect;
 use rust_decimal_macros::dec;
 use std::collections::HashSet;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use crate::settings::CurrencyPairSetting;
 use mmb_domain::exchanges::symbol::Symbol;
-use mmb_domain::market::{CurrencyId, ExchangeAccountId};
+use mmb_domain::market::{CurrencyId, CurrencyPair, ExchangeAccountId};
 
 use super::exchange::Exchange;
 
@@ -20,8 +22,7 @@ impl Exchange {
         self.setup_supported_currencies(supported_currencies);
 
         for symbol in exchange_symbols {
-            self.leverage_by_currency_pair
-                .insert(symbol.currency_pair(), dec!(1));
+            self.set_leverage(symbol.currency_pair(), dec!(1));
         }
 
         let currency_pairs = currency_pair_settings.as_ref().with_expect(|| {
@@ -38,6 +39,51 @@ impl Exchange {
         ));
     }
 
+    /// Re-request metadata of an already traded symbol and replace it in `self.symbols`.
+    /// Orders aren't touched because they reference symbol by currency pair only.
+    /// Symbols cached in `CurrencyPairToSymbolConverter` are invalidated by `symbols_version`
+    pub async fn refresh_symbol_metadata(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> Result<Arc<Symbol>> {
+        if !self.symbols.contains_key(&currency_pair) {
+            anyhow::bail!(
+                "Unable to refresh metadata of unknown currency pair {currency_pair} on {}",
+                self.exchange_account_id
+            );
+        }
+
+        let symbol = self
+            .exchange_client
+            .build_all_symbols()
+            .await
+            .with_context(|| {
+                format!(
+                    "Unable to get symbols for {} while refreshing {currency_pair}",
+                    self.exchange_account_id
+                )
+            })?
+            .into_iter()
+            .find(|symbol| symbol.currency_pair() == currency_pair)
+            .with_context(|| {
+                format!(
+                    "Symbol {currency_pair} is not returned by exchange {} anymore",
+                    self.exchange_account_id
+                )
+            })?;
+        let symbol = self.apply_symbol_settings(symbol);
+
+        self.symbols.insert(currency_pair, symbol.clone());
+        self.symbols_version.fetch_add(1, Ordering::SeqCst);
+
+        log::info!(
+            "Metadata of symbol {currency_pair} refreshed on {}",
+            self.exchange_account_id
+        );
+
+        Ok(symbol)
+    }
+
     async fn request_symbols_with_retries(&self) -> Vec<Arc<Symbol>> {
         const MAX_RETRIES: u8 = 5;
         for retry in 0..=MAX_RETRIES {
@@ -68,7 +114,22 @@ impl Exchange {
         }
     }
 
+    fn apply_symbol_settings(&self, symbol: Arc<Symbol>) -> Arc<Symbol> {
+        match *self.amount_margin_error_in_ticks.lock() {
+            Some(amount_margin_error_in_ticks) => Arc::new(Symbol {
+                amount_margin_error_in_ticks,
+                ..Symbol::clone(&symbol)
+            }),
+            None => symbol,
+        }
+    }
+
     fn setup_symbols(&self, symbols: Vec<Arc<Symbol>>) {
+        let symbols = symbols
+            .into_iter()
+            .map(|symbol| self.apply_symbol_settings(symbol))
+            .collect_vec();
+
         let currencies = symbols
             .iter()
             .flat_map(|x| [x.base_currency_code, x.quote_currency_code])
@@ -80,6 +141,7 @@ impl Exchange {
         symbols.iter().for_each(|symbol| {
             self.symbols.insert(symbol.currency_pair(), symbol.clone());
         });
+        self.symbols_version.fetch_add(1, Ordering::SeqCst);
 
         let exchange_client = &self.exchange_client;
         let current_specific_currencies = symbols
@@ -143,3 +205,115 @@ fn get_matched_currency_pair(
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchanges::general::features::OpenOrdersType;
+    use crate::exchanges::general::test_helper::{get_test_exchange_with_client, TestClient};
+    use mmb_domain::exchanges::symbol::{Precision, Round};
+    use mmb_utils::logger::init_logger;
+
+    fn create_symbol(price_tick: rust_decimal::Decimal) -> Arc<Symbol> {
+        Arc::new(Symbol::new(
+            false,
+            "PHB".into(),
+            "PHB".into(),
+            "BTC".into(),
+            "BTC".into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            "PHB".into(),
+            None,
+            Precision::ByTick { tick: price_tick },
+            Precision::ByTick { tick: dec!(0) },
+        ))
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn refreshed_price_tick_used_in_price_round() {
+        init_logger();
+
+        let exchange_client = TestClient {
+            build_all_symbols: Some(Box::new(|| Ok(vec![create_symbol(dec!(0.5))]))),
+            ..TestClient::default()
+        };
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            create_symbol(dec!(0.1)),
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            exchange_client,
+            OpenOrdersType::AllCurrencyPair,
+        );
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+
+        let symbol = exchange.get_symbol(currency_pair).expect("in test");
+        assert_eq!(symbol.price_round(dec!(1.23), Round::Floor), dec!(1.2));
+
+        let refreshed_symbol = exchange
+            .refresh_symbol_metadata(currency_pair)
+            .await
+            .expect("in test");
+        assert_eq!(
+            refreshed_symbol.price_round(dec!(1.23), Round::Floor),
+            dec!(1)
+        );
+
+        let symbol = exchange.get_symbol(currency_pair).expect("in test");
+        assert_eq!(symbol.price_round(dec!(1.23), Round::Floor), dec!(1));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn amount_margin_error_from_settings_applied_to_symbols() {
+        init_logger();
+
+        let exchange_client = TestClient {
+            build_all_symbols: Some(Box::new(|| Ok(vec![create_symbol(dec!(0.1))]))),
+            ..TestClient::default()
+        };
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            create_symbol(dec!(0.1)),
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            exchange_client,
+            OpenOrdersType::AllCurrencyPair,
+        );
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+
+        exchange.set_amount_margin_error_in_ticks(Some(dec!(0.5)));
+        exchange
+            .build_symbols(&Some(vec![CurrencyPairSetting::Ordinary {
+                base: "PHB".into(),
+                quote: "BTC".into(),
+            }]))
+            .await;
+
+        let symbol = exchange.get_symbol(currency_pair).expect("in test");
+        assert_eq!(symbol.amount_margin_error_in_ticks, dec!(0.5));
+
+        let refreshed_symbol = exchange
+            .refresh_symbol_metadata(currency_pair)
+            .await
+            .expect("in test");
+        assert_eq!(refreshed_symbol.amount_margin_error_in_ticks, dec!(0.5));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn refresh_symbol_metadata_of_unknown_currency_pair() {
+        init_logger();
+
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            create_symbol(dec!(0.1)),
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            TestClient::default(),
+            OpenOrdersType::AllCurrencyPair,
+        );
+
+        let unknown_currency_pair = CurrencyPair::from_codes("ETH".into(), "BTC".into());
+        assert!(exchange
+            .refresh_symbol_metadata(unknown_currency_pair)
+            .await
+            .is_err());
+    }
+}
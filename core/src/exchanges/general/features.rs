@@ -1,4 +1,4 @@
-use mmb_domain::events::AllowedEventSourceType;
+use mmb_domain::events::{AllowedEventSourceType, EventSourceType};
 
 #[derive(Debug)]
 pub enum OpenOrdersType {
@@ -83,9 +83,12 @@ pub struct OrderFeatures {
     /// Stop loss orders are supported
     // TODO Flag is not used in core, is it redundant?
     pub supports_stop_loss_order: bool,
+    /// Good-till-time orders are supported natively, otherwise such orders are canceled by bot on expiry
+    pub supports_good_till_time: bool,
 }
 
 impl OrderFeatures {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         maker_only: bool,
         supports_get_order_info_by_client_order_id: bool,
@@ -94,6 +97,7 @@ impl OrderFeatures {
         order_was_completed_error_for_cancellation: bool,
         supports_already_cancelled_order: bool,
         supports_stop_loss_order: bool,
+        supports_good_till_time: bool,
     ) -> Self {
         Self {
             maker_only,
@@ -103,6 +107,7 @@ impl OrderFeatures {
             order_was_completed_error_for_cancellation,
             supports_already_cancelled_order,
             supports_stop_loss_order,
+            supports_good_till_time,
         }
     }
 }
@@ -122,6 +127,39 @@ pub struct OrderTradeOption {
     pub supports_my_trades_from_time: bool,
 }
 
+/// Sources of fill events ordered from the most prioritized one.
+/// When a fill of already received trade comes from more prioritized source, its metadata (commission, role)
+/// replaces metadata of the recorded fill. Sources which are not listed are never prioritized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FillSourcePriority(Vec<EventSourceType>);
+
+impl FillSourcePriority {
+    pub fn new(sources: Vec<EventSourceType>) -> Self {
+        Self(sources)
+    }
+
+    fn rank(&self, source_type: EventSourceType) -> Option<usize> {
+        self.0.iter().position(|&x| x == source_type)
+    }
+
+    pub fn is_higher(&self, source_type: EventSourceType, than: EventSourceType) -> bool {
+        match (self.rank(source_type), self.rank(than)) {
+            (Some(rank), Some(than_rank)) => rank < than_rank,
+            _ => false,
+        }
+    }
+}
+
+impl Default for FillSourcePriority {
+    fn default() -> Self {
+        Self(vec![
+            EventSourceType::WebSocket,
+            EventSourceType::Rest,
+            EventSourceType::RestFallback,
+        ])
+    }
+}
+
 pub struct ExchangeFeatures {
     /// Exchange client possibility of getting open orders: all in single request or by each currency pair separately
     // TODO Possible redundant cause it's exchange client implementation part and core always requests all open orders
@@ -144,6 +182,8 @@ pub struct ExchangeFeatures {
     pub allowed_fill_event_source_type: AllowedEventSourceType,
     // used only for debug
     pub allowed_cancel_event_source_type: AllowedEventSourceType,
+    /// Which source of fill events wins when the same trade is received from several sources
+    pub fill_source_priority: FillSourcePriority,
 }
 
 impl ExchangeFeatures {
@@ -169,6 +209,7 @@ impl ExchangeFeatures {
             allowed_create_event_source_type,
             allowed_fill_event_source_type,
             allowed_cancel_event_source_type,
+            fill_source_priority: FillSourcePriority::default(),
         }
     }
 }
@@ -82,6 +82,7 @@ impl Exchange {
 
                 self.add_event_on_order_change(order, OrderEventType::CancelOrderFailed)
                     .with_expect(|| format!("Failed to add event CancelOrderFailed on order change {client_order_id:?}"));
+                self.emit_order_lifecycle_metrics(order);
 
                 log::warn!(
                     "Order cancellation failed: {client_order_id} {exchange_order_id:?} on {} with error: {:?} {:?} {}",
@@ -325,6 +326,10 @@ mod test {
                 EventSourceType::WebSocket,
             );
 
+            // only lifecycle metrics of canceled order are sent
+            let received_event = event_receiver.try_recv().expect("in test");
+            assert!(matches!(received_event, ExchangeEvent::Metrics(_)));
+
             let error = event_receiver.try_recv().expect_err("should be error");
             assert_eq!(error, TryRecvError::Empty);
         }
@@ -44,7 +44,7 @@ impl Exchange {
             return;
         }
 
-        if exchange_order_id.is_empty() {
+        if exchange_order_id.is_placeholder() {
             panic!("Received HandleOrderFilled with an empty exchangeOrderId {args_to_log:?}",);
         }
 
@@ -123,6 +123,11 @@ impl Exchange {
                 .with_expect(|| format!("Failed to add event CancelOrderSucceeded on order change {client_order_id}"));
         }
 
+        self.emit_order_lifecycle_metrics(order);
+        if status == OrderStatus::Canceling {
+            self.emit_cancel_ack_latency_metrics(order);
+        }
+
         log::info!(
             "Order was successfully cancelled {client_order_id:?} {exchange_order_id:?} on {}",
             self.exchange_account_id
@@ -138,7 +143,7 @@ impl Exchange {
 mod test {
     use super::*;
     use crate::exchanges::general::test_helper;
-    use mmb_domain::events::ExchangeEvent;
+    use mmb_domain::events::{ExchangeEvent, MetricsEventType};
     use mmb_domain::market::CurrencyPair;
     use mmb_domain::order::snapshot::{OrderRole, OrderSide};
     use rstest::rstest;
@@ -150,7 +155,7 @@ mod test {
         let (exchange, _rx) = test_helper::get_test_exchange(false);
 
         let client_order_id = ClientOrderId::unique_id();
-        let exchange_order_id = ExchangeOrderId::new("".into());
+        let exchange_order_id = ExchangeOrderId::placeholder();
         let filled_amount = dec!(1);
         let source_type = EventSourceType::Rest;
 
@@ -171,7 +176,7 @@ mod test {
         let (exchange, _rx) = test_helper::get_test_exchange(false);
 
         let client_order_id = ClientOrderId::unique_id();
-        let exchange_order_id = ExchangeOrderId::new("".into());
+        let exchange_order_id = ExchangeOrderId::placeholder();
 
         let already_closed =
             exchange.order_already_closed(status, &client_order_id, &exchange_order_id);
@@ -203,7 +208,7 @@ mod test {
 
         test_helper::try_add_snapshot_by_exchange_id(&exchange, &order_ref);
 
-        let exchange_order_id = ExchangeOrderId::new("".into());
+        let exchange_order_id = ExchangeOrderId::placeholder();
         let filled_amount = Some(dec!(5));
         let source_type = EventSourceType::Rest;
         exchange.update_local_order(&order_ref, filled_amount, source_type, &exchange_order_id);
@@ -232,7 +237,7 @@ mod test {
 
         test_helper::try_add_snapshot_by_exchange_id(&exchange, &order_ref);
 
-        let exchange_order_id = ExchangeOrderId::new("".into());
+        let exchange_order_id = ExchangeOrderId::placeholder();
         let filled_amount = Some(dec!(5));
         let source_type = EventSourceType::Rest;
         exchange.update_local_order(&order_ref, filled_amount, source_type, &exchange_order_id);
@@ -266,7 +271,7 @@ mod test {
 
         test_helper::try_add_snapshot_by_exchange_id(&exchange, &order_ref);
 
-        let exchange_order_id = ExchangeOrderId::new("".into());
+        let exchange_order_id = ExchangeOrderId::placeholder();
         let filled_amount = Some(dec!(5));
         let source_type = EventSourceType::Rest;
         exchange.update_local_order(&order_ref, filled_amount, source_type, &exchange_order_id);
@@ -303,7 +308,7 @@ mod test {
 
         test_helper::try_add_snapshot_by_exchange_id(&exchange, &order_ref);
 
-        let exchange_order_id = ExchangeOrderId::new("".into());
+        let exchange_order_id = ExchangeOrderId::placeholder();
         let filled_amount = Some(dec!(5));
         let source_type = EventSourceType::Rest;
         exchange.update_local_order(&order_ref, filled_amount, source_type, &exchange_order_id);
@@ -320,4 +325,48 @@ mod test {
         let gotten_id = event.order.client_order_id();
         assert_eq!(gotten_id, client_order_id);
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn cancel_ack_latency_metrics_reflect_cancel_delay() {
+        let (exchange, mut event_receiver) = test_helper::get_test_exchange(false);
+
+        let client_order_id = ClientOrderId::unique_id();
+        let order_ref = test_helper::create_order_ref(
+            &client_order_id,
+            Some(OrderRole::Maker),
+            exchange.exchange_account_id,
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            dec!(0.8),
+            dec!(12),
+            OrderSide::Buy,
+        );
+        test_helper::try_add_snapshot_by_exchange_id(&exchange, &order_ref);
+
+        let cancel_delay = chrono::Duration::milliseconds(300);
+        order_ref
+            .fn_mut(|order| order.set_status(OrderStatus::Canceling, Utc::now() - cancel_delay));
+        assert_eq!(exchange.average_cancel_ack_latency(), None);
+
+        let exchange_order_id = ExchangeOrderId::placeholder();
+        exchange.update_local_order(&order_ref, None, EventSourceType::Rest, &exchange_order_id);
+
+        let cancel_ack_latencies = std::iter::from_fn(|| event_receiver.try_recv().ok())
+            .filter_map(|event| match event {
+                ExchangeEvent::Metrics(metrics_event)
+                    if matches!(
+                        metrics_event.event_type(),
+                        MetricsEventType::CancelAckLatency
+                    ) =>
+                {
+                    Some(metrics_event.latency())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(cancel_ack_latencies.len(), 1);
+        assert!(cancel_ack_latencies[0] >= cancel_delay.num_milliseconds());
+        let average_cancel_ack_latency = exchange.average_cancel_ack_latency().expect("in test");
+        assert!(average_cancel_ack_latency >= cancel_delay.num_milliseconds());
+    }
 }
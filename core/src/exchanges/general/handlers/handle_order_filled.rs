@@ -1,6 +1,7 @@
 use crate::exchanges::general::handlers::should_ignore_event;
+use crate::settings::{ConvertedCommissionRounding, FillTypeMismatchReaction};
 use crate::{exchanges::general::exchange::Exchange, math::ConvertPercentToRate};
-use chrono::Utc;
+use dashmap::mapref::entry::Entry;
 use function_name::named;
 use mmb_domain::events::{
     AllowedEventSourceType, EventSourceType, MetricsEventInfoBase, MetricsEventType, TradeId,
@@ -12,13 +13,14 @@ use mmb_domain::order::event::OrderEventType;
 use mmb_domain::order::fill::{OrderFill, OrderFillType};
 use mmb_domain::order::pool::OrderRef;
 use mmb_domain::order::snapshot::{Amount, OrderOptions, Price};
-use mmb_domain::order::snapshot::{ClientOrderFillId, OrderRole};
+use mmb_domain::order::snapshot::{ClientOrderFillId, OrderExecutionType, OrderRole};
 use mmb_domain::order::snapshot::{
-    ClientOrderId, ExchangeOrderId, OrderSide, OrderSnapshot, OrderStatus,
+    ClientOrderId, ExchangeOrderId, OrderSide, OrderSnapshot, OrderStatus, OrderType,
 };
 use mmb_utils::DateTime;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -84,11 +86,154 @@ pub struct FillEvent {
     pub fill_date: Option<DateTime>,
 }
 
+/// Builder of `FillEvent` with mandatory fields as parameters of `new` and optional ones set to `None`
+pub struct FillEventBuilder {
+    fill_event: FillEvent,
+}
+
+impl FillEventBuilder {
+    pub fn new(
+        source_type: EventSourceType,
+        trade_id: Option<TradeId>,
+        exchange_order_id: ExchangeOrderId,
+        fill_price: Price,
+        fill_amount: FillAmount,
+        fill_type: OrderFillType,
+    ) -> FillEventBuilder {
+        Self {
+            fill_event: FillEvent {
+                source_type,
+                trade_id,
+                client_order_id: None,
+                exchange_order_id,
+                fill_price,
+                fill_amount,
+                order_role: None,
+                commission_currency_code: None,
+                commission_rate: None,
+                commission_amount: None,
+                fill_type,
+                special_order_data: None,
+                fill_date: None,
+            },
+        }
+    }
+
+    pub fn client_order_id(mut self, client_order_id: ClientOrderId) -> FillEventBuilder {
+        self.fill_event.client_order_id = Some(client_order_id);
+        self
+    }
+
+    pub fn order_role(mut self, order_role: OrderRole) -> FillEventBuilder {
+        self.fill_event.order_role = Some(order_role);
+        self
+    }
+
+    pub fn commission_currency_code(
+        mut self,
+        commission_currency_code: CurrencyCode,
+    ) -> FillEventBuilder {
+        self.fill_event.commission_currency_code = Some(commission_currency_code);
+        self
+    }
+
+    pub fn commission_rate(mut self, commission_rate: Percent) -> FillEventBuilder {
+        self.fill_event.commission_rate = Some(commission_rate);
+        self
+    }
+
+    pub fn commission_amount(mut self, commission_amount: Amount) -> FillEventBuilder {
+        self.fill_event.commission_amount = Some(commission_amount);
+        self
+    }
+
+    pub fn special_order_data(mut self, special_order_data: SpecialOrderData) -> FillEventBuilder {
+        self.fill_event.special_order_data = Some(special_order_data);
+        self
+    }
+
+    pub fn fill_date(mut self, fill_date: DateTime) -> FillEventBuilder {
+        self.fill_event.fill_date = Some(fill_date);
+        self
+    }
+
+    pub fn build(self) -> FillEvent {
+        self.fill_event
+    }
+}
+
 impl Exchange {
     #[named]
     pub fn handle_order_filled(&self, fill_event: &mut FillEvent) {
-        log::trace!(concat!("started ", function_name!(), " {:?}"), fill_event);
+        let span = self.fill_handling_span(fill_event);
+        let _span_guard = span.enter();
+
+        tracing::trace!(concat!("started ", function_name!(), " {:?}"), fill_event);
+
+        if self.buffer_fill_if_processing_paused(fill_event) {
+            return;
+        }
+
+        self.apply_order_fill(fill_event);
+    }
+
+    fn fill_handling_span(&self, fill_event: &FillEvent) -> tracing::Span {
+        tracing::info_span!(
+            "handle_order_filled",
+            exchange_account_id = %self.exchange_account_id,
+            client_order_id = fill_event.client_order_id.as_ref().map(|x| x.as_str()),
+            exchange_order_id = fill_event.exchange_order_id.as_str(),
+        )
+    }
+
+    fn buffer_fill_if_processing_paused(&self, fill_event: &FillEvent) -> bool {
+        if self.fill_processing_enabled.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        let mut buffered_fills_manager = self.buffered_fills_manager.lock();
+        // processing could be resumed concurrently, buffered fills are applied under the same lock on resuming
+        if self.fill_processing_enabled.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        tracing::info!(
+            trade_id = ?fill_event.trade_id,
+            "Buffered a fill while fill processing is paused"
+        );
+        buffered_fills_manager.add_paused_fill(fill_event.clone());
+        true
+    }
+
+    /// Pause or resume applying fills to orders and balances on the exchange, e.g. during manual intervention.
+    /// Fills received while processing is paused are buffered and applied on resuming in order of receiving
+    pub fn set_fill_processing_enabled(&self, enabled: bool) {
+        if !enabled {
+            log::info!("Pause fill processing on {}", self.exchange_account_id);
+            self.fill_processing_enabled.store(false, Ordering::SeqCst);
+            return;
+        }
+
+        loop {
+            let mut buffered_fills_manager = self.buffered_fills_manager.lock();
+            let mut fill_event = match buffered_fills_manager.pop_paused_fill() {
+                Some(fill_event) => fill_event,
+                None => {
+                    self.fill_processing_enabled.store(true, Ordering::SeqCst);
+                    break;
+                }
+            };
+            drop(buffered_fills_manager);
 
+            let span = self.fill_handling_span(&fill_event);
+            let _span_guard = span.enter();
+            self.apply_order_fill(&mut fill_event);
+        }
+
+        log::info!("Resume fill processing on {}", self.exchange_account_id);
+    }
+
+    fn apply_order_fill(&self, fill_event: &mut FillEvent) {
         let args_to_log = (
             self.exchange_account_id,
             fill_event.trade_id.clone(),
@@ -102,14 +247,31 @@ impl Exchange {
             self.features.allowed_fill_event_source_type,
             fill_event.source_type,
         ) {
-            log::info!("Ignoring fill {args_to_log:?}");
+            tracing::info!(
+                trade_id = ?fill_event.trade_id,
+                source_type = ?fill_event.source_type,
+                allowed_source_type = ?self.features.allowed_fill_event_source_type,
+                "Ignoring fill"
+            );
             return;
         }
 
-        if fill_event.exchange_order_id.is_empty() {
+        if fill_event.exchange_order_id.is_placeholder() {
             panic!("Received HandleOrderFilled with an empty exchangeOrderId {args_to_log:?}",);
         }
 
+        // price of liquidation and close position fills may be unknown, so they are handled as special orders
+        if fill_event.fill_price.is_zero() && !fill_event.fill_type.is_special() {
+            match fill_event.fill_type.is_settlement() {
+                true => self.handle_settlement(fill_event),
+                false => tracing::error!(
+                    trade_id = ?fill_event.trade_id,
+                    "Rejected fill with zero price because only settlement and special fills can have zero price"
+                ),
+            }
+            return;
+        }
+
         self.add_special_order_if_need(fill_event, &args_to_log);
 
         match self
@@ -118,7 +280,10 @@ impl Exchange {
             .get(&fill_event.exchange_order_id)
         {
             None => {
-                log::info!("Received a fill for not existing order {args_to_log:?}",);
+                tracing::info!(
+                    trade_id = ?fill_event.trade_id,
+                    "Received a fill for not existing order"
+                );
 
                 self.buffered_fills_manager
                     .lock()
@@ -132,40 +297,223 @@ impl Exchange {
                     );
                 }
             }
-            Some(order_ref) => self.create_and_add_order_fill(fill_event, &order_ref),
+            Some(order_ref) => {
+                tracing::Span::current()
+                    .record("client_order_id", order_ref.client_order_id().as_str());
+
+                if let Some(mut frozen_fills) =
+                    self.frozen_orders.get_mut(&order_ref.client_order_id())
+                {
+                    tracing::info!(
+                        trade_id = ?fill_event.trade_id,
+                        "Buffered a fill for frozen order"
+                    );
+                    frozen_fills.push(fill_event.clone());
+                    return;
+                }
+
+                self.create_and_add_order_fill(fill_event, &order_ref)
+            }
         }
     }
 
-    fn was_trade_already_received(
-        trade_id: &Option<TradeId>,
-        order_fills: &[OrderFill],
-        order_ref: &OrderRef,
-    ) -> bool {
-        let current_trade_id = match trade_id {
-            None => return false,
-            Some(trade_id) => trade_id,
+    /// Settlement fill changes balance by its commission only, so it isn't added to the order
+    /// and no price math is applied
+    fn handle_settlement(&self, fill_event: &FillEvent) {
+        let (Some(commission_amount), Some(commission_currency_code)) = (
+            fill_event.commission_amount,
+            fill_event.commission_currency_code,
+        ) else {
+            tracing::warn!(
+                trade_id = ?fill_event.trade_id,
+                "Settlement fill without commission doesn't change balance"
+            );
+            return;
+        };
+
+        let balance_manager = self
+            .balance_manager
+            .lock()
+            .as_ref()
+            .and_then(|balance_manager| balance_manager.upgrade());
+        match balance_manager {
+            None => tracing::warn!(
+                trade_id = ?fill_event.trade_id,
+                "BalanceManager isn't set to apply settlement fill"
+            ),
+            Some(balance_manager) => balance_manager.lock().settlement_was_received(
+                self.exchange_account_id,
+                commission_currency_code,
+                -commission_amount,
+            ),
+        }
+    }
+
+    /// Stop applying fills to the order while it's handled manually.
+    /// Fills received for a frozen order are buffered and applied on `unfreeze_order`
+    pub fn freeze_order(&self, client_order_id: &ClientOrderId) {
+        log::info!(
+            "Freeze order {client_order_id} on {}",
+            self.exchange_account_id
+        );
+        self.frozen_orders
+            .entry(client_order_id.clone())
+            .or_default();
+    }
+
+    /// Resume applying fills to the order and apply fills buffered while the order was frozen in order of receiving
+    pub fn unfreeze_order(&self, client_order_id: &ClientOrderId) {
+        // entry is locked until buffered fills are applied, so fills received meanwhile wait for it
+        // and are applied after the buffered ones
+        let mut frozen_order = match self.frozen_orders.entry(client_order_id.clone()) {
+            Entry::Occupied(frozen_order) => frozen_order,
+            Entry::Vacant(_) => {
+                log::warn!(
+                    "Order {client_order_id} on {} isn't frozen",
+                    self.exchange_account_id
+                );
+                return;
+            }
         };
 
-        if order_fills.iter().any(|fill| {
+        let frozen_fills = std::mem::take(frozen_order.get_mut());
+        log::info!(
+            "Unfreeze order {client_order_id} on {} with {} buffered fills",
+            self.exchange_account_id,
+            frozen_fills.len()
+        );
+
+        // buffered fills already passed checks of `apply_order_fill` on receiving
+        for mut fill_event in frozen_fills {
+            let order_ref = self
+                .orders
+                .cache_by_exchange_id
+                .get(&fill_event.exchange_order_id)
+                .map(|order_ref| order_ref.clone());
+            match order_ref {
+                Some(order_ref) => self.create_and_add_order_fill(&mut fill_event, &order_ref),
+                None => log::error!(
+                    "Order {client_order_id} {:?} on {} isn't found to apply buffered fill {:?}",
+                    fill_event.exchange_order_id,
+                    self.exchange_account_id,
+                    fill_event.trade_id
+                ),
+            }
+        }
+
+        frozen_order.remove();
+    }
+
+    pub fn is_order_frozen(&self, client_order_id: &ClientOrderId) -> bool {
+        self.frozen_orders.contains_key(client_order_id)
+    }
+
+    fn find_already_received_fill<'a>(
+        fill_event: &FillEvent,
+        order_fills: &'a [OrderFill],
+    ) -> Option<&'a OrderFill> {
+        let current_trade_id = fill_event.trade_id.as_ref()?;
+
+        let received_fill = order_fills.iter().find(|fill| {
             fill.trade_id()
                 .map(|fill_trade_id| fill_trade_id == current_trade_id)
                 .unwrap_or(false)
-        }) {
-            log::info!(
-                "Trade with {current_trade_id} was received already for order {order_ref:?}"
-            );
+        })?;
 
-            return true;
-        }
+        tracing::info!("Trade with {current_trade_id} was received already");
 
-        false
+        Some(received_fill)
     }
 
-    fn diff_fill_after_non_diff(
+    fn update_fill_from_prioritized_source(
+        &self,
         fill_event: &FillEvent,
-        order_fills: &[OrderFill],
+        received_fill: &OrderFill,
         order_ref: &OrderRef,
-    ) -> bool {
+    ) {
+        let received_source_type = match received_fill.event_source_type() {
+            None => return,
+            Some(source_type) => source_type,
+        };
+
+        if !self
+            .features
+            .fill_source_priority
+            .is_higher(fill_event.source_type, received_source_type)
+        {
+            return;
+        }
+
+        tracing::info!(
+            "Updating metadata of fill {} received from {received_source_type:?} by fill from {:?}",
+            received_fill.id(),
+            fill_event.source_type,
+        );
+
+        let fill_id = received_fill.id();
+        order_ref.fn_mut(|order| {
+            if let Some(fill) = order.fills.fills.iter_mut().find(|x| x.id() == fill_id) {
+                fill.update_metadata(
+                    fill_event.source_type,
+                    fill_event.order_role.map(Into::into),
+                    fill_event.commission_currency_code,
+                    fill_event.commission_amount,
+                );
+            }
+        });
+
+        if let Some(commission_amount) = fill_event.commission_amount {
+            let commission_currency_code = fill_event
+                .commission_currency_code
+                .unwrap_or(received_fill.commission_currency_code());
+            if commission_amount != received_fill.commission_amount()
+                || commission_currency_code != received_fill.commission_currency_code()
+            {
+                self.move_balance_by_commission_difference(
+                    received_fill,
+                    commission_currency_code,
+                    commission_amount,
+                );
+            }
+        }
+
+        self.data_recorder()
+            .save_order(&order_ref.deep_clone())
+            .unwrap_or_else(|err| {
+                tracing::error!("Unable save order {}: {err:?}", order_ref.client_order_id())
+            });
+    }
+
+    /// Commission of the received fill is already applied to balances,
+    /// so balance is moved by the difference between applied and updated commissions
+    fn move_balance_by_commission_difference(
+        &self,
+        received_fill: &OrderFill,
+        commission_currency_code: CurrencyCode,
+        commission_amount: Amount,
+    ) {
+        let balance_manager = self
+            .balance_manager
+            .lock()
+            .as_ref()
+            .and_then(|balance_manager| balance_manager.upgrade());
+        match balance_manager {
+            None => tracing::warn!(
+                "BalanceManager isn't set to apply commission difference of fill {} on {}",
+                received_fill.id(),
+                self.exchange_account_id
+            ),
+            Some(balance_manager) => balance_manager.lock().fill_commission_was_updated(
+                self.exchange_account_id,
+                received_fill.commission_currency_code(),
+                received_fill.commission_amount(),
+                commission_currency_code,
+                commission_amount,
+            ),
+        }
+    }
+
+    fn diff_fill_after_non_diff(fill_event: &FillEvent, order_fills: &[OrderFill]) -> bool {
         if matches!(fill_event.fill_amount, FillAmount::Incremental { .. })
             && order_fills.iter().any(|fill| !fill.is_incremental_fill())
         {
@@ -173,7 +521,10 @@ impl Exchange {
             // It happens when WebSocket is glitchy and we miss update and the problem is we have no idea how to handle diff updates
             // after applying a non-diff one as there's no TradeId, so we have to ignore all the diff updates afterwards
             // relying only on fallbacks
-            log::warn!("Unable to process a diff fill after a non-diff one {order_ref:?}");
+            tracing::warn!(
+                "Unable to process a diff fill after a non-diff one, fills count {}",
+                order_fills.len()
+            );
 
             return true;
         }
@@ -184,16 +535,13 @@ impl Exchange {
     fn filled_amount_not_less_event_fill(
         fill_event: &FillEvent,
         order_filled_amount: Amount,
-        order_ref: &OrderRef,
     ) -> bool {
         match fill_event.fill_amount {
             FillAmount::Total {
                 total_filled_amount,
             } if order_filled_amount >= total_filled_amount => {
-                log::warn!(
-                    "order.filled_amount is {order_filled_amount} >= received fill {total_filled_amount}, so non-diff fill for {} {:?} should be ignored",
-                    order_ref.client_order_id(),
-                    order_ref.exchange_order_id(),
+                tracing::warn!(
+                    "order.filled_amount is {order_filled_amount} >= received fill {total_filled_amount}, so non-diff fill should be ignored"
                 );
 
                 true
@@ -202,15 +550,51 @@ impl Exchange {
         }
     }
 
+    /// Special fills (liquidation, close position) should belong to special orders of the same type
+    /// and user trades shouldn't belong to special orders. Orders of unknown type can't be checked
+    fn is_fill_type_mismatched(order_type: OrderType, fill_type: OrderFillType) -> bool {
+        if order_type == OrderType::Unknown {
+            return false;
+        }
+
+        match fill_type {
+            OrderFillType::Liquidation => order_type != OrderType::Liquidation,
+            OrderFillType::ClosePosition => order_type != OrderType::ClosePosition,
+            OrderFillType::UserTrade => {
+                matches!(
+                    order_type,
+                    OrderType::Liquidation | OrderType::ClosePosition
+                )
+            }
+            OrderFillType::Funding => false,
+        }
+    }
+
+    fn should_reject_fill_by_type(&self, fill_event: &FillEvent, order_ref: &OrderRef) -> bool {
+        let order_type = order_ref.order_type();
+        if !Self::is_fill_type_mismatched(order_type, fill_event.fill_type) {
+            return false;
+        }
+
+        let reaction = *self.fill_type_mismatch_reaction.lock();
+        tracing::error!(
+            "Fill type {:?} is inconsistent with {order_type:?} order, reaction: {reaction:?}",
+            fill_event.fill_type,
+        );
+
+        reaction == FillTypeMismatchReaction::Reject
+    }
+
     fn should_miss_fill(
         fill_event: &FillEvent,
         order_filled_amount: Amount,
         last_fill_amount: Amount,
-        order_ref: &OrderRef,
     ) -> bool {
         if let Some(total_filled_amount) = fill_event.fill_amount.total_filled_amount() {
             if order_filled_amount + last_fill_amount != total_filled_amount {
-                log::warn!("Fill was missed because {order_filled_amount} != {total_filled_amount} for {order_ref:?}");
+                tracing::warn!(
+                    "Fill was missed because {order_filled_amount} + {last_fill_amount} != {total_filled_amount}"
+                );
                 return true;
             }
         }
@@ -223,7 +607,6 @@ impl Exchange {
         symbol: &Symbol,
         order_fills: &[OrderFill],
         order_filled_amount: Amount,
-        order_ref: &OrderRef,
     ) -> Option<(Price, Amount, Decimal)> {
         fn calc_last_fill(
             fill_event: &FillEvent,
@@ -246,7 +629,7 @@ impl Exchange {
                 let (_, last_fill_amount, last_fill_cost) =
                     calc_last_fill(fill_event, total_filled_amount, symbol);
 
-                let cost_diff = Self::calculate_cost_diff(order_fills, order_ref, last_fill_cost)?;
+                let cost_diff = Self::calculate_cost_diff(order_fills, last_fill_cost)?;
                 let (price, amount, cost) = Self::calculate_last_fill_data(
                     last_fill_amount,
                     order_filled_amount,
@@ -267,11 +650,7 @@ impl Exchange {
         };
 
         if last_fill_amount.is_zero() {
-            log::warn!(
-                "last_fill_amount was received for 0 for {}, {:?}",
-                order_ref.client_order_id(),
-                order_ref.exchange_order_id()
-            );
+            tracing::warn!("last_fill_amount was received for 0");
 
             return None;
         }
@@ -279,16 +658,12 @@ impl Exchange {
         Some((last_fill_price, last_fill_amount, last_fill_cost))
     }
 
-    fn calculate_cost_diff(
-        order_fills: &[OrderFill],
-        order_ref: &OrderRef,
-        last_fill_cost: Decimal,
-    ) -> Option<Decimal> {
+    fn calculate_cost_diff(order_fills: &[OrderFill], last_fill_cost: Decimal) -> Option<Decimal> {
         // Diff should be calculated only if it is not the first fill
         let total_filled_cost: Decimal = order_fills.iter().map(|fill| fill.cost()).sum();
         let cost_diff = last_fill_cost - total_filled_cost;
         if cost_diff <= dec!(0) {
-            log::warn!("cost_diff is {cost_diff} which is <= 0 for {order_ref:?}");
+            tracing::warn!("cost_diff is {cost_diff} which is <= 0");
             return None;
         }
 
@@ -335,23 +710,53 @@ impl Exchange {
             );
         }
 
+        if status == OrderStatus::Canceled {
+            // exchange filled the order before processing cancellation, so the fill is real
+            tracing::warn!("Fill was received for already canceled order {fill_event:?}");
+            return false;
+        }
+
         if was_cancellation_event_raised {
-            log::warn!(
+            tracing::warn!(
                 "Fill was received for a {status:?} {was_cancellation_event_raised} {fill_event:?}"
             );
         }
         was_cancellation_event_raised
     }
 
-    fn get_order_role(fill_event: &FillEvent, order_ref: &OrderRef) -> OrderRole {
+    /// Fill without commission, commission rate and known role gets configured default role
+    /// and zero commission, otherwise it's treated as an error.
+    /// Maker only order can't be filled as taker, so it always gets maker role
+    fn get_order_role(&self, fill_event: &mut FillEvent, order_ref: &OrderRef) -> OrderRole {
         match fill_event.order_role {
             Some(order_role) => order_role,
             None => {
+                if order_ref.header().options.execution_type()
+                    == Some(OrderExecutionType::MakerOnly)
+                {
+                    return OrderRole::Maker;
+                }
+
                 if fill_event.commission_amount.is_none()
                     && fill_event.commission_rate.is_none()
                     && order_ref.role().is_none()
                 {
-                    panic!("Fill has neither commission nor commission rate");
+                    let default_role = self
+                        .commissionless_fill_role
+                        .lock()
+                        .expect("Fill has neither commission nor commission rate");
+
+                    if !self
+                        .was_commissionless_fill_logged
+                        .swap(true, Ordering::Relaxed)
+                    {
+                        tracing::warn!(
+                            "Fill has neither commission nor commission rate, assuming {default_role:?} role with zero commission for such fills"
+                        );
+                    }
+
+                    fill_event.commission_amount = Some(dec!(0));
+                    return default_role;
                 }
 
                 order_ref.role().expect("Unable to determine order_role")
@@ -418,7 +823,11 @@ impl Exchange {
                         .as_ref()
                         .expect("There are no top bid in order book");
                     let price_bnb_quote = bid.price;
-                    *converted_commission_amount = commission_amount * price_bnb_quote;
+                    *converted_commission_amount = self.round_converted_commission(
+                        symbol,
+                        symbol.quote_currency_code(),
+                        commission_amount * price_bnb_quote,
+                    );
                     *converted_commission_currency_code = symbol.quote_currency_code();
                 }
                 None => {
@@ -434,13 +843,72 @@ impl Exchange {
                                 .as_ref()
                                 .expect("There are no top ask in order book");
                             let price_quote_bnb = ask.price;
-                            *converted_commission_amount = commission_amount / price_quote_bnb;
+                            *converted_commission_amount = self.round_converted_commission(
+                                symbol,
+                                symbol.quote_currency_code(),
+                                commission_amount / price_quote_bnb,
+                            );
                             *converted_commission_currency_code = symbol.quote_currency_code();
                         }
-                        None => log::error!(
-                            "Top bids and asks for {} and currency pair {currency_pair:?} do not exist",
-                            self.exchange_account_id,
-                        ),
+                        None => match self.convert_commission_by_price_source(
+                            commission_currency_code,
+                            symbol.quote_currency_code(),
+                            commission_amount,
+                        ) {
+                            Some(commission_in_quote) => {
+                                *converted_commission_amount = self.round_converted_commission(
+                                    symbol,
+                                    symbol.quote_currency_code(),
+                                    commission_in_quote,
+                                );
+                                *converted_commission_currency_code = symbol.quote_currency_code();
+                            }
+                            None => tracing::error!(
+                                "Top bids and asks for currency pair {currency_pair:?} do not exist"
+                            ),
+                        },
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fallback for commission conversion if there are no top prices for the direct conversion.
+    /// Uses only prices cached by price source service, so fill handling never waits for it
+    fn convert_commission_by_price_source(
+        &self,
+        from: CurrencyCode,
+        to: CurrencyCode,
+        commission_amount: Amount,
+    ) -> Option<Amount> {
+        self.price_source_service
+            .lock()
+            .as_ref()?
+            .try_convert_amount_sync(from, to, commission_amount)
+    }
+
+    /// Converted commission is rounded by precision of its currency: price precision is expressed
+    /// in quote currency and amount precision in amount currency
+    fn round_converted_commission(
+        &self,
+        symbol: &Symbol,
+        converted_commission_currency_code: CurrencyCode,
+        converted_commission: Amount,
+    ) -> Amount {
+        match *self.converted_commission_rounding.lock() {
+            ConvertedCommissionRounding::Disabled => converted_commission,
+            ConvertedCommissionRounding::Up => {
+                if converted_commission_currency_code == symbol.quote_currency_code() {
+                    return symbol.price_round(converted_commission, Round::Ceiling);
+                }
+
+                match symbol.try_amount_round(converted_commission, Round::Ceiling) {
+                    Ok(rounded) => rounded,
+                    Err(error) => {
+                        tracing::warn!(
+                            "Converted commission {converted_commission} {converted_commission_currency_code} isn't rounded: {error:?}"
+                        );
+                        converted_commission
                     }
                 }
             }
@@ -459,16 +927,20 @@ impl Exchange {
         }
     }
 
-    fn send_order_filled_event(&self, order_ref: &OrderRef) {
+    fn send_order_filled_event(&self, order_ref: &OrderRef, is_filled_after_cancel: bool) {
         let cloned_order = Arc::new(order_ref.deep_clone());
-        self.add_event_on_order_change(order_ref, OrderEventType::OrderFilled { cloned_order })
+        let event_type = match is_filled_after_cancel {
+            true => OrderEventType::FilledAfterCancel { cloned_order },
+            false => OrderEventType::OrderFilled { cloned_order },
+        };
+        self.add_event_on_order_change(order_ref, event_type)
             .expect("Unable to send event, probably receiver is dropped already");
     }
 
     fn react_if_order_completed(&self, order_filled_amount: Amount, order_ref: &OrderRef) {
         if order_filled_amount == order_ref.amount() {
             order_ref.fn_mut(|order| {
-                order.set_status(OrderStatus::Completed, Utc::now());
+                order.set_status(OrderStatus::Completed, self.now());
             });
 
             let cloned_order = Arc::new(order_ref.deep_clone());
@@ -477,6 +949,22 @@ impl Exchange {
                 OrderEventType::OrderCompleted { cloned_order },
             )
             .expect("Unable to send event, probably receiver is dropped already");
+
+            let (order_init_time, order_finished_time) = order_ref
+                .fn_ref(|snapshot| (snapshot.props.init_time, snapshot.props.finished_time));
+            match order_finished_time {
+                Some(order_finished_time) => self.emit_order_metrics(&MetricsEventInfoBase::new(
+                    order_init_time.timestamp_millis(),
+                    order_finished_time.timestamp_millis(),
+                    MetricsEventType::OrderFromCreateToFill,
+                )),
+                None => tracing::error!(
+                    "Completed order {} on {} doesn't have finished time",
+                    order_ref.client_order_id(),
+                    self.exchange_account_id
+                ),
+            }
+            self.emit_order_lifecycle_metrics(order_ref);
         }
     }
 
@@ -485,6 +973,7 @@ impl Exchange {
         &self,
         trade_id: &Option<TradeId>,
         is_diff: bool,
+        source_type: EventSourceType,
         fill_type: OrderFillType,
         symbol: &Symbol,
         order_ref: &OrderRef,
@@ -512,14 +1001,12 @@ impl Exchange {
 
         let rounded_fill_price = symbol.price_round(last_fill_price, Round::ToNearest);
 
-        let client_order_id = order_ref.client_order_id();
         let side = order_ref.side();
-        let exchange_order_id = order_ref.exchange_order_id();
 
         let order_fill = OrderFill::new(
             Uuid::new_v4(),
             Some(ClientOrderFillId::unique_id()),
-            Utc::now(),
+            self.now(),
             fill_type,
             trade_id.clone(),
             rounded_fill_price,
@@ -533,14 +1020,20 @@ impl Exchange {
             converted_commission_amount,
             expected_converted_commission_amount,
             is_diff,
-            None,
+            Some(source_type),
             Some(side),
         );
 
-        log::info!(
-            "Adding a fill {} {trade_id:?} {client_order_id} {exchange_order_id:?} {order_fill:?}",
-            self.exchange_account_id
-        );
+        tracing::info!("Adding a fill {trade_id:?} {order_fill:?}");
+
+        self.data_recorder()
+            .save_fill(&order_fill)
+            .unwrap_or_else(|err| {
+                tracing::error!(
+                    "Unable save order fill {} {trade_id:?}: {err:?}",
+                    order_ref.client_order_id()
+                )
+            });
 
         order_ref.fn_mut(move |order| order.add_fill(order_fill));
     }
@@ -548,15 +1041,20 @@ impl Exchange {
     fn create_and_add_order_fill(&self, fill_event: &mut FillEvent, order_ref: &OrderRef) {
         let (order_fills, order_filled_amount) = order_ref.get_fills();
 
-        if Self::was_trade_already_received(&fill_event.trade_id, &order_fills, order_ref) {
+        if let Some(received_fill) = Self::find_already_received_fill(fill_event, &order_fills) {
+            self.update_fill_from_prioritized_source(fill_event, received_fill, order_ref);
+            return;
+        }
+
+        if Self::diff_fill_after_non_diff(fill_event, &order_fills) {
             return;
         }
 
-        if Self::diff_fill_after_non_diff(fill_event, &order_fills, order_ref) {
+        if Self::filled_amount_not_less_event_fill(fill_event, order_filled_amount) {
             return;
         }
 
-        if Self::filled_amount_not_less_event_fill(fill_event, order_filled_amount, order_ref) {
+        if self.should_reject_fill_by_type(fill_event, order_ref) {
             return;
         }
 
@@ -568,13 +1066,12 @@ impl Exchange {
             &symbol,
             &order_fills,
             order_filled_amount,
-            order_ref,
         ) {
             Some(last_fill_data) => last_fill_data,
             None => return,
         };
 
-        if Self::should_miss_fill(fill_event, order_filled_amount, last_fill_amount, order_ref) {
+        if Self::should_miss_fill(fill_event, order_filled_amount, last_fill_amount) {
             return;
         }
 
@@ -582,13 +1079,15 @@ impl Exchange {
             return;
         }
 
-        log::info!("Received fill {fill_event:?} {last_fill_price} {last_fill_amount}");
+        tracing::info!("Received fill {fill_event:?} {last_fill_price} {last_fill_amount}");
+
+        let is_filled_after_cancel = order_ref.status() == OrderStatus::Canceled;
 
         let commission_currency_code = fill_event
             .commission_currency_code
             .unwrap_or_else(|| symbol.get_commission_currency_code(order_ref.side()));
 
-        let order_role = Self::get_order_role(fill_event, order_ref);
+        let order_role = self.get_order_role(fill_event, order_ref);
 
         let expected_commission_rate = self.set_commission_rate(fill_event, order_role);
 
@@ -616,6 +1115,7 @@ impl Exchange {
         self.add_fill(
             &fill_event.trade_id,
             matches!(fill_event.fill_amount, FillAmount::Incremental { .. }),
+            fill_event.source_type,
             fill_event.fill_type,
             &symbol,
             order_ref,
@@ -635,7 +1135,7 @@ impl Exchange {
 
         self.panic_if_fill_amounts_conformity(order_filled_amount, order_ref);
 
-        self.send_order_filled_event(order_ref);
+        self.send_order_filled_event(order_ref, is_filled_after_cancel);
 
         if fill_event.source_type == EventSourceType::RestFallback {
             // TODO some metrics
@@ -643,20 +1143,11 @@ impl Exchange {
 
         self.react_if_order_completed(order_filled_amount, order_ref);
 
-        let (order_init_time, order_finished_time) =
-            order_ref.fn_ref(|snapshot| (snapshot.props.init_time, snapshot.props.finished_time));
-        if let Some(order_finished_time) = order_finished_time {
-            let metrics_event_info = MetricsEventInfoBase::new(
-                order_init_time.timestamp_millis(),
-                order_finished_time.timestamp_millis(),
-                MetricsEventType::OrderFromCreateToFill,
-            );
-            self.save_metrics(&metrics_event_info, 0);
-        }
-
-        self.event_recorder
-            .save(&mut order_ref.deep_clone())
-            .expect("Failure save order");
+        self.data_recorder()
+            .save_order(&order_ref.deep_clone())
+            .unwrap_or_else(|err| {
+                tracing::error!("Unable save order {}: {err:?}", order_ref.client_order_id())
+            });
     }
 
     fn add_special_order_if_need(&self, fill_event: &mut FillEvent, args_to_log: &ArgsToLog) {
@@ -736,13 +1227,20 @@ impl Exchange {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::balance::manager::balance_manager::BalanceManager;
+    use crate::database::data_recorder::InMemoryDataRecorder;
+    use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
+    use crate::misc::time::Clock;
     use crate::{
         exchanges::general::exchange::OrderBookTop, exchanges::general::exchange::PriceLevel,
         exchanges::general::test_helper, exchanges::general::test_helper::create_order_ref,
         exchanges::general::test_helper::get_test_exchange,
     };
     use anyhow::{Context, Result};
-    use chrono::Utc;
+    use chrono::{TimeZone, Utc};
+    use itertools::Itertools;
+    use mmb_domain::events::{ExchangeBalance, ExchangeBalancesAndPositions, ExchangeEvent};
+    use mmb_domain::exchanges::symbol::Precision;
     use mmb_domain::market::CurrencyCode;
     use mmb_domain::order::fill::OrderFill;
     use mmb_domain::order::pool::OrdersPool;
@@ -751,6 +1249,8 @@ mod test {
         SystemInternalOrderProps,
     };
     use mmb_domain::order::snapshot::{OrderType, UserOrder};
+    use mmb_utils::hashmap;
+    use rstest::rstest;
     use serde_json::json;
     use uuid::Uuid;
 
@@ -761,26 +1261,27 @@ mod test {
     mod liquidation {
         use super::*;
 
+        fn liquidation_fill_event_builder(
+            exchange_order_id: &str,
+            fill_price: Price,
+            total_filled_amount: Amount,
+        ) -> FillEventBuilder {
+            FillEventBuilder::new(
+                EventSourceType::WebSocket,
+                Some(trade_id_from_str("empty")),
+                ExchangeOrderId::new(exchange_order_id.into()),
+                fill_price,
+                FillAmount::Total {
+                    total_filled_amount,
+                },
+                OrderFillType::Liquidation,
+            )
+        }
+
         #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
         #[should_panic(expected = "Special order data should be set for liquidation trade")]
         async fn empty_special_order_data() {
-            let mut fill_event = FillEvent {
-                source_type: EventSourceType::WebSocket,
-                trade_id: Some(trade_id_from_str("empty")),
-                client_order_id: None,
-                exchange_order_id: ExchangeOrderId::new("test".into()),
-                fill_price: dec!(0),
-                fill_amount: FillAmount::Total {
-                    total_filled_amount: dec!(0),
-                },
-                order_role: None,
-                commission_currency_code: None,
-                commission_rate: None,
-                commission_amount: None,
-                fill_type: OrderFillType::Liquidation,
-                special_order_data: None,
-                fill_date: None,
-            };
+            let mut fill_event = liquidation_fill_event_builder("test", dec!(0), dec!(0)).build();
 
             let (exchange, _) = get_test_exchange(false);
             exchange.handle_order_filled(&mut fill_event);
@@ -791,27 +1292,14 @@ mod test {
             expected = "Client order id cannot be set for liquidation or close position trade"
         )]
         async fn not_empty_client_order_id() {
-            let mut fill_event = FillEvent {
-                source_type: EventSourceType::WebSocket,
-                trade_id: Some(trade_id_from_str("empty")),
-                client_order_id: Some(ClientOrderId::unique_id()),
-                exchange_order_id: ExchangeOrderId::new("test".into()),
-                fill_price: dec!(0),
-                fill_amount: FillAmount::Total {
-                    total_filled_amount: dec!(0),
-                },
-                order_role: None,
-                commission_currency_code: None,
-                commission_rate: None,
-                commission_amount: None,
-                fill_type: OrderFillType::Liquidation,
-                special_order_data: Some(SpecialOrderData {
+            let mut fill_event = liquidation_fill_event_builder("test", dec!(0), dec!(0))
+                .client_order_id(ClientOrderId::unique_id())
+                .special_order_data(SpecialOrderData {
                     currency_pair: CurrencyPair::from_codes("te".into(), "st".into()),
                     order_side: OrderSide::Buy,
                     order_amount: dec!(7),
-                }),
-                fill_date: None,
-            };
+                })
+                .build();
 
             let (exchange, _) = get_test_exchange(false);
             exchange.handle_order_filled(&mut fill_event);
@@ -822,31 +1310,17 @@ mod test {
             let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
             let order_side = OrderSide::Buy;
             let order_amount = dec!(12);
-            let order_role = None;
             let fill_price = dec!(0.2);
-            let fill_amount = FillAmount::Total {
-                total_filled_amount: dec!(5),
-            };
+            let total_filled_amount = dec!(5);
 
-            let mut fill_event = FillEvent {
-                source_type: EventSourceType::WebSocket,
-                trade_id: Some(trade_id_from_str("empty")),
-                client_order_id: None,
-                exchange_order_id: ExchangeOrderId::new("test".into()),
-                fill_price,
-                fill_amount,
-                order_role,
-                commission_currency_code: None,
-                commission_rate: None,
-                commission_amount: None,
-                fill_type: OrderFillType::Liquidation,
-                special_order_data: Some(SpecialOrderData {
-                    currency_pair,
-                    order_side,
-                    order_amount,
-                }),
-                fill_date: None,
-            };
+            let mut fill_event =
+                liquidation_fill_event_builder("test", fill_price, total_filled_amount)
+                    .special_order_data(SpecialOrderData {
+                        currency_pair,
+                        order_side,
+                        order_amount,
+                    })
+                    .build();
 
             let (exchange, _event_received) = get_test_exchange(false);
             exchange.handle_order_filled(&mut fill_event);
@@ -866,34 +1340,20 @@ mod test {
             assert_eq!(order.role(), Some(OrderRole::Taker));
 
             let (fills, filled_amount) = order.get_fills();
-            assert_eq!(Some(filled_amount), fill_amount.total_filled_amount());
+            assert_eq!(filled_amount, total_filled_amount);
             assert_eq!(fills.get(0).expect("in test").price(), fill_price);
         }
 
         #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
         #[should_panic(expected = "Received HandleOrderFilled with an empty exchangeOrderId")]
         async fn empty_exchange_order_id() {
-            let mut fill_event = FillEvent {
-                source_type: EventSourceType::WebSocket,
-                trade_id: Some(trade_id_from_str("empty")),
-                client_order_id: None,
-                exchange_order_id: ExchangeOrderId::new("".into()),
-                fill_price: dec!(0),
-                fill_amount: FillAmount::Total {
-                    total_filled_amount: dec!(0),
-                },
-                order_role: None,
-                commission_currency_code: None,
-                commission_rate: None,
-                commission_amount: None,
-                fill_type: OrderFillType::Liquidation,
-                special_order_data: Some(SpecialOrderData {
+            let mut fill_event = liquidation_fill_event_builder("", dec!(0), dec!(0))
+                .special_order_data(SpecialOrderData {
                     currency_pair: CurrencyPair::from_codes("te".into(), "st".into()),
                     order_side: OrderSide::Buy,
                     order_amount: dec!(0),
-                }),
-                fill_date: None,
-            };
+                })
+                .build();
 
             let (exchange, _event_receiver) = get_test_exchange(false);
             exchange.handle_order_filled(&mut fill_event);
@@ -919,7 +1379,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id: Some(trade_id.clone()),
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price: dec!(0),
             fill_amount,
             order_role: None,
@@ -999,7 +1459,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id: Some(trade_id),
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price: dec!(0),
             fill_amount,
             order_role: None,
@@ -1077,7 +1537,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price: dec!(0),
             fill_amount,
             order_role: None,
@@ -1156,7 +1616,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price: dec!(0.2),
             fill_amount,
             order_role: None,
@@ -1235,7 +1695,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price: dec!(0.2),
             fill_amount,
             order_role: None,
@@ -1290,7 +1750,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price: dec!(0.2),
             fill_amount,
             order_role: None,
@@ -1345,7 +1805,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price,
             fill_amount,
             order_role: None,
@@ -1382,6 +1842,210 @@ mod test {
         assert!(order_ref.get_fills().0.is_empty());
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn fill_after_cancel_is_added_to_canceled_order() {
+        let (exchange, mut event_receiver) = get_test_exchange(false);
+
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let order_amount = dec!(5);
+        let mut order = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderOptions::limit(dec!(0.2)),
+            Some(OrderRole::Maker),
+            exchange.exchange_account_id,
+            currency_pair,
+            order_amount,
+            OrderSide::Buy,
+            None,
+            "FromTest",
+        );
+        order.set_status(OrderStatus::Canceled, Utc::now());
+        order.internal_props.was_cancellation_event_raised = true;
+        let order_ref = OrdersPool::new().add_snapshot_initial(&order);
+
+        let mut fill_event = FillEvent {
+            source_type: EventSourceType::WebSocket,
+            trade_id: Some(trade_id_from_str("test_trade_id")),
+            client_order_id: None,
+            exchange_order_id: ExchangeOrderId::placeholder(),
+            fill_price: dec!(0.2),
+            fill_amount: FillAmount::Incremental {
+                fill_amount: order_amount,
+                total_filled_amount: None,
+            },
+            order_role: None,
+            commission_currency_code: None,
+            commission_rate: None,
+            commission_amount: None,
+            fill_type: OrderFillType::UserTrade,
+            special_order_data: None,
+            fill_date: None,
+        };
+
+        exchange.create_and_add_order_fill(&mut fill_event, &order_ref);
+
+        let (fills, filled_amount) = order_ref.get_fills();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(filled_amount, order_amount);
+        assert_eq!(order_ref.status(), OrderStatus::Completed);
+
+        let event_types = std::iter::from_fn(|| event_receiver.try_recv().ok())
+            .filter_map(|event| match event {
+                ExchangeEvent::OrderEvent(order_event) => Some(order_event.event_type),
+                _ => None,
+            })
+            .collect_vec();
+        assert!(matches!(
+            event_types.as_slice(),
+            [
+                OrderEventType::FilledAfterCancel { .. },
+                OrderEventType::OrderCompleted { .. }
+            ]
+        ));
+    }
+
+    #[rstest]
+    #[case(FillTypeMismatchReaction::Log, 1)]
+    #[case(FillTypeMismatchReaction::Reject, 0)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn liquidation_fill_for_limit_order_is_flagged(
+        #[case] reaction: FillTypeMismatchReaction,
+        #[case] expected_fills_count: usize,
+    ) {
+        let (exchange, _event_receiver) = get_test_exchange(false);
+        exchange.set_fill_type_mismatch_reaction(reaction);
+
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let order = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderOptions::limit(dec!(0.2)),
+            Some(OrderRole::Maker),
+            exchange.exchange_account_id,
+            currency_pair,
+            dec!(5),
+            OrderSide::Buy,
+            None,
+            "FromTest",
+        );
+        let order_ref = OrdersPool::new().add_snapshot_initial(&order);
+
+        let mut fill_event = FillEvent {
+            source_type: EventSourceType::WebSocket,
+            trade_id: Some(trade_id_from_str("test_trade_id")),
+            client_order_id: None,
+            exchange_order_id: ExchangeOrderId::new("some_order_id".into()),
+            fill_price: dec!(0.2),
+            fill_amount: FillAmount::Incremental {
+                fill_amount: dec!(1),
+                total_filled_amount: None,
+            },
+            order_role: None,
+            commission_currency_code: None,
+            commission_rate: None,
+            commission_amount: None,
+            fill_type: OrderFillType::Liquidation,
+            special_order_data: Some(SpecialOrderData {
+                currency_pair,
+                order_side: OrderSide::Buy,
+                order_amount: dec!(0),
+            }),
+            fill_date: None,
+        };
+
+        assert!(Exchange::is_fill_type_mismatched(
+            order_ref.order_type(),
+            fill_event.fill_type
+        ));
+
+        exchange.create_and_add_order_fill(&mut fill_event, &order_ref);
+
+        assert_eq!(order_ref.get_fills().0.len(), expected_fills_count);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn fills_of_frozen_order_applied_after_unfreeze() {
+        let (exchange, _event_receiver) = get_test_exchange(false);
+
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let client_order_id = ClientOrderId::unique_id();
+        let exchange_order_id: ExchangeOrderId = "some_order_id".into();
+
+        let header = OrderHeader::with_user_order(
+            client_order_id.clone(),
+            exchange.exchange_account_id,
+            currency_pair,
+            OrderSide::Buy,
+            dec!(12),
+            UserOrder::limit(dec!(0.2)),
+            None,
+            None,
+            "FromTest".to_owned(),
+        );
+        let props = OrderSimpleProps::new(
+            Utc::now(),
+            Some(OrderRole::Maker),
+            Some(exchange_order_id.clone()),
+            Default::default(),
+            None,
+        );
+        let order = OrderSnapshot::new(
+            header,
+            props,
+            OrderFills::default(),
+            OrderStatusHistory::default(),
+            SystemInternalOrderProps::default(),
+            None,
+        );
+
+        let order_pool = OrdersPool::new();
+        let order_ref = order_pool.add_snapshot_initial(&order);
+        test_helper::try_add_snapshot_by_exchange_id(&exchange, &order_ref);
+
+        let create_fill_event = |trade_id: &str, fill_price, fill_amount| FillEvent {
+            source_type: EventSourceType::WebSocket,
+            trade_id: Some(trade_id_from_str(trade_id)),
+            client_order_id: Some(client_order_id.clone()),
+            exchange_order_id: exchange_order_id.clone(),
+            fill_price,
+            fill_amount: FillAmount::Incremental {
+                fill_amount,
+                total_filled_amount: None,
+            },
+            order_role: None,
+            commission_currency_code: None,
+            commission_rate: None,
+            commission_amount: Some(dec!(0.01)),
+            fill_type: OrderFillType::UserTrade,
+            special_order_data: None,
+            fill_date: None,
+        };
+
+        exchange.freeze_order(&client_order_id);
+        assert!(exchange.is_order_frozen(&client_order_id));
+
+        exchange.handle_order_filled(&mut create_fill_event("first_trade_id", dec!(0.2), dec!(2)));
+        exchange.handle_order_filled(&mut create_fill_event(
+            "second_trade_id",
+            dec!(0.3),
+            dec!(3),
+        ));
+
+        let (fills, filled_amount) = order_ref.get_fills();
+        assert!(fills.is_empty());
+        assert_eq!(filled_amount, dec!(0));
+
+        exchange.unfreeze_order(&client_order_id);
+        assert!(!exchange.is_order_frozen(&client_order_id));
+
+        let (fills, filled_amount) = order_ref.get_fills();
+        assert_eq!(filled_amount, dec!(5));
+        assert_eq!(fills.len(), 2);
+        assert_eq!(fills[0].price(), dec!(0.2));
+        assert_eq!(fills[0].amount(), dec!(2));
+        assert_eq!(fills[1].price(), dec!(0.3));
+        assert_eq!(fills[1].amount(), dec!(3));
+    }
+
     // TODO Can be improved via testing only calculate_cost_diff_function
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn calculate_cost_diff_on_buy_side() {
@@ -1971,7 +2635,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price: dec!(0.8),
             fill_amount,
             order_role: None,
@@ -2028,7 +2692,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price: dec!(0.8),
             fill_amount,
             order_role: Some(OrderRole::Taker),
@@ -2089,7 +2753,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price: dec!(0.8),
             fill_amount,
             order_role: None,
@@ -2147,11 +2811,11 @@ mod test {
         let order_amount = dec!(12);
         let trade_id = Some(trade_id_from_str("test_trade_id"));
 
-        let fill_event = FillEvent {
+        let mut fill_event = FillEvent {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price: dec!(0.8),
             fill_amount,
             order_role: None,
@@ -2183,7 +2847,106 @@ mod test {
         let order_pool = OrdersPool::new();
         let order_ref = order_pool.add_snapshot_initial(&order);
 
-        Exchange::get_order_role(&fill_event, &order_ref);
+        exchange.get_order_role(&mut fill_event, &order_ref);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn commissionless_fill_with_unknown_role_uses_default_role() {
+        let (exchange, _event_receiver) = get_test_exchange(false);
+        exchange.set_commissionless_fill_role(Some(OrderRole::Taker));
+
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let order = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderOptions::liquidation(dec!(0.2)),
+            None,
+            exchange.exchange_account_id,
+            currency_pair,
+            dec!(12),
+            OrderSide::Buy,
+            None,
+            "FromTest",
+        );
+        let order_ref = OrdersPool::new().add_snapshot_initial(&order);
+
+        let mut fill_event = FillEvent {
+            source_type: EventSourceType::WebSocket,
+            trade_id: Some(trade_id_from_str("test_trade_id")),
+            client_order_id: None,
+            exchange_order_id: ExchangeOrderId::placeholder(),
+            fill_price: dec!(0.2),
+            fill_amount: FillAmount::Incremental {
+                fill_amount: dec!(5),
+                total_filled_amount: None,
+            },
+            order_role: None,
+            commission_currency_code: None,
+            commission_rate: None,
+            commission_amount: None,
+            fill_type: OrderFillType::Liquidation,
+            special_order_data: Some(SpecialOrderData {
+                currency_pair,
+                order_side: OrderSide::Buy,
+                order_amount: dec!(0),
+            }),
+            fill_date: None,
+        };
+
+        exchange.create_and_add_order_fill(&mut fill_event, &order_ref);
+
+        let (fills, _) = order_ref.get_fills();
+        assert_eq!(fills.len(), 1);
+
+        let fill = &fills[0];
+        assert_eq!(fill.role(), OrderFillRole::Taker);
+        assert_eq!(fill.commission_amount(), dec!(0));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn maker_only_order_filled_as_maker() {
+        let (exchange, _event_receiver) = get_test_exchange(false);
+
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let order = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderOptions::maker_only(dec!(0.2)),
+            None,
+            exchange.exchange_account_id,
+            currency_pair,
+            dec!(12),
+            OrderSide::Buy,
+            None,
+            "FromTest",
+        );
+        let order_ref = OrdersPool::new().add_snapshot_initial(&order);
+
+        let mut fill_event = FillEvent {
+            source_type: EventSourceType::WebSocket,
+            trade_id: Some(trade_id_from_str("test_trade_id")),
+            client_order_id: None,
+            exchange_order_id: ExchangeOrderId::placeholder(),
+            fill_price: dec!(0.2),
+            fill_amount: FillAmount::Incremental {
+                fill_amount: dec!(5),
+                total_filled_amount: None,
+            },
+            order_role: None,
+            commission_currency_code: None,
+            commission_rate: None,
+            commission_amount: None,
+            fill_type: OrderFillType::UserTrade,
+            special_order_data: None,
+            fill_date: None,
+        };
+
+        exchange.create_and_add_order_fill(&mut fill_event, &order_ref);
+
+        let (fills, _) = order_ref.get_fills();
+        assert_eq!(fills.len(), 1);
+
+        let fill = &fills[0];
+        assert_eq!(fill.role(), OrderFillRole::Maker);
+        assert_eq!(fill.commission_amount(), dec!(0.1) / dec!(100) * dec!(5));
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -2206,7 +2969,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price: dec!(0.8),
             fill_amount,
             order_role: None,
@@ -2269,7 +3032,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price: dec!(0.8),
             fill_amount,
             order_role: None,
@@ -2332,7 +3095,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price: dec!(0.8),
             fill_amount,
             order_role: None,
@@ -2404,7 +3167,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price: dec!(0.8),
             fill_amount,
             order_role: None,
@@ -2466,7 +3229,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price,
             fill_amount,
             order_role: None,
@@ -2527,7 +3290,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price,
             fill_amount,
             order_role: None,
@@ -2588,7 +3351,7 @@ mod test {
             source_type: EventSourceType::WebSocket,
             trade_id,
             client_order_id: None,
-            exchange_order_id: ExchangeOrderId::new("".into()),
+            exchange_order_id: ExchangeOrderId::placeholder(),
             fill_price,
             fill_amount,
             order_role: None,
@@ -2731,6 +3494,7 @@ mod test {
             exchange.add_fill(
                 &trade_id,
                 is_diff,
+                EventSourceType::WebSocket,
                 OrderFillType::Liquidation,
                 &symbol,
                 &order_ref,
@@ -2793,6 +3557,7 @@ mod test {
             exchange.add_fill(
                 &trade_id,
                 is_diff,
+                EventSourceType::WebSocket,
                 OrderFillType::Liquidation,
                 &symbol,
                 &order_ref,
@@ -2853,6 +3618,7 @@ mod test {
             exchange.add_fill(
                 &trade_id,
                 is_diff,
+                EventSourceType::WebSocket,
                 OrderFillType::Liquidation,
                 &symbol,
                 &order_ref,
@@ -2968,6 +3734,78 @@ mod test {
             Ok(())
         }
 
+        #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+        async fn order_from_create_to_fill_metrics_emitted_on_completion() -> Result<()> {
+            let (exchange, mut event_receiver) = get_test_exchange(false);
+            let client_order_id = ClientOrderId::unique_id();
+            let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+            let order_amount = dec!(12);
+            let order_ref = create_order_ref(
+                &client_order_id,
+                Some(OrderRole::Maker),
+                exchange.exchange_account_id,
+                currency_pair,
+                dec!(0.2),
+                order_amount,
+                OrderSide::Buy,
+            );
+
+            exchange.react_if_order_completed(order_amount, &order_ref);
+
+            let metrics_event = std::iter::from_fn(|| event_receiver.try_recv().ok())
+                .find_map(|event| match event {
+                    ExchangeEvent::Metrics(v) => Some(v),
+                    _ => None,
+                })
+                .context("Metrics event was not received")?;
+
+            assert!(matches!(
+                metrics_event.event_type(),
+                MetricsEventType::OrderFromCreateToFill
+            ));
+            assert!(metrics_event.latency() >= 0);
+            Ok(())
+        }
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+        async fn order_lifecycle_metrics_emitted_on_each_status_change() {
+            let (exchange, mut event_receiver) = get_test_exchange(false);
+            let client_order_id = ClientOrderId::unique_id();
+            let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+            let order_amount = dec!(12);
+            let order_ref = create_order_ref(
+                &client_order_id,
+                Some(OrderRole::Maker),
+                exchange.exchange_account_id,
+                currency_pair,
+                dec!(0.2),
+                order_amount,
+                OrderSide::Buy,
+            );
+            assert_eq!(order_ref.status(), OrderStatus::Creating);
+
+            order_ref.fn_mut(|order| order.set_status(OrderStatus::Created, Utc::now()));
+            exchange.emit_order_lifecycle_metrics(&order_ref);
+            exchange.react_if_order_completed(order_amount, &order_ref);
+
+            let lifecycle_metrics = std::iter::from_fn(|| event_receiver.try_recv().ok())
+                .filter_map(|event| match event {
+                    ExchangeEvent::Metrics(metrics_event) => match metrics_event.event_type() {
+                        MetricsEventType::OrderLifeCycle(status) => {
+                            Some((status, metrics_event.latency()))
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(lifecycle_metrics.len(), 2);
+            assert_eq!(lifecycle_metrics[0].0, OrderStatus::Created);
+            assert_eq!(lifecycle_metrics[1].0, OrderStatus::Completed);
+            assert!(lifecycle_metrics.iter().all(|(_, latency)| *latency >= 0));
+        }
+
         #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
         async fn order_not_filled() {
             let (exchange, _event_receiver) = get_test_exchange(false);
@@ -2999,6 +3837,10 @@ mod test {
 
     mod update_commission_for_bnb_case {
         use super::*;
+        use crate::misc::time;
+        use crate::services::usd_convertion::price_source_service::test::PriceSourceServiceTestBase;
+        use mmb_domain::order_book_data;
+        use parking_lot::Mutex;
 
         #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
         async fn using_top_bid() {
@@ -3087,9 +3929,16 @@ mod test {
             assert_eq!(converted_commission_currency_code, right_currency_code);
         }
 
+        #[rstest]
+        #[case::rounded_up(ConvertedCommissionRounding::Up, dec!(5))]
+        #[case::not_rounded(ConvertedCommissionRounding::Disabled, dec!(4.95))]
         #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-        async fn fatal_error() {
+        async fn converted_commission_rounding(
+            #[case] rounding: ConvertedCommissionRounding,
+            #[case] right_amount: Amount,
+        ) {
             let (exchange, _event_receiver) = get_test_exchange(false);
+            exchange.set_converted_commission_rounding(rounding);
 
             let commission_currency_code = CurrencyCode::new("BNB");
             let symbol = exchange
@@ -3100,8 +3949,100 @@ mod test {
                 .value()
                 .clone();
             let commission_amount = dec!(15);
-            let mut converted_commission_amount = dec!(3);
-            let mut converted_commission_currency_code = CurrencyCode::new("BTC");
+            let mut converted_commission_amount = commission_amount;
+            let mut converted_commission_currency_code = commission_currency_code;
+
+            let currency_pair =
+                CurrencyPair::from_codes(commission_currency_code, symbol.quote_currency_code);
+            let order_book_top = OrderBookTop {
+                ask: None,
+                bid: Some(PriceLevel {
+                    price: dec!(0.33),
+                    amount: dec!(0.1),
+                }),
+            };
+            exchange
+                .order_book_top
+                .insert(currency_pair, order_book_top);
+
+            exchange.update_commission_for_bnb_case(
+                commission_currency_code,
+                &symbol,
+                commission_amount,
+                &mut converted_commission_amount,
+                &mut converted_commission_currency_code,
+            );
+
+            assert_eq!(converted_commission_amount, right_amount);
+            assert_eq!(
+                converted_commission_currency_code,
+                symbol.quote_currency_code
+            );
+        }
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+        async fn using_price_source_if_no_top_prices() {
+            let seconds_offset = Arc::new(Mutex::new(0));
+            let (_time_mock, _time_locker) = time::tests::init_mock(seconds_offset);
+
+            let (exchange, _event_receiver) = get_test_exchange(false);
+
+            let commission_currency_code = CurrencyCode::new("BNB");
+            let symbol = exchange
+                .symbols
+                .iter()
+                .next()
+                .expect("in test")
+                .value()
+                .clone();
+            let commission_amount = dec!(15);
+            let mut converted_commission_amount = commission_amount;
+            let mut converted_commission_currency_code = commission_currency_code;
+
+            let price_source_service = PriceSourceServiceTestBase::create_service_with_snapshot(
+                commission_currency_code,
+                symbol.quote_currency_code,
+                CurrencyPair::from_codes(commission_currency_code, symbol.quote_currency_code),
+                order_book_data![
+                    dec!(0.4) => dec!(1),
+                    ;
+                    dec!(0.2) => dec!(1),
+                ]
+                .to_orderbook_snapshot(Utc::now()),
+            );
+            exchange.set_price_source_service(Some(price_source_service));
+
+            exchange.update_commission_for_bnb_case(
+                commission_currency_code,
+                &symbol,
+                commission_amount,
+                &mut converted_commission_amount,
+                &mut converted_commission_currency_code,
+            );
+
+            // middle price of BNB/BTC is 0.3
+            assert_eq!(converted_commission_amount, dec!(4.5));
+            assert_eq!(
+                converted_commission_currency_code,
+                symbol.quote_currency_code
+            );
+        }
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+        async fn fatal_error() {
+            let (exchange, _event_receiver) = get_test_exchange(false);
+
+            let commission_currency_code = CurrencyCode::new("BNB");
+            let symbol = exchange
+                .symbols
+                .iter()
+                .next()
+                .expect("in test")
+                .value()
+                .clone();
+            let commission_amount = dec!(15);
+            let mut converted_commission_amount = dec!(3);
+            let mut converted_commission_currency_code = CurrencyCode::new("BTC");
 
             exchange.update_commission_for_bnb_case(
                 commission_currency_code,
@@ -3242,4 +4183,643 @@ mod test {
         let order_status = order_ref.status();
         assert_eq!(order_status, OrderStatus::Completed);
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn fill_time_taken_from_exchange_clock() {
+        struct FixedClock(DateTime);
+
+        impl Clock for FixedClock {
+            fn now(&self) -> DateTime {
+                self.0
+            }
+        }
+
+        let (exchange, _event_receiver) = get_test_exchange(false);
+        let fixed_time = Utc
+            .with_ymd_and_hms(2022, 3, 4, 5, 6, 7)
+            .single()
+            .expect("in test");
+        exchange.set_clock(Arc::new(FixedClock(fixed_time)));
+
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let order_amount = dec!(5);
+        let exchange_order_id: ExchangeOrderId = "some_order_id".into();
+        let header = OrderHeader::with_user_order(
+            ClientOrderId::unique_id(),
+            exchange.exchange_account_id,
+            currency_pair,
+            OrderSide::Buy,
+            order_amount,
+            UserOrder::limit(dec!(0.2)),
+            None,
+            None,
+            "FromTest".to_owned(),
+        );
+        let props = OrderSimpleProps::new(
+            Utc::now(),
+            Some(OrderRole::Maker),
+            Some(exchange_order_id.clone()),
+            Default::default(),
+            None,
+        );
+        let order = OrderSnapshot::new(
+            header,
+            props,
+            OrderFills::default(),
+            OrderStatusHistory::default(),
+            SystemInternalOrderProps::default(),
+            None,
+        );
+        let order_ref = OrdersPool::new().add_snapshot_initial(&order);
+        test_helper::try_add_snapshot_by_exchange_id(&exchange, &order_ref);
+
+        let mut fill_event = FillEventBuilder::new(
+            EventSourceType::WebSocket,
+            Some(trade_id_from_str("test_trade_id")),
+            exchange_order_id,
+            dec!(0.2),
+            FillAmount::Total {
+                total_filled_amount: order_amount,
+            },
+            OrderFillType::UserTrade,
+        )
+        .commission_amount(dec!(0))
+        .build();
+
+        exchange.handle_order_filled(&mut fill_event);
+
+        let (fills, _) = order_ref.get_fills();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].receive_time(), fixed_time);
+        assert_eq!(order_ref.status(), OrderStatus::Completed);
+        assert_eq!(
+            order_ref.fn_ref(|order| order.props.finished_time),
+            Some(fixed_time)
+        );
+    }
+
+    #[rstest]
+    #[case::settlement(OrderFillType::Funding, dec!(9.99))]
+    #[case::user_trade(OrderFillType::UserTrade, dec!(10))]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn zero_price_derivative_fill(
+        #[case] fill_type: OrderFillType,
+        #[case] expected_balance: Amount,
+    ) {
+        let phb = CurrencyCode::new("PHB");
+        let btc = CurrencyCode::new("BTC");
+        let currency_pair = CurrencyPair::from_codes(phb, btc);
+        let symbol = Arc::new(Symbol::new(
+            true,
+            "PHB".into(),
+            phb,
+            "BTC".into(),
+            btc,
+            None,
+            None,
+            None,
+            None,
+            None,
+            btc,
+            Some(phb),
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0) },
+        ));
+        let (exchange, _event_receiver) =
+            test_helper::get_test_exchange_with_symbol(symbol.clone());
+        let exchange_account_id = exchange.exchange_account_id;
+
+        let balance_manager = BalanceManager::new(
+            CurrencyPairToSymbolConverter::new(hashmap![exchange_account_id => exchange.clone()]),
+            None,
+        );
+        exchange.setup_balance_manager(balance_manager.clone());
+        balance_manager
+            .lock()
+            .update_exchange_balance(
+                exchange_account_id,
+                &ExchangeBalancesAndPositions {
+                    balances: vec![ExchangeBalance {
+                        currency_code: phb,
+                        balance: dec!(10),
+                    }],
+                    positions: None,
+                    event_time: None,
+                },
+            )
+            .expect("in test");
+
+        let exchange_order_id: ExchangeOrderId = "some_order_id".into();
+        let header = OrderHeader::with_user_order(
+            ClientOrderId::unique_id(),
+            exchange_account_id,
+            currency_pair,
+            OrderSide::Buy,
+            dec!(5),
+            UserOrder::limit(dec!(0.2)),
+            None,
+            None,
+            "FromTest".to_owned(),
+        );
+        let props = OrderSimpleProps::new(
+            Utc::now(),
+            Some(OrderRole::Maker),
+            Some(exchange_order_id.clone()),
+            Default::default(),
+            None,
+        );
+        let order = OrderSnapshot::new(
+            header,
+            props,
+            OrderFills::default(),
+            OrderStatusHistory::default(),
+            SystemInternalOrderProps::default(),
+            None,
+        );
+        let order_ref = OrdersPool::new().add_snapshot_initial(&order);
+        test_helper::try_add_snapshot_by_exchange_id(&exchange, &order_ref);
+
+        let mut fill_event = FillEventBuilder::new(
+            EventSourceType::Rest,
+            Some(trade_id_from_str("zero_price_trade_id")),
+            exchange_order_id,
+            dec!(0),
+            FillAmount::Incremental {
+                fill_amount: dec!(1),
+                total_filled_amount: None,
+            },
+            fill_type,
+        )
+        .commission_currency_code(phb)
+        .commission_amount(dec!(0.01))
+        .build();
+
+        exchange.handle_order_filled(&mut fill_event);
+
+        let (fills, filled_amount) = order_ref.get_fills();
+        assert!(fills.is_empty());
+        assert_eq!(filled_amount, dec!(0));
+
+        let balance = balance_manager
+            .lock()
+            .get_exchange_balance(exchange_account_id, symbol, phb);
+        assert_eq!(balance, Some(expected_balance));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn completed_order_is_recorded_once() {
+        let (exchange, _event_receiver) = get_test_exchange(false);
+        let data_recorder = Arc::new(InMemoryDataRecorder::default());
+        exchange.set_data_recorder(data_recorder.clone());
+
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let order_amount = dec!(5);
+        let exchange_order_id: ExchangeOrderId = "some_order_id".into();
+        let header = OrderHeader::with_user_order(
+            ClientOrderId::unique_id(),
+            exchange.exchange_account_id,
+            currency_pair,
+            OrderSide::Buy,
+            order_amount,
+            UserOrder::limit(dec!(0.2)),
+            None,
+            None,
+            "FromTest".to_owned(),
+        );
+        let props = OrderSimpleProps::new(
+            Utc::now(),
+            Some(OrderRole::Maker),
+            Some(exchange_order_id.clone()),
+            Default::default(),
+            None,
+        );
+        let order = OrderSnapshot::new(
+            header,
+            props,
+            OrderFills::default(),
+            OrderStatusHistory::default(),
+            SystemInternalOrderProps::default(),
+            None,
+        );
+        let order_ref = OrdersPool::new().add_snapshot_initial(&order);
+        test_helper::try_add_snapshot_by_exchange_id(&exchange, &order_ref);
+
+        for (trade_id, fill_amount) in [("first_trade_id", dec!(2)), ("second_trade_id", dec!(3))] {
+            let mut fill_event = FillEventBuilder::new(
+                EventSourceType::WebSocket,
+                Some(trade_id_from_str(trade_id)),
+                exchange_order_id.clone(),
+                dec!(0.2),
+                FillAmount::Incremental {
+                    fill_amount,
+                    total_filled_amount: None,
+                },
+                OrderFillType::UserTrade,
+            )
+            .commission_amount(dec!(0))
+            .build();
+
+            exchange.handle_order_filled(&mut fill_event);
+        }
+
+        assert_eq!(order_ref.status(), OrderStatus::Completed);
+
+        let recorded_fills = data_recorder.fills();
+        assert_eq!(recorded_fills.len(), 2);
+
+        let completed_orders = data_recorder
+            .orders()
+            .into_iter()
+            .filter(|order| order.status() == OrderStatus::Completed)
+            .collect_vec();
+        assert_eq!(completed_orders.len(), 1);
+        assert_eq!(
+            completed_orders[0].header.client_order_id,
+            order_ref.client_order_id()
+        );
+        assert_eq!(completed_orders[0].fills.filled_amount, order_amount);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn websocket_fill_updates_metadata_of_fallback_fill() {
+        let (exchange, _event_receiver) = get_test_exchange(false);
+
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let exchange_order_id: ExchangeOrderId = "some_order_id".into();
+        let header = OrderHeader::with_user_order(
+            ClientOrderId::unique_id(),
+            exchange.exchange_account_id,
+            currency_pair,
+            OrderSide::Buy,
+            dec!(5),
+            UserOrder::limit(dec!(0.2)),
+            None,
+            None,
+            "FromTest".to_owned(),
+        );
+        let props = OrderSimpleProps::new(
+            Utc::now(),
+            Some(OrderRole::Maker),
+            Some(exchange_order_id.clone()),
+            Default::default(),
+            None,
+        );
+        let order = OrderSnapshot::new(
+            header,
+            props,
+            OrderFills::default(),
+            OrderStatusHistory::default(),
+            SystemInternalOrderProps::default(),
+            None,
+        );
+        let order_ref = OrdersPool::new().add_snapshot_initial(&order);
+        test_helper::try_add_snapshot_by_exchange_id(&exchange, &order_ref);
+
+        let fill_amount = FillAmount::Incremental {
+            fill_amount: dec!(2),
+            total_filled_amount: None,
+        };
+        let mut fallback_fill_event = FillEventBuilder::new(
+            EventSourceType::RestFallback,
+            Some(trade_id_from_str("test_trade_id")),
+            exchange_order_id.clone(),
+            dec!(0.2),
+            fill_amount,
+            OrderFillType::UserTrade,
+        )
+        .build();
+        exchange.handle_order_filled(&mut fallback_fill_event);
+
+        let mut websocket_fill_event = FillEventBuilder::new(
+            EventSourceType::WebSocket,
+            Some(trade_id_from_str("test_trade_id")),
+            exchange_order_id,
+            dec!(0.2),
+            fill_amount,
+            OrderFillType::UserTrade,
+        )
+        .order_role(OrderRole::Taker)
+        .commission_amount(dec!(0.01))
+        .build();
+        exchange.handle_order_filled(&mut websocket_fill_event);
+
+        let (fills, filled_amount) = order_ref.get_fills();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(filled_amount, dec!(2));
+
+        let fill = &fills[0];
+        assert_eq!(fill.event_source_type(), Some(EventSourceType::WebSocket));
+        assert_eq!(fill.role(), OrderFillRole::Taker);
+        assert_eq!(fill.commission_amount(), dec!(0.01));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn websocket_fill_moves_balance_by_commission_difference() {
+        let btc = CurrencyCode::new("BTC");
+        let symbol = Arc::new(test_helper::create_test_symbol(
+            false,
+            "PHB",
+            "BTC",
+            "PHB",
+            None,
+            dec!(0.1),
+            dec!(0),
+        ));
+        let (exchange, _event_receiver) =
+            test_helper::get_test_exchange_with_symbol(symbol.clone());
+        let exchange_account_id = exchange.exchange_account_id;
+
+        let balance_manager = BalanceManager::new(
+            CurrencyPairToSymbolConverter::new(hashmap![exchange_account_id => exchange.clone()]),
+            None,
+        );
+        exchange.setup_balance_manager(balance_manager.clone());
+        balance_manager
+            .lock()
+            .update_exchange_balance(
+                exchange_account_id,
+                &ExchangeBalancesAndPositions {
+                    balances: vec![ExchangeBalance {
+                        currency_code: btc,
+                        balance: dec!(10),
+                    }],
+                    positions: None,
+                    event_time: None,
+                },
+            )
+            .expect("in test");
+
+        let exchange_order_id: ExchangeOrderId = "some_order_id".into();
+        let header = OrderHeader::with_user_order(
+            ClientOrderId::unique_id(),
+            exchange_account_id,
+            symbol.currency_pair(),
+            OrderSide::Buy,
+            dec!(5),
+            UserOrder::limit(dec!(0.2)),
+            None,
+            None,
+            "FromTest".to_owned(),
+        );
+        let props = OrderSimpleProps::new(
+            Utc::now(),
+            Some(OrderRole::Maker),
+            Some(exchange_order_id.clone()),
+            Default::default(),
+            None,
+        );
+        let order = OrderSnapshot::new(
+            header,
+            props,
+            OrderFills::default(),
+            OrderStatusHistory::default(),
+            SystemInternalOrderProps::default(),
+            None,
+        );
+        let order_ref = OrdersPool::new().add_snapshot_initial(&order);
+        test_helper::try_add_snapshot_by_exchange_id(&exchange, &order_ref);
+
+        let fill_amount = FillAmount::Incremental {
+            fill_amount: dec!(2),
+            total_filled_amount: None,
+        };
+        let mut fallback_fill_event = FillEventBuilder::new(
+            EventSourceType::RestFallback,
+            Some(trade_id_from_str("test_trade_id")),
+            exchange_order_id.clone(),
+            dec!(0.2),
+            fill_amount,
+            OrderFillType::UserTrade,
+        )
+        .commission_currency_code(btc)
+        .commission_amount(dec!(0.03))
+        .build();
+        exchange.handle_order_filled(&mut fallback_fill_event);
+
+        let mut websocket_fill_event = FillEventBuilder::new(
+            EventSourceType::WebSocket,
+            Some(trade_id_from_str("test_trade_id")),
+            exchange_order_id,
+            dec!(0.2),
+            fill_amount,
+            OrderFillType::UserTrade,
+        )
+        .commission_currency_code(btc)
+        .commission_amount(dec!(0.01))
+        .build();
+        exchange.handle_order_filled(&mut websocket_fill_event);
+
+        let fill = order_ref.get_fills().0[0].clone();
+        assert_eq!(fill.commission_amount(), dec!(0.01));
+
+        // fill itself is applied to balances by strategy, so only the commission difference is seen here
+        let balance = balance_manager
+            .lock()
+            .get_exchange_balance(exchange_account_id, symbol, btc);
+        assert_eq!(balance, Some(dec!(10.02)));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn fills_are_applied_in_order_after_fill_processing_resumed() {
+        let (exchange, _event_receiver) = get_test_exchange(false);
+
+        let exchange_order_id: ExchangeOrderId = "some_order_id".into();
+        let header = OrderHeader::with_user_order(
+            ClientOrderId::unique_id(),
+            exchange.exchange_account_id,
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            OrderSide::Buy,
+            dec!(5),
+            UserOrder::limit(dec!(0.2)),
+            None,
+            None,
+            "FromTest".to_owned(),
+        );
+        let props = OrderSimpleProps::new(
+            Utc::now(),
+            Some(OrderRole::Maker),
+            Some(exchange_order_id.clone()),
+            Default::default(),
+            None,
+        );
+        let order = OrderSnapshot::new(
+            header,
+            props,
+            OrderFills::default(),
+            OrderStatusHistory::default(),
+            SystemInternalOrderProps::default(),
+            None,
+        );
+        let order_ref = OrdersPool::new().add_snapshot_initial(&order);
+        test_helper::try_add_snapshot_by_exchange_id(&exchange, &order_ref);
+
+        exchange.set_fill_processing_enabled(false);
+
+        for (trade_id, fill_price, fill_amount) in [
+            ("first_trade_id", dec!(0.2), dec!(2)),
+            ("second_trade_id", dec!(0.3), dec!(1)),
+        ] {
+            let mut fill_event = FillEventBuilder::new(
+                EventSourceType::WebSocket,
+                Some(trade_id_from_str(trade_id)),
+                exchange_order_id.clone(),
+                fill_price,
+                FillAmount::Incremental {
+                    fill_amount,
+                    total_filled_amount: None,
+                },
+                OrderFillType::UserTrade,
+            )
+            .build();
+            exchange.handle_order_filled(&mut fill_event);
+        }
+
+        let (fills, filled_amount) = order_ref.get_fills();
+        assert!(fills.is_empty());
+        assert_eq!(filled_amount, dec!(0));
+
+        exchange.set_fill_processing_enabled(true);
+
+        let (fills, filled_amount) = order_ref.get_fills();
+        assert_eq!(filled_amount, dec!(3));
+        assert_eq!(
+            fills
+                .iter()
+                .map(|fill| (fill.trade_id().cloned(), fill.price(), fill.amount()))
+                .collect_vec(),
+            vec![
+                (
+                    Some(trade_id_from_str("first_trade_id")),
+                    dec!(0.2),
+                    dec!(2)
+                ),
+                (
+                    Some(trade_id_from_str("second_trade_id")),
+                    dec!(0.3),
+                    dec!(1)
+                ),
+            ]
+        );
+    }
+
+    mod tracing_span {
+        use super::*;
+        use parking_lot::Mutex;
+        use std::collections::HashMap;
+        use std::fmt::Debug;
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::Subscriber;
+        use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+        use tracing_subscriber::registry::LookupSpan;
+
+        const SPAN_NAME: &str = "handle_order_filled";
+
+        /// Collects fields of `handle_order_filled` span
+        #[derive(Default, Clone)]
+        struct SpanFieldsCapture {
+            fields: Arc<Mutex<HashMap<String, String>>>,
+        }
+
+        struct SpanFieldsVisitor<'a>(&'a mut HashMap<String, String>);
+
+        impl Visit for SpanFieldsVisitor<'_> {
+            fn record_str(&mut self, field: &Field, value: &str) {
+                self.0.insert(field.name().to_owned(), value.to_owned());
+            }
+
+            fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+                self.0.insert(field.name().to_owned(), format!("{value:?}"));
+            }
+        }
+
+        impl<S> Layer<S> for SpanFieldsCapture
+        where
+            S: Subscriber + for<'a> LookupSpan<'a>,
+        {
+            fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+                if attrs.metadata().name() == SPAN_NAME {
+                    attrs.record(&mut SpanFieldsVisitor(&mut self.fields.lock()));
+                }
+            }
+
+            fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+                if ctx.metadata(id).map(|metadata| metadata.name()) == Some(SPAN_NAME) {
+                    values.record(&mut SpanFieldsVisitor(&mut self.fields.lock()));
+                }
+            }
+        }
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+        async fn fill_is_handled_in_span_with_order_ids() {
+            let (exchange, _event_receiver) = get_test_exchange(false);
+
+            let client_order_id = ClientOrderId::unique_id();
+            let exchange_order_id: ExchangeOrderId = "some_order_id".into();
+            let header = OrderHeader::with_user_order(
+                client_order_id.clone(),
+                exchange.exchange_account_id,
+                CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+                OrderSide::Buy,
+                dec!(5),
+                UserOrder::limit(dec!(0.2)),
+                None,
+                None,
+                "FromTest".to_owned(),
+            );
+            let props = OrderSimpleProps::new(
+                Utc::now(),
+                Some(OrderRole::Maker),
+                Some(exchange_order_id.clone()),
+                Default::default(),
+                None,
+            );
+            let order = OrderSnapshot::new(
+                header,
+                props,
+                OrderFills::default(),
+                OrderStatusHistory::default(),
+                SystemInternalOrderProps::default(),
+                None,
+            );
+            let order_ref = OrdersPool::new().add_snapshot_initial(&order);
+            test_helper::try_add_snapshot_by_exchange_id(&exchange, &order_ref);
+
+            // client order id isn't received with the fill, so it's recorded to the span from the found order
+            let mut fill_event = FillEventBuilder::new(
+                EventSourceType::WebSocket,
+                Some(trade_id_from_str("test_trade_id")),
+                exchange_order_id.clone(),
+                dec!(0.2),
+                FillAmount::Incremental {
+                    fill_amount: dec!(2),
+                    total_filled_amount: None,
+                },
+                OrderFillType::UserTrade,
+            )
+            .build();
+
+            let capture = SpanFieldsCapture::default();
+            let subscriber = tracing_subscriber::registry().with(capture.clone());
+            tracing::subscriber::with_default(subscriber, || {
+                exchange.handle_order_filled(&mut fill_event)
+            });
+
+            assert_eq!(order_ref.filled_amount(), dec!(2));
+
+            let fields = capture.fields.lock();
+            assert_eq!(
+                fields.get("exchange_account_id"),
+                Some(&exchange.exchange_account_id.to_string())
+            );
+            assert_eq!(
+                fields.get("client_order_id").map(String::as_str),
+                Some(client_order_id.as_str())
+            );
+            assert_eq!(
+                fields.get("exchange_order_id").map(String::as_str),
+                Some(exchange_order_id.as_str())
+            );
+        }
+    }
 }
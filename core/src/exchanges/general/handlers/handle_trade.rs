@@ -53,6 +53,11 @@ impl Exchange {
             }
         }
 
+        for trade in &trades_event.trades {
+            self.trades_arrival
+                .add(currency_pair, trade.side, trades_event.receipt_time);
+        }
+
         self.events_channel
             .send(ExchangeEvent::Trades(trades_event.clone()))
             .expect("Unable to send trades event. Probably receiver is already dropped");
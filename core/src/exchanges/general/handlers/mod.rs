@@ -13,8 +13,43 @@ pub(crate) fn should_ignore_event(
     use EventSourceType::*;
 
     match allowed_event_source_type {
-        FallbackOnly if source_type != RestFallback => true,
-        NonFallback if source_type != Rest && source_type != WebSocket => true,
-        _ => false,
+        All => false,
+        FallbackOnly => source_type != RestFallback,
+        NonFallback => source_type != Rest && source_type != WebSocket,
+        RpcOnly => source_type != Rpc,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mmb_domain::events::{AllowedEventSourceType, EventSourceType};
+    use rstest::rstest;
+
+    use super::should_ignore_event;
+
+    #[rstest]
+    #[case::all(AllowedEventSourceType::All, false)]
+    #[case::fallback_only(AllowedEventSourceType::FallbackOnly, true)]
+    #[case::non_fallback(AllowedEventSourceType::NonFallback, true)]
+    #[case::rpc_only(AllowedEventSourceType::RpcOnly, false)]
+    fn rpc_event_handling(
+        #[case] allowed_event_source_type: AllowedEventSourceType,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(
+            should_ignore_event(allowed_event_source_type, EventSourceType::Rpc),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::rest_fallback(EventSourceType::RestFallback)]
+    #[case::rest(EventSourceType::Rest)]
+    #[case::web_socket(EventSourceType::WebSocket)]
+    fn rpc_only_ignores_exchange_events(#[case] source_type: EventSourceType) {
+        assert!(should_ignore_event(
+            AllowedEventSourceType::RpcOnly,
+            source_type
+        ));
     }
 }
@@ -8,6 +8,7 @@ pub mod handlers;
 pub mod order;
 pub mod polling_timeout_manager;
 pub mod request_type;
+pub(crate) mod trades_arrival;
 
 #[cfg(test)]
 pub mod test_helper;
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use futures::future::join_all;
 use itertools::Itertools;
 use mmb_domain::events::EventSourceType;
@@ -9,6 +9,7 @@ use mmb_domain::order::snapshot::{ClientOrderId, ExchangeOrderId, OrderInfo, Ord
 use mmb_utils::cancellation_token::CancellationToken;
 use tokio::sync::oneshot;
 
+use crate::exchanges::general::request_type::RequestType;
 use crate::exchanges::traits::ExchangeError;
 use crate::misc::time::time_manager;
 use crate::{exchanges::general::exchange::Exchange, exchanges::general::exchange::RequestResult};
@@ -68,6 +69,7 @@ impl Exchange {
             }
             _ => {
                 order.fn_mut(|order| order.set_status(OrderStatus::Canceling, time_manager::now()));
+                self.emit_order_lifecycle_metrics(order);
 
                 log::info!(
                     "Submitting order cancellation {client_order_id} {exchange_order_id:?} on {}",
@@ -189,6 +191,59 @@ impl Exchange {
         }
     }
 
+    /// Cancel orders concurrently and return outcome of cancellation for each order,
+    /// so that only failed cancellations can be reissued.
+    /// Requests are reserved in `TimeoutManager` to respect rate limits of exchange
+    pub async fn cancel_orders_batch(
+        &self,
+        orders: Vec<OrderRef>,
+        cancellation_token: CancellationToken,
+    ) -> Vec<(ClientOrderId, Result<()>)> {
+        join_all(orders.iter().map(|order| async {
+            let outcome = self
+                .cancel_order_with_reservation(order, cancellation_token.clone())
+                .await;
+            (order.client_order_id(), outcome)
+        }))
+        .await
+    }
+
+    async fn cancel_order_with_reservation(
+        &self,
+        order: &OrderRef,
+        cancellation_token: CancellationToken,
+    ) -> Result<()> {
+        self.timeout_manager
+            .reserve_when_available(
+                self.exchange_account_id,
+                RequestType::CancelOrder,
+                None,
+                cancellation_token.clone(),
+            )
+            .await
+            .into_result()?;
+
+        match self.start_cancel_order(order, cancellation_token).await? {
+            Some(CancelOrderResult {
+                outcome: RequestResult::Error(error),
+                ..
+            }) => bail!(
+                "Failed to cancel order {} on {}: {error:?}",
+                order.client_order_id(),
+                self.exchange_account_id
+            ),
+            Some(_) => Ok(()),
+            None => match order.status() {
+                OrderStatus::Canceled => Ok(()),
+                status => bail!(
+                    "Cancellation of order {} on {} wasn't confirmed, order status is {status:?}",
+                    order.client_order_id(),
+                    self.exchange_account_id
+                ),
+            },
+        }
+    }
+
     pub(crate) async fn cancel_orders(
         &self,
         orders: Vec<OrderInfo>,
@@ -228,3 +283,142 @@ impl Exchange {
         join_all(futures).await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use mmb_domain::events::EventSourceType;
+    use mmb_domain::exchanges::symbol::{Precision, Symbol};
+    use mmb_domain::market::{CurrencyPair, ExchangeAccountId, ExchangeErrorType};
+    use mmb_domain::order::pool::OrderRef;
+    use mmb_domain::order::snapshot::{
+        ClientOrderId, ExchangeOrderId, OrderRole, OrderSide, OrderStatus,
+    };
+    use mmb_utils::cancellation_token::CancellationToken;
+    use rust_decimal_macros::dec;
+
+    use crate::exchanges::general::features::OpenOrdersType;
+    use crate::exchanges::general::order::cancel::CancelOrderResult;
+    use crate::exchanges::general::test_helper::{
+        create_order_ref, get_test_exchange_with_client, TestClient,
+    };
+    use crate::exchanges::traits::ExchangeError;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn cancel_orders_batch_reports_failed_cancellation() {
+        let failed_exchange_order_id = ExchangeOrderId::from("failed");
+        let exchange_client = TestClient {
+            cancel_order: Some(Box::new({
+                let failed_exchange_order_id = failed_exchange_order_id.clone();
+                move |order, exchange_order_id| {
+                    if *exchange_order_id == failed_exchange_order_id {
+                        return CancelOrderResult::failed(
+                            ExchangeError::new(
+                                ExchangeErrorType::Unknown,
+                                "Rejected in test".to_owned(),
+                                None,
+                            ),
+                            EventSourceType::Rest,
+                        );
+                    }
+
+                    CancelOrderResult::succeed(order.client_order_id(), EventSourceType::Rest, None)
+                }
+            })),
+            ..TestClient::default()
+        };
+
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let symbol = Arc::new(Symbol::new(
+            false,
+            "PHB".into(),
+            "PHB".into(),
+            "BTC".into(),
+            "BTC".into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            "PHB".into(),
+            None,
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0) },
+        ));
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            symbol,
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            exchange_client,
+            OpenOrdersType::AllCurrencyPair,
+        );
+
+        let create_created_order = |exchange_order_id: &str| -> OrderRef {
+            let order_ref = create_order_ref(
+                &ClientOrderId::unique_id(),
+                Some(OrderRole::Maker),
+                exchange.exchange_account_id,
+                currency_pair,
+                dec!(0.8),
+                dec!(12),
+                OrderSide::Buy,
+            );
+            let exchange_order_id = ExchangeOrderId::from(exchange_order_id);
+            order_ref.fn_mut(|x| {
+                x.props.exchange_order_id = Some(exchange_order_id.clone());
+                x.set_status(OrderStatus::Created, chrono::Utc::now());
+            });
+            let _ = exchange
+                .orders
+                .cache_by_exchange_id
+                .insert(exchange_order_id, order_ref.clone());
+            order_ref
+        };
+
+        let orders = vec![
+            create_created_order("first"),
+            create_created_order("failed"),
+            create_created_order("third"),
+        ];
+
+        // exchange confirms successful cancellations via websocket
+        let confirmation = tokio::spawn({
+            let exchange = exchange.clone();
+            let orders = vec![orders[0].clone(), orders[2].clone()];
+            async move {
+                for order in orders {
+                    let exchange_order_id = order.exchange_order_id().expect("in test");
+                    while !exchange
+                        .order_cancellation_events
+                        .contains_key(&exchange_order_id)
+                    {
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                    }
+
+                    exchange.raise_order_cancelled(
+                        order.client_order_id(),
+                        exchange_order_id,
+                        EventSourceType::WebSocket,
+                    );
+                }
+            }
+        });
+
+        let outcomes = exchange
+            .cancel_orders_batch(orders.clone(), CancellationToken::default())
+            .await;
+        confirmation.await.expect("in test");
+
+        assert_eq!(outcomes.len(), 3);
+        for ((client_order_id, outcome), order) in outcomes.iter().zip(&orders) {
+            assert_eq!(*client_order_id, order.client_order_id());
+            let is_failed_order =
+                order.exchange_order_id() == Some(failed_exchange_order_id.clone());
+            assert_eq!(outcome.is_err(), is_failed_order, "{outcome:?}");
+        }
+        assert_eq!(orders[0].status(), OrderStatus::Canceled);
+        assert_eq!(orders[1].status(), OrderStatus::FailedToCancel);
+        assert_eq!(orders[2].status(), OrderStatus::Canceled);
+    }
+}
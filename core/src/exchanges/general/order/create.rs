@@ -1,8 +1,10 @@
 use crate::exchanges::general::exchange::RequestResult::{Error, Success};
 use crate::exchanges::general::handlers::should_ignore_event;
 use crate::exchanges::general::request_type::RequestType;
+use crate::exchanges::timeouts::request::RequestPriority;
 use crate::exchanges::timeouts::requests_timeout_manager::RequestGroupId;
 use crate::exchanges::traits::ExchangeError;
+use crate::infrastructure::spawn_future;
 use crate::misc::time::time_manager;
 use crate::{exchanges::general::exchange::Exchange, exchanges::general::exchange::RequestResult};
 use anyhow::{bail, Context, Result};
@@ -14,11 +16,13 @@ use mmb_domain::market::{ExchangeAccountId, ExchangeErrorType};
 use mmb_domain::order::event::OrderEventType;
 use mmb_domain::order::pool::OrderRef;
 use mmb_domain::order::snapshot::{
-    ClientOrderId, ExchangeOrderId, OrderHeader, OrderInfo, OrderStatus, OrderType,
+    ClientOrderId, ExchangeOrderId, OrderHeader, OrderInfo, OrderStatus, OrderTimeInForce,
+    OrderType,
 };
 use mmb_utils::cancellation_token::CancellationToken;
+use mmb_utils::infrastructure::SpawnFutureFlags;
 use mmb_utils::time::ToStdExpected;
-use mmb_utils::{nothing_to_do, OPERATION_CANCELED_MSG};
+use mmb_utils::{nothing_to_do, DateTime, OPERATION_CANCELED_MSG};
 use std::borrow::Cow;
 use std::time::Duration;
 use tokio::sync::oneshot;
@@ -46,6 +50,49 @@ impl CreateOrderResult {
     }
 }
 
+/// Classification of failed order creation which defines whether creation can be retried
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CreateOrderError {
+    /// Request was rejected before placing order (rate limit), so order can be submitted again
+    Transient,
+    /// Request could reach exchange before failing (5xx, send error, timeout),
+    /// so order may be placed and its state should be resolved before submitting it again
+    Ambiguous,
+    /// Order can't be created as is (insufficient balance, invalid order, etc.)
+    Terminal,
+}
+
+impl CreateOrderError {
+    pub fn from_error_type(error_type: ExchangeErrorType) -> Self {
+        match error_type {
+            ExchangeErrorType::RateLimit => CreateOrderError::Transient,
+            ExchangeErrorType::ServiceUnavailable
+            | ExchangeErrorType::SendError
+            | ExchangeErrorType::PendingError(_) => CreateOrderError::Ambiguous,
+            _ => CreateOrderError::Terminal,
+        }
+    }
+}
+
+/// Bounded exponential backoff for retrying order creation after transient errors
+#[derive(Debug, Clone, Copy)]
+pub struct CreateOrderRetry {
+    /// Max number of creation attempts including the first one
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for CreateOrderRetry {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
 impl Exchange {
     pub async fn create_order(
         &self,
@@ -57,6 +104,32 @@ impl Exchange {
 
         log::info!("Submitting order {order_header:?}");
 
+        // order without prefix would be treated as manual one by reconciliation of open orders
+        if !self.is_own_client_order_id(&order_header.client_order_id) {
+            bail!(
+                "Client order id {} doesn't have configured prefix on {}, use Exchange::generate_client_order_id",
+                order_header.client_order_id,
+                self.exchange_account_id
+            );
+        }
+
+        if let Some(symbol) = self.symbols.get(&order_header.currency_pair) {
+            let error_context = || {
+                format!(
+                    "Unable to create order {} on {}",
+                    order_header.client_order_id, self.exchange_account_id
+                )
+            };
+
+            if let Some(price) = order_header.source_price {
+                symbol.validate_price(price).with_context(error_context)?;
+            }
+
+            symbol
+                .validate_order(order_header.source_price, order_header.amount)
+                .with_context(error_context)?;
+        }
+
         let order = self.orders.add_simple_initial(
             order_header,
             time_manager::now(),
@@ -163,7 +236,7 @@ impl Exchange {
                     handle_poll_creation_order_res(&order, poll_result, linked_ct)?;
                 }
             }
-            NonFallback => {
+            NonFallback | RpcOnly => {
                 let created_order_result = create_order_fut.await;
                 handle_create_order_res(
                     self,
@@ -184,6 +257,285 @@ impl Exchange {
         Ok(order)
     }
 
+    /// Wait for a free request slot with specified priority and then submit order.
+    /// Risk-reducing orders should use `RequestPriority::High` to be dispatched
+    /// before new aggressive orders while requests are rate-limited.
+    pub async fn create_order_with_priority(
+        &self,
+        order_header: &OrderHeader,
+        priority: RequestPriority,
+        pre_reservation_group_id: Option<RequestGroupId>,
+        cancellation_token: CancellationToken,
+    ) -> Result<OrderRef> {
+        self.timeout_manager
+            .reserve_when_available_with_priority(
+                self.exchange_account_id,
+                RequestType::CreateOrder,
+                priority,
+                pre_reservation_group_id,
+                cancellation_token.clone(),
+            )
+            .await
+            .into_result()?;
+
+        self.create_order(order_header, pre_reservation_group_id, cancellation_token)
+            .await
+    }
+
+    /// Create order retrying with exponential backoff while creation fails with transient errors.
+    /// Every attempt waits for a free request slot in timeout manager and uses new client order id,
+    /// because order of failed attempt stays in `FailedToCreate` status.
+    /// After ambiguous errors order is requested by client order id first and submitted again
+    /// only if exchange doesn't know it, so the same order is never placed twice
+    pub async fn create_order_with_retry(
+        &self,
+        order_header: &OrderHeader,
+        retry: CreateOrderRetry,
+        pre_reservation_group_id: Option<RequestGroupId>,
+        cancellation_token: CancellationToken,
+    ) -> Result<OrderRef> {
+        let mut order_header = Cow::Borrowed(order_header);
+        let mut backoff = retry.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            self.timeout_manager
+                .reserve_when_available(
+                    self.exchange_account_id,
+                    RequestType::CreateOrder,
+                    pre_reservation_group_id,
+                    cancellation_token.clone(),
+                )
+                .await
+                .into_result()?;
+
+            let error = match self
+                .create_order(
+                    &order_header,
+                    pre_reservation_group_id,
+                    cancellation_token.clone(),
+                )
+                .await
+            {
+                Ok(order) => return Ok(order),
+                Err(error) => error,
+            };
+
+            let client_order_id = &order_header.client_order_id;
+            let error_type = self
+                .orders
+                .cache_by_client_id
+                .get(client_order_id)
+                .and_then(|order| order.fn_ref(|x| x.internal_props.last_creation_error_type));
+            let can_retry = match error_type.map(CreateOrderError::from_error_type) {
+                Some(CreateOrderError::Transient) => true,
+                Some(CreateOrderError::Ambiguous) => {
+                    self.is_order_not_placed(
+                        client_order_id,
+                        pre_reservation_group_id,
+                        cancellation_token.clone(),
+                    )
+                    .await?
+                }
+                Some(CreateOrderError::Terminal) | None => false,
+            };
+            if !can_retry || attempt >= retry.max_attempts {
+                return Err(error.context(format!(
+                    "Unable to create order {client_order_id} on {} in {attempt} attempt(s)",
+                    self.exchange_account_id
+                )));
+            }
+
+            log::warn!(
+                "Creation of order {client_order_id} failed with transient error {error_type:?} on {}, retrying in {backoff:?}",
+                self.exchange_account_id
+            );
+
+            tokio::select! {
+                _ = sleep(backoff) => nothing_to_do(),
+                _ = cancellation_token.when_cancelled() => bail!(OPERATION_CANCELED_MSG),
+            }
+
+            backoff = (backoff * 2).min(retry.max_backoff);
+            attempt += 1;
+            order_header.to_mut().client_order_id = self.generate_client_order_id();
+        }
+    }
+
+    /// Check by client order id that order which failed to create with ambiguous error isn't placed on exchange.
+    /// Returns false if exchange doesn't support getting order info by client order id
+    /// and error if the order is found on exchange
+    async fn is_order_not_placed(
+        &self,
+        client_order_id: &ClientOrderId,
+        pre_reservation_group_id: Option<RequestGroupId>,
+        cancellation_token: CancellationToken,
+    ) -> Result<bool> {
+        if !self
+            .features
+            .order_features
+            .supports_get_order_info_by_client_order_id
+        {
+            log::warn!(
+                "Order {client_order_id} isn't retried on {} because its state can't be checked by client order id",
+                self.exchange_account_id
+            );
+            return Ok(false);
+        }
+
+        let order = self
+            .orders
+            .cache_by_client_id
+            .get(client_order_id)
+            .map(|x| x.clone())
+            .with_context(|| {
+                format!(
+                    "Order {client_order_id} is not found in local orders pool on {}",
+                    self.exchange_account_id
+                )
+            })?;
+
+        self.timeout_manager
+            .reserve_when_available(
+                self.exchange_account_id,
+                RequestType::GetOrderInfo,
+                pre_reservation_group_id,
+                cancellation_token,
+            )
+            .await
+            .into_result()?;
+
+        match self.get_order_info(&order).await {
+            Err(error) if error.error_type == ExchangeErrorType::OrderNotFound => Ok(true),
+            Err(error) => bail!(
+                "Unable to check state of order {client_order_id} on {} after failed creation: {error:?}",
+                self.exchange_account_id
+            ),
+            Ok(order_info) => bail!(
+                "Order {client_order_id} was placed as {} with status {:?} on {} despite creation error",
+                order_info.exchange_order_id,
+                order_info.order_status,
+                self.exchange_account_id
+            ),
+        }
+    }
+
+    /// Create order and resolve its real state if creation isn't finished in `create_timeout`.
+    /// Client order id is sent to exchange as idempotency key, so after timeout order info is
+    /// requested by client order id to find out whether order was actually placed,
+    /// instead of leaving order in ambiguous `Creating` state
+    pub async fn create_order_idempotent(
+        &self,
+        order_header: &OrderHeader,
+        create_timeout: Duration,
+        pre_reservation_group_id: Option<RequestGroupId>,
+        cancellation_token: CancellationToken,
+    ) -> Result<OrderRef> {
+        if !self
+            .features
+            .order_features
+            .supports_get_order_info_by_client_order_id
+        {
+            bail!(
+                "Idempotent order creation requires getting order info by client order id which isn't supported on {}",
+                self.exchange_account_id
+            );
+        }
+
+        self.timeout_manager
+            .reserve_when_available(
+                self.exchange_account_id,
+                RequestType::CreateOrder,
+                pre_reservation_group_id,
+                cancellation_token.clone(),
+            )
+            .await
+            .into_result()?;
+
+        let client_order_id = &order_header.client_order_id;
+        let linked_ct = cancellation_token.create_linked_token();
+        let create_order_fut =
+            self.create_order(order_header, pre_reservation_group_id, linked_ct.clone());
+        match timeout(create_timeout, create_order_fut).await {
+            Ok(create_order_result) => return create_order_result,
+            Err(_) => {
+                linked_ct.cancel();
+                self.order_creation_events.remove(client_order_id);
+            }
+        }
+
+        log::warn!(
+            "Order {client_order_id} wasn't created in {create_timeout:?} on {}, checking its state by client order id",
+            self.exchange_account_id
+        );
+
+        let order = self
+            .orders
+            .cache_by_client_id
+            .get(client_order_id)
+            .map(|x| x.clone())
+            .with_context(|| {
+                format!(
+                    "Order {client_order_id} is not found in local orders pool on {}",
+                    self.exchange_account_id
+                )
+            })?;
+
+        if order.status() == OrderStatus::Creating {
+            self.timeout_manager
+                .reserve_when_available(
+                    self.exchange_account_id,
+                    RequestType::GetOrderInfo,
+                    pre_reservation_group_id,
+                    cancellation_token.clone(),
+                )
+                .await
+                .into_result()?;
+
+            match self.get_order_info(&order).await {
+                Ok(order_info) => {
+                    let exchange_order_id = order.fn_mut(|order| {
+                        order.props.exchange_order_id = Some(order_info.exchange_order_id.clone());
+                        order.exchange_order_id()
+                    });
+                    self.handle_creating_order_from_check_order_info(
+                        client_order_id,
+                        &exchange_order_id,
+                        &order,
+                        &order_info,
+                    );
+                }
+                Err(error) if error.error_type == ExchangeErrorType::OrderNotFound => self
+                    .handle_create_order_failed(
+                        client_order_id,
+                        &error,
+                        EventSourceType::RestFallback,
+                    )?,
+                Err(error) => bail!(
+                    "Unable to resolve state of order {client_order_id} on {} after creation timeout: {error:?}",
+                    self.exchange_account_id
+                ),
+            }
+        }
+
+        match order.status() {
+            OrderStatus::Creating => bail!(
+                "Order {client_order_id} is still in Creating state after creation timeout on {}",
+                self.exchange_account_id
+            ),
+            OrderStatus::FailedToCreate => bail!(
+                "Order {client_order_id} wasn't created on {}",
+                self.exchange_account_id
+            ),
+            _ => {
+                self.handle_created_order(&order, pre_reservation_group_id, cancellation_token)
+                    .await
+                    .unwrap_or_else(|err| log::error!("failed handle_created_order: {err}"));
+
+                Ok(order)
+            }
+        }
+    }
+
     async fn handle_created_order(
         &self,
         order: &OrderRef,
@@ -220,9 +572,52 @@ impl Exchange {
             header.exchange_account_id,
         );
 
+        if let OrderTimeInForce::Gtt(expire_time) = header.time_in_force {
+            if !self.features.order_features.supports_good_till_time && !order.is_finished() {
+                self.cancel_order_on_expiry(order.clone(), expire_time);
+            }
+        }
+
         Ok(())
     }
 
+    /// Cancel good-till-time order when its time passes if exchange doesn't support such orders natively.
+    /// Fills received after the cancellation are still handled as for any canceled order
+    fn cancel_order_on_expiry(&self, order: OrderRef, expire_time: DateTime) {
+        let client_order_id = order.client_order_id();
+        let delay = (expire_time - self.now()).to_std().unwrap_or_default();
+        let exchange_weak = self.exchange_weak.clone();
+
+        let action = async move {
+            sleep(delay).await;
+
+            let exchange = match exchange_weak.upgrade() {
+                Some(exchange) => exchange,
+                None => return Ok(()),
+            };
+
+            if order.is_finished() {
+                return Ok(());
+            }
+
+            log::info!(
+                "Order {} expired at {expire_time} and will be canceled",
+                order.client_order_id()
+            );
+            let _ = exchange
+                .cancel_order(&order, CancellationToken::default())
+                .await;
+
+            Ok(())
+        };
+
+        let _ = spawn_future(
+            &format!("Cancel expired order {client_order_id}"),
+            SpawnFutureFlags::STOP_BY_TOKEN,
+            action,
+        );
+    }
+
     async fn poll_order_create(
         &self,
         order: OrderRef,
@@ -515,7 +910,7 @@ impl Exchange {
     }
 
     #[named]
-    fn handle_create_order_failed(
+    pub(super) fn handle_create_order_failed(
         &self,
         client_order_id: &ClientOrderId,
         exchange_error: &ExchangeError,
@@ -589,6 +984,7 @@ impl Exchange {
                 });
 
                 self.add_event_on_order_change(order, OrderEventType::CreateOrderFailed)?;
+                self.emit_order_lifecycle_metrics(order);
 
                 self.event_recorder
                     .save(&mut order.deep_clone())
@@ -721,6 +1117,7 @@ impl Exchange {
                 }
 
                 self.add_event_on_order_change(order, OrderEventType::CreateOrderSucceeded)?;
+                self.emit_order_lifecycle_metrics(order);
 
                 let mut buffered_fills_manager = self.buffered_fills_manager.lock();
                 if let Some(buffered_fills) = buffered_fills_manager.get_fills(exchange_order_id) {
@@ -805,3 +1202,639 @@ impl Exchange {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use chrono::Utc;
+    use mmb_domain::events::EventSourceType;
+    use mmb_domain::exchanges::symbol::{Precision, Symbol};
+    use mmb_domain::market::{CurrencyPair, ExchangeAccountId, ExchangeErrorType};
+    use mmb_domain::order::fill::OrderFillType;
+    use mmb_domain::order::pool::OrderRef;
+    use mmb_domain::order::snapshot::{
+        Amount, ClientOrderId, ExchangeOrderId, OrderHeader, OrderInfo, OrderRole, OrderSide,
+        OrderStatus, OrderTimeInForce, OrderType, UserOrder,
+    };
+    use mmb_utils::cancellation_token::CancellationToken;
+    use parking_lot::Mutex;
+    use rust_decimal_macros::dec;
+    use tokio::time::{sleep, timeout};
+
+    use crate::exchanges::general::exchange::Exchange;
+    use crate::exchanges::general::features::OpenOrdersType;
+    use crate::exchanges::general::handlers::handle_order_filled::{FillAmount, FillEvent};
+    use crate::exchanges::general::order::cancel::CancelOrderResult;
+    use crate::exchanges::general::order::create::{CreateOrderResult, CreateOrderRetry};
+    use crate::exchanges::general::test_helper::{get_test_exchange_with_client, TestClient};
+    use crate::exchanges::traits::ExchangeError;
+    use crate::infrastructure::init_lifetime_manager;
+
+    fn create_symbol() -> Arc<Symbol> {
+        Arc::new(Symbol::new(
+            false,
+            "PHB".into(),
+            "PHB".into(),
+            "BTC".into(),
+            "BTC".into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            "PHB".into(),
+            None,
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0) },
+        ))
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn market_order_created_without_price_and_filled() {
+        let exchange_order_id = ExchangeOrderId::from("market_order");
+        let exchange_client = TestClient {
+            create_order: Some(Box::new({
+                let exchange_order_id = exchange_order_id.clone();
+                move |order| {
+                    assert_eq!(order.order_type(), OrderType::Market);
+                    assert_eq!(order.source_price(), None);
+                    CreateOrderResult::succeed(&exchange_order_id, EventSourceType::Rest)
+                }
+            })),
+            ..TestClient::default()
+        };
+
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let symbol = create_symbol();
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            symbol.clone(),
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            exchange_client,
+            OpenOrdersType::AllCurrencyPair,
+        );
+
+        let client_order_id = ClientOrderId::unique_id();
+        let order_header = OrderHeader::with_user_order(
+            client_order_id.clone(),
+            exchange.exchange_account_id,
+            currency_pair,
+            OrderSide::Buy,
+            dec!(4),
+            UserOrder::market(),
+            None,
+            None,
+            "FromTest".to_owned(),
+        );
+
+        // exchange confirms order creation via websocket
+        let confirmation = tokio::spawn({
+            let exchange = exchange.clone();
+            let client_order_id = client_order_id.clone();
+            let exchange_order_id = exchange_order_id.clone();
+            async move {
+                while !exchange
+                    .order_creation_events
+                    .contains_key(&client_order_id)
+                {
+                    sleep(Duration::from_millis(10)).await;
+                }
+
+                exchange.raise_order_created(
+                    &client_order_id,
+                    &exchange_order_id,
+                    EventSourceType::WebSocket,
+                );
+            }
+        });
+
+        let order = exchange
+            .create_order(&order_header, None, CancellationToken::default())
+            .await
+            .expect("in test");
+        confirmation.await.expect("in test");
+
+        assert_eq!(order.status(), OrderStatus::Created);
+        assert_eq!(order.order_type(), OrderType::Market);
+        assert_eq!(order.source_price(), None);
+        assert_eq!(order.exchange_order_id(), Some(exchange_order_id.clone()));
+
+        let handle_fill = |trade_id: &str, fill_price, fill_amount, total_filled_amount| {
+            let mut fill_event = FillEvent {
+                source_type: EventSourceType::WebSocket,
+                trade_id: Some(trade_id.to_owned().into()),
+                client_order_id: Some(client_order_id.clone()),
+                exchange_order_id: exchange_order_id.clone(),
+                fill_price,
+                fill_amount: FillAmount::Incremental {
+                    fill_amount,
+                    total_filled_amount: Some(total_filled_amount),
+                },
+                order_role: Some(OrderRole::Taker),
+                commission_currency_code: None,
+                commission_rate: None,
+                commission_amount: None,
+                fill_type: OrderFillType::UserTrade,
+                special_order_data: None,
+                fill_date: None,
+            };
+            exchange.handle_order_filled(&mut fill_event);
+        };
+        handle_fill("first_trade", dec!(0.2), dec!(1), dec!(1));
+        handle_fill("second_trade", dec!(0.4), dec!(3), dec!(4));
+
+        let (fills, filled_amount) = order.get_fills();
+        assert_eq!(fills.len(), 2);
+        assert_eq!(filled_amount, dec!(4));
+        assert_eq!(order.status(), OrderStatus::Completed);
+        // (1 * 0.2 + 3 * 0.4) / 4
+        assert_eq!(order.average_fill_price(&symbol), Some(dec!(0.35)));
+        assert_eq!(order.source_price(), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn timed_out_order_creation_reconciled_by_client_order_id() {
+        let exchange_order_id = ExchangeOrderId::from("created_order");
+        let exchange_client = TestClient {
+            // order is placed, but confirmation via websocket is never received
+            create_order: Some(Box::new({
+                let exchange_order_id = exchange_order_id.clone();
+                move |_| CreateOrderResult::succeed(&exchange_order_id, EventSourceType::Rest)
+            })),
+            get_order_info: Some(Box::new({
+                let exchange_order_id = exchange_order_id.clone();
+                move |order| {
+                    Ok(OrderInfo::new(
+                        order.currency_pair(),
+                        exchange_order_id.clone(),
+                        order.client_order_id(),
+                        order.side(),
+                        OrderStatus::Created,
+                        order.price(),
+                        order.amount(),
+                        dec!(0),
+                        dec!(0),
+                        None,
+                        None,
+                        None,
+                    ))
+                }
+            })),
+            ..TestClient::default()
+        };
+
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            create_symbol(),
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            exchange_client,
+            OpenOrdersType::AllCurrencyPair,
+        );
+
+        let client_order_id = ClientOrderId::unique_id();
+        let order_header = OrderHeader::with_user_order(
+            client_order_id.clone(),
+            exchange.exchange_account_id,
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            OrderSide::Buy,
+            dec!(4),
+            UserOrder::limit(dec!(0.2)),
+            None,
+            None,
+            "FromTest".to_owned(),
+        );
+
+        let order = exchange
+            .create_order_idempotent(
+                &order_header,
+                Duration::from_millis(200),
+                None,
+                CancellationToken::default(),
+            )
+            .await
+            .expect("in test");
+
+        assert_eq!(order.client_order_id(), client_order_id);
+        assert_eq!(order.status(), OrderStatus::Created);
+        assert_eq!(order.exchange_order_id(), Some(exchange_order_id.clone()));
+        assert!(exchange
+            .orders
+            .cache_by_exchange_id
+            .contains_key(&exchange_order_id));
+        assert!(!exchange
+            .order_creation_events
+            .contains_key(&client_order_id));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn order_creation_retried_after_service_unavailable() {
+        let exchange_order_id = ExchangeOrderId::from("created_order");
+        let requested_client_order_ids = Arc::new(Mutex::new(Vec::<ClientOrderId>::new()));
+        let exchange_client = TestClient {
+            // failed order isn't placed
+            get_order_info: Some(Box::new(|_| {
+                Err(ExchangeError::new(
+                    ExchangeErrorType::OrderNotFound,
+                    "Order not found".to_owned(),
+                    None,
+                ))
+            })),
+            // first attempt fails with 503, second one is placed
+            create_order: Some(Box::new({
+                let exchange_order_id = exchange_order_id.clone();
+                let requested_client_order_ids = requested_client_order_ids.clone();
+                move |order| {
+                    let mut requested_client_order_ids = requested_client_order_ids.lock();
+                    requested_client_order_ids.push(order.client_order_id());
+                    match requested_client_order_ids.len() {
+                        1 => CreateOrderResult::failed(
+                            ExchangeError::new(
+                                ExchangeErrorType::ServiceUnavailable,
+                                "503 Service Unavailable".to_owned(),
+                                None,
+                            ),
+                            EventSourceType::Rest,
+                        ),
+                        _ => CreateOrderResult::succeed(&exchange_order_id, EventSourceType::Rest),
+                    }
+                }
+            })),
+            ..TestClient::default()
+        };
+
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            create_symbol(),
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            exchange_client,
+            OpenOrdersType::AllCurrencyPair,
+        );
+
+        let client_order_id = ClientOrderId::unique_id();
+        let order_header = OrderHeader::with_user_order(
+            client_order_id.clone(),
+            exchange.exchange_account_id,
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            OrderSide::Buy,
+            dec!(4),
+            UserOrder::limit(dec!(0.2)),
+            None,
+            None,
+            "FromTest".to_owned(),
+        );
+
+        // exchange confirms creation of the retried order via websocket
+        let confirmation = tokio::spawn({
+            let exchange = exchange.clone();
+            let exchange_order_id = exchange_order_id.clone();
+            let requested_client_order_ids = requested_client_order_ids.clone();
+            async move {
+                let retried_client_order_id = loop {
+                    let retried_client_order_id = requested_client_order_ids.lock().get(1).cloned();
+                    match retried_client_order_id {
+                        Some(retried_client_order_id)
+                            if exchange
+                                .order_creation_events
+                                .contains_key(&retried_client_order_id) =>
+                        {
+                            break retried_client_order_id
+                        }
+                        _ => sleep(Duration::from_millis(10)).await,
+                    }
+                };
+
+                exchange.raise_order_created(
+                    &retried_client_order_id,
+                    &exchange_order_id,
+                    EventSourceType::WebSocket,
+                );
+            }
+        });
+
+        let retry = CreateOrderRetry {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+        };
+        let order = exchange
+            .create_order_with_retry(&order_header, retry, None, CancellationToken::default())
+            .await
+            .expect("in test");
+        confirmation.await.expect("in test");
+
+        let requested_client_order_ids = requested_client_order_ids.lock().clone();
+        assert_eq!(requested_client_order_ids.len(), 2);
+        assert_eq!(requested_client_order_ids[0], client_order_id);
+        assert_ne!(requested_client_order_ids[1], client_order_id);
+
+        assert_eq!(order.client_order_id(), requested_client_order_ids[1]);
+        assert_eq!(order.status(), OrderStatus::Created);
+        assert_eq!(order.exchange_order_id(), Some(exchange_order_id));
+
+        let failed_order = exchange
+            .orders
+            .cache_by_client_id
+            .get(&client_order_id)
+            .map(|x| x.clone())
+            .expect("in test");
+        assert_eq!(failed_order.status(), OrderStatus::FailedToCreate);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn order_creation_not_retried_if_failed_order_was_placed() {
+        let exchange_order_id = ExchangeOrderId::from("created_order");
+        let create_requests_count = Arc::new(Mutex::new(0));
+        let exchange_client = TestClient {
+            // request times out on the way back, but the order is placed
+            create_order: Some(Box::new({
+                let create_requests_count = create_requests_count.clone();
+                move |_| {
+                    *create_requests_count.lock() += 1;
+                    CreateOrderResult::failed(
+                        ExchangeError::new(
+                            ExchangeErrorType::ServiceUnavailable,
+                            "504 Gateway Timeout".to_owned(),
+                            None,
+                        ),
+                        EventSourceType::Rest,
+                    )
+                }
+            })),
+            get_order_info: Some(Box::new({
+                let exchange_order_id = exchange_order_id.clone();
+                move |order| {
+                    Ok(OrderInfo::new(
+                        order.currency_pair(),
+                        exchange_order_id.clone(),
+                        order.client_order_id(),
+                        order.side(),
+                        OrderStatus::Created,
+                        order.price(),
+                        order.amount(),
+                        dec!(0),
+                        dec!(0),
+                        None,
+                        None,
+                        None,
+                    ))
+                }
+            })),
+            ..TestClient::default()
+        };
+
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            create_symbol(),
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            exchange_client,
+            OpenOrdersType::AllCurrencyPair,
+        );
+
+        let order_header = OrderHeader::with_user_order(
+            ClientOrderId::unique_id(),
+            exchange.exchange_account_id,
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            OrderSide::Buy,
+            dec!(4),
+            UserOrder::limit(dec!(0.2)),
+            None,
+            None,
+            "FromTest".to_owned(),
+        );
+
+        let retry = CreateOrderRetry {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+        };
+        let error = exchange
+            .create_order_with_retry(&order_header, retry, None, CancellationToken::default())
+            .await
+            .expect_err("in test");
+
+        assert!(format!("{error:?}").contains("despite creation error"));
+        assert_eq!(*create_requests_count.lock(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn order_without_client_order_id_prefix_is_rejected() {
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            create_symbol(),
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            TestClient::default(),
+            OpenOrdersType::AllCurrencyPair,
+        );
+        exchange.set_client_order_id_prefix(Some("mmb".to_owned()));
+
+        let order_header = OrderHeader::with_user_order(
+            ClientOrderId::unique_id(),
+            exchange.exchange_account_id,
+            currency_pair,
+            OrderSide::Buy,
+            dec!(4),
+            UserOrder::market(),
+            None,
+            None,
+            "FromTest".to_owned(),
+        );
+
+        let result = exchange
+            .create_order(&order_header, None, CancellationToken::default())
+            .await;
+
+        assert!(result.is_err());
+        assert!(exchange
+            .orders
+            .cache_by_client_id
+            .get(&order_header.client_order_id)
+            .is_none());
+    }
+
+    async fn create_confirmed_order(
+        exchange: &Arc<Exchange>,
+        order_header: &OrderHeader,
+        exchange_order_id: &ExchangeOrderId,
+    ) -> OrderRef {
+        // exchange confirms order creation via websocket
+        let confirmation = tokio::spawn({
+            let exchange = exchange.clone();
+            let client_order_id = order_header.client_order_id.clone();
+            let exchange_order_id = exchange_order_id.clone();
+            async move {
+                while !exchange
+                    .order_creation_events
+                    .contains_key(&client_order_id)
+                {
+                    sleep(Duration::from_millis(10)).await;
+                }
+
+                exchange.raise_order_created(
+                    &client_order_id,
+                    &exchange_order_id,
+                    EventSourceType::WebSocket,
+                );
+            }
+        });
+
+        let order = exchange
+            .create_order(order_header, None, CancellationToken::default())
+            .await
+            .expect("in test");
+        confirmation.await.expect("in test");
+
+        order
+    }
+
+    fn handle_fill(
+        exchange: &Exchange,
+        order: &OrderRef,
+        trade_id: &str,
+        fill_amount: Amount,
+        total_filled_amount: Amount,
+    ) {
+        let mut fill_event = FillEvent {
+            source_type: EventSourceType::WebSocket,
+            trade_id: Some(trade_id.to_owned().into()),
+            client_order_id: Some(order.client_order_id()),
+            exchange_order_id: order.exchange_order_id().expect("in test"),
+            fill_price: order.price(),
+            fill_amount: FillAmount::Incremental {
+                fill_amount,
+                total_filled_amount: Some(total_filled_amount),
+            },
+            order_role: Some(OrderRole::Taker),
+            commission_currency_code: None,
+            commission_rate: None,
+            commission_amount: None,
+            fill_type: OrderFillType::UserTrade,
+            special_order_data: None,
+            fill_date: None,
+        };
+        exchange.handle_order_filled(&mut fill_event);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn immediate_or_cancel_order_partially_filled_and_canceled() {
+        let exchange_order_id = ExchangeOrderId::from("ioc_order");
+        let exchange_client = TestClient {
+            create_order: Some(Box::new({
+                let exchange_order_id = exchange_order_id.clone();
+                move |order| {
+                    assert_eq!(order.header().time_in_force, OrderTimeInForce::Ioc);
+                    CreateOrderResult::succeed(&exchange_order_id, EventSourceType::Rest)
+                }
+            })),
+            ..TestClient::default()
+        };
+
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            create_symbol(),
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            exchange_client,
+            OpenOrdersType::AllCurrencyPair,
+        );
+
+        let order_header = OrderHeader::with_user_order(
+            ClientOrderId::unique_id(),
+            exchange.exchange_account_id,
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            OrderSide::Buy,
+            dec!(4),
+            UserOrder::limit(dec!(0.2)),
+            None,
+            None,
+            "FromTest".to_owned(),
+        )
+        .with_time_in_force(OrderTimeInForce::Ioc);
+
+        let order = create_confirmed_order(&exchange, &order_header, &exchange_order_id).await;
+
+        // exchange fills available part of order and cancels the rest immediately
+        handle_fill(&exchange, &order, "first_trade", dec!(1), dec!(1));
+        exchange.raise_order_cancelled(
+            order.client_order_id(),
+            exchange_order_id,
+            EventSourceType::WebSocket,
+        );
+
+        let (fills, filled_amount) = order.get_fills();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(filled_amount, dec!(1));
+        assert_eq!(order.status(), OrderStatus::Canceled);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn good_till_time_order_canceled_locally_on_expiry() {
+        let _ = init_lifetime_manager();
+
+        let exchange_order_id = ExchangeOrderId::from("gtt_order");
+        let cancel_requests_count = Arc::new(Mutex::new(0));
+        let exchange_client = TestClient {
+            create_order: Some(Box::new({
+                let exchange_order_id = exchange_order_id.clone();
+                move |_| CreateOrderResult::succeed(&exchange_order_id, EventSourceType::Rest)
+            })),
+            cancel_order: Some(Box::new({
+                let cancel_requests_count = cancel_requests_count.clone();
+                move |order, _| {
+                    *cancel_requests_count.lock() += 1;
+                    CancelOrderResult::succeed(order.client_order_id(), EventSourceType::Rest, None)
+                }
+            })),
+            ..TestClient::default()
+        };
+
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            create_symbol(),
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            exchange_client,
+            OpenOrdersType::AllCurrencyPair,
+        );
+
+        let order_header = OrderHeader::with_user_order(
+            ClientOrderId::unique_id(),
+            exchange.exchange_account_id,
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            OrderSide::Buy,
+            dec!(4),
+            UserOrder::limit(dec!(0.2)),
+            None,
+            None,
+            "FromTest".to_owned(),
+        )
+        .with_time_in_force(OrderTimeInForce::Gtt(
+            Utc::now() + chrono::Duration::milliseconds(100),
+        ));
+
+        let order = create_confirmed_order(&exchange, &order_header, &exchange_order_id).await;
+        assert_eq!(order.status(), OrderStatus::Created);
+
+        // exchange confirms cancellation via websocket
+        while !exchange
+            .order_cancellation_events
+            .contains_key(&exchange_order_id)
+        {
+            sleep(Duration::from_millis(10)).await;
+        }
+        exchange.raise_order_cancelled(
+            order.client_order_id(),
+            exchange_order_id,
+            EventSourceType::WebSocket,
+        );
+
+        timeout(Duration::from_secs(1), async {
+            while order.status() != OrderStatus::Canceled {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("order should be canceled on expiry");
+        assert_eq!(*cancel_requests_count.lock(), 1);
+
+        // fill of trade which happened before cancellation is still processed
+        handle_fill(&exchange, &order, "first_trade", dec!(1), dec!(1));
+
+        let (fills, filled_amount) = order.get_fills();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(filled_amount, dec!(1));
+    }
+}
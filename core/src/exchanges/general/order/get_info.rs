@@ -1,14 +1,115 @@
 use crate::exchanges::general::exchange::Exchange;
 use crate::exchanges::traits::ExchangeError;
-use anyhow::*;
+use futures::StreamExt;
 use mmb_domain::market::ExchangeErrorType;
 use mmb_domain::order::pool::OrderRef;
-use mmb_domain::order::snapshot::OrderInfo;
+use mmb_domain::order::snapshot::{ClientOrderId, OrderInfo};
+use std::collections::HashMap;
 
 impl Exchange {
     pub async fn get_order_info(&self, order: &OrderRef) -> Result<OrderInfo, ExchangeError> {
+        self.check_order_info_request(order)?;
+
         let (client_order_id, exchange_order_id) = order.order_ids();
-        if exchange_order_id.is_none()
+        log::info!(
+            "get_order_info response: {client_order_id}, {exchange_order_id:?} on {}",
+            self.exchange_account_id
+        );
+
+        self.exchange_client.get_order_info(order).await
+    }
+
+    /// Get info of several orders, e.g. for reconciliation after reconnect.
+    /// Uses single batch request if exchange supports it, otherwise orders are requested one by one
+    /// with bounded concurrency. Result contains entry for every requested client order id
+    pub async fn get_orders_info(
+        &self,
+        client_order_ids: &[ClientOrderId],
+    ) -> HashMap<ClientOrderId, Result<OrderInfo, ExchangeError>> {
+        const MAX_CONCURRENT_REQUESTS: usize = 5;
+
+        let mut orders_info = HashMap::with_capacity(client_order_ids.len());
+        let mut orders = Vec::with_capacity(client_order_ids.len());
+        for client_order_id in client_order_ids {
+            let order = match self.orders.cache_by_client_id.get(client_order_id) {
+                Some(order) => order.clone(),
+                None => {
+                    let error_msg = format!("Order {client_order_id} isn't found in local pool");
+                    orders_info.insert(
+                        client_order_id.clone(),
+                        Err(ExchangeError::new(
+                            ExchangeErrorType::OrderNotFound,
+                            error_msg,
+                            None,
+                        )),
+                    );
+                    continue;
+                }
+            };
+
+            match self.check_order_info_request(&order) {
+                Ok(()) => orders.push(order),
+                Err(error) => {
+                    orders_info.insert(client_order_id.clone(), Err(error));
+                }
+            }
+        }
+
+        if orders.is_empty() {
+            return orders_info;
+        }
+
+        if !self.exchange_client.supports_get_orders_info() {
+            let requested_orders_info: Vec<_> = futures::stream::iter(orders)
+                .map(|order| async move {
+                    let order_info = self.get_order_info(&order).await;
+                    (order.client_order_id(), order_info)
+                })
+                .buffered(MAX_CONCURRENT_REQUESTS)
+                .collect()
+                .await;
+
+            orders_info.extend(requested_orders_info);
+            return orders_info;
+        }
+
+        log::info!(
+            "get_orders_info request for {} orders on {}",
+            orders.len(),
+            self.exchange_account_id
+        );
+
+        match self.exchange_client.get_orders_info(&orders).await {
+            Ok(received_orders_info) => {
+                let mut received_orders_info: HashMap<_, _> = received_orders_info
+                    .into_iter()
+                    .map(|order_info| (order_info.client_order_id.clone(), order_info))
+                    .collect();
+
+                for order in orders {
+                    let client_order_id = order.client_order_id();
+                    let order_info = received_orders_info.remove(&client_order_id).ok_or_else(|| {
+                        ExchangeError::new(
+                            ExchangeErrorType::OrderNotFound,
+                            format!("Order info for {client_order_id} isn't received in batch response"),
+                            None,
+                        )
+                    });
+                    orders_info.insert(client_order_id, order_info);
+                }
+            }
+            Err(error) => {
+                for order in orders {
+                    orders_info.insert(order.client_order_id(), Err(error.clone()));
+                }
+            }
+        }
+
+        orders_info
+    }
+
+    fn check_order_info_request(&self, order: &OrderRef) -> Result<(), ExchangeError> {
+        if order.exchange_order_id().is_none()
             && !self
                 .features
                 .order_features
@@ -23,11 +124,161 @@ impl Exchange {
             ));
         }
 
-        log::info!(
-            "get_order_info response: {client_order_id}, {exchange_order_id:?} on {}",
-            self.exchange_account_id
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use itertools::Itertools;
+    use mmb_domain::exchanges::symbol::{Precision, Symbol};
+    use mmb_domain::market::{CurrencyPair, ExchangeAccountId};
+    use mmb_domain::order::pool::OrderRef;
+    use mmb_domain::order::snapshot::{
+        ClientOrderId, OrderHeader, OrderInfo, OrderSide, OrderStatus, UserOrder,
+    };
+    use rust_decimal_macros::dec;
+
+    use crate::exchanges::general::exchange::Exchange;
+    use crate::exchanges::general::features::OpenOrdersType;
+    use crate::exchanges::general::test_helper::{get_test_exchange_with_client, TestClient};
+    use crate::exchanges::traits::ExchangeError;
+    use crate::misc::time::time_manager;
+
+    fn test_order_info(order: &OrderRef) -> OrderInfo {
+        OrderInfo::new(
+            order.currency_pair(),
+            format!("exchange_{}", order.client_order_id())
+                .as_str()
+                .into(),
+            order.client_order_id(),
+            order.side(),
+            OrderStatus::Created,
+            order.price(),
+            order.amount(),
+            dec!(0),
+            dec!(0),
+            None,
+            None,
+            None,
+        )
+    }
+
+    fn create_exchange_with_orders(
+        exchange_client: TestClient,
+        orders_count: usize,
+    ) -> (Arc<Exchange>, Vec<ClientOrderId>) {
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+        let symbol = Arc::new(Symbol::new(
+            false,
+            "PHB".into(),
+            "PHB".into(),
+            "BTC".into(),
+            "BTC".into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            "PHB".into(),
+            None,
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0) },
+        ));
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            symbol,
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            exchange_client,
+            OpenOrdersType::AllCurrencyPair,
         );
 
-        self.exchange_client.get_order_info(order).await
+        let client_order_ids = (0..orders_count)
+            .map(|_| {
+                let header = OrderHeader::with_user_order(
+                    ClientOrderId::unique_id(),
+                    exchange.exchange_account_id,
+                    currency_pair,
+                    OrderSide::Buy,
+                    dec!(5),
+                    UserOrder::limit(dec!(0.2)),
+                    None,
+                    None,
+                    "FromTest".to_owned(),
+                );
+                exchange
+                    .orders
+                    .add_simple_initial(&header, time_manager::now(), None)
+                    .client_order_id()
+            })
+            .collect_vec();
+
+        (exchange, client_order_ids)
+    }
+
+    fn assert_orders_info(
+        orders_info: &HashMap<ClientOrderId, Result<OrderInfo, ExchangeError>>,
+        client_order_ids: &[ClientOrderId],
+    ) {
+        assert_eq!(orders_info.len(), client_order_ids.len());
+        for client_order_id in client_order_ids {
+            let order_info = orders_info
+                .get(client_order_id)
+                .expect("in test")
+                .as_ref()
+                .expect("in test");
+            assert_eq!(order_info.client_order_id, *client_order_id);
+            assert_eq!(
+                order_info.exchange_order_id.as_str(),
+                format!("exchange_{client_order_id}")
+            );
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn get_orders_info_by_single_batch_request() {
+        let batch_requests_count = Arc::new(AtomicUsize::new(0));
+        // get_order_info isn't set, so requesting orders one by one fails the test
+        let exchange_client = TestClient {
+            get_orders_info: Some(Box::new({
+                let batch_requests_count = batch_requests_count.clone();
+                move |orders| {
+                    batch_requests_count.fetch_add(1, Ordering::SeqCst);
+                    Ok(orders.iter().map(test_order_info).collect())
+                }
+            })),
+            ..TestClient::default()
+        };
+        let (exchange, client_order_ids) = create_exchange_with_orders(exchange_client, 3);
+
+        let orders_info = exchange.get_orders_info(&client_order_ids).await;
+
+        assert_eq!(batch_requests_count.load(Ordering::SeqCst), 1);
+        assert_orders_info(&orders_info, &client_order_ids);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn get_orders_info_one_by_one_without_batch_support() {
+        let single_requests_count = Arc::new(AtomicUsize::new(0));
+        // get_orders_info isn't set, so exchange client doesn't support batch request
+        let exchange_client = TestClient {
+            get_order_info: Some(Box::new({
+                let single_requests_count = single_requests_count.clone();
+                move |order| {
+                    single_requests_count.fetch_add(1, Ordering::SeqCst);
+                    Ok(test_order_info(order))
+                }
+            })),
+            ..TestClient::default()
+        };
+        let (exchange, client_order_ids) = create_exchange_with_orders(exchange_client, 3);
+
+        let orders_info = exchange.get_orders_info(&client_order_ids).await;
+
+        assert_eq!(single_requests_count.load(Ordering::SeqCst), 3);
+        assert_orders_info(&orders_info, &client_order_ids);
     }
 }
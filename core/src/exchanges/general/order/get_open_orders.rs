@@ -2,7 +2,9 @@ use crate::exchanges::general::request_type::RequestType;
 use crate::misc::time::time_manager;
 use crate::{exchanges::general::exchange::Exchange, exchanges::general::features::OpenOrdersType};
 use anyhow::bail;
+use futures::StreamExt;
 use itertools::Itertools;
+use mmb_domain::market::CurrencyPair;
 use mmb_domain::order::snapshot::{
     ClientOrderId, OrderHeader, OrderInfo, OrderOptions, OrderSimpleProps, OrderSnapshot,
 };
@@ -53,22 +55,12 @@ impl Exchange {
                 self.exchange_client.get_open_orders().await?
             }
             OpenOrdersType::OneCurrencyPair => {
-                let currency_pair_orders =
-                    futures::future::join_all(self.symbols.iter().map(|x| async move {
-                        self.timeout_manager
-                            .reserve_when_available(
-                                self.exchange_account_id,
-                                RequestType::GetOpenOrders,
-                                None,
-                                CancellationToken::default(),
-                            )
-                            .await
-                            .into_result()?;
-                        self.exchange_client
-                            .get_open_orders_by_currency_pair(x.currency_pair())
-                            .await
-                    }))
-                    .await;
+                let currency_pair_orders = futures::future::join_all(
+                    self.symbols
+                        .iter()
+                        .map(|x| self.get_open_orders_by_currency_pair(x.currency_pair())),
+                )
+                .await;
 
                 currency_pair_orders
                     .into_iter()
@@ -88,8 +80,88 @@ impl Exchange {
         Ok(open_orders)
     }
 
+    /// Get open orders for accounts with many open orders. For `OpenOrdersType::OneCurrencyPair`
+    /// currency pairs are requested with bounded concurrency and a failed request is retried
+    /// for its currency pair only instead of requesting all currency pairs again
+    pub async fn get_open_orders_paged(&self) -> anyhow::Result<Vec<OrderInfo>> {
+        const MAX_CONCURRENT_REQUESTS: usize = 5;
+
+        match self.features.open_orders_type {
+            OpenOrdersType::OneCurrencyPair => {
+                let currency_pairs = self.symbols.iter().map(|x| x.currency_pair()).collect_vec();
+                let currency_pair_orders: Vec<_> = futures::stream::iter(currency_pairs)
+                    .map(|currency_pair| {
+                        self.get_open_orders_by_currency_pair_with_retries(currency_pair)
+                    })
+                    .buffered(MAX_CONCURRENT_REQUESTS)
+                    .collect()
+                    .await;
+
+                currency_pair_orders.into_iter().flatten_ok().try_collect()
+            }
+            _ => self.get_open_orders(false).await,
+        }
+    }
+
+    async fn get_open_orders_by_currency_pair_with_retries(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> anyhow::Result<Vec<OrderInfo>> {
+        const MAX_ATTEMPTS: u32 = 3;
+
+        let mut attempt = 1;
+        loop {
+            match self.get_open_orders_by_currency_pair(currency_pair).await {
+                Ok(orders) => return Ok(orders),
+                Err(error) if attempt < MAX_ATTEMPTS => {
+                    log::warn!(
+                        "Failed attempt {attempt} to get open orders for {currency_pair} on {}: {error:?}",
+                        self.exchange_account_id
+                    );
+                    attempt += 1;
+                }
+                Err(error) => {
+                    return Err(error.context(format!(
+                        "Failed to get open orders for {currency_pair} on {} after {MAX_ATTEMPTS} attempts",
+                        self.exchange_account_id
+                    )))
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    async fn get_open_orders_by_currency_pair(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> anyhow::Result<Vec<OrderInfo>> {
+        self.timeout_manager
+            .reserve_when_available(
+                self.exchange_account_id,
+                RequestType::GetOpenOrders,
+                None,
+                CancellationToken::default(),
+            )
+            .await
+            .into_result()?;
+
+        self.exchange_client
+            .get_open_orders_by_currency_pair(currency_pair)
+            .await
+    }
+
     fn add_missing_open_orders(&self, open_orders: &[OrderInfo]) {
         for order_info in open_orders {
+            if !self.is_own_client_order_id(&order_info.client_order_id) {
+                log::trace!(
+                    "Skipped open order created not by bot {} {} on {}",
+                    order_info.client_order_id,
+                    order_info.exchange_order_id,
+                    self.exchange_account_id,
+                );
+                continue;
+            }
+
             if order_info.client_order_id.as_str().is_empty()
                 && self
                     .orders
@@ -161,3 +233,141 @@ impl Exchange {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use anyhow::bail;
+    use itertools::Itertools;
+    use mmb_domain::exchanges::symbol::{Precision, Symbol};
+    use mmb_domain::market::{CurrencyPair, ExchangeAccountId};
+    use mmb_domain::order::snapshot::{
+        ClientOrderId, ExchangeOrderId, OrderInfo, OrderSide, OrderStatus,
+    };
+    use rust_decimal_macros::dec;
+
+    use crate::exchanges::general::features::OpenOrdersType;
+    use crate::exchanges::general::test_helper::{
+        get_test_exchange, get_test_exchange_with_client, TestClient,
+    };
+
+    fn open_order_info(client_order_id: ClientOrderId, exchange_order_id: &str) -> OrderInfo {
+        open_order_info_for(
+            CurrencyPair::from_codes("PHB".into(), "BTC".into()),
+            client_order_id,
+            exchange_order_id,
+        )
+    }
+
+    fn open_order_info_for(
+        currency_pair: CurrencyPair,
+        client_order_id: ClientOrderId,
+        exchange_order_id: &str,
+    ) -> OrderInfo {
+        OrderInfo::new(
+            currency_pair,
+            exchange_order_id.into(),
+            client_order_id,
+            OrderSide::Buy,
+            OrderStatus::Created,
+            dec!(0.2),
+            dec!(1),
+            dec!(0),
+            dec!(0),
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn only_orders_with_client_order_id_prefix_are_added_as_missing() {
+        let (exchange, _event_receiver) = get_test_exchange(false);
+        exchange.set_client_order_id_prefix(Some("mmb-".to_owned()));
+
+        let own_client_order_id = exchange.generate_client_order_id();
+        assert!(own_client_order_id.as_str().starts_with("mmb"));
+        assert!(!own_client_order_id.as_str().starts_with("mmb-"));
+
+        let manual_client_order_id = ClientOrderId::from("web_123");
+        assert!(!exchange.is_own_client_order_id(&manual_client_order_id));
+
+        exchange.add_missing_open_orders(&[
+            open_order_info(own_client_order_id, "own"),
+            open_order_info(manual_client_order_id, "manual"),
+        ]);
+
+        let own_exchange_order_id: ExchangeOrderId = "own".into();
+        let manual_exchange_order_id: ExchangeOrderId = "manual".into();
+        let cache_by_exchange_id = &exchange.orders.cache_by_exchange_id;
+        assert!(cache_by_exchange_id.contains_key(&own_exchange_order_id));
+        assert!(!cache_by_exchange_id.contains_key(&manual_exchange_order_id));
+    }
+
+    fn create_symbol(base: &str, quote: &str) -> Arc<Symbol> {
+        Arc::new(Symbol::new(
+            false,
+            base.into(),
+            base.into(),
+            quote.into(),
+            quote.into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            base.into(),
+            None,
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0) },
+        ))
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn get_open_orders_paged_retries_failed_currency_pair() {
+        let phb_btc = create_symbol("PHB", "BTC");
+        let eth_btc = create_symbol("ETH", "BTC");
+        let failing_currency_pair = phb_btc.currency_pair();
+
+        let requests_count = Arc::new(AtomicUsize::new(0));
+        let failing_pair_requests_count = requests_count.clone();
+        let exchange_client = TestClient {
+            open_orders_by_currency_pair: Some(Box::new(move |currency_pair| {
+                if currency_pair == failing_currency_pair
+                    && failing_pair_requests_count.fetch_add(1, Ordering::SeqCst) == 0
+                {
+                    bail!("Timeout of open orders request for {currency_pair}");
+                }
+
+                let exchange_order_id = format!("{currency_pair}_order");
+                Ok(vec![open_order_info_for(
+                    currency_pair,
+                    ClientOrderId::unique_id(),
+                    &exchange_order_id,
+                )])
+            })),
+            ..TestClient::default()
+        };
+
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            phb_btc,
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            exchange_client,
+            OpenOrdersType::OneCurrencyPair,
+        );
+        let _ = exchange
+            .symbols
+            .insert(eth_btc.currency_pair(), eth_btc.clone());
+
+        let open_orders = exchange.get_open_orders_paged().await.expect("in test");
+
+        let mut currency_pairs = open_orders.iter().map(|x| x.currency_pair).collect_vec();
+        currency_pairs.sort_by_key(|x| x.to_string());
+        let mut expected = vec![failing_currency_pair, eth_btc.currency_pair()];
+        expected.sort_by_key(|x| x.to_string());
+        assert_eq!(currency_pairs, expected);
+        assert_eq!(requests_count.load(Ordering::SeqCst), 2);
+    }
+}
@@ -1,16 +1,18 @@
 use crate::exchanges::general::exchange::RequestResult;
+use crate::exchanges::general::request_type::RequestType;
 use crate::exchanges::general::{exchange::Exchange, features::RestFillsType};
 use anyhow::{bail, Context, Result};
 use itertools::Itertools;
 use mmb_domain::events::TradeId;
 use mmb_domain::exchanges::symbol::Symbol;
-use mmb_domain::market::CurrencyCode;
+use mmb_domain::market::{CurrencyCode, CurrencyPair};
 use mmb_domain::order::fill::OrderFillType;
 use mmb_domain::order::pool::OrderRef;
 use mmb_domain::order::snapshot::{Amount, Price};
 use mmb_domain::order::snapshot::{ExchangeOrderId, OrderRole};
 use mmb_utils::DateTime;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct OrderTrade {
@@ -68,6 +70,91 @@ impl Exchange {
         }
     }
 
+    /// Get own trades executed in `[from, to]`. Trades are requested page by page from `from` forwards
+    /// until trades newer than `to` are received, so range isn't limited by max count of trades per request.
+    /// Each next page starts from the last received trade. Empty page doesn't stop requesting trades by time
+    /// because exchange may return trades only within limited window after the requested time
+    /// (24 hours on Binance), so the window is stepped forward until it passes `to`
+    pub async fn get_my_trades_in_range(
+        &self,
+        currency_pair: CurrencyPair,
+        from: DateTime,
+        to: DateTime,
+    ) -> Result<Vec<OrderTrade>> {
+        let empty_page_step = chrono::Duration::hours(24);
+
+        let symbol = self.get_symbol(currency_pair)?;
+
+        let mut received_trade_ids = HashSet::new();
+        let mut trades = Vec::new();
+        let mut page_start = from;
+        let mut from_trade_id = None;
+        loop {
+            self.timeout_manager
+                .reserve_when_available(
+                    self.exchange_account_id,
+                    RequestType::GetMyTrades,
+                    None,
+                    self.lifetime_manager.stop_token(),
+                )
+                .await
+                .into_result()?;
+
+            let page = match self
+                .exchange_client
+                .get_my_trades_from(&symbol, page_start, from_trade_id.as_ref())
+                .await
+            {
+                RequestResult::Success(page) => page,
+                RequestResult::Error(error) => bail!(
+                    "Failed to get trades from {page_start} for {currency_pair} on {}: {error:?}",
+                    self.exchange_account_id
+                ),
+            };
+
+            let (last_trade_id, last_datetime) = match page.last() {
+                Some(last_trade) => (last_trade.trade_id.clone(), last_trade.datetime),
+                None => {
+                    // there are no trades after the last received one
+                    if from_trade_id.is_some() {
+                        break;
+                    }
+
+                    page_start += empty_page_step;
+                    if page_start > to {
+                        break;
+                    }
+
+                    continue;
+                }
+            };
+
+            let mut has_new_trades = false;
+            for trade in page {
+                // the next page starts from the last trade of the current one,
+                // so already received trades are skipped
+                if !received_trade_ids.insert(trade.trade_id.clone()) {
+                    continue;
+                }
+
+                has_new_trades = true;
+                if from <= trade.datetime && trade.datetime <= to {
+                    trades.push(trade);
+                }
+            }
+
+            if !has_new_trades || last_datetime > to {
+                break;
+            }
+
+            page_start = last_datetime;
+            from_trade_id = Some(last_trade_id);
+        }
+
+        trades.sort_by_key(|x| x.datetime);
+        Ok(trades)
+    }
+
     async fn get_my_trades_with_filter(
         &self,
         symbol: &Symbol,
@@ -91,3 +178,161 @@ impl Exchange {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use chrono::{Duration, TimeZone, Utc};
+    use itertools::Itertools;
+    use mmb_domain::events::TradeId;
+    use mmb_domain::market::{CurrencyPair, ExchangeAccountId};
+    use mmb_domain::order::fill::OrderFillType;
+    use mmb_domain::order::snapshot::{ExchangeOrderId, OrderRole};
+    use mmb_utils::DateTime;
+    use rust_decimal_macros::dec;
+
+    use super::OrderTrade;
+    use crate::exchanges::general::exchange::{Exchange, RequestResult};
+    use crate::exchanges::general::features::OpenOrdersType;
+    use crate::exchanges::general::test_helper::{
+        create_test_symbol, get_test_exchange_with_client, TestClient,
+    };
+
+    fn create_order_trade(trade_number: usize, datetime: DateTime) -> OrderTrade {
+        OrderTrade::new(
+            ExchangeOrderId::from("test_order"),
+            TradeId::Number(trade_number as u64),
+            datetime,
+            dec!(0.2),
+            dec!(1),
+            OrderRole::Maker,
+            "BTC".into(),
+            None,
+            Some(dec!(0)),
+            OrderFillType::UserTrade,
+        )
+    }
+
+    fn create_exchange(exchange_client: TestClient) -> Arc<Exchange> {
+        let symbol = Arc::new(create_test_symbol(
+            false,
+            "PHB",
+            "BTC",
+            "PHB",
+            None,
+            dec!(0.1),
+            dec!(0),
+        ));
+        let (exchange, _event_receiver) = get_test_exchange_with_client(
+            symbol,
+            ExchangeAccountId::new("local_exchange_account_id", 0),
+            exchange_client,
+            OpenOrdersType::AllCurrencyPair,
+        );
+        exchange
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn get_my_trades_in_range_stitches_pages() {
+        const PAGE_LIMIT: usize = 3;
+
+        let start = Utc
+            .with_ymd_and_hms(2022, 1, 1, 0, 0, 0)
+            .single()
+            .expect("in test");
+        let trades_datetimes = (0..7).map(|i| start + Duration::minutes(i)).collect_vec();
+        let requests_count = Arc::new(AtomicUsize::new(0));
+
+        let exchange_client = TestClient {
+            get_my_trades_from: Some(Box::new({
+                let trades_datetimes = trades_datetimes.clone();
+                let requests_count = requests_count.clone();
+                move |_, from_datetime, from_trade_id| {
+                    requests_count.fetch_add(1, Ordering::SeqCst);
+                    let page = trades_datetimes
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, datetime)| match from_trade_id {
+                            Some(from_trade_id) => *i as u64 >= from_trade_id.number(),
+                            None => **datetime >= from_datetime,
+                        })
+                        .take(PAGE_LIMIT)
+                        .map(|(i, datetime)| create_order_trade(i, *datetime))
+                        .collect();
+                    RequestResult::Success(page)
+                }
+            })),
+            ..TestClient::default()
+        };
+        let exchange = create_exchange(exchange_client);
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+
+        let trades = exchange
+            .get_my_trades_in_range(currency_pair, trades_datetimes[1], trades_datetimes[4])
+            .await
+            .expect("in test");
+
+        assert_eq!(requests_count.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            trades.iter().map(|x| x.datetime).collect_vec(),
+            trades_datetimes[1..5].to_vec()
+        );
+        assert_eq!(
+            trades.iter().map(|x| x.trade_id.clone()).collect_vec(),
+            (1..5).map(TradeId::Number).collect_vec()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn get_my_trades_in_range_steps_forward_over_empty_windows() {
+        let start = Utc
+            .with_ymd_and_hms(2022, 1, 1, 0, 0, 0)
+            .single()
+            .expect("in test");
+        let trades_datetimes = vec![
+            start + Duration::days(3),
+            start + Duration::days(3) + Duration::minutes(1),
+        ];
+
+        // exchange returns trades only within 24 hours after requested time
+        let exchange_client = TestClient {
+            get_my_trades_from: Some(Box::new({
+                let trades_datetimes = trades_datetimes.clone();
+                move |_, from_datetime, from_trade_id| {
+                    let page = trades_datetimes
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, datetime)| match from_trade_id {
+                            Some(from_trade_id) => *i as u64 >= from_trade_id.number(),
+                            None => {
+                                from_datetime <= **datetime
+                                    && **datetime < from_datetime + Duration::hours(24)
+                            }
+                        })
+                        .map(|(i, datetime)| create_order_trade(i, *datetime))
+                        .collect();
+                    RequestResult::Success(page)
+                }
+            })),
+            ..TestClient::default()
+        };
+        let exchange = create_exchange(exchange_client);
+        let currency_pair = CurrencyPair::from_codes("PHB".into(), "BTC".into());
+
+        let trades = exchange
+            .get_my_trades_in_range(
+                currency_pair,
+                start - Duration::hours(1),
+                start + Duration::days(4),
+            )
+            .await
+            .expect("in test");
+
+        assert_eq!(
+            trades.iter().map(|x| x.datetime).collect_vec(),
+            trades_datetimes
+        );
+    }
+}
@@ -0,0 +1,569 @@
+use anyhow::{bail, Context, Result};
+use mmb_domain::order::pool::OrderRef;
+use mmb_domain::order::snapshot::{
+    Amount, ExchangeOrderId, OrderHeader, OrderOptions, OrderStatus, Price, UserOrder,
+};
+use mmb_utils::cancellation_token::CancellationToken;
+use rust_decimal_macros::dec;
+
+use crate::exchanges::general::exchange::{Exchange, RequestResult};
+use crate::exchanges::general::request_type::RequestType;
+use crate::misc::time::time_manager;
+
+impl Exchange {
+    /// Replace resting limit order with a new one with `new_price` and `new_amount`.
+    /// Native cancel-replace is used if exchange supports it, otherwise the order is canceled and then created again.
+    /// The new order takes over the reservation of replaced order, so reserved balance isn't freed in between:
+    /// approved part of replaced order is transferred to not approved amount of the reservation after
+    /// the exchange confirms its cancellation and the new order is approved in the same reservation on creation.
+    /// NOTE: price of the reservation isn't changed
+    pub async fn replace_order(
+        &self,
+        existing: &OrderRef,
+        new_price: Price,
+        new_amount: Amount,
+        cancellation_token: CancellationToken,
+    ) -> Result<OrderRef> {
+        let header = existing.header();
+        let client_order_id = &header.client_order_id;
+
+        let execution_type = match &header.options {
+            OrderOptions::User(UserOrder::Limit { execution_type, .. }) => *execution_type,
+            _ => bail!(
+                "Unable to replace order {client_order_id} on {} because only limit orders can be replaced",
+                self.exchange_account_id
+            ),
+        };
+
+        let exchange_order_id = match (existing.status(), existing.exchange_order_id()) {
+            (OrderStatus::Created, Some(exchange_order_id)) => exchange_order_id,
+            (status, _) => bail!(
+                "Unable to replace order {client_order_id} with status {status:?} on {}",
+                self.exchange_account_id
+            ),
+        };
+
+        let new_header = OrderHeader::with_user_order(
+            self.generate_client_order_id(),
+            self.exchange_account_id,
+            header.currency_pair,
+            header.side,
+            new_amount,
+            UserOrder::Limit {
+                price: new_price,
+                execution_type,
+            },
+            header.reservation_id,
+            header.signal_id.clone(),
+            header.strategy_name.clone(),
+        );
+
+        log::info!(
+            "Replacing order {client_order_id} with {} on {}",
+            new_header.client_order_id,
+            self.exchange_account_id
+        );
+
+        self.check_reservation_for_replace(existing, &new_header)?;
+
+        match self.exchange_client.supports_cancel_replace() {
+            true => {
+                self.cancel_replace_order(
+                    existing,
+                    &exchange_order_id,
+                    &new_header,
+                    cancellation_token,
+                )
+                .await
+            }
+            false => {
+                self.cancel_and_create_order(existing, &new_header, cancellation_token)
+                    .await
+            }
+        }
+    }
+
+    async fn cancel_replace_order(
+        &self,
+        existing: &OrderRef,
+        exchange_order_id: &ExchangeOrderId,
+        new_header: &OrderHeader,
+        cancellation_token: CancellationToken,
+    ) -> Result<OrderRef> {
+        self.timeout_manager
+            .reserve_when_available(
+                self.exchange_account_id,
+                RequestType::CreateOrder,
+                None,
+                cancellation_token,
+            )
+            .await
+            .into_result()?;
+
+        let new_order = self.orders.add_simple_initial(
+            new_header,
+            time_manager::now(),
+            self.exchange_client.get_initial_extension_data(),
+        );
+
+        let result = self
+            .exchange_client
+            .cancel_replace_order(existing, exchange_order_id, &new_order)
+            .await;
+
+        match &result.outcome {
+            RequestResult::Success(new_exchange_order_id) => {
+                self.handle_cancel_order_succeeded(
+                    Some(&existing.client_order_id()),
+                    exchange_order_id,
+                    None,
+                    result.source_type,
+                );
+                if let Err(error) = self.transfer_approved_part_of_replaced_order(existing) {
+                    log::error!("{error:?}");
+                }
+                self.handle_create_order_succeeded(
+                    self.exchange_account_id,
+                    &new_header.client_order_id,
+                    new_exchange_order_id,
+                    result.source_type,
+                )?;
+
+                Ok(new_order)
+            }
+            RequestResult::Error(error) => {
+                self.handle_create_order_failed(
+                    &new_header.client_order_id,
+                    error,
+                    result.source_type,
+                )?;
+
+                bail!(
+                    "Failed to replace order {} on {}: {error:?}",
+                    existing.client_order_id(),
+                    self.exchange_account_id
+                )
+            }
+        }
+    }
+
+    async fn cancel_and_create_order(
+        &self,
+        existing: &OrderRef,
+        new_header: &OrderHeader,
+        cancellation_token: CancellationToken,
+    ) -> Result<OrderRef> {
+        let client_order_id = existing.client_order_id();
+
+        self.timeout_manager
+            .reserve_when_available(
+                self.exchange_account_id,
+                RequestType::CancelOrder,
+                None,
+                cancellation_token.clone(),
+            )
+            .await
+            .into_result()?;
+
+        let cancel_outcome = self
+            .cancel_order(existing, cancellation_token.clone())
+            .await
+            .with_context(|| {
+                format!(
+                    "Cancellation of order {client_order_id} for replace was interrupted on {}",
+                    self.exchange_account_id
+                )
+            })?;
+
+        if let RequestResult::Error(error) = cancel_outcome.outcome {
+            bail!(
+                "Failed to cancel order {client_order_id} for replace on {}: {error:?}",
+                self.exchange_account_id
+            );
+        }
+
+        // exchange doesn't unreserve balance of canceled order, so the rest of its approved part
+        // is still reserved and can be handed over to the new order
+        self.transfer_approved_part_of_replaced_order(existing)?;
+
+        self.timeout_manager
+            .reserve_when_available(
+                self.exchange_account_id,
+                RequestType::CreateOrder,
+                None,
+                cancellation_token.clone(),
+            )
+            .await
+            .into_result()?;
+
+        self.create_order(new_header, None, cancellation_token)
+            .await
+    }
+
+    /// Check that reservation of replaced order has enough amount for the new order
+    /// after approved part of replaced order is returned to the reservation
+    fn check_reservation_for_replace(
+        &self,
+        existing: &OrderRef,
+        new_header: &OrderHeader,
+    ) -> Result<()> {
+        let reservation_id = match new_header.reservation_id {
+            Some(reservation_id) => reservation_id,
+            None => return Ok(()),
+        };
+
+        let balance_manager = self
+            .balance_manager
+            .lock()
+            .as_ref()
+            .and_then(|balance_manager| balance_manager.upgrade())
+            .context("BalanceManager should be initialized before replacing orders")?;
+        let balance_manager = balance_manager.lock();
+
+        let client_order_id = existing.client_order_id();
+        let reservation = balance_manager
+            .get_reservation(reservation_id)
+            .with_context(|| format!("Can't find reservation {reservation_id}"))?;
+        let approved_amount = reservation
+            .approved_parts
+            .get(&client_order_id)
+            .map(|approved_part| approved_part.unreserved_amount)
+            .with_context(|| {
+                format!("There is no approved part for {client_order_id} in {reservation_id}")
+            })?;
+
+        let available_amount = reservation.not_approved_amount + approved_amount;
+        if available_amount < new_header.amount {
+            bail!(
+                "Unable to replace order {client_order_id} with amount {} because only {available_amount} is reserved in {reservation_id}",
+                new_header.amount
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Transfer the rest of approved part of replaced order to not approved amount of its reservation,
+    /// so the new order can be approved in the same reservation on creation
+    fn transfer_approved_part_of_replaced_order(&self, existing: &OrderRef) -> Result<()> {
+        let reservation_id = match existing.header().reservation_id {
+            Some(reservation_id) => reservation_id,
+            None => return Ok(()),
+        };
+
+        let balance_manager = self
+            .balance_manager
+            .lock()
+            .as_ref()
+            .and_then(|balance_manager| balance_manager.upgrade())
+            .context("BalanceManager ref can't be upgraded while replacing order")?;
+        let mut balance_manager = balance_manager.lock();
+
+        let client_order_id = existing.client_order_id();
+        let approved_amount = balance_manager
+            .get_reservation(reservation_id)
+            .and_then(|reservation| reservation.approved_parts.get(&client_order_id))
+            .map_or(dec!(0), |approved_part| approved_part.unreserved_amount);
+
+        let _ = balance_manager
+            .try_transfer_reservation(
+                reservation_id,
+                reservation_id,
+                approved_amount,
+                &Some(client_order_id.clone()),
+            )
+            .with_context(|| {
+                format!("Failed to transfer approved part of replaced order {client_order_id} in {reservation_id}")
+            })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use mmb_domain::events::{
+        EventSourceType, ExchangeBalance, ExchangeBalancesAndPositions, ExchangeEvent,
+    };
+    use mmb_domain::exchanges::symbol::{Precision, Symbol};
+    use mmb_domain::market::{CurrencyPair, ExchangeAccountId};
+    use mmb_domain::order::pool::OrderRef;
+    use mmb_domain::order::snapshot::{
+        ClientOrderId, ExchangeOrderId, OrderHeader, OrderSide, OrderStatus, ReservationId,
+        UserOrder,
+    };
+    use mmb_utils::cancellation_token::CancellationToken;
+    use mmb_utils::hashmap;
+    use parking_lot::Mutex;
+    use rust_decimal_macros::dec;
+    use tokio::sync::broadcast;
+    use tokio::time::sleep;
+
+    use crate::balance::manager::balance_manager::BalanceManager;
+    use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
+    use crate::exchanges::general::exchange::Exchange;
+    use crate::exchanges::general::features::OpenOrdersType;
+    use crate::exchanges::general::order::cancel::CancelOrderResult;
+    use crate::exchanges::general::order::create::CreateOrderResult;
+    use crate::exchanges::general::test_helper::{get_test_exchange_with_client, TestClient};
+    use crate::misc::reserve_parameters::ReserveParameters;
+    use crate::misc::time;
+    use crate::service_configuration::configuration_descriptor::ConfigurationDescriptor;
+
+    struct ReplaceTestContext {
+        exchange: Arc<Exchange>,
+        balance_manager: Arc<Mutex<BalanceManager>>,
+        configuration_descriptor: ConfigurationDescriptor,
+        reservation_id: ReservationId,
+        existing: OrderRef,
+        _event_receiver: broadcast::Receiver<ExchangeEvent>,
+    }
+
+    /// Exchange with created order approved for the whole reservation with amount 5
+    fn init_replace_test(exchange_client: TestClient) -> ReplaceTestContext {
+        let currency_pair = CurrencyPair::from_codes("ETH".into(), "BTC".into());
+        let symbol = Arc::new(Symbol::new(
+            false,
+            "ETH".into(),
+            "ETH".into(),
+            "BTC".into(),
+            "BTC".into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            "ETH".into(),
+            Some("BTC".into()),
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0.001) },
+        ));
+        let exchange_account_id = ExchangeAccountId::new("local_exchange_account_id", 0);
+        let (exchange, event_receiver) = get_test_exchange_with_client(
+            symbol.clone(),
+            exchange_account_id,
+            exchange_client,
+            OpenOrdersType::AllCurrencyPair,
+        );
+
+        let balance_manager = BalanceManager::new(
+            CurrencyPairToSymbolConverter::new(hashmap![exchange_account_id => exchange.clone()]),
+            None,
+        );
+        exchange.setup_balance_manager(balance_manager.clone());
+        balance_manager
+            .lock()
+            .update_exchange_balance(
+                exchange_account_id,
+                &ExchangeBalancesAndPositions {
+                    balances: vec![ExchangeBalance {
+                        currency_code: "ETH".into(),
+                        balance: dec!(10),
+                    }],
+                    positions: None,
+                    event_time: None,
+                },
+            )
+            .expect("in test");
+
+        let configuration_descriptor = ConfigurationDescriptor::new(
+            "LiquidityGenerator".into(),
+            "local_exchange_account_id;ETH/BTC".into(),
+        );
+        let reserve_parameters = ReserveParameters::new(
+            configuration_descriptor,
+            exchange_account_id,
+            symbol,
+            OrderSide::Sell,
+            dec!(0.2),
+            dec!(5),
+        );
+        let reservation_id = balance_manager
+            .lock()
+            .try_reserve(&reserve_parameters, &mut None)
+            .expect("in test");
+
+        let order_header = OrderHeader::with_user_order(
+            ClientOrderId::unique_id(),
+            exchange_account_id,
+            currency_pair,
+            OrderSide::Sell,
+            dec!(5),
+            UserOrder::limit(dec!(0.2)),
+            Some(reservation_id),
+            None,
+            "FromTest".to_owned(),
+        );
+        let existing =
+            exchange
+                .orders
+                .add_simple_initial(&order_header, time::time_manager::now(), None);
+        let existing_exchange_order_id = ExchangeOrderId::from("existing_order");
+        existing.fn_mut(|order| {
+            order.props.exchange_order_id = Some(existing_exchange_order_id.clone());
+            order.set_status(OrderStatus::Created, time::time_manager::now());
+        });
+        exchange
+            .orders
+            .cache_by_exchange_id
+            .insert(existing_exchange_order_id, existing.clone());
+        balance_manager.lock().approve_reservation(
+            reservation_id,
+            &order_header.client_order_id,
+            dec!(5),
+        );
+
+        ReplaceTestContext {
+            exchange,
+            balance_manager,
+            configuration_descriptor,
+            reservation_id,
+            existing,
+            _event_receiver: event_receiver,
+        }
+    }
+
+    fn assert_reservation_taken_over(context: &ReplaceTestContext, new_order: &OrderRef) {
+        let ReplaceTestContext {
+            existing,
+            reservation_id,
+            ..
+        } = context;
+
+        assert_eq!(existing.status(), OrderStatus::Canceled);
+        assert_eq!(new_order.status(), OrderStatus::Created);
+        assert_eq!(new_order.header().reservation_id, Some(*reservation_id));
+
+        let mut balance_manager = context.balance_manager.lock();
+        let assert_reservation = |balance_manager: &BalanceManager| {
+            let reservation = balance_manager.get_reservation_expected(*reservation_id);
+            assert_eq!(reservation.unreserved_amount, dec!(5));
+            assert_eq!(reservation.not_approved_amount, dec!(1));
+            assert!(!reservation
+                .approved_parts
+                .contains_key(&existing.client_order_id()));
+            assert!(!reservation.approved_parts[&new_order.client_order_id()].is_canceled);
+        };
+        assert_reservation(&balance_manager);
+
+        // approved part of replaced order isn't returned to the reservation again when the order is finished
+        balance_manager
+            .order_was_finished(context.configuration_descriptor, &existing.deep_clone());
+        assert_reservation(&balance_manager);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn reservation_preserved_across_replace() {
+        let (_mock_object, _mock_locker) = time::tests::init_mock(Arc::new(Mutex::new(0)));
+
+        let new_exchange_order_id = ExchangeOrderId::from("replacing_order");
+        let exchange_client = TestClient {
+            cancel_replace_order: Some(Box::new({
+                let new_exchange_order_id = new_exchange_order_id.clone();
+                move |_, _, new_order| {
+                    assert_eq!(new_order.price(), dec!(0.3));
+                    CreateOrderResult::succeed(&new_exchange_order_id, EventSourceType::Rest)
+                }
+            })),
+            ..TestClient::default()
+        };
+        let context = init_replace_test(exchange_client);
+
+        let new_order = context
+            .exchange
+            .replace_order(
+                &context.existing,
+                dec!(0.3),
+                dec!(4),
+                CancellationToken::default(),
+            )
+            .await
+            .expect("in test");
+
+        assert_eq!(new_order.exchange_order_id(), Some(new_exchange_order_id));
+        assert_reservation_taken_over(&context, &new_order);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn reservation_preserved_across_cancel_and_create() {
+        let (_mock_object, _mock_locker) = time::tests::init_mock(Arc::new(Mutex::new(0)));
+
+        let new_exchange_order_id = ExchangeOrderId::from("replacing_order");
+        let created_client_order_id = Arc::new(Mutex::new(None));
+        // exchange doesn't support cancel-replace
+        let exchange_client = TestClient {
+            cancel_order: Some(Box::new(|order, _| {
+                CancelOrderResult::succeed(order.client_order_id(), EventSourceType::Rest, None)
+            })),
+            create_order: Some(Box::new({
+                let new_exchange_order_id = new_exchange_order_id.clone();
+                let created_client_order_id = created_client_order_id.clone();
+                move |new_order| {
+                    assert_eq!(new_order.price(), dec!(0.3));
+                    *created_client_order_id.lock() = Some(new_order.client_order_id());
+                    CreateOrderResult::succeed(&new_exchange_order_id, EventSourceType::Rest)
+                }
+            })),
+            ..TestClient::default()
+        };
+        let context = init_replace_test(exchange_client);
+
+        // exchange confirms cancellation and creation via websocket
+        let confirmation = tokio::spawn({
+            let exchange = context.exchange.clone();
+            let existing = context.existing.clone();
+            let new_exchange_order_id = new_exchange_order_id.clone();
+            async move {
+                let existing_exchange_order_id = existing.exchange_order_id().expect("in test");
+                while !exchange
+                    .order_cancellation_events
+                    .contains_key(&existing_exchange_order_id)
+                {
+                    sleep(Duration::from_millis(10)).await;
+                }
+                exchange.raise_order_cancelled(
+                    existing.client_order_id(),
+                    existing_exchange_order_id,
+                    EventSourceType::WebSocket,
+                );
+
+                let new_client_order_id = loop {
+                    let new_client_order_id = created_client_order_id.lock().clone();
+                    match new_client_order_id {
+                        Some(new_client_order_id)
+                            if exchange
+                                .order_creation_events
+                                .contains_key(&new_client_order_id) =>
+                        {
+                            break new_client_order_id
+                        }
+                        _ => sleep(Duration::from_millis(10)).await,
+                    }
+                };
+                exchange.raise_order_created(
+                    &new_client_order_id,
+                    &new_exchange_order_id,
+                    EventSourceType::WebSocket,
+                );
+            }
+        });
+
+        let new_order = context
+            .exchange
+            .replace_order(
+                &context.existing,
+                dec!(0.3),
+                dec!(4),
+                CancellationToken::default(),
+            )
+            .await
+            .expect("in test");
+        confirmation.await.expect("in test");
+
+        assert_eq!(new_order.exchange_order_id(), Some(new_exchange_order_id));
+        assert_reservation_taken_over(&context, &new_order);
+    }
+}
@@ -121,8 +121,10 @@ impl Exchange {
         };
 
         let is_poll_enabled = self.features.websocket_options.cancellation_notification
-            && self.features.allowed_cancel_event_source_type
-                != AllowedEventSourceType::NonFallback;
+            && !matches!(
+                self.features.allowed_cancel_event_source_type,
+                AllowedEventSourceType::NonFallback | AllowedEventSourceType::RpcOnly
+            );
 
         pin_mut!(poll_cancellation_fut);
 
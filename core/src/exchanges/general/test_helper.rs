@@ -11,7 +11,7 @@ use crate::{
             exchange::Exchange,
             features::{
                 ExchangeFeatures, OpenOrdersType, OrderFeatures, OrderTradeOption,
-                RestFillsFeatures, WebSocketOptions,
+                RestFillsFeatures, RestFillsType, WebSocketOptions,
             },
         },
         timeouts::{
@@ -27,7 +27,9 @@ use async_trait::async_trait;
 use chrono::Duration;
 use dashmap::DashMap;
 use futures::executor::block_on;
-use mmb_domain::events::{AllowedEventSourceType, ExchangeBalancesAndPositions, ExchangeEvent};
+use mmb_domain::events::{
+    AllowedEventSourceType, ExchangeBalancesAndPositions, ExchangeEvent, TradeId,
+};
 use mmb_domain::exchanges::commission::{Commission, CommissionForType};
 use mmb_domain::exchanges::symbol::{BeforeAfter, Precision, Symbol};
 use mmb_domain::market::{
@@ -37,6 +39,7 @@ use mmb_domain::order::pool::{OrderRef, OrdersPool};
 use mmb_domain::order::snapshot::{Amount, ExchangeOrderId, OrderOptions, Price};
 use mmb_domain::order::snapshot::{ClientOrderId, OrderInfo, OrderRole, OrderSide, OrderSnapshot};
 use mmb_domain::position::{ActivePosition, ClosedPosition};
+use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use tokio::sync::broadcast;
 use url::Url;
@@ -54,20 +57,80 @@ use mmb_utils::{cancellation_token::CancellationToken, hashmap, DateTime};
 
 use super::order::get_order_trades::OrderTrade;
 
-pub struct TestClient;
+pub(crate) type OpenOrdersByCurrencyPairFn =
+    Box<dyn Fn(CurrencyPair) -> Result<Vec<OrderInfo>> + Send + Sync>;
+
+pub(crate) type CreateOrderFn = Box<dyn Fn(&OrderRef) -> CreateOrderResult + Send + Sync>;
+
+pub(crate) type CancelOrderFn =
+    Box<dyn Fn(&OrderRef, &ExchangeOrderId) -> CancelOrderResult + Send + Sync>;
+
+pub(crate) type CancelReplaceOrderFn =
+    Box<dyn Fn(&OrderRef, &ExchangeOrderId, &OrderRef) -> CreateOrderResult + Send + Sync>;
+
+pub(crate) type GetOrderInfoFn =
+    Box<dyn Fn(&OrderRef) -> Result<OrderInfo, ExchangeError> + Send + Sync>;
+
+pub(crate) type GetOrdersInfoFn =
+    Box<dyn Fn(&[OrderRef]) -> Result<Vec<OrderInfo>, ExchangeError> + Send + Sync>;
+
+pub(crate) type GetMyTradesFn =
+    Box<dyn Fn(&Symbol) -> RequestResult<Vec<OrderTrade>> + Send + Sync>;
+
+pub(crate) type GetMyTradesFromFn = Box<
+    dyn Fn(&Symbol, DateTime, Option<&TradeId>) -> RequestResult<Vec<OrderTrade>> + Send + Sync,
+>;
+
+pub(crate) type BuildAllSymbolsFn = Box<dyn Fn() -> Result<Vec<Arc<Symbol>>> + Send + Sync>;
+
+#[derive(Default)]
+pub struct TestClient {
+    pub(crate) create_order: Option<CreateOrderFn>,
+    pub(crate) open_orders_by_currency_pair: Option<OpenOrdersByCurrencyPairFn>,
+    pub(crate) cancel_order: Option<CancelOrderFn>,
+    pub(crate) cancel_replace_order: Option<CancelReplaceOrderFn>,
+    pub(crate) get_order_info: Option<GetOrderInfoFn>,
+    pub(crate) get_orders_info: Option<GetOrdersInfoFn>,
+    pub(crate) get_my_trades: Option<GetMyTradesFn>,
+    pub(crate) get_my_trades_from: Option<GetMyTradesFromFn>,
+    pub(crate) build_all_symbols: Option<BuildAllSymbolsFn>,
+    pub(crate) supported_currencies: DashMap<CurrencyId, CurrencyCode>,
+}
 
 #[async_trait]
 impl ExchangeClient for TestClient {
-    async fn create_order(&self, _order: &OrderRef) -> CreateOrderResult {
-        unimplemented!("doesn't need in UT")
+    async fn create_order(&self, order: &OrderRef) -> CreateOrderResult {
+        match &self.create_order {
+            Some(create_order) => create_order(order),
+            None => unimplemented!("doesn't need in UT"),
+        }
     }
 
     async fn cancel_order(
         &self,
-        _order: &OrderRef,
-        _exchange_order_id: &ExchangeOrderId,
+        order: &OrderRef,
+        exchange_order_id: &ExchangeOrderId,
     ) -> CancelOrderResult {
-        unimplemented!("doesn't need in UT")
+        match &self.cancel_order {
+            Some(cancel_order) => cancel_order(order, exchange_order_id),
+            None => unimplemented!("doesn't need in UT"),
+        }
+    }
+
+    fn supports_cancel_replace(&self) -> bool {
+        self.cancel_replace_order.is_some()
+    }
+
+    async fn cancel_replace_order(
+        &self,
+        order: &OrderRef,
+        exchange_order_id: &ExchangeOrderId,
+        new_order: &OrderRef,
+    ) -> CreateOrderResult {
+        match &self.cancel_replace_order {
+            Some(cancel_replace_order) => cancel_replace_order(order, exchange_order_id, new_order),
+            None => unimplemented!("doesn't need in UT"),
+        }
     }
 
     async fn cancel_all_orders(&self, _currency_pair: CurrencyPair) -> Result<()> {
@@ -80,13 +143,30 @@ impl ExchangeClient for TestClient {
 
     async fn get_open_orders_by_currency_pair(
         &self,
-        _currency_pair: CurrencyPair,
+        currency_pair: CurrencyPair,
     ) -> Result<Vec<OrderInfo>> {
-        unimplemented!("doesn't need in UT")
+        match &self.open_orders_by_currency_pair {
+            Some(open_orders_by_currency_pair) => open_orders_by_currency_pair(currency_pair),
+            None => unimplemented!("doesn't need in UT"),
+        }
     }
 
-    async fn get_order_info(&self, _order: &OrderRef) -> Result<OrderInfo, ExchangeError> {
-        unimplemented!("doesn't need in UT")
+    async fn get_order_info(&self, order: &OrderRef) -> Result<OrderInfo, ExchangeError> {
+        match &self.get_order_info {
+            Some(get_order_info) => get_order_info(order),
+            None => unimplemented!("doesn't need in UT"),
+        }
+    }
+
+    fn supports_get_orders_info(&self) -> bool {
+        self.get_orders_info.is_some()
+    }
+
+    async fn get_orders_info(&self, orders: &[OrderRef]) -> Result<Vec<OrderInfo>, ExchangeError> {
+        match &self.get_orders_info {
+            Some(get_orders_info) => get_orders_info(orders),
+            None => unimplemented!("doesn't need in UT"),
+        }
     }
 
     async fn close_position(
@@ -107,14 +187,32 @@ impl ExchangeClient for TestClient {
 
     async fn get_my_trades(
         &self,
-        _symbol: &Symbol,
+        symbol: &Symbol,
         _last_date_time: Option<DateTime>,
     ) -> RequestResult<Vec<OrderTrade>> {
-        unimplemented!("doesn't need in UT")
+        match &self.get_my_trades {
+            Some(get_my_trades) => get_my_trades(symbol),
+            None => unimplemented!("doesn't need in UT"),
+        }
+    }
+
+    async fn get_my_trades_from(
+        &self,
+        symbol: &Symbol,
+        from_datetime: DateTime,
+        from_trade_id: Option<&TradeId>,
+    ) -> RequestResult<Vec<OrderTrade>> {
+        match &self.get_my_trades_from {
+            Some(get_my_trades_from) => get_my_trades_from(symbol, from_datetime, from_trade_id),
+            None => unimplemented!("doesn't need in UT"),
+        }
     }
 
     async fn build_all_symbols(&self) -> Result<Vec<Arc<Symbol>>> {
-        unimplemented!("doesn't need in UT")
+        match &self.build_all_symbols {
+            Some(build_all_symbols) => build_all_symbols(),
+            None => unimplemented!("doesn't need in UT"),
+        }
     }
 
     async fn get_server_time(&self) -> Option<Result<i64>> {
@@ -136,11 +234,11 @@ impl Support for TestClient {
     }
 
     fn on_connected(&self) -> Result<()> {
-        unimplemented!("doesn't need in UT")
+        Ok(())
     }
 
     fn on_disconnected(&self) -> Result<()> {
-        unimplemented!("doesn't need in UT")
+        Ok(())
     }
 
     fn set_send_websocket_message_callback(&mut self, _callback: SendWebsocketMessageCb) {}
@@ -165,12 +263,12 @@ impl Support for TestClient {
         unimplemented!("doesn't need in UT")
     }
 
-    fn get_specific_currency_pair(&self, _currency_pair: CurrencyPair) -> SpecificCurrencyPair {
-        unimplemented!("doesn't need in UT")
+    fn get_specific_currency_pair(&self, currency_pair: CurrencyPair) -> SpecificCurrencyPair {
+        SpecificCurrencyPair::new(currency_pair.as_str())
     }
 
     fn get_supported_currencies(&self) -> &DashMap<CurrencyId, CurrencyCode> {
-        unimplemented!("doesn't need in UT")
+        &self.supported_currencies
     }
 
     fn should_log_message(&self, _message: &str) -> bool {
@@ -228,6 +326,34 @@ pub(crate) fn get_test_exchange_by_currency_codes_and_amount_code(
     get_test_exchange_with_symbol(symbol)
 }
 
+/// Symbol without price and amount limits. Currency ids are the same as currency codes
+pub(crate) fn create_test_symbol(
+    is_derivative: bool,
+    base_currency_code: &str,
+    quote_currency_code: &str,
+    amount_currency_code: &str,
+    balance_currency_code: Option<&str>,
+    price_tick: Decimal,
+    amount_tick: Decimal,
+) -> Symbol {
+    Symbol::new(
+        is_derivative,
+        base_currency_code.into(),
+        base_currency_code.into(),
+        quote_currency_code.into(),
+        quote_currency_code.into(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        amount_currency_code.into(),
+        balance_currency_code.map(Into::into),
+        Precision::ByTick { tick: price_tick },
+        Precision::ByTick { tick: amount_tick },
+    )
+}
+
 pub(crate) fn get_test_exchange_by_currency_codes(
     is_derivative: bool,
     base_currency_code: &str,
@@ -255,11 +381,41 @@ pub(crate) fn get_test_exchange_with_symbol(
 pub(crate) fn get_test_exchange_with_symbol_and_id(
     symbol: Arc<Symbol>,
     exchange_account_id: ExchangeAccountId,
+) -> (Arc<Exchange>, broadcast::Receiver<ExchangeEvent>) {
+    get_test_exchange_with_client(
+        symbol,
+        exchange_account_id,
+        TestClient::default(),
+        OpenOrdersType::AllCurrencyPair,
+    )
+}
+
+pub(crate) fn get_test_exchange_with_client(
+    symbol: Arc<Symbol>,
+    exchange_account_id: ExchangeAccountId,
+    exchange_client: TestClient,
+    open_orders_type: OpenOrdersType,
+) -> (Arc<Exchange>, broadcast::Receiver<ExchangeEvent>) {
+    get_test_exchange_with_client_and_fills_type(
+        symbol,
+        exchange_account_id,
+        exchange_client,
+        open_orders_type,
+        RestFillsType::default(),
+    )
+}
+
+pub(crate) fn get_test_exchange_with_client_and_fills_type(
+    symbol: Arc<Symbol>,
+    exchange_account_id: ExchangeAccountId,
+    exchange_client: TestClient,
+    open_orders_type: OpenOrdersType,
+    fills_type: RestFillsType,
 ) -> (Arc<Exchange>, broadcast::Receiver<ExchangeEvent>) {
     let lifetime_manager = AppLifetimeManager::new(CancellationToken::new());
     let (tx, rx) = broadcast::channel(10);
 
-    let exchange_client = Box::new(TestClient);
+    let exchange_client = Box::new(exchange_client);
     let referral_reward = dec!(40);
     let commission = Commission::new(
         CommissionForType::new(dec!(0.1), referral_reward),
@@ -282,8 +438,8 @@ pub(crate) fn get_test_exchange_with_symbol_and_id(
         exchange_client,
         OrdersPool::new(),
         ExchangeFeatures::new(
-            OpenOrdersType::AllCurrencyPair,
-            RestFillsFeatures::default(),
+            open_orders_type,
+            RestFillsFeatures::new(fills_type),
             OrderFeatures {
                 supports_get_order_info_by_client_order_id: true,
                 ..OrderFeatures::default()
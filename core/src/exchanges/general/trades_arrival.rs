@@ -0,0 +1,146 @@
+use std::collections::VecDeque;
+
+use dashmap::DashMap;
+use mmb_domain::market::CurrencyPair;
+use mmb_domain::order::snapshot::OrderSide;
+use mmb_utils::DateTime;
+use rust_decimal::Decimal;
+
+/// Window of recent trades used to estimate trades arrival rate
+const TRADES_ARRIVAL_WINDOW_SECS: i64 = 60;
+
+/// Receipt times of recent trades by currency pair and taker side
+#[derive(Default)]
+pub(crate) struct TradesArrival {
+    trades_by_currency_pair: DashMap<CurrencyPair, VecDeque<(DateTime, OrderSide)>>,
+}
+
+impl TradesArrival {
+    pub fn add(&self, currency_pair: CurrencyPair, taker_side: OrderSide, receipt_time: DateTime) {
+        let mut trades = self
+            .trades_by_currency_pair
+            .entry(currency_pair)
+            .or_default();
+
+        trades.push_back((receipt_time, taker_side));
+        Self::remove_outdated(&mut trades, receipt_time);
+    }
+
+    /// Trades per second with specified taker side.
+    /// Returns `None` if no trades were received for `currency_pair`
+    pub fn rate_per_second(
+        &self,
+        currency_pair: CurrencyPair,
+        taker_side: OrderSide,
+        now: DateTime,
+    ) -> Option<Decimal> {
+        let mut trades = self.trades_by_currency_pair.get_mut(&currency_pair)?;
+        Self::remove_outdated(&mut trades, now);
+
+        let trades_count = trades
+            .iter()
+            .filter(|(_, side)| *side == taker_side)
+            .count();
+
+        Some(Decimal::from(trades_count) / Decimal::from(TRADES_ARRIVAL_WINDOW_SECS))
+    }
+
+    fn remove_outdated(trades: &mut VecDeque<(DateTime, OrderSide)>, now: DateTime) {
+        let window_start = now - chrono::Duration::seconds(TRADES_ARRIVAL_WINDOW_SECS);
+        while trades
+            .front()
+            .is_some_and(|(receipt_time, _)| *receipt_time < window_start)
+        {
+            trades.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchanges::general::test_helper::get_test_exchange;
+    use chrono::{Duration, TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn rate_per_second_by_taker_side_within_window() {
+        let trades_arrival = TradesArrival::default();
+        let currency_pair = CurrencyPair::from_codes("base".into(), "quote".into());
+        let start = Utc
+            .with_ymd_and_hms(2021, 9, 20, 0, 0, 0)
+            .single()
+            .expect("in test");
+
+        assert_eq!(
+            trades_arrival.rate_per_second(currency_pair, OrderSide::Sell, start),
+            None
+        );
+
+        for secs in 0..6 {
+            trades_arrival.add(
+                currency_pair,
+                OrderSide::Sell,
+                start + Duration::seconds(secs),
+            );
+        }
+        trades_arrival.add(currency_pair, OrderSide::Buy, start);
+
+        let now = start + Duration::seconds(10);
+        assert_eq!(
+            trades_arrival.rate_per_second(currency_pair, OrderSide::Sell, now),
+            Some(dec!(0.1))
+        );
+
+        let after_window = start + Duration::seconds(TRADES_ARRIVAL_WINDOW_SECS + 3);
+        assert_eq!(
+            trades_arrival.rate_per_second(currency_pair, OrderSide::Sell, after_window),
+            Some(dec!(0.05))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn maker_fill_probability_decreases_with_distance() {
+        let (exchange, _event_receiver) = get_test_exchange(false);
+        let currency_pair = exchange
+            .symbols
+            .iter()
+            .next()
+            .expect("in test")
+            .value()
+            .currency_pair();
+        let horizon = std::time::Duration::from_secs(10);
+
+        assert_eq!(
+            exchange.maker_fill_probability(currency_pair, OrderSide::Buy, 0, horizon),
+            None
+        );
+
+        // 0.5 taker sells per second
+        for _ in 0..30 {
+            exchange
+                .trades_arrival
+                .add(currency_pair, OrderSide::Sell, Utc::now());
+        }
+
+        let probabilities: Vec<_> = [0, 1, 5]
+            .into_iter()
+            .map(|distance_ticks| {
+                exchange
+                    .maker_fill_probability(currency_pair, OrderSide::Buy, distance_ticks, horizon)
+                    .expect("in test")
+            })
+            .collect();
+
+        assert!(probabilities[0] > probabilities[1]);
+        assert!(probabilities[1] > probabilities[2]);
+        assert!(probabilities[2] > dec!(0));
+        assert!(probabilities[0] < dec!(1));
+
+        // no taker buys, so resting sell order isn't expected to be filled
+        assert_eq!(
+            exchange.maker_fill_probability(currency_pair, OrderSide::Sell, 0, horizon),
+            Some(dec!(0))
+        );
+    }
+}
@@ -82,6 +82,7 @@ impl InternalEventsLoop {
                 ExchangeEvent::BalanceUpdate(_) => {}
                 ExchangeEvent::LiquidationPrice(_) => {}
                 ExchangeEvent::Trades(_) => {}
+                ExchangeEvent::Metrics(_) => {}
             }
         }
     }
@@ -7,3 +7,4 @@ pub(crate) mod internal_events_loop;
 pub mod rest_client;
 pub mod timeouts;
 pub mod traits;
+pub mod websocket_disconnects_guard;
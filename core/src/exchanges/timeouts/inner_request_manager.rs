@@ -1,6 +1,7 @@
 use super::{
     more_or_equals_available_requests_count_trigger_scheduler::MoreOrEqualsAvailableRequestsCountTriggerScheduler,
-    pre_reserved_group::PreReservedGroup, request::Request,
+    pre_reserved_group::PreReservedGroup,
+    request::{Request, RequestPriority},
     triggers::handle_trigger_trait::TriggerHandler,
 };
 use crate::exchanges::general::request_type::RequestType;
@@ -11,6 +12,7 @@ use function_name::named;
 use mmb_domain::market::ExchangeAccountId;
 use mmb_utils::DateTime;
 use std::collections::HashMap;
+use uuid::Uuid;
 
 pub(super) struct InnerRequestsTimeoutManager {
     pub(super) requests_per_period: usize,
@@ -49,7 +51,8 @@ impl InnerRequestsTimeoutManager {
             return false;
         }
 
-        let request = self.add_request(request_type, current_time, None);
+        let priority = RequestPriority::default_for(request_type);
+        let request = self.add_request(request_type, priority, current_time, None);
         self.last_time = Some(current_time);
 
         log::info!("Reserved request {request_type:?} without group, instant {current_time}");
@@ -78,14 +81,14 @@ impl InnerRequestsTimeoutManager {
         count
     }
 
-    #[named]
     pub(super) fn add_request(
         &mut self,
         request_type: RequestType,
+        priority: RequestPriority,
         current_time: DateTime,
         group_id: Option<RequestGroupId>,
     ) -> Request {
-        let request = Request::new(request_type, current_time, group_id);
+        let request = Request::new(request_type, priority, current_time, group_id);
 
         let request_index = self
             .requests
@@ -94,6 +97,67 @@ impl InnerRequestsTimeoutManager {
 
         self.requests.insert(request_index, request.clone());
 
+        self.handle_triggers_after_adding_request();
+
+        request
+    }
+
+    /// Add request which should wait for `request_start_time`. If there are pending requests
+    /// (scheduled after `current_time`) with lower priority, the new request takes the slot of the
+    /// first of them and all following pending requests are shifted to the next slots.
+    /// Pending requests are always ordered by descending priority, so shifting keeps the
+    /// schedule valid: the set of occupied slots is only extended by `request_start_time`.
+    pub(super) fn add_prioritized_request(
+        &mut self,
+        request_type: RequestType,
+        priority: RequestPriority,
+        request_start_time: DateTime,
+        current_time: DateTime,
+    ) -> Request {
+        let first_pending_index = self
+            .requests
+            .partition_point(|r| r.allowed_start_time <= current_time);
+
+        let preempted_index = self.requests[first_pending_index..]
+            .iter()
+            .position(|r| r.priority < priority);
+
+        let preempted_index = match preempted_index {
+            None => return self.add_request(request_type, priority, request_start_time, None),
+            Some(preempted_index) => preempted_index,
+        };
+
+        let mut pending_requests = self.requests.split_off(first_pending_index);
+        let mut slots: Vec<DateTime> = pending_requests
+            .iter()
+            .map(|r| r.allowed_start_time)
+            .collect();
+        slots.push(request_start_time);
+
+        let request = Request::new(request_type, priority, request_start_time, None);
+        pending_requests.insert(preempted_index, request);
+
+        for (pending_request, slot) in pending_requests.iter_mut().zip(slots) {
+            pending_request.allowed_start_time = slot;
+        }
+
+        let request = pending_requests[preempted_index].clone();
+        self.requests.append(&mut pending_requests);
+
+        self.handle_triggers_after_adding_request();
+
+        request
+    }
+
+    pub(super) fn get_request_start_time(&self, request_id: Uuid) -> Option<DateTime> {
+        self.requests
+            .iter()
+            .find(|r| r.id == request_id)
+            .map(|r| r.allowed_start_time)
+    }
+
+    #[named]
+    fn handle_triggers_after_adding_request(&mut self) {
         let last_request_start_time = self
             .requests
             .last()
@@ -102,8 +166,6 @@ impl InnerRequestsTimeoutManager {
 
         self.handle_all_decreasing_triggers();
         self.handle_all_increasing_triggers(last_request_start_time);
-
-        request
     }
 
     pub(super) fn handle_all_decreasing_triggers(&mut self) {
@@ -274,6 +336,17 @@ impl InnerRequestsTimeoutManager {
     pub(super) fn get_period_duration(&self) -> Duration {
         self.period_duration
     }
+
+    /// Time left until the oldest request of current period becomes outdated and frees a slot
+    pub(super) fn get_next_refill_in(&self, current_time: DateTime) -> Duration {
+        self.requests
+            .iter()
+            .filter(|request| request.allowed_start_time <= current_time)
+            .map(|request| request.allowed_start_time + self.period_duration - current_time)
+            .min()
+            .unwrap_or_else(Duration::zero)
+            .max(Duration::zero())
+    }
 }
 
 #[derive(Default)]
@@ -1,11 +1,33 @@
 use mmb_utils::DateTime;
+use uuid::Uuid;
 
 use crate::exchanges::general::request_type::RequestType;
 use crate::exchanges::timeouts::requests_timeout_manager::RequestGroupId;
 
+/// Order in which requests waiting for a free slot of the rate limiter are dispatched.
+/// Requests with higher priority take slots of pending requests with lower priority.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum RequestPriority {
+    Low,
+    Normal,
+    High,
+}
+
+impl RequestPriority {
+    pub fn default_for(request_type: RequestType) -> Self {
+        match request_type {
+            // cancellation reduces risk, so it should never wait behind new orders
+            RequestType::CancelOrder => RequestPriority::High,
+            _ => RequestPriority::Normal,
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Request {
+    pub(crate) id: Uuid,
     pub(crate) request_type: RequestType,
+    pub(crate) priority: RequestPriority,
     pub(crate) allowed_start_time: DateTime,
     pub(crate) group_id: Option<RequestGroupId>,
 }
@@ -13,11 +35,14 @@ pub struct Request {
 impl Request {
     pub fn new(
         request_type: RequestType,
+        priority: RequestPriority,
         allowed_start_time: DateTime,
         group_id: Option<RequestGroupId>,
     ) -> Self {
         Self {
+            id: Uuid::new_v4(),
             request_type,
+            priority,
             allowed_start_time,
             group_id,
         }
@@ -14,7 +14,8 @@ use uuid::Uuid;
 use super::{
     inner_request_manager::InnerRequestsTimeoutManager,
     more_or_equals_available_requests_count_trigger_scheduler::MoreOrEqualsAvailableRequestsCountTriggerScheduler,
-    pre_reserved_group::PreReservedGroup, request::Request,
+    pre_reserved_group::PreReservedGroup,
+    request::{Request, RequestPriority},
     triggers::every_requests_count_change_trigger::EveryRequestsCountChangeTrigger,
     triggers::less_or_equals_requests_count_trigger::LessOrEqualsRequestsCountTrigger,
 };
@@ -37,6 +38,15 @@ impl Display for RequestGroupId {
     }
 }
 
+/// Snapshot of requests rate limit state for pacing outgoing requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestBudget {
+    /// Requests count that can be reserved instantly without waiting
+    pub available_now: usize,
+    /// Time until the next slot is freed. Zero if there are no reserved requests in current period
+    pub refill_in: std::time::Duration,
+}
+
 pub struct RequestsTimeoutManager {
     inner: Mutex<InnerRequestsTimeoutManager>,
 }
@@ -199,7 +209,9 @@ impl RequestsTimeoutManager {
                     return false;
                 }
 
-                let request = inner.add_request(request_type, current_time, Some(group.id));
+                let priority = RequestPriority::default_for(request_type);
+                let request =
+                    inner.add_request(request_type, priority, current_time, Some(group.id));
 
                 log::info!(
                     "Request {request_type:?} reserved for group with pre_reserved_group_id {pre_reserved_group_id},
@@ -232,6 +244,21 @@ impl RequestsTimeoutManager {
         request_type: RequestType,
         current_time: DateTime,
         cancellation_token: CancellationToken,
+    ) -> (JoinHandle<FutureOutcome>, DateTime, Duration) {
+        self.reserve_when_available_with_priority(
+            request_type,
+            RequestPriority::default_for(request_type),
+            current_time,
+            cancellation_token,
+        )
+    }
+
+    pub fn reserve_when_available_with_priority(
+        self: Arc<Self>,
+        request_type: RequestType,
+        priority: RequestPriority,
+        current_time: DateTime,
+        cancellation_token: CancellationToken,
     ) -> (JoinHandle<FutureOutcome>, DateTime, Duration) {
         // NOTE: calculation doesn't support request cancellation
         // NOTE: suppose that exchange restriction work as your have n request on period and n request from beginning of next period and so on
@@ -239,6 +266,7 @@ impl RequestsTimeoutManager {
         // Algorithm:
         // 1. We check: can we do request now
         // 2. if not form schedule for request where put at start period by requestsPerPeriod requests
+        // 3. if there are pending requests with lower priority, take the slot of the first of them
 
         let mut inner = self.inner.lock();
 
@@ -247,34 +275,33 @@ impl RequestsTimeoutManager {
 
         let _available_requests_count = inner.get_all_available_requests_count();
 
-        let mut request_start_time;
-        let delay;
-        let available_requests_count_for_period;
-        let request = if let Some(last_request) = inner.requests.last() {
-            let last_request_start_time = last_request.allowed_start_time;
-
-            available_requests_count_for_period =
-                inner.get_available_requests_count_in_last_period(last_request_start_time);
-            request_start_time = if available_requests_count_for_period == 0 {
-                last_request_start_time + inner.period_duration + inner.delay_to_next_time_period
-            } else {
-                last_request_start_time
-            };
+        let request_start_time = match inner.requests.last() {
+            Some(last_request) => {
+                let last_request_start_time = last_request.allowed_start_time;
+
+                let available_requests_count_for_period =
+                    inner.get_available_requests_count_in_last_period(last_request_start_time);
+                let request_start_time = if available_requests_count_for_period == 0 {
+                    last_request_start_time
+                        + inner.period_duration
+                        + inner.delay_to_next_time_period
+                } else {
+                    last_request_start_time
+                };
 
-            request_start_time = request_start_time.max(current_time);
-            delay = request_start_time - current_time;
-            inner.add_request(request_type, request_start_time, None)
-        } else {
-            request_start_time = current_time;
-            delay = Duration::zero();
-            // available_requests_count_for_period = inner.requests_per_period;
-            inner.add_request(request_type, current_time, None)
+                request_start_time.max(current_time)
+            }
+            None => current_time,
         };
 
-        log::info!("Request {request_type:?} reserved, available in request_start_time {request_start_time}");
+        let request =
+            inner.add_prioritized_request(request_type, priority, request_start_time, current_time);
+        let request_start_time = request.allowed_start_time;
+        let delay = request_start_time - current_time;
 
-        // TODO save to DataRecorder. Delete drop
-        // drop(available_requests_count_for_period);
+        log::info!("Request {request_type:?} with priority {priority:?} reserved, available in request_start_time {request_start_time}");
+
+        // TODO save to DataRecorder
 
         inner.last_time = Some(current_time);
 
@@ -301,32 +328,49 @@ impl RequestsTimeoutManager {
         delay: Duration,
         cancellation_token: CancellationToken,
     ) -> Result<()> {
-        // Should never panic, because function wait_for_request_availability
-        // has one call with guaranteed non-negative delay.
-        let delay = delay.to_std_expected();
-
-        match timeout(delay, cancellation_token.when_cancelled()).await {
-            Err(_) => {
-                let strong_self = Self::try_get_strong(weak_self)?;
-                (strong_self.inner.lock().time_has_come_for_request)(request);
-            }
-            Ok(()) => {
-                let strong_self = Self::try_get_strong(weak_self)?;
-                let mut inner = strong_self.inner.lock();
-                (inner.time_has_come_for_request)(request.clone());
-                if let Some(position) = inner
-                    .requests
-                    .iter()
-                    .position(|stored_request| *stored_request == request)
-                {
-                    inner.requests.remove(position);
-                }
+        let mut scheduled_start_time = request.allowed_start_time;
+        let mut delay = delay;
+
+        loop {
+            // Should never panic, because function wait_for_request_availability
+            // has one call with guaranteed non-negative delay
+            // and the request can only be shifted to a later slot.
+            let std_delay = delay.to_std_expected();
+
+            match timeout(std_delay, cancellation_token.when_cancelled()).await {
+                Err(_) => {
+                    let strong_self = Self::try_get_strong(weak_self.clone())?;
+                    let inner = strong_self.inner.lock();
+
+                    // request could be shifted by request with higher priority while we were waiting
+                    if let Some(start_time) = inner.get_request_start_time(request.id) {
+                        if start_time > scheduled_start_time {
+                            delay = start_time - scheduled_start_time;
+                            scheduled_start_time = start_time;
+                            continue;
+                        }
+                    }
 
-                bail!(OPERATION_CANCELED_MSG)
-            }
-        };
+                    (inner.time_has_come_for_request)(request);
 
-        Ok(())
+                    return Ok(());
+                }
+                Ok(()) => {
+                    let strong_self = Self::try_get_strong(weak_self)?;
+                    let mut inner = strong_self.inner.lock();
+                    (inner.time_has_come_for_request)(request.clone());
+                    if let Some(position) = inner
+                        .requests
+                        .iter()
+                        .position(|stored_request| stored_request.id == request.id)
+                    {
+                        inner.requests.remove(position);
+                    }
+
+                    bail!(OPERATION_CANCELED_MSG)
+                }
+            };
+        }
     }
 
     fn try_get_strong(
@@ -382,6 +426,18 @@ impl RequestsTimeoutManager {
     pub fn get_period_duration(&self) -> std::time::Duration {
         self.inner.lock().get_period_duration().to_std_expected()
     }
+
+    pub fn get_request_budget(&self, current_time: DateTime) -> RequestBudget {
+        let mut inner = self.inner.lock();
+
+        let current_time = inner.get_non_decreasing_time(current_time);
+        inner.remove_outdated_requests(current_time);
+
+        RequestBudget {
+            available_now: inner.get_available_requests_count_at_present(current_time),
+            refill_in: inner.get_next_refill_in(current_time).to_std_expected(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -2205,6 +2261,127 @@ mod test {
         }
     }
 
+    mod priority {
+        use crate::infrastructure::init_lifetime_manager;
+
+        use super::*;
+
+        #[rstest]
+        #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+        async fn high_priority_cancel_dispatched_before_low_priority_create(
+            timeout_manager: Arc<RequestsTimeoutManager>,
+        ) -> Result<()> {
+            let _ = init_lifetime_manager();
+
+            // Arrange
+            let period_duration = Duration::milliseconds(100);
+            let delay_to_next_time_period = Duration::milliseconds(1);
+            let current_time = Utc::now();
+            {
+                let mut inner = timeout_manager.inner.lock();
+                inner.requests_per_period = 1;
+                inner.period_duration = period_duration;
+            }
+
+            let reserved_instant =
+                timeout_manager.try_reserve_instant(RequestType::CreateOrder, current_time, None);
+            assert!(reserved_instant);
+
+            let dispatched_requests = Arc::new(Mutex::new(Vec::new()));
+            timeout_manager.inner.lock().time_has_come_for_request = {
+                let dispatched_requests = dispatched_requests.clone();
+                Box::new(move |request| dispatched_requests.lock().push(request.request_type))
+            };
+
+            // Act
+            let (create_handle, create_start_time, _) = timeout_manager
+                .clone()
+                .reserve_when_available_with_priority(
+                    RequestType::CreateOrder,
+                    RequestPriority::Low,
+                    current_time,
+                    CancellationToken::default(),
+                );
+            let (cancel_handle, cancel_start_time, cancel_delay) = timeout_manager
+                .clone()
+                .reserve_when_available_with_priority(
+                    RequestType::CancelOrder,
+                    RequestPriority::High,
+                    current_time,
+                    CancellationToken::default(),
+                );
+
+            // Assert
+            let first_slot = current_time + period_duration + delay_to_next_time_period;
+            assert_eq!(create_start_time, first_slot);
+            assert_eq!(cancel_start_time, first_slot);
+            assert_eq!(cancel_delay, period_duration + delay_to_next_time_period);
+
+            {
+                let inner = timeout_manager.inner.lock();
+                assert_eq!(inner.requests.len(), 3);
+
+                let cancel_request = inner.requests[1].clone();
+                assert_eq!(cancel_request.request_type, RequestType::CancelOrder);
+                assert_eq!(cancel_request.allowed_start_time, first_slot);
+
+                let create_request = inner.requests[2].clone();
+                assert_eq!(create_request.request_type, RequestType::CreateOrder);
+                assert_eq!(
+                    create_request.allowed_start_time,
+                    first_slot + period_duration + delay_to_next_time_period
+                );
+            }
+
+            cancel_handle.await?.into_result()?;
+            create_handle.await?.into_result()?;
+
+            assert_eq!(
+                *dispatched_requests.lock(),
+                vec![RequestType::CancelOrder, RequestType::CreateOrder]
+            );
+
+            Ok(())
+        }
+
+        #[rstest]
+        #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+        async fn same_priority_keeps_order(
+            timeout_manager: Arc<RequestsTimeoutManager>,
+        ) -> Result<()> {
+            let _ = init_lifetime_manager();
+
+            // Arrange
+            timeout_manager.inner.lock().requests_per_period = 1;
+            let current_time = Utc::now();
+
+            let reserved_instant =
+                timeout_manager.try_reserve_instant(RequestType::CreateOrder, current_time, None);
+            assert!(reserved_instant);
+
+            // Act
+            let (_, first_start_time, _) = timeout_manager.clone().reserve_when_available(
+                RequestType::CreateOrder,
+                current_time,
+                CancellationToken::default(),
+            );
+            let (_, second_start_time, _) = timeout_manager.clone().reserve_when_available(
+                RequestType::GetOrderInfo,
+                current_time,
+                CancellationToken::default(),
+            );
+
+            // Assert
+            assert!(first_start_time < second_start_time);
+
+            let inner = timeout_manager.inner.lock();
+            assert_eq!(inner.requests[1].request_type, RequestType::CreateOrder);
+            assert_eq!(inner.requests[2].request_type, RequestType::GetOrderInfo);
+
+            Ok(())
+        }
+    }
+
     mod triggers {
         use parking_lot::Mutex;
 
@@ -15,8 +15,9 @@ use anyhow::Result;
 use chrono::Utc;
 
 use crate::exchanges::general::request_type::RequestType;
+use crate::exchanges::timeouts::request::RequestPriority;
 use crate::exchanges::timeouts::requests_timeout_manager::{
-    RequestGroupId, RequestsTimeoutManager,
+    RequestBudget, RequestGroupId, RequestsTimeoutManager,
 };
 use mmb_domain::market::ExchangeAccountId;
 
@@ -79,6 +80,25 @@ impl TimeoutManager {
         request_type: RequestType,
         pre_reservation_group_id: Option<RequestGroupId>,
         cancellation_token: CancellationToken,
+    ) -> impl Future<Output = FutureOutcome> + Send + Sync {
+        self.reserve_when_available_with_priority(
+            exchange_account_id,
+            request_type,
+            RequestPriority::default_for(request_type),
+            pre_reservation_group_id,
+            cancellation_token,
+        )
+    }
+
+    /// Same as `reserve_when_available` but while the limiter is saturated the request
+    /// is dispatched before already waiting requests with lower priority
+    pub fn reserve_when_available_with_priority(
+        &self,
+        exchange_account_id: ExchangeAccountId,
+        request_type: RequestType,
+        priority: RequestPriority,
+        pre_reservation_group_id: Option<RequestGroupId>,
+        cancellation_token: CancellationToken,
     ) -> impl Future<Output = FutureOutcome> + Send + Sync {
         let inner = self.inner[&exchange_account_id].clone();
 
@@ -99,7 +119,12 @@ impl TimeoutManager {
 
         let now = now();
         if pre_reservation_group_id.is_none() {
-            let result = inner.reserve_when_available(request_type, now, cancellation_token);
+            let result = inner.reserve_when_available_with_priority(
+                request_type,
+                priority,
+                now,
+                cancellation_token,
+            );
             return Either::Left(convert(result.0));
         }
 
@@ -111,7 +136,12 @@ impl TimeoutManager {
             )));
         }
 
-        let result = inner.reserve_when_available(request_type, now, cancellation_token);
+        let result = inner.reserve_when_available_with_priority(
+            request_type,
+            priority,
+            now,
+            cancellation_token,
+        );
         Either::Left(convert(result.0))
     }
 
@@ -121,6 +151,13 @@ impl TimeoutManager {
             .with_expect(|| format!("Can't find timeout manger for {exchange_account_id}"))
             .get_period_duration()
     }
+
+    pub fn get_request_budget(&self, exchange_account_id: ExchangeAccountId) -> RequestBudget {
+        self.inner
+            .get(&exchange_account_id)
+            .with_expect(|| format!("Can't find timeout manger for {exchange_account_id}"))
+            .get_request_budget(now())
+    }
 }
 
 pub fn now() -> DateTime {
@@ -16,7 +16,7 @@ use anyhow::Result;
 use async_trait::async_trait;
 use dashmap::DashMap;
 use mmb_domain::events::{EventSourceType, ExchangeBalancesAndPositions, MetricsEventInfo};
-use mmb_domain::events::{ExchangeEvent, Trade};
+use mmb_domain::events::{ExchangeEvent, Trade, TradeId};
 use mmb_domain::exchanges::symbol::{BeforeAfter, Symbol};
 use mmb_domain::market::CurrencyId;
 use mmb_domain::market::{
@@ -26,7 +26,7 @@ use mmb_domain::market::{
 use mmb_domain::order::pool::{OrderRef, OrdersPool};
 use mmb_domain::order::snapshot::Price;
 use mmb_domain::order::snapshot::{
-    ClientOrderId, ExchangeOrderId, OrderInfo, OrderInfoExtensionData, OrderSide,
+    ClientOrderId, ExchangeOrderId, OrderInfo, OrderInfoExtensionData, OrderRejectReason, OrderSide,
 };
 use mmb_domain::position::{ActivePosition, ClosedPosition};
 use mmb_utils::DateTime;
@@ -44,6 +44,9 @@ pub struct ExchangeError {
     pub error_type: ExchangeErrorType,
     pub message: String,
     pub code: Option<i64>,
+    /// Unified reason if exchange rejected the order
+    #[serde(default)]
+    pub reject_reason: Option<OrderRejectReason>,
 }
 
 impl ExchangeError {
@@ -52,9 +55,15 @@ impl ExchangeError {
             error_type,
             message,
             code,
+            reject_reason: None,
         }
     }
 
+    pub fn with_reject_reason(mut self, reject_reason: OrderRejectReason) -> Self {
+        self.reject_reason = Some(reject_reason);
+        self
+    }
+
     pub fn authentication(message: String) -> Self {
         ExchangeError::new(ExchangeErrorType::Authentication, message, None)
     }
@@ -71,6 +80,7 @@ impl ExchangeError {
             error_type: ExchangeErrorType::Unknown,
             message: message.to_owned(),
             code: None,
+            reject_reason: None,
         }
     }
 
@@ -98,6 +108,22 @@ pub trait ExchangeClient: Support {
         exchange_order_id: &ExchangeOrderId,
     ) -> CancelOrderResult;
 
+    /// Exchange is able to cancel an order and create a new one in a single request
+    fn supports_cancel_replace(&self) -> bool {
+        false
+    }
+
+    /// Cancel `order` and create `new_order` in a single request.
+    /// Should be implemented only if `supports_cancel_replace` returns true
+    async fn cancel_replace_order(
+        &self,
+        _order: &OrderRef,
+        _exchange_order_id: &ExchangeOrderId,
+        _new_order: &OrderRef,
+    ) -> CreateOrderResult {
+        unimplemented!("Cancel-replace isn't supported by exchange")
+    }
+
     async fn cancel_all_orders(&self, currency_pair: CurrencyPair) -> Result<()>;
 
     async fn get_open_orders(&self) -> Result<Vec<OrderInfo>>;
@@ -109,6 +135,17 @@ pub trait ExchangeClient: Support {
 
     async fn get_order_info(&self, order: &OrderRef) -> Result<OrderInfo, ExchangeError>;
 
+    /// Exchange is able to get info of several orders in a single request
+    fn supports_get_orders_info(&self) -> bool {
+        false
+    }
+
+    /// Get info of several orders in a single request. Orders absent in response are considered as not found.
+    /// Should be implemented only if `supports_get_orders_info` returns true
+    async fn get_orders_info(&self, _orders: &[OrderRef]) -> Result<Vec<OrderInfo>, ExchangeError> {
+        unimplemented!("Getting info of several orders isn't supported by exchange")
+    }
+
     /// Must be implemented for derivative exchanges
     /// If exchange doesn't support futures the method must call panic (unimplemented!())
     async fn close_position(
@@ -136,6 +173,20 @@ pub trait ExchangeClient: Support {
         from_datetime: Option<DateTime>,
     ) -> RequestResult<Vec<OrderTrade>>;
 
+    /// Get the oldest trades executed not earlier than `from_datetime` sorted by execution time.
+    /// If `from_trade_id` is set, trades starting from this trade id are returned instead, when exchange supports it.
+    /// Count of returned trades is limited by exchange, so newer trades should be requested page by page
+    async fn get_my_trades_from(
+        &self,
+        _symbol: &Symbol,
+        _from_datetime: DateTime,
+        _from_trade_id: Option<&TradeId>,
+    ) -> RequestResult<Vec<OrderTrade>> {
+        RequestResult::Error(ExchangeError::unknown(
+            "Getting trades page by page isn't supported by exchange",
+        ))
+    }
+
     async fn build_all_symbols(&self) -> Result<Vec<Arc<Symbol>>>;
 
     /// Only for centralized exchanges
@@ -207,6 +258,11 @@ pub trait Support: Send + Sync {
 
     fn get_settings(&self) -> &ExchangeSettings;
 
+    /// Max length of client order id accepted by exchange
+    fn max_client_order_id_len(&self) -> usize {
+        36
+    }
+
     fn get_initial_extension_data(&self) -> Option<Box<dyn OrderInfoExtensionData>> {
         None
     }
@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use chrono::Duration;
+use mmb_domain::market::ExchangeAccountId;
+use mmb_utils::DateTime;
+
+use crate::exchanges::block_reasons::WEBSOCKET_DISCONNECTS_EXCEEDED;
+use crate::exchanges::exchange_blocker::{BlockType, ExchangeBlocker};
+use crate::settings::WebsocketDisconnectsLimit;
+
+/// Kill switch for exchange account which websocket connection flaps.
+/// When websocket disconnects more than `max_disconnects` times during `period`, exchange account
+/// is blocked until manual unblocking, because trading without websocket events is trading blind
+pub struct WebsocketDisconnectsGuard {
+    max_disconnects: usize,
+    period: Duration,
+    disconnect_times: VecDeque<DateTime>,
+}
+
+impl WebsocketDisconnectsGuard {
+    pub fn new(limit: &WebsocketDisconnectsLimit) -> Self {
+        Self {
+            max_disconnects: limit.max_disconnects,
+            period: Duration::seconds(limit.period_secs as i64),
+            disconnect_times: VecDeque::new(),
+        }
+    }
+
+    /// Register websocket disconnect and engage kill switch if disconnects limit is exceeded.
+    /// Return true if kill switch was engaged by this disconnect
+    pub fn on_disconnected(
+        &mut self,
+        exchange_account_id: ExchangeAccountId,
+        time: DateTime,
+        exchange_blocker: &Arc<ExchangeBlocker>,
+    ) -> bool {
+        self.disconnect_times.push_back(time);
+        while let Some(&first) = self.disconnect_times.front() {
+            if time - first <= self.period {
+                break;
+            }
+            let _ = self.disconnect_times.pop_front();
+        }
+
+        if self.disconnect_times.len() <= self.max_disconnects
+            || exchange_blocker
+                .is_blocked_by_reason(exchange_account_id, WEBSOCKET_DISCONNECTS_EXCEEDED)
+        {
+            return false;
+        }
+
+        log::error!(
+            "Websocket of {} disconnected {} times for {} that exceeded {}. Engaging kill switch",
+            exchange_account_id,
+            self.disconnect_times.len(),
+            self.period,
+            self.max_disconnects
+        );
+
+        exchange_blocker.block(
+            exchange_account_id,
+            WEBSOCKET_DISCONNECTS_EXCEEDED,
+            BlockType::Manual,
+        );
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::init_lifetime_manager;
+    use chrono::Utc;
+
+    #[tokio::test]
+    async fn kill_switch_engaged_after_disconnects_limit_exceeded() {
+        let _ = init_lifetime_manager();
+        let exchange_account_id = ExchangeAccountId::new("ExchangeId", 0);
+        let exchange_blocker = ExchangeBlocker::new(vec![exchange_account_id]);
+        let mut guard = WebsocketDisconnectsGuard::new(&WebsocketDisconnectsLimit {
+            max_disconnects: 3,
+            period_secs: 60,
+        });
+
+        let start = Utc::now();
+        for i in 0..3 {
+            let time = start + Duration::seconds(i * 10);
+            assert!(!guard.on_disconnected(exchange_account_id, time, &exchange_blocker));
+            assert!(!exchange_blocker
+                .is_blocked_by_reason(exchange_account_id, WEBSOCKET_DISCONNECTS_EXCEEDED));
+        }
+
+        let time = start + Duration::seconds(30);
+        assert!(guard.on_disconnected(exchange_account_id, time, &exchange_blocker));
+        assert!(exchange_blocker
+            .is_blocked_by_reason(exchange_account_id, WEBSOCKET_DISCONNECTS_EXCEEDED));
+    }
+
+    #[tokio::test]
+    async fn kill_switch_not_engaged_for_disconnects_spread_over_time() {
+        let _ = init_lifetime_manager();
+        let exchange_account_id = ExchangeAccountId::new("ExchangeId", 0);
+        let exchange_blocker = ExchangeBlocker::new(vec![exchange_account_id]);
+        let mut guard = WebsocketDisconnectsGuard::new(&WebsocketDisconnectsLimit {
+            max_disconnects: 3,
+            period_secs: 60,
+        });
+
+        let start = Utc::now();
+        for i in 0..10 {
+            let time = start + Duration::seconds(i * 30);
+            assert!(!guard.on_disconnected(exchange_account_id, time, &exchange_blocker));
+        }
+
+        assert!(!exchange_blocker.is_blocked(exchange_account_id));
+    }
+}
@@ -2,6 +2,7 @@ use mmb_database::impl_event;
 use mmb_domain::market::CurrencyPair;
 use mmb_domain::market::ExchangeId;
 use mmb_domain::order::snapshot::{Amount, Price};
+use rust_decimal::Decimal;
 use serde::Serialize;
 use std::fmt::{Debug, Formatter};
 
@@ -31,15 +32,35 @@ impl From<Option<&str>> for Reason {
     }
 }
 
+/// Typed intermediate value of available balance calculation.
+/// Collected alongside human-readable reasons for programmatic analysis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExplanationStep {
+    /// Virtual balance before any adjustments
+    RawBalance(Amount),
+    Leverage(Decimal),
+    /// Free amount of position added to balance
+    FreeAmount(Amount),
+    /// Balance without untouchable part
+    AfterUntouchable(Amount),
+    /// Balance with applied amount limit
+    AfterLimit(Amount),
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct Explanation {
     reasons: Vec<String>,
+    steps: Vec<ExplanationStep>,
 }
 
 impl Explanation {
     pub(crate) fn get_reasons(&self) -> &[String] {
         self.reasons.as_slice()
     }
+
+    pub fn steps(&self) -> &[ExplanationStep] {
+        self.steps.as_slice()
+    }
 }
 
 impl Explanation {
@@ -50,6 +71,10 @@ impl Explanation {
         }
     }
 
+    pub fn add_step(&mut self, step: ExplanationStep) {
+        self.steps.push(step);
+    }
+
     #[cfg(test)]
     fn reasons(self) -> Vec<String> {
         self.reasons
@@ -105,6 +130,8 @@ impl<T: Eq + PartialEq> Eq for WithExplanation<T> {}
 pub trait OptionExplanationAddReasonExt {
     fn add_reason(&mut self, reason: String);
 
+    fn add_step(&mut self, step: ExplanationStep);
+
     fn with_reason<C>(&mut self, f: impl FnOnce() -> C)
     where
         C: Into<Reason>;
@@ -117,6 +144,12 @@ impl OptionExplanationAddReasonExt for Option<Explanation> {
         }
     }
 
+    fn add_step(&mut self, step: ExplanationStep) {
+        if let Some(explanation) = self {
+            explanation.add_step(step);
+        }
+    }
+
     fn with_reason<C>(&mut self, reason: impl FnOnce() -> C)
     where
         C: Into<Reason>,
@@ -171,4 +204,18 @@ mod tests {
         let expected = vec!["test".to_string()];
         assert_eq!(explanation.reasons(), expected);
     }
+
+    #[test]
+    pub fn add_step() {
+        let mut explanation = Some(Explanation::default());
+
+        explanation.add_step(ExplanationStep::Leverage(Decimal::TWO));
+
+        let explanation = explanation.expect("in test");
+        assert_eq!(
+            explanation.steps(),
+            &[ExplanationStep::Leverage(Decimal::TWO)]
+        );
+        assert!(explanation.get_reasons().is_empty());
+    }
 }
@@ -207,6 +207,11 @@ where
         currency_pair_to_symbol_converter,
         Some(event_recorder.clone()),
     );
+    if let Some(log_balance_changes) = settings.core.log_balance_changes {
+        balance_manager
+            .lock()
+            .set_log_balance_changes(log_balance_changes);
+    }
 
     BalanceManager::update_balances_for_exchanges(
         balance_manager.clone(),
@@ -20,7 +20,7 @@ use anyhow::Result;
 use dashmap::DashMap;
 use futures::future::join_all;
 use futures::FutureExt;
-use mmb_domain::events::{ExchangeEvent, ExchangeEvents};
+use mmb_domain::events::{ExchangeEvent, ExchangeEvents, ExchangeEventsReceiver, LagPolicy};
 use mmb_domain::market::ExchangeAccountId;
 use mmb_utils::cancellation_token::CancellationToken;
 use mmb_utils::infrastructure::WithExpect;
@@ -188,6 +188,10 @@ impl EngineContext {
     pub fn get_events_channel(&self) -> broadcast::Receiver<ExchangeEvent> {
         self.exchange_events.get_events_channel()
     }
+
+    pub fn subscribe_events_with_policy(&self, policy: LagPolicy) -> ExchangeEventsReceiver {
+        self.exchange_events.subscribe_with_policy(policy)
+    }
 }
 
 async fn cancel_opened_orders(
@@ -5,6 +5,7 @@ use mmb_domain::order::snapshot::OrderSide;
 use mmb_utils::{
     cancellation_token::CancellationToken,
     infrastructure::{FutureOutcome, SpawnFutureFlags},
+    nothing_to_do,
 };
 use mockall_double::double;
 use parking_lot::Mutex;
@@ -25,16 +26,19 @@ pub fn close_position_if_needed(
 ) -> Option<JoinHandle<FutureOutcome>> {
     match balance_manager {
         Some(balance_manager) => {
-            if balance_manager
-                .lock()
-                .get_position(
-                    market_account_id.exchange_account_id,
-                    market_account_id.currency_pair,
-                    OrderSide::Buy,
-                )
-                .is_zero()
-            {
-                return None;
+            let position = balance_manager.lock().get_position(
+                market_account_id.exchange_account_id,
+                market_account_id.currency_pair,
+                OrderSide::Buy,
+            );
+
+            match position {
+                Ok(position) if position.is_zero() => return None,
+                Ok(_) => nothing_to_do(),
+                // unknown position may be non-zero, so it's safer to try closing
+                Err(error) => log::error!(
+                    "Failed to get position on {market_account_id:?}, closing positions anyway: {error:?}"
+                ),
             }
         }
         None => return None,
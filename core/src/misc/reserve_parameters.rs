@@ -1,6 +1,7 @@
 use mmb_domain::order::snapshot::{Amount, Price};
 use std::hash::Hash;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::balance::manager::balance_reservation::BalanceReservation;
 use crate::service_configuration::configuration_descriptor::ConfigurationDescriptor;
@@ -8,6 +9,14 @@ use mmb_domain::exchanges::symbol::Symbol;
 use mmb_domain::market::ExchangeAccountId;
 use mmb_domain::order::snapshot::OrderSide;
 
+/// Currency in which amount is passed to `ReserveParameters::new_with_amount_kind`
+#[derive(Clone, Copy, Hash, Debug, Default, Eq, PartialEq)]
+pub enum ReserveAmountKind {
+    #[default]
+    Base,
+    Quote,
+}
+
 #[derive(Clone, Hash, Debug, Eq, PartialEq)]
 pub struct ReserveParameters {
     pub(crate) price: Price,
@@ -16,6 +25,8 @@ pub struct ReserveParameters {
     pub(crate) symbol: Arc<Symbol>,
     pub(crate) exchange_account_id: ExchangeAccountId,
     pub(crate) configuration_descriptor: ConfigurationDescriptor,
+    /// Time to live of the reservation, see `BalanceReservation::expires_at`
+    pub(crate) ttl: Option<Duration>,
 }
 
 impl ReserveParameters {
@@ -34,9 +45,45 @@ impl ReserveParameters {
             order_side,
             price,
             amount,
+            ttl: None,
         }
     }
 
+    /// Reservation created by these parameters is swept after `ttl` if it wasn't approved for any order
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Create parameters with amount specified in base or quote currency, e.g. when sell size
+    /// is expressed in quote terms. Amount is translated into amount currency of symbol
+    /// at reservation price, so it isn't rounded to amount precision
+    pub fn new_with_amount_kind(
+        configuration_descriptor: ConfigurationDescriptor,
+        exchange_account_id: ExchangeAccountId,
+        symbol: Arc<Symbol>,
+        order_side: OrderSide,
+        price: Price,
+        amount: Amount,
+        amount_kind: ReserveAmountKind,
+    ) -> Self {
+        let amount_currency_code = match amount_kind {
+            ReserveAmountKind::Base => symbol.base_currency_code(),
+            ReserveAmountKind::Quote => symbol.quote_currency_code(),
+        };
+        let amount =
+            symbol.convert_amount_into_amount_currency_code(amount_currency_code, amount, price);
+
+        ReserveParameters::new(
+            configuration_descriptor,
+            exchange_account_id,
+            symbol,
+            order_side,
+            price,
+            amount,
+        )
+    }
+
     pub fn from_reservation(reservation: &BalanceReservation, amount: Amount) -> Self {
         ReserveParameters::new(
             reservation.configuration_descriptor,
@@ -59,6 +106,7 @@ impl ReserveParameters {
             order_side: reservation.order_side,
             price,
             amount,
+            ttl: None,
         }
     }
 }
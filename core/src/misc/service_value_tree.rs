@@ -7,6 +7,7 @@ use crate::service_configuration::configuration_descriptor::{
 use mmb_domain::market::{CurrencyCode, CurrencyPair, ExchangeAccountId};
 use serde::Serialize;
 
+use anyhow::{Context, Result};
 use mmb_domain::order::snapshot::Amount;
 use mmb_utils::hashmap;
 use rust_decimal_macros::dec;
@@ -193,6 +194,22 @@ impl ServiceValueTree {
         );
     }
 
+    /// Returns removed value if it was set
+    pub fn remove_by_balance_request(
+        &mut self,
+        balance_request: &BalanceRequest,
+    ) -> Option<Amount> {
+        self.get_mut_by_currency_pair(
+            balance_request.configuration_descriptor.service_name,
+            balance_request
+                .configuration_descriptor
+                .service_configuration_key,
+            balance_request.exchange_account_id,
+            balance_request.currency_pair,
+        )?
+        .remove(&balance_request.currency_code)
+    }
+
     pub fn get_as_balances(&self) -> HashMap<BalanceRequest, Amount> {
         self.tree
             .iter()
@@ -227,17 +244,22 @@ impl ServiceValueTree {
             .collect()
     }
 
-    pub fn add(&mut self, input: &ServiceValueTree) {
+    pub fn add(&mut self, input: &ServiceValueTree) -> Result<()> {
         for (request, value) in input.get_as_balances() {
-            self.add_by_request(&request, value);
+            self.add_by_request(&request, value)?;
         }
+        Ok(())
     }
 
-    pub fn add_by_request(&mut self, request: &BalanceRequest, value: Amount) {
-        self.set_by_balance_request(
-            request,
-            self.get_by_balance_request(request).unwrap_or(dec!(0)) + value,
-        );
+    /// Returns an error without changing the tree if the sum overflows `Decimal`
+    pub fn add_by_request(&mut self, request: &BalanceRequest, value: Amount) -> Result<()> {
+        let current_value = self.get_by_balance_request(request).unwrap_or(dec!(0));
+        let new_value = current_value.checked_add(value).with_context(|| {
+            format!("Overflow on adding {value} to {current_value} for {request:?}")
+        })?;
+
+        self.set_by_balance_request(request, new_value);
+        Ok(())
     }
 }
 
@@ -248,6 +270,7 @@ mod test {
     use mmb_domain::market::{CurrencyCode, CurrencyPair, ExchangeAccountId};
 
     use mmb_utils::{hashmap, logger::init_logger};
+    use rust_decimal::Decimal;
     use rust_decimal_macros::dec;
 
     use mmb_domain::order::snapshot::Amount;
@@ -498,10 +521,35 @@ mod test {
             new_value,
         );
 
-        new_tree.add(&test_data.0);
+        new_tree.add(&test_data.0).expect("in test");
         assert_eq!(new_tree.get_as_balances(), test_data.1);
     }
 
+    #[test]
+    pub fn add_by_request_overflow() {
+        init_logger();
+        let mut service_value_tree = ServiceValueTree::default();
+
+        let request = BalanceRequest::new(
+            ConfigurationDescriptor::new("name".into(), "name".into()),
+            ExchangeAccountId::new("acc_0", 0),
+            CurrencyPair::from_codes("b_0".into(), "q_0".into()),
+            CurrencyCode::new("0"),
+        );
+
+        service_value_tree
+            .add_by_request(&request, Decimal::MAX)
+            .expect("in test");
+        assert!(service_value_tree
+            .add_by_request(&request, Decimal::MAX)
+            .is_err());
+
+        assert_eq!(
+            service_value_tree.get_by_balance_request(&request),
+            Some(Decimal::MAX)
+        );
+    }
+
     #[test]
     pub fn compare() {
         init_logger();
@@ -1,6 +1,20 @@
+use mmb_utils::DateTime;
 #[cfg(test)]
 use mockall::automock;
 
+/// Source of current time which can be replaced to control time, e.g. in tests and backtests
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime {
+        chrono::Utc::now()
+    }
+}
+
 /// If you'll use this mod in some tests, mocks object should be created.
 /// Automock doesn't support default implementation.
 /// NOTE: you need to avoid using mock objects in a parallel way https://docs.rs/mockall/0.10.2/mockall/#static-methods
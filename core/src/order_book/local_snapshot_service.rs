@@ -1,9 +1,66 @@
+use itertools::{EitherOrBoth, Itertools};
 use mmb_domain::market::{MarketAccountId, MarketId};
+use mmb_domain::order::snapshot::{Amount, Price, PriceByOrderSide, SortedOrderData};
 use mmb_domain::order_book::event;
 use mmb_domain::order_book::local_order_book_snapshot::{LocalOrderBookSnapshot, ResultAskBidFix};
 use mmb_utils::infrastructure::WithExpect;
+use mmb_utils::DateTime;
 use std::collections::HashMap;
 
+/// Change of a single price level of order book
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceLevelChange {
+    Added {
+        price: Price,
+        amount: Amount,
+    },
+    Removed {
+        price: Price,
+    },
+    Updated {
+        price: Price,
+        previous_amount: Amount,
+        amount: Amount,
+    },
+}
+
+/// Price levels changed by applying order book event, sorted by price ascending
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderBookDiff {
+    pub market_account_id: MarketAccountId,
+    pub asks: Vec<PriceLevelChange>,
+    pub bids: Vec<PriceLevelChange>,
+}
+
+impl OrderBookDiff {
+    pub fn is_empty(&self) -> bool {
+        self.asks.is_empty() && self.bids.is_empty()
+    }
+}
+
+fn diff_price_levels(
+    previous: &SortedOrderData,
+    current: &SortedOrderData,
+) -> Vec<PriceLevelChange> {
+    previous
+        .iter()
+        .merge_join_by(current.iter(), |(lhs, _), (rhs, _)| lhs.cmp(rhs))
+        .filter_map(|levels| match levels {
+            EitherOrBoth::Left((&price, _)) => Some(PriceLevelChange::Removed { price }),
+            EitherOrBoth::Right((&price, &amount)) => {
+                Some(PriceLevelChange::Added { price, amount })
+            }
+            EitherOrBoth::Both((&price, &previous_amount), (_, &amount)) => {
+                (previous_amount != amount).then_some(PriceLevelChange::Updated {
+                    price,
+                    previous_amount,
+                    amount,
+                })
+            }
+        })
+        .collect()
+}
+
 /// Produce and actualize current logical state of order book snapshot according to logical time of handled order book events
 pub struct LocalSnapshotsService {
     local_snapshots: HashMap<MarketId, LocalOrderBookSnapshot>,
@@ -24,10 +81,43 @@ impl LocalSnapshotsService {
             .with_expect(|| format!("Can't get snapshot for {:?}", market_id))
     }
 
+    /// Top prices with time of the last applied order book event, so data freshness can be checked
+    pub fn top_of_book(&self, market_id: MarketId) -> Option<(PriceByOrderSide, DateTime)> {
+        self.local_snapshots
+            .get(&market_id)
+            .map(|snapshot| (snapshot.get_top_prices(), snapshot.last_update_time))
+    }
+
     /// Create snapshot if it does not exist
     /// Update snapshot if suitable data arrive
     /// Returns `Some(MarketAccountId)` if snapshot update succeeded, otherwise `None`
     pub fn update(&mut self, event: &event::OrderBookEvent) -> Option<MarketAccountId> {
+        self.apply_event(event)
+    }
+
+    /// Same as `update`, but also returns price levels changed by the event
+    /// Returns `None` if snapshot wasn't updated
+    pub fn update_returning_diff(
+        &mut self,
+        event: &event::OrderBookEvent,
+    ) -> Option<OrderBookDiff> {
+        let (previous_asks, previous_bids) = self
+            .local_snapshots
+            .get(&event.market_account_id().market_id())
+            .map(|snapshot| (snapshot.asks.clone(), snapshot.bids.clone()))
+            .unwrap_or_default();
+
+        let market_account_id = self.apply_event(event)?;
+        let snapshot = self.get_snapshot_expected(market_account_id.market_id());
+
+        Some(OrderBookDiff {
+            market_account_id,
+            asks: diff_price_levels(&previous_asks, &snapshot.asks),
+            bids: diff_price_levels(&previous_bids, &snapshot.bids),
+        })
+    }
+
+    fn apply_event(&mut self, event: &event::OrderBookEvent) -> Option<MarketAccountId> {
         let market_account_id = event.market_account_id();
         let market_id = market_account_id.market_id();
 
@@ -71,7 +161,7 @@ impl Default for LocalSnapshotsService {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
+    use chrono::{TimeZone, Utc};
     use mmb_domain::market::{CurrencyPair, ExchangeAccountId, ExchangeId};
     use mmb_domain::order_book::order_book_data;
     use mmb_domain::order_book_data;
@@ -314,4 +404,128 @@ mod tests {
         assert_eq!(snapshot.asks, expected.asks);
         assert_eq!(snapshot.bids, expected.bids);
     }
+
+    #[test]
+    fn top_of_book_time_is_last_applied_event_time() {
+        let mut snapshot_service = LocalSnapshotsService::default();
+        let currency_pair = CurrencyPair::from_codes("base".into(), "quote".into());
+
+        let mut snapshot_event = create_order_book_event_for_tests(
+            "does_not_matter".into(),
+            currency_pair,
+            event::EventType::Snapshot,
+            order_book_data![
+                dec!(3.4) => dec!(1.2),
+                ;
+                dec!(2.9) => dec!(7.8),
+            ],
+        );
+        snapshot_event.creation_time = Utc
+            .with_ymd_and_hms(2021, 9, 20, 0, 0, 0)
+            .single()
+            .expect("in test");
+        let market_id = snapshot_service
+            .update(&snapshot_event)
+            .expect("in test")
+            .market_id();
+
+        let mut update_event = create_order_book_event_for_tests(
+            "does_not_matter".into(),
+            currency_pair,
+            event::EventType::Update,
+            order_book_data![
+                dec!(3.2) => dec!(0.5),
+                ;
+            ],
+        );
+        update_event.creation_time = Utc
+            .with_ymd_and_hms(2021, 9, 20, 0, 0, 5)
+            .single()
+            .expect("in test");
+        snapshot_service.update(&update_event).expect("in test");
+
+        let (top_prices, time) = snapshot_service.top_of_book(market_id).expect("in test");
+
+        assert_eq!(
+            top_prices,
+            PriceByOrderSide::new(Some(dec!(2.9)), Some(dec!(3.2)))
+        );
+        assert_eq!(time, update_event.creation_time);
+    }
+
+    #[test]
+    fn update_returning_diff_reports_changed_levels() {
+        let mut snapshot_service = LocalSnapshotsService::default();
+        let currency_pair = CurrencyPair::from_codes("base".into(), "quote".into());
+
+        let snapshot_event = create_order_book_event_for_tests(
+            "does_not_matter".into(),
+            currency_pair,
+            event::EventType::Snapshot,
+            order_book_data![
+                dec!(3.4) => dec!(1.2),
+                dec!(3.0) => dec!(4.2),
+                ;
+                dec!(2.9) => dec!(7.8),
+                dec!(1.0) => dec!(0.1),
+            ],
+        );
+        let diff = snapshot_service
+            .update_returning_diff(&snapshot_event)
+            .expect("in test");
+        assert_eq!(diff.asks.len(), 2);
+        assert_eq!(diff.bids.len(), 2);
+
+        let update_event = create_order_book_event_for_tests(
+            "does_not_matter".into(),
+            currency_pair,
+            event::EventType::Update,
+            order_book_data![
+                dec!(3.4) => dec!(0),
+                dec!(3.2) => dec!(0.5),
+                ;
+                dec!(2.9) => dec!(7.8),
+                dec!(1.0) => dec!(2.1),
+            ],
+        );
+        let diff = snapshot_service
+            .update_returning_diff(&update_event)
+            .expect("in test");
+
+        assert_eq!(
+            diff.asks,
+            vec![
+                PriceLevelChange::Added {
+                    price: dec!(3.2),
+                    amount: dec!(0.5),
+                },
+                PriceLevelChange::Removed { price: dec!(3.4) },
+            ]
+        );
+        assert_eq!(
+            diff.bids,
+            vec![PriceLevelChange::Updated {
+                price: dec!(1.0),
+                previous_amount: dec!(0.1),
+                amount: dec!(2.1),
+            }]
+        );
+    }
+
+    #[test]
+    fn update_returning_diff_without_snapshot() {
+        let mut snapshot_service = LocalSnapshotsService::default();
+
+        let update_event = create_order_book_event_for_tests(
+            "does_not_matter".into(),
+            CurrencyPair::from_codes("base".into(), "quote".into()),
+            event::EventType::Update,
+            order_book_data![
+                dec!(3.2) => dec!(0.5),
+                ;
+            ],
+        );
+
+        assert_eq!(snapshot_service.update_returning_diff(&update_event), None);
+    }
 }
@@ -1,7 +1,7 @@
 use mmb_domain::market::ExchangeAccountId;
 use mmb_domain::order::snapshot::ExchangeOrderId;
 use mmb_utils::infrastructure::WithExpect;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 use crate::exchanges::general::handlers::handle_order_filled::FillAmount;
 use crate::exchanges::general::handlers::handle_order_filled::FillEvent;
@@ -11,6 +11,8 @@ use super::buffered_fill::BufferedFill;
 #[derive(Default)]
 pub struct BufferedFillsManager {
     buffered_fills: HashMap<ExchangeOrderId, Vec<BufferedFill>>,
+    /// Fills received while fill processing is paused, in order of receiving
+    paused_fills: VecDeque<FillEvent>,
 }
 
 impl BufferedFillsManager {
@@ -80,4 +82,13 @@ impl BufferedFillsManager {
     pub fn remove_fills(&mut self, exchange_order_id: &ExchangeOrderId) {
         self.buffered_fills.remove(exchange_order_id);
     }
+
+    pub fn add_paused_fill(&mut self, fill_event: FillEvent) {
+        self.paused_fills.push_back(fill_event);
+    }
+
+    /// The earliest received fill among buffered while fill processing is paused
+    pub fn pop_paused_fill(&mut self) -> Option<FillEvent> {
+        self.paused_fills.pop_front()
+    }
 }
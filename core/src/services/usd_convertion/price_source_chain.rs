@@ -21,4 +21,26 @@ impl PriceSourceChain {
             rebase_price_steps,
         }
     }
+
+    /// Chain converting from `end_currency_code` to `start_currency_code` by the same markets
+    pub fn reversed(&self) -> Self {
+        let rebase_price_steps = self
+            .rebase_price_steps
+            .iter()
+            .rev()
+            .map(|step| {
+                RebasePriceStep::new(
+                    step.exchange_id,
+                    step.symbol.clone(),
+                    step.direction.opposite(),
+                )
+            })
+            .collect();
+
+        Self::new(
+            self.end_currency_code,
+            self.start_currency_code,
+            rebase_price_steps,
+        )
+    }
 }
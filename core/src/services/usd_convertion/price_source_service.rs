@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     fmt,
     sync::Arc,
 };
@@ -7,21 +7,28 @@ use std::{
 #[double]
 use crate::exchanges::general::currency_pair_to_symbol_converter::CurrencyPairToSymbolConverter;
 
+#[double]
+use crate::misc::time::time_manager;
+
 use crate::{
     infrastructure::spawn_future,
     order_book::local_snapshot_service::LocalSnapshotsService,
-    services::usd_convertion::{prices_calculator, rebase_price_step::RebaseDirection},
-    settings::CurrencyPriceSourceSettings,
+    services::usd_convertion::{
+        prices_calculator::{self, PriceSelection},
+        rebase_price_step::RebaseDirection,
+    },
+    settings::{CurrencyPriceSourceSettings, PriceSourceStalenessSettings},
 };
 
 use anyhow::{bail, Context, Result};
+use chrono::Duration;
 use itertools::Itertools;
 use mmb_domain::events::ExchangeEvent;
 use mmb_domain::exchanges::symbol::Symbol;
 use mmb_domain::market::{CurrencyCode, ExchangeId, MarketId};
 use mmb_domain::order::snapshot::Amount;
 use mmb_domain::order::snapshot::PriceByOrderSide;
-use mmb_utils::infrastructure::{SpawnFutureFlags, WithExpect};
+use mmb_utils::infrastructure::SpawnFutureFlags;
 use mmb_utils::{cancellation_token::CancellationToken, send_expected::SendExpected, DateTime};
 use mockall_double::double;
 use parking_lot::Mutex;
@@ -29,26 +36,79 @@ use rust_decimal::Decimal;
 use tokio::sync::{broadcast, mpsc, oneshot};
 
 use super::{
-    convert_currency_direction::ConvertCurrencyDirection, price_source_chain::PriceSourceChain,
-    price_sources_loader::PriceSourcesLoader, prices_sources_saver::PriceSourcesSaver,
+    convert_currency_direction::ConvertCurrencyDirection,
+    price_source_chain::PriceSourceChain,
+    price_sources_loader::{PriceSourcesLoader, SavedPriceSource},
+    prices_sources_saver::PriceSourcesSaver,
     rebase_price_step::RebasePriceStep,
 };
 
+/// Age after which cached top prices aren't used by `PriceSourceService::try_convert_amount_sync`
+const MAX_CACHED_PRICES_AGE_SECS: i64 = 60;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedPrices {
+    pub prices: PriceByOrderSide,
+    pub update_time: DateTime,
+}
+
+/// Latest top prices of price source markets shared between event loop and service
+pub type CachedPricesByMarketId = Arc<Mutex<HashMap<MarketId, CachedPrices>>>;
+
+/// Tracks time of the last order book update of price source markets,
+/// so conversions aren't made by order books which stopped receiving updates
+#[derive(Default)]
+struct MarketsStaleness {
+    last_update_by_market_id: HashMap<MarketId, DateTime>,
+    stale_market_ids: HashSet<MarketId>,
+}
+
+impl MarketsStaleness {
+    fn market_updated(&mut self, market_id: MarketId, now: DateTime) {
+        self.last_update_by_market_id.insert(market_id, now);
+        if self.stale_market_ids.remove(&market_id) {
+            log::info!("Price source market {market_id} is updated again after being stale");
+        }
+    }
+
+    fn check(&mut self, now: DateTime, stale_after: Duration) {
+        for (market_id, last_update) in &self.last_update_by_market_id {
+            if now - *last_update > stale_after && self.stale_market_ids.insert(*market_id) {
+                log::warn!(
+                    "Price source market {market_id} became stale: there were no order book updates since {last_update}"
+                );
+            }
+        }
+    }
+
+    fn is_chain_stale(&self, chain: &PriceSourceChain) -> bool {
+        chain
+            .rebase_price_steps
+            .iter()
+            .any(|step| self.stale_market_ids.contains(&step.market_id()))
+    }
+}
+
 pub struct PriceSourceEventLoop {
     price_sources_saver: PriceSourcesSaver,
     all_market_ids: HashSet<MarketId>,
     local_snapshot_service: LocalSnapshotsService,
     price_cache: HashMap<MarketId, PriceByOrderSide>,
+    cached_prices: CachedPricesByMarketId,
+    staleness: MarketsStaleness,
+    staleness_settings: PriceSourceStalenessSettings,
     rx_core: broadcast::Receiver<ExchangeEvent>,
-    convert_currency_notification_receiver: mpsc::Receiver<ConvertAmount>,
+    convert_currency_notification_receiver: mpsc::Receiver<ConvertAmountMessage>,
 }
 
 impl PriceSourceEventLoop {
     pub async fn run(
         price_source_chains: Vec<PriceSourceChain>,
         price_sources_saver: PriceSourcesSaver,
+        cached_prices: CachedPricesByMarketId,
+        staleness_settings: PriceSourceStalenessSettings,
         rx_core: broadcast::Receiver<ExchangeEvent>,
-        convert_currency_notification_receiver: mpsc::Receiver<ConvertAmount>,
+        convert_currency_notification_receiver: mpsc::Receiver<ConvertAmountMessage>,
         cancellation_token: CancellationToken,
     ) {
         let run_action = async move {
@@ -57,6 +117,9 @@ impl PriceSourceEventLoop {
                 all_market_ids: Self::map_to_used_market_ids(price_source_chains),
                 local_snapshot_service: LocalSnapshotsService::default(),
                 price_cache: HashMap::new(),
+                cached_prices,
+                staleness: MarketsStaleness::default(),
+                staleness_settings,
                 rx_core,
                 convert_currency_notification_receiver,
             };
@@ -72,17 +135,16 @@ impl PriceSourceEventLoop {
     }
 
     async fn run_loop(&mut self, cancellation_token: CancellationToken) -> Result<()> {
+        let mut staleness_check_interval =
+            tokio::time::interval(self.staleness_settings.check_interval);
         loop {
             tokio::select! {
                 main_event_res = self.convert_currency_notification_receiver.recv() => {
-                   let convert_amount = main_event_res.context("Error during receiving event on convert_currency_notification_receiver")?;
-
-                    let result = prices_calculator::convert_amount(
-                        convert_amount.src_amount,
-                        &self.local_snapshot_service,
-                        &convert_amount.chain,
-                    );
-                    convert_amount.task_finished_sender.send_expected(result);
+                    let message = main_event_res.context("Error during receiving event on convert_currency_notification_receiver")?;
+                    Self::handle_convert_message(&self.local_snapshot_service, &self.staleness, message);
+                },
+                _ = staleness_check_interval.tick() => {
+                    self.staleness.check(time_manager::now(), self.staleness_settings.stale_after);
                 },
                 core_event_res = self.rx_core.recv() => {
                     let event = core_event_res.context("Error during receiving event on rx_core")?;
@@ -94,6 +156,7 @@ impl PriceSourceEventLoop {
                             );
                             if self.all_market_ids.contains(&market_id) {
                                 let _ = self.local_snapshot_service.update(&order_book_event);
+                                self.staleness.market_updated(market_id, time_manager::now());
                                 self.update_cache_and_save(market_id);
                             }
                         },
@@ -105,6 +168,47 @@ impl PriceSourceEventLoop {
         }
     }
 
+    fn handle_convert_message(
+        local_snapshot_service: &LocalSnapshotsService,
+        staleness: &MarketsStaleness,
+        message: ConvertAmountMessage,
+    ) {
+        let convert = |src_amount, chain: &PriceSourceChain, price_selection| match staleness
+            .is_chain_stale(chain)
+        {
+            true => None,
+            false => prices_calculator::convert_amount(
+                src_amount,
+                local_snapshot_service,
+                chain,
+                price_selection,
+            ),
+        };
+
+        match message {
+            ConvertAmountMessage::ConvertAmount(convert_amount) => {
+                let result = convert(
+                    convert_amount.src_amount,
+                    &convert_amount.chain,
+                    convert_amount.price_selection,
+                );
+                convert_amount.task_finished_sender.send_expected(result);
+            }
+            ConvertAmountMessage::ConvertAmountBatch(batch) => {
+                // all amounts are converted against the same state of snapshots
+                let results = batch
+                    .requests
+                    .iter()
+                    .map(|request| {
+                        let (chain, src_amount) = request.as_ref()?;
+                        convert(*src_amount, chain, batch.price_selection)
+                    })
+                    .collect_vec();
+                batch.task_finished_sender.send_expected(results);
+            }
+        }
+    }
+
     fn try_update_cache(&mut self, market_id: MarketId, new_value: PriceByOrderSide) -> bool {
         if let Some(old_value) = self.price_cache.get_mut(&market_id) {
             return match old_value == &new_value {
@@ -124,6 +228,14 @@ impl PriceSourceEventLoop {
         let snapshot = self.local_snapshot_service.get_snapshot_expected(market_id);
 
         let price_by_order_side = snapshot.get_top_prices();
+        self.cached_prices.lock().insert(
+            market_id,
+            CachedPrices {
+                prices: price_by_order_side.clone(),
+                update_time: snapshot.last_update_time,
+            },
+        );
+
         if self.try_update_cache(market_id, price_by_order_side.clone()) {
             self.price_sources_saver
                 .save(market_id, price_by_order_side);
@@ -137,17 +249,19 @@ impl PriceSourceEventLoop {
                 price_source_chain
                     .rebase_price_steps
                     .into_iter()
-                    .map(|step| MarketId::new(step.exchange_id, step.symbol.currency_pair()))
+                    .map(|step| step.market_id())
             })
             .collect()
     }
 }
 
 pub struct PriceSourceService {
+    currency_pair_to_symbol_converter: Arc<CurrencyPairToSymbolConverter>,
     price_sources_loader: PriceSourcesLoader,
-    tx_main: mpsc::Sender<ConvertAmount>,
-    convert_currency_notification_receiver: Mutex<Option<mpsc::Receiver<ConvertAmount>>>,
+    tx_main: mpsc::Sender<ConvertAmountMessage>,
+    convert_currency_notification_receiver: Mutex<Option<mpsc::Receiver<ConvertAmountMessage>>>,
     price_source_chains: HashMap<ConvertCurrencyDirection, PriceSourceChain>,
+    cached_prices: CachedPricesByMarketId,
 }
 
 impl PriceSourceService {
@@ -155,33 +269,57 @@ impl PriceSourceService {
         currency_pair_to_symbol_converter: Arc<CurrencyPairToSymbolConverter>,
         price_source_settings: &[CurrencyPriceSourceSettings],
         price_sources_loader: PriceSourcesLoader,
-    ) -> Arc<Self> {
+    ) -> Result<Arc<Self>> {
         let price_source_chains = Self::prepare_price_source_chains(
             price_source_settings,
-            currency_pair_to_symbol_converter,
-        );
+            currency_pair_to_symbol_converter.clone(),
+        )?;
         let (tx_main, convert_currency_notification_receiver) = mpsc::channel(20_000);
 
-        Arc::new(Self {
+        Ok(Arc::new(Self {
+            currency_pair_to_symbol_converter,
             price_sources_loader,
             tx_main,
             convert_currency_notification_receiver: Mutex::new(Some(
                 convert_currency_notification_receiver,
             )),
-            price_source_chains: price_source_chains
-                .into_iter()
-                .map(|x| {
-                    (
-                        ConvertCurrencyDirection::new(x.start_currency_code, x.end_currency_code),
-                        x,
-                    )
-                })
-                .collect(),
-        })
+            price_source_chains: Self::with_reversed_chains(price_source_chains),
+            cached_prices: Default::default(),
+        }))
+    }
+
+    /// Index chains by direction and register reversed chain for every direction
+    /// whose reverse direction isn't configured explicitly
+    fn with_reversed_chains(
+        price_source_chains: Vec<PriceSourceChain>,
+    ) -> HashMap<ConvertCurrencyDirection, PriceSourceChain> {
+        let mut chains: HashMap<_, _> = price_source_chains
+            .into_iter()
+            .map(|x| {
+                (
+                    ConvertCurrencyDirection::new(x.start_currency_code, x.end_currency_code),
+                    x,
+                )
+            })
+            .collect();
+
+        let reversed_chains = chains.values().map(|x| x.reversed()).collect_vec();
+        for reversed_chain in reversed_chains {
+            chains
+                .entry(ConvertCurrencyDirection::new(
+                    reversed_chain.start_currency_code,
+                    reversed_chain.end_currency_code,
+                ))
+                .or_insert(reversed_chain);
+        }
+
+        chains
     }
+
     pub async fn start(
         self: Arc<Self>,
         price_sources_saver: PriceSourcesSaver,
+        staleness_settings: PriceSourceStalenessSettings,
         rx_core: broadcast::Receiver<ExchangeEvent>,
         cancellation_token: CancellationToken,
     ) {
@@ -191,113 +329,141 @@ impl PriceSourceService {
             .take()
             .expect("PriceSourceEventLoop::convert_currency_notification_receiver is none");
 
+        // exchanges use cached prices of the service for converting commission of fills
+        let exchanges = self
+            .currency_pair_to_symbol_converter
+            .exchanges_by_id()
+            .values()
+            .cloned()
+            .collect_vec();
+        for exchange in &exchanges {
+            exchange.set_price_source_service(Some(self.clone()));
+        }
+
         PriceSourceEventLoop::run(
             self.price_source_chains.values().cloned().collect_vec(),
             price_sources_saver,
+            self.cached_prices.clone(),
+            staleness_settings,
             rx_core,
             receiver,
             cancellation_token,
         )
         .await;
+
+        for exchange in &exchanges {
+            exchange.set_price_source_service(None);
+        }
     }
 
+    /// Build the shortest chain of rebase steps for each setting. Currency pairs of a setting
+    /// are treated as edges between currencies, so any connected set of pairs yields a chain
+    /// regardless of the order in which pairs are listed.
     pub fn prepare_price_source_chains(
         price_source_settings: &[CurrencyPriceSourceSettings],
         currency_pair_to_symbol_converter: Arc<CurrencyPairToSymbolConverter>,
-    ) -> Vec<PriceSourceChain> {
+    ) -> Result<Vec<PriceSourceChain>> {
         if price_source_settings.is_empty() {
-            panic!("price_source_settings shouldn't be empty");
+            bail!("price_source_settings shouldn't be empty");
         }
 
         price_source_settings
             .iter()
             .map(|setting| {
-                if setting.start_currency_code == setting.end_currency_code {
-                    return PriceSourceChain::new(
-                        setting.start_currency_code,
-                        setting.end_currency_code,
-                        Vec::<RebasePriceStep>::new(),
-                    );
-                }
+                Self::prepare_price_source_chain(setting, &currency_pair_to_symbol_converter)
+            })
+            .collect()
+    }
 
-                let mut symbol_by_currency_code = HashMap::new();
-                for pair in &setting.exchange_id_currency_pair_settings {
-                    let symbol = currency_pair_to_symbol_converter
-                        .get_symbol(pair.exchange_account_id, pair.currency_pair);
-                    Self::add_symbol_to_hashmap(
-                        symbol.quote_currency_code(),
-                        pair.exchange_account_id.exchange_id,
-                        symbol.clone(),
-                        &mut symbol_by_currency_code,
-                    );
-                    Self::add_symbol_to_hashmap(
-                        symbol.base_currency_code(),
-                        pair.exchange_account_id.exchange_id,
-                        symbol.clone(),
-                        &mut symbol_by_currency_code,
-                    );
-                }
+    fn prepare_price_source_chain(
+        setting: &CurrencyPriceSourceSettings,
+        currency_pair_to_symbol_converter: &CurrencyPairToSymbolConverter,
+    ) -> Result<PriceSourceChain> {
+        if setting.start_currency_code == setting.end_currency_code {
+            return Ok(PriceSourceChain::new(
+                setting.start_currency_code,
+                setting.end_currency_code,
+                Vec::<RebasePriceStep>::new(),
+            ));
+        }
 
-                let mut rebase_price_steps = Vec::new();
-                let mut current_currency_code = setting.start_currency_code;
-
-                for _ in 0..setting.exchange_id_currency_pair_settings.len() {
-                    let list = symbol_by_currency_code
-                        .get(&current_currency_code)
-                        .with_expect(||
-                            Self::format_panic_message(
-                                setting,
-                                format_args!(
-                                    "Can't find currency pair for currency {}",
-                                    current_currency_code
-                                ),
-                            ),
-                        );
+        let mut steps_by_currency_code = HashMap::new();
+        for pair in &setting.exchange_id_currency_pair_settings {
+            let symbol = currency_pair_to_symbol_converter
+                .get_symbol(pair.exchange_account_id, pair.currency_pair);
+            Self::add_symbol_to_hashmap(
+                symbol.base_currency_code(),
+                pair.exchange_account_id.exchange_id,
+                symbol.clone(),
+                &mut steps_by_currency_code,
+            );
+            Self::add_symbol_to_hashmap(
+                symbol.quote_currency_code(),
+                pair.exchange_account_id.exchange_id,
+                symbol.clone(),
+                &mut steps_by_currency_code,
+            );
+        }
 
-                    if list.len() > 1 {
-                        panic!("{}", Self::format_panic_message(
-                            setting,
-                            format_args! { "There are more than 1 symbol in the list for currency {}",
-                            current_currency_code}
-                        ));
-                    }
+        // BFS over currencies, so the first time we reach end currency we have the shortest chain
+        let mut incoming_step_by_currency_code = HashMap::new();
+        let mut visited_currency_codes = HashSet::from([setting.start_currency_code]);
+        let mut queue = VecDeque::from([setting.start_currency_code]);
+        while let Some(current_currency_code) = queue.pop_front() {
+            if current_currency_code == setting.end_currency_code {
+                break;
+            }
 
-                    let step = list.first().expect("List is empty");
+            let steps = match steps_by_currency_code.get(&current_currency_code) {
+                Some(steps) => steps,
+                None => continue,
+            };
 
-                    rebase_price_steps.push(step.clone());
+            for step in steps {
+                let next_currency_code = step.target_currency_code();
+                if visited_currency_codes.insert(next_currency_code) {
+                    incoming_step_by_currency_code.insert(next_currency_code, step.clone());
+                    queue.push_back(next_currency_code);
+                }
+            }
+        }
 
-                    current_currency_code = match step.direction {
-                        RebaseDirection::ToQuote => step.symbol.quote_currency_code,
-                        RebaseDirection::ToBase => step.symbol.base_currency_code,
-                    };
+        if !visited_currency_codes.contains(&setting.end_currency_code) {
+            bail!(Self::format_error_message(
+                setting,
+                format_args!(
+                    "Currency {} is not reachable from {} by specified currency pairs",
+                    setting.end_currency_code, setting.start_currency_code
+                ),
+            ));
+        }
 
-                    if current_currency_code == setting.end_currency_code {
-                        break;
-                    }
-                    let step_symbol = step.symbol.clone();
-                    symbol_by_currency_code
-                        .get_mut(&current_currency_code)
-                        .with_expect(||
-                            Self::format_panic_message(
-                                setting,
-                                format_args!(
-                                    "Can't find currency pair for currency {}",
-                                    current_currency_code
-                                ),
-                            ),
-                        )
-                        .retain(|x| x.symbol != step_symbol);
-                }
-                PriceSourceChain::new(
-                    setting.start_currency_code,
-                    setting.end_currency_code,
-                    rebase_price_steps,
-                )
-            })
-            .collect_vec()
+        let mut rebase_price_steps = Vec::new();
+        let mut current_currency_code = setting.end_currency_code;
+        while current_currency_code != setting.start_currency_code {
+            let step = incoming_step_by_currency_code
+                .remove(&current_currency_code)
+                .with_context(|| {
+                    Self::format_error_message(
+                        setting,
+                        format_args!(
+                            "Can't find currency pair for currency {current_currency_code}"
+                        ),
+                    )
+                })?;
+            current_currency_code = step.source_currency_code();
+            rebase_price_steps.push(step);
+        }
+        rebase_price_steps.reverse();
+
+        Ok(PriceSourceChain::new(
+            setting.start_currency_code,
+            setting.end_currency_code,
+            rebase_price_steps,
+        ))
     }
 
-    fn format_panic_message(
+    fn format_error_message(
         setting: &CurrencyPriceSourceSettings,
         reason: fmt::Arguments,
     ) -> String {
@@ -320,14 +486,55 @@ impl PriceSourceService {
         list.push(RebasePriceStep::new(exchange_id, symbol, direction));
     }
 
+    /// Markets whose order books are used for converting from 'from' currency to 'to' currency,
+    /// in the order of rebase steps. Return None if there is no price source chain for the direction
+    pub fn market_ids_for(&self, from: CurrencyCode, to: CurrencyCode) -> Option<Vec<MarketId>> {
+        self.price_source_chains
+            .get(&ConvertCurrencyDirection::new(from, to))
+            .map(|chain| {
+                chain
+                    .rebase_price_steps
+                    .iter()
+                    .map(|step| step.market_id())
+                    .collect_vec()
+            })
+    }
+
+    /// All conversion directions which have a price source chain, e.g. for checking on startup
+    /// that every used currency can be converted
+    pub fn available_conversions(&self) -> Vec<ConvertCurrencyDirection> {
+        self.price_source_chains.keys().cloned().collect_vec()
+    }
+
     /// Convert amount from 'from' currency position to 'to' currency by current price
-    /// Return converted amount or None if can't calculate price for converting and Err if something bad was happened
+    /// Return converted amount or None if can't calculate price for converting and Err if something bad was happened.
+    /// None is also returned while any order book of the chain has an empty side (e.g. right after subscription)
     pub async fn convert_amount(
         &self,
         from: CurrencyCode,
         to: CurrencyCode,
         src_amount: Amount,
         cancellation_token: CancellationToken,
+    ) -> Result<Option<Amount>> {
+        self.convert_amount_with_price_selection(
+            from,
+            to,
+            src_amount,
+            PriceSelection::default(),
+            cancellation_token,
+        )
+        .await
+    }
+
+    /// Same as `convert_amount`, but uses prices of order books selected by `price_selection`,
+    /// e.g. mid price for mark-to-market or the worse side for conservative valuation
+    pub async fn convert_amount_with_price_selection(
+        &self,
+        from: CurrencyCode,
+        to: CurrencyCode,
+        src_amount: Amount,
+        price_selection: PriceSelection,
+        cancellation_token: CancellationToken,
     ) -> Result<Option<Amount>> {
         let convert_currency_direction = ConvertCurrencyDirection::new(from, to);
 
@@ -342,7 +549,12 @@ impl PriceSourceService {
         let (tx_result, rx_result) = oneshot::channel();
         if let Err(error) = self
             .tx_main
-            .send(ConvertAmount::new(chain.clone(), src_amount, tx_result))
+            .send(ConvertAmountMessage::ConvertAmount(ConvertAmount::new(
+                chain.clone(),
+                src_amount,
+                price_selection,
+                tx_result,
+            )))
             .await
         {
             let message = format!(
@@ -361,42 +573,190 @@ impl PriceSourceService {
         }
     }
 
+    /// Best-effort synchronous conversion by top prices cached by the event loop, e.g. for using under a lock.
+    /// Never waits for the event loop, so returns None if there is no price source chain for the direction
+    /// or prices of any market of the chain weren't cached during last `MAX_CACHED_PRICES_AGE_SECS` seconds
+    pub fn try_convert_amount_sync(
+        &self,
+        from: CurrencyCode,
+        to: CurrencyCode,
+        src_amount: Amount,
+    ) -> Option<Amount> {
+        let chain = self
+            .price_source_chains
+            .get(&ConvertCurrencyDirection::new(from, to))?;
+
+        let min_update_time = time_manager::now() - Duration::seconds(MAX_CACHED_PRICES_AGE_SECS);
+        let cached_prices = self.cached_prices.lock();
+        prices_calculator::convert_amount_by_top_prices(src_amount, chain, |market_id| {
+            cached_prices
+                .get(&market_id)
+                .filter(|cached| cached.update_time >= min_update_time)
+                .map(|cached| cached.prices.clone())
+        })
+    }
+
+    /// Convert many amounts with one round-trip to the event loop.
+    /// All amounts are converted by the same prices, results are in the order of `requests`.
+    /// Result item is None if there is no price source chain for the direction or
+    /// there are no prices for converting
+    pub async fn convert_amount_batch(
+        &self,
+        requests: Vec<(CurrencyCode, CurrencyCode, Amount)>,
+        cancellation_token: CancellationToken,
+    ) -> Vec<Option<Amount>> {
+        self.convert_amount_batch_with_price_selection(
+            requests,
+            PriceSelection::default(),
+            cancellation_token,
+        )
+        .await
+    }
+
+    /// Same as `convert_amount_batch`, but uses prices of order books selected by `price_selection`
+    pub async fn convert_amount_batch_with_price_selection(
+        &self,
+        requests: Vec<(CurrencyCode, CurrencyCode, Amount)>,
+        price_selection: PriceSelection,
+        cancellation_token: CancellationToken,
+    ) -> Vec<Option<Amount>> {
+        let requests_count = requests.len();
+        let requests = requests
+            .into_iter()
+            .map(|(from, to, src_amount)| {
+                let convert_currency_direction = ConvertCurrencyDirection::new(from, to);
+                match self.price_source_chains.get(&convert_currency_direction) {
+                    Some(chain) => Some((chain.clone(), src_amount)),
+                    None => {
+                        log::warn!(
+                            "Failed to get price_sources_chain for {:?}",
+                            convert_currency_direction
+                        );
+                        None
+                    }
+                }
+            })
+            .collect_vec();
+
+        let (tx_result, rx_result) = oneshot::channel();
+        if let Err(error) = self
+            .tx_main
+            .send(ConvertAmountMessage::ConvertAmountBatch(
+                ConvertAmountBatch::new(requests, price_selection, tx_result),
+            ))
+            .await
+        {
+            let message = format!(
+                "PriceSourceService::convert_amount_batch(): Unable to send: {:?}. Channel is closed",
+                error
+            );
+            if !cancellation_token.is_cancellation_requested() {
+                panic!("{} but cancellation hasn't been requested", message);
+            }
+            log::warn!("{}.", message);
+        }
+
+        tokio::select! {
+            result = rx_result => result.unwrap_or_else(|_| {
+                log::warn!("Sender of result was dropped in PriceSourceService::convert_amount_batch()");
+                vec![None; requests_count]
+            }),
+            _ = cancellation_token.when_cancelled() => vec![None; requests_count],
+        }
+    }
+
+    /// Same as `convert_amount_batch`, but return Err if there is no price source chain for any of the directions
+    pub async fn convert_amounts(
+        &self,
+        conversions: Vec<(CurrencyCode, CurrencyCode, Amount)>,
+        cancellation_token: CancellationToken,
+    ) -> Result<Vec<Option<Amount>>> {
+        for (from, to, _) in &conversions {
+            let convert_currency_direction = ConvertCurrencyDirection::new(*from, *to);
+            if !self
+                .price_source_chains
+                .contains_key(&convert_currency_direction)
+            {
+                bail!(
+                    "Failed to get price_sources_chain for {:?}",
+                    convert_currency_direction
+                );
+            }
+        }
+
+        Ok(self
+            .convert_amount_batch(conversions, cancellation_token)
+            .await)
+    }
+
+    /// Convert amount from 'from' currency position to 'to' currency by prices saved at `time_in_past`.
+    /// Return Err if price sources can't be loaded and None if there are no prices for converting.
+    /// Saved prices older than `options.max_gap` from `time_in_past` are considered stale and aren't used
     pub async fn convert_amount_in_past(
         &self,
         from: CurrencyCode,
         to: CurrencyCode,
         src_amount: Amount,
         time_in_past: DateTime,
+        options: ConvertInPastOptions,
         cancellation_token: CancellationToken,
-    ) -> Option<Amount> {
+    ) -> Result<Option<Amount>> {
         let price_sources = self
             .price_sources_loader
             .load(time_in_past, cancellation_token.clone())
             .await
-            .with_expect(|| {
+            .with_context(|| {
                 format!(
                     "Failed to get price_sources for {} from database",
                     time_in_past
                 )
-            });
+            })?;
 
         let convert_currency_direction = ConvertCurrencyDirection::new(from, to);
 
         let prices_source_chain = self
             .price_source_chains
             .get(&convert_currency_direction)
-            .with_expect(|| {
+            .with_context(|| {
                 format!(
                     "Failed to get price_source_chain for {:?} from {:?}",
                     convert_currency_direction, self.price_source_chains
                 )
-            });
-        prices_calculator::convert_amount_in_past(
+            })?;
+
+        let price_cache =
+            Self::remove_stale_price_sources(price_sources, time_in_past, options.max_gap);
+        Ok(prices_calculator::convert_amount_in_past(
             src_amount,
-            &price_sources,
+            &price_cache,
             time_in_past,
             prices_source_chain,
-        )
+            options.price_selection,
+        ))
+    }
+
+    fn remove_stale_price_sources(
+        price_sources: HashMap<MarketId, SavedPriceSource>,
+        time_in_past: DateTime,
+        max_gap: Duration,
+    ) -> HashMap<MarketId, PriceByOrderSide> {
+        price_sources
+            .into_iter()
+            .filter(|(market_id, price_source)| {
+                let is_fresh = time_in_past - price_source.save_time <= max_gap;
+                if !is_fresh {
+                    log::warn!(
+                        "Price source {:?} saved at {} is older than {} from {}",
+                        market_id,
+                        price_source.save_time,
+                        max_gap,
+                        time_in_past
+                    );
+                }
+                is_fresh
+            })
+            .map(|(market_id, price_source)| (market_id, price_source.prices))
+            .collect()
     }
 }
 
@@ -404,6 +764,7 @@ impl PriceSourceService {
 pub struct ConvertAmount {
     pub chain: PriceSourceChain,
     pub src_amount: Amount,
+    pub price_selection: PriceSelection,
     pub task_finished_sender: oneshot::Sender<Option<Decimal>>,
 }
 
@@ -411,21 +772,76 @@ impl ConvertAmount {
     pub fn new(
         chain: PriceSourceChain,
         src_amount: Amount,
+        price_selection: PriceSelection,
         task_finished_sender: oneshot::Sender<Option<Decimal>>,
     ) -> Self {
         Self {
             chain,
             src_amount,
+            price_selection,
             task_finished_sender,
         }
     }
 }
 
+#[derive(Debug)]
+pub struct ConvertAmountBatch {
+    pub requests: Vec<Option<(PriceSourceChain, Amount)>>,
+    pub price_selection: PriceSelection,
+    pub task_finished_sender: oneshot::Sender<Vec<Option<Amount>>>,
+}
+
+impl ConvertAmountBatch {
+    pub fn new(
+        requests: Vec<Option<(PriceSourceChain, Amount)>>,
+        price_selection: PriceSelection,
+        task_finished_sender: oneshot::Sender<Vec<Option<Amount>>>,
+    ) -> Self {
+        Self {
+            requests,
+            price_selection,
+            task_finished_sender,
+        }
+    }
+}
+
+/// Options of `PriceSourceService::convert_amount_in_past`
+#[derive(Debug, Clone, Copy)]
+pub struct ConvertInPastOptions {
+    /// Saved prices older than `max_gap` from the conversion time are considered stale
+    pub max_gap: Duration,
+    pub price_selection: PriceSelection,
+}
+
+impl ConvertInPastOptions {
+    pub fn new(max_gap: Duration) -> Self {
+        Self {
+            max_gap,
+            price_selection: PriceSelection::default(),
+        }
+    }
+
+    pub fn with_price_selection(mut self, price_selection: PriceSelection) -> Self {
+        self.price_selection = price_selection;
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum ConvertAmountMessage {
+    ConvertAmount(ConvertAmount),
+    ConvertAmountBatch(ConvertAmountBatch),
+}
+
 #[cfg(test)]
 pub mod test {
+    use chrono::Utc;
     use mmb_domain::exchanges::symbol::Precision;
     use mmb_domain::market::CurrencyPair;
     use mmb_domain::market::ExchangeAccountId;
+    use mmb_domain::order_book::local_order_book_snapshot::LocalOrderBookSnapshot;
+    use mmb_domain::order_book_data;
+    use mmb_utils::hashmap;
     use rstest::rstest;
     use rust_decimal_macros::dec;
 
@@ -433,6 +849,7 @@ pub mod test {
         exchanges::general::test_helper::{
             get_test_exchange_by_currency_codes, get_test_exchange_with_symbol,
         },
+        misc::time,
         settings::ExchangeIdCurrencyPairSettings,
     };
 
@@ -456,6 +873,73 @@ pub mod test {
         pub fn exchange_account_id_3() -> ExchangeAccountId {
             ExchangeAccountId::new(PriceSourceServiceTestBase::exchange_id(), 2)
         }
+
+        /// Service converting `from` currency to `to` by order book `snapshot` of `currency_pair`.
+        /// Conversion requests are handled by spawned loop until the service is dropped
+        pub fn create_service_with_snapshot(
+            from: CurrencyCode,
+            to: CurrencyCode,
+            currency_pair: CurrencyPair,
+            snapshot: LocalOrderBookSnapshot,
+        ) -> Arc<PriceSourceService> {
+            let price_source_settings = vec![CurrencyPriceSourceSettings::new(
+                from,
+                to,
+                vec![ExchangeIdCurrencyPairSettings {
+                    exchange_account_id: PriceSourceServiceTestBase::exchange_account_id(),
+                    currency_pair,
+                }],
+            )];
+
+            let (mut converter, _locker) = CurrencyPairToSymbolConverter::init_mock();
+            converter
+                .expect_get_symbol()
+                .returning(move |_, currency_pair| {
+                    let codes = currency_pair.to_codes();
+                    get_test_exchange_by_currency_codes(
+                        false,
+                        codes.base.as_str(),
+                        codes.quote.as_str(),
+                    )
+                    .0
+                    .get_symbol(currency_pair)
+                    .expect("failed to get currency pair")
+                });
+
+            let price_source_service = PriceSourceService::new(
+                Arc::new(converter),
+                &price_source_settings,
+                PriceSourcesLoader::default(),
+            )
+            .expect("in test");
+
+            let market_id = MarketId::new(PriceSourceServiceTestBase::exchange_id(), currency_pair);
+            price_source_service.cached_prices.lock().insert(
+                market_id,
+                CachedPrices {
+                    prices: snapshot.get_top_prices(),
+                    update_time: snapshot.last_update_time,
+                },
+            );
+            let local_snapshot_service =
+                LocalSnapshotsService::new(hashmap![market_id => snapshot]);
+            let mut receiver = price_source_service
+                .convert_currency_notification_receiver
+                .lock()
+                .take()
+                .expect("in test");
+            tokio::spawn(async move {
+                while let Some(message) = receiver.recv().await {
+                    PriceSourceEventLoop::handle_convert_message(
+                        &local_snapshot_service,
+                        &MarketsStaleness::default(),
+                        message,
+                    );
+                }
+            });
+
+            price_source_service
+        }
     }
 
     fn create_symbol(base: CurrencyCode, quote: CurrencyCode) -> Arc<Symbol> {
@@ -488,7 +972,8 @@ pub mod test {
         let actual = PriceSourceService::prepare_price_source_chains(
             &price_source_settings,
             Arc::new(converter),
-        );
+        )
+        .expect("in test");
 
         // Assert
         let expected = PriceSourceChain::new(usdt, usdt, Vec::new());
@@ -514,7 +999,8 @@ pub mod test {
         let actual = PriceSourceService::prepare_price_source_chains(
             &price_source_settings,
             Arc::new(converter),
-        );
+        )
+        .expect("in test");
 
         // Assert
         let expected = PriceSourceChain::new(usdt, usdt, Vec::new());
@@ -561,7 +1047,8 @@ pub mod test {
         let actual = PriceSourceService::prepare_price_source_chains(
             &price_source_settings,
             Arc::new(converter),
-        );
+        )
+        .expect("in test");
 
         // Assert
         let expected = PriceSourceChain::new(
@@ -638,7 +1125,8 @@ pub mod test {
         let actual = PriceSourceService::prepare_price_source_chains(
             &price_source_settings,
             Arc::new(converter),
-        );
+        )
+        .expect("in test");
 
         // Assert
         let expected = PriceSourceChain::new(
@@ -724,7 +1212,8 @@ pub mod test {
         let actual = PriceSourceService::prepare_price_source_chains(
             &price_source_settings,
             Arc::new(converter),
-        );
+        )
+        .expect("in test");
 
         // Assert
         let expected = PriceSourceChain::new(
@@ -812,10 +1301,7 @@ pub mod test {
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    #[should_panic(
-        expected = "Can't build correct chain of currency pairs of price sources for karma/usdt"
-    )]
-    async fn throw_exception_when_less_currencies_then_needed() {
+    async fn error_when_less_currencies_then_needed() {
         let eos = "EOS".into();
         let btc = "BTC".into();
         let usdt = "USDT".into();
@@ -858,9 +1344,659 @@ pub mod test {
                 .expect("failed to get currency pair")
             });
 
-        let _ = PriceSourceService::prepare_price_source_chains(
+        let error = PriceSourceService::prepare_price_source_chains(
+            &price_source_settings,
+            Arc::new(converter),
+        )
+        .expect_err("in test");
+
+        assert!(error.to_string().starts_with(
+            "Can't build correct chain of currency pairs of price sources for karma/usdt"
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn when_intermediate_currency_in_several_pairs() {
+        let eos = "EOS".into();
+        let btc = "BTC".into();
+        let usdt = "USDT".into();
+        let karma = "KARMA".into();
+        let eth = "ETH".into();
+
+        let price_source_settings = vec![CurrencyPriceSourceSettings::new(
+            karma,
+            usdt,
+            vec![
+                ExchangeIdCurrencyPairSettings {
+                    exchange_account_id: PriceSourceServiceTestBase::exchange_account_id(),
+                    currency_pair: CurrencyPair::from_codes(karma, eos),
+                },
+                ExchangeIdCurrencyPairSettings {
+                    exchange_account_id: PriceSourceServiceTestBase::exchange_account_id(),
+                    currency_pair: CurrencyPair::from_codes(eos, eth),
+                },
+                ExchangeIdCurrencyPairSettings {
+                    exchange_account_id: PriceSourceServiceTestBase::exchange_account_id(),
+                    currency_pair: CurrencyPair::from_codes(btc, eos),
+                },
+                ExchangeIdCurrencyPairSettings {
+                    exchange_account_id: PriceSourceServiceTestBase::exchange_account_id(),
+                    currency_pair: CurrencyPair::from_codes(btc, usdt),
+                },
+            ],
+        )];
+
+        let karma_eos = create_symbol(karma, eos);
+        let eos_eth = create_symbol(eos, eth);
+        let btc_eos = create_symbol(btc, eos);
+        let btc_usdt = create_symbol(btc, usdt);
+
+        let symbols = [
+            karma_eos.clone(),
+            eos_eth,
+            btc_eos.clone(),
+            btc_usdt.clone(),
+        ];
+        let (mut converter, _locker) = CurrencyPairToSymbolConverter::init_mock();
+        converter
+            .expect_get_symbol()
+            .returning(move |_, currency_pair| {
+                symbols
+                    .iter()
+                    .find(|symbol| symbol.currency_pair() == currency_pair)
+                    .cloned()
+                    .expect("failed to get currency pair")
+            });
+
+        // Act
+        let actual = PriceSourceService::prepare_price_source_chains(
             &price_source_settings,
             Arc::new(converter),
+        )
+        .expect("in test");
+
+        // Assert
+        let expected = PriceSourceChain::new(
+            karma,
+            usdt,
+            vec![
+                RebasePriceStep::new(
+                    PriceSourceServiceTestBase::exchange_id(),
+                    karma_eos,
+                    RebaseDirection::ToQuote,
+                ),
+                RebasePriceStep::new(
+                    PriceSourceServiceTestBase::exchange_id(),
+                    btc_eos,
+                    RebaseDirection::ToBase,
+                ),
+                RebasePriceStep::new(
+                    PriceSourceServiceTestBase::exchange_id(),
+                    btc_usdt,
+                    RebaseDirection::ToQuote,
+                ),
+            ],
+        );
+
+        assert_eq!(actual.first().expect("in test"), &expected);
+    }
+
+    /// Service converting BTC to USDT by USDT/BTC order book with middle price 4 and USDT to itself.
+    /// Conversion requests should be handled by the returned `LocalSnapshotsService`
+    fn create_btc_to_usdt_service(
+        btc: CurrencyCode,
+        usdt: CurrencyCode,
+    ) -> (Arc<PriceSourceService>, LocalSnapshotsService) {
+        let currency_pair = CurrencyPair::from_codes(usdt, btc);
+
+        let price_source_settings = vec![
+            CurrencyPriceSourceSettings::new(
+                btc,
+                usdt,
+                vec![ExchangeIdCurrencyPairSettings {
+                    exchange_account_id: PriceSourceServiceTestBase::exchange_account_id(),
+                    currency_pair,
+                }],
+            ),
+            CurrencyPriceSourceSettings::new(usdt, usdt, Vec::new()),
+        ];
+
+        let (mut converter, _locker) = CurrencyPairToSymbolConverter::init_mock();
+        converter
+            .expect_get_symbol()
+            .returning(move |_, currency_pair| {
+                get_test_exchange_by_currency_codes(false, "USDT", "BTC")
+                    .0
+                    .get_symbol(currency_pair)
+                    .expect("failed to get currency pair")
+            });
+
+        let price_source_service = PriceSourceService::new(
+            Arc::new(converter),
+            &price_source_settings,
+            PriceSourcesLoader::default(),
+        )
+        .expect("in test");
+
+        let snapshot = order_book_data![
+            dec!(6) => dec!(1.2),
+            ;
+            dec!(2) => dec!(9),
+        ]
+        .to_orderbook_snapshot(Utc::now());
+        let market_id = MarketId::new(PriceSourceServiceTestBase::exchange_id(), currency_pair);
+        let local_snapshot_service = LocalSnapshotsService::new(hashmap![market_id => snapshot]);
+
+        (price_source_service, local_snapshot_service)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn convert_amount_batch_preserves_requests_order() {
+        let usdt = "USDT".into();
+        let btc = "BTC".into();
+        let (price_source_service, local_snapshot_service) = create_btc_to_usdt_service(btc, usdt);
+
+        let mut receiver = price_source_service
+            .convert_currency_notification_receiver
+            .lock()
+            .take()
+            .expect("in test");
+        let event_loop = tokio::spawn(async move {
+            let message = receiver.recv().await.expect("in test");
+            PriceSourceEventLoop::handle_convert_message(
+                &local_snapshot_service,
+                &MarketsStaleness::default(),
+                message,
+            );
+        });
+
+        // Act
+        let actual = price_source_service
+            .convert_amount_batch(
+                vec![
+                    (btc, usdt, dec!(8)),
+                    (usdt, usdt, dec!(5)),
+                    (btc, usdt, dec!(4)),
+                ],
+                CancellationToken::default(),
+            )
+            .await;
+
+        // Assert
+        event_loop.await.expect("in test");
+
+        // middle price of USDT/BTC is 4
+        assert_eq!(actual, vec![Some(dec!(2)), Some(dec!(5)), Some(dec!(1))]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn convert_amount_batch_by_selected_price() {
+        let usdt = "USDT".into();
+        let btc = "BTC".into();
+        let (price_source_service, local_snapshot_service) = create_btc_to_usdt_service(btc, usdt);
+
+        let mut receiver = price_source_service
+            .convert_currency_notification_receiver
+            .lock()
+            .take()
+            .expect("in test");
+        let event_loop = tokio::spawn(async move {
+            let message = receiver.recv().await.expect("in test");
+            PriceSourceEventLoop::handle_convert_message(
+                &local_snapshot_service,
+                &MarketsStaleness::default(),
+                message,
+            );
+        });
+
+        // Act
+        let actual = price_source_service
+            .convert_amount_batch_with_price_selection(
+                vec![(btc, usdt, dec!(12)), (usdt, usdt, dec!(5))],
+                PriceSelection::Bid,
+                CancellationToken::default(),
+            )
+            .await;
+
+        // Assert
+        event_loop.await.expect("in test");
+
+        // top bid of USDT/BTC is 2
+        assert_eq!(actual, vec![Some(dec!(6)), Some(dec!(5))]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn convert_amounts_matches_individual_conversions() {
+        let usdt = "USDT".into();
+        let btc = "BTC".into();
+        let (price_source_service, local_snapshot_service) = create_btc_to_usdt_service(btc, usdt);
+
+        let conversions = vec![
+            (btc, usdt, dec!(8)),
+            (usdt, usdt, dec!(5)),
+            (btc, usdt, dec!(4)),
+        ];
+
+        let mut receiver = price_source_service
+            .convert_currency_notification_receiver
+            .lock()
+            .take()
+            .expect("in test");
+        let messages_count = conversions.len() + 1;
+        let event_loop = tokio::spawn(async move {
+            for _ in 0..messages_count {
+                let message = receiver.recv().await.expect("in test");
+                PriceSourceEventLoop::handle_convert_message(
+                    &local_snapshot_service,
+                    &MarketsStaleness::default(),
+                    message,
+                );
+            }
+        });
+
+        // Act
+        let actual = price_source_service
+            .convert_amounts(conversions.clone(), CancellationToken::default())
+            .await
+            .expect("in test");
+
+        let mut expected = Vec::new();
+        for (from, to, amount) in conversions {
+            let converted = price_source_service
+                .convert_amount(from, to, amount, CancellationToken::default())
+                .await
+                .expect("in test");
+            expected.push(converted);
+        }
+
+        // Assert
+        event_loop.await.expect("in test");
+
+        // middle price of USDT/BTC is 4
+        assert_eq!(actual, vec![Some(dec!(2)), Some(dec!(5)), Some(dec!(1))]);
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn convert_amounts_fails_without_price_source_chain() {
+        let usdt = "USDT".into();
+        let btc = "BTC".into();
+        let eos = "EOS".into();
+        let (price_source_service, _) = create_btc_to_usdt_service(btc, usdt);
+
+        let result = price_source_service
+            .convert_amounts(
+                vec![(btc, usdt, dec!(8)), (usdt, eos, dec!(5))],
+                CancellationToken::default(),
+            )
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn market_ids_for_two_steps_chain() {
+        let eos = "EOS".into();
+        let btc = "BTC".into();
+        let usdt = "USDT".into();
+        let currency_pair_1 = CurrencyPair::from_codes(eos, btc);
+        let currency_pair_2 = CurrencyPair::from_codes(btc, usdt);
+
+        let price_source_settings = vec![CurrencyPriceSourceSettings::new(
+            eos,
+            usdt,
+            vec![
+                ExchangeIdCurrencyPairSettings {
+                    exchange_account_id: PriceSourceServiceTestBase::exchange_account_id(),
+                    currency_pair: currency_pair_1,
+                },
+                ExchangeIdCurrencyPairSettings {
+                    exchange_account_id: PriceSourceServiceTestBase::exchange_account_id_2(),
+                    currency_pair: currency_pair_2,
+                },
+            ],
+        )];
+
+        let symbol_1 = create_symbol(eos, btc);
+        let symbol_2 = create_symbol(btc, usdt);
+
+        let (mut converter, _locker) = CurrencyPairToSymbolConverter::init_mock();
+        converter
+            .expect_get_symbol()
+            .returning(move |exchange_account_id, currency_pair| {
+                if exchange_account_id == PriceSourceServiceTestBase::exchange_account_id() {
+                    get_test_exchange_with_symbol(symbol_1.clone())
+                } else {
+                    get_test_exchange_with_symbol(symbol_2.clone())
+                }
+                .0
+                .get_symbol(currency_pair)
+                .expect("failed to get currency pair")
+            });
+
+        let price_source_service = PriceSourceService::new(
+            Arc::new(converter),
+            &price_source_settings,
+            PriceSourcesLoader::default(),
+        )
+        .expect("in test");
+
+        // Act
+        let actual = price_source_service.market_ids_for(eos, usdt);
+
+        // Assert
+        let exchange_id = PriceSourceServiceTestBase::exchange_id();
+        assert_eq!(
+            actual,
+            Some(vec![
+                MarketId::new(exchange_id, currency_pair_1),
+                MarketId::new(exchange_id, currency_pair_2),
+            ])
+        );
+        assert_eq!(
+            price_source_service.market_ids_for(usdt, eos),
+            Some(vec![
+                MarketId::new(exchange_id, currency_pair_2),
+                MarketId::new(exchange_id, currency_pair_1),
+            ])
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn convert_amount_both_directions_by_forward_chain() {
+        let usdt = "USDT".into();
+        let btc = "BTC".into();
+        let currency_pair = CurrencyPair::from_codes(usdt, btc);
+
+        let snapshot = order_book_data![
+            dec!(6) => dec!(1.2),
+            ;
+            dec!(2) => dec!(9),
+        ]
+        .to_orderbook_snapshot(Utc::now());
+        let price_source_service = PriceSourceServiceTestBase::create_service_with_snapshot(
+            btc,
+            usdt,
+            currency_pair,
+            snapshot,
+        );
+
+        // middle price of USDT/BTC is 4
+        let forward = price_source_service
+            .convert_amount(btc, usdt, dec!(8), CancellationToken::default())
+            .await
+            .expect("in test");
+        assert_eq!(forward, Some(dec!(2)));
+
+        let backward = price_source_service
+            .convert_amount(usdt, btc, dec!(2), CancellationToken::default())
+            .await
+            .expect("in test");
+        assert_eq!(backward, Some(dec!(8)));
+    }
+
+    #[test]
+    fn explicit_reverse_chain_is_not_replaced() {
+        let usdt = "USDT".into();
+        let btc = "BTC".into();
+        let symbol = create_symbol(btc, usdt);
+        let exchange_id = PriceSourceServiceTestBase::exchange_id();
+
+        let forward = PriceSourceChain::new(
+            btc,
+            usdt,
+            vec![RebasePriceStep::new(
+                exchange_id,
+                symbol.clone(),
+                RebaseDirection::ToQuote,
+            )],
+        );
+        let explicit_reverse = PriceSourceChain::new(usdt, btc, Vec::new());
+
+        let chains = PriceSourceService::with_reversed_chains(vec![
+            forward.clone(),
+            explicit_reverse.clone(),
+        ]);
+
+        assert_eq!(chains.len(), 2);
+        assert_eq!(
+            chains.get(&ConvertCurrencyDirection::new(btc, usdt)),
+            Some(&forward)
+        );
+        assert_eq!(
+            chains.get(&ConvertCurrencyDirection::new(usdt, btc)),
+            Some(&explicit_reverse)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn available_conversions_lists_all_chains() {
+        let usdt = "USDT".into();
+        let btc = "BTC".into();
+        let currency_pair = CurrencyPair::from_codes(btc, usdt);
+
+        let price_source_settings = vec![
+            CurrencyPriceSourceSettings::new(
+                btc,
+                usdt,
+                vec![ExchangeIdCurrencyPairSettings {
+                    exchange_account_id: PriceSourceServiceTestBase::exchange_account_id(),
+                    currency_pair,
+                }],
+            ),
+            CurrencyPriceSourceSettings::new(usdt, usdt, Vec::new()),
+        ];
+
+        let symbol = create_symbol(btc, usdt);
+        let (mut converter, _locker) = CurrencyPairToSymbolConverter::init_mock();
+        converter
+            .expect_get_symbol()
+            .returning(move |_, currency_pair| {
+                get_test_exchange_with_symbol(symbol.clone())
+                    .0
+                    .get_symbol(currency_pair)
+                    .expect("failed to get currency pair")
+            });
+
+        let price_source_service = PriceSourceService::new(
+            Arc::new(converter),
+            &price_source_settings,
+            PriceSourcesLoader::default(),
+        )
+        .expect("in test");
+
+        let actual: HashSet<_> = price_source_service
+            .available_conversions()
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            actual,
+            HashSet::from([
+                ConvertCurrencyDirection::new(btc, usdt),
+                ConvertCurrencyDirection::new(usdt, btc),
+                ConvertCurrencyDirection::new(usdt, usdt),
+            ])
+        );
+    }
+
+    #[test]
+    fn remove_stale_price_sources_older_than_max_gap() {
+        let time_in_past = Utc::now();
+        let market_id_1 = MarketId::new(
+            PriceSourceServiceTestBase::exchange_id(),
+            CurrencyPair::from_codes("BTC".into(), "USDT".into()),
+        );
+        let market_id_2 = MarketId::new(
+            PriceSourceServiceTestBase::exchange_id(),
+            CurrencyPair::from_codes("EOS".into(), "USDT".into()),
+        );
+        let prices = PriceByOrderSide::new(Some(dec!(1)), Some(dec!(2)));
+        let price_sources = hashmap![
+            market_id_1 => SavedPriceSource {
+                save_time: time_in_past - Duration::minutes(1),
+                prices: prices.clone(),
+            },
+            market_id_2 => SavedPriceSource {
+                save_time: time_in_past - Duration::hours(1),
+                prices: prices.clone(),
+            }
+        ];
+
+        let actual = PriceSourceService::remove_stale_price_sources(
+            price_sources,
+            time_in_past,
+            Duration::minutes(5),
+        );
+
+        assert_eq!(actual, hashmap![market_id_1 => prices]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn try_convert_amount_sync_by_fresh_cached_prices() {
+        let seconds_offset = Arc::new(Mutex::new(0));
+        let (_time_mock, _time_locker) = time::tests::init_mock(seconds_offset);
+
+        let btc = "BTC".into();
+        let usdt = "USDT".into();
+        let currency_pair = CurrencyPair::from_codes(btc, usdt);
+
+        let price_source_settings = vec![CurrencyPriceSourceSettings::new(
+            btc,
+            usdt,
+            vec![ExchangeIdCurrencyPairSettings {
+                exchange_account_id: PriceSourceServiceTestBase::exchange_account_id(),
+                currency_pair,
+            }],
+        )];
+
+        let symbol = create_symbol(btc, usdt);
+        let (mut converter, _locker) = CurrencyPairToSymbolConverter::init_mock();
+        converter
+            .expect_get_symbol()
+            .returning(move |_, currency_pair| {
+                get_test_exchange_with_symbol(symbol.clone())
+                    .0
+                    .get_symbol(currency_pair)
+                    .expect("failed to get currency pair")
+            });
+
+        let price_source_service = PriceSourceService::new(
+            Arc::new(converter),
+            &price_source_settings,
+            PriceSourcesLoader::default(),
+        )
+        .expect("in test");
+
+        assert_eq!(
+            price_source_service.try_convert_amount_sync(btc, usdt, dec!(2)),
+            None
+        );
+
+        let market_id = MarketId::new(PriceSourceServiceTestBase::exchange_id(), currency_pair);
+        let update_cached_prices = |update_time| {
+            price_source_service.cached_prices.lock().insert(
+                market_id,
+                CachedPrices {
+                    prices: PriceByOrderSide::new(Some(dec!(1)), Some(dec!(3))),
+                    update_time,
+                },
+            )
+        };
+
+        update_cached_prices(time_manager::now());
+        assert_eq!(
+            price_source_service.try_convert_amount_sync(btc, usdt, dec!(2)),
+            Some(dec!(4))
+        );
+        assert_eq!(
+            price_source_service.try_convert_amount_sync(usdt, btc, dec!(2)),
+            Some(dec!(1))
+        );
+
+        update_cached_prices(
+            time_manager::now() - Duration::seconds(MAX_CACHED_PRICES_AGE_SECS + 1),
+        );
+        assert_eq!(
+            price_source_service.try_convert_amount_sync(btc, usdt, dec!(2)),
+            None
         );
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn convert_amount_returns_none_when_market_became_stale() {
+        let seconds_offset = Arc::new(Mutex::new(0));
+        let (_time_mock, _time_locker) = time::tests::init_mock(seconds_offset.clone());
+
+        let usdt = "USDT".into();
+        let btc = "BTC".into();
+        let currency_pair = CurrencyPair::from_codes(usdt, btc);
+
+        let price_source_settings = vec![CurrencyPriceSourceSettings::new(
+            btc,
+            usdt,
+            vec![ExchangeIdCurrencyPairSettings {
+                exchange_account_id: PriceSourceServiceTestBase::exchange_account_id(),
+                currency_pair,
+            }],
+        )];
+
+        let (mut converter, _locker) = CurrencyPairToSymbolConverter::init_mock();
+        converter
+            .expect_get_symbol()
+            .returning(move |_, currency_pair| {
+                get_test_exchange_by_currency_codes(false, "USDT", "BTC")
+                    .0
+                    .get_symbol(currency_pair)
+                    .expect("failed to get currency pair")
+            });
+
+        let chain = PriceSourceService::prepare_price_source_chains(
+            &price_source_settings,
+            Arc::new(converter),
+        )
+        .expect("in test")
+        .pop()
+        .expect("in test");
+
+        let snapshot = order_book_data![
+            dec!(6) => dec!(1.2),
+            ;
+            dec!(2) => dec!(9),
+        ]
+        .to_orderbook_snapshot(Utc::now());
+        let market_id = MarketId::new(PriceSourceServiceTestBase::exchange_id(), currency_pair);
+        let local_snapshot_service = LocalSnapshotsService::new(hashmap![market_id => snapshot]);
+
+        let convert = |staleness: &MarketsStaleness| {
+            let (tx_result, mut rx_result) = oneshot::channel();
+            PriceSourceEventLoop::handle_convert_message(
+                &local_snapshot_service,
+                staleness,
+                ConvertAmountMessage::ConvertAmount(ConvertAmount::new(
+                    chain.clone(),
+                    dec!(8),
+                    PriceSelection::Mid,
+                    tx_result,
+                )),
+            );
+            rx_result.try_recv().expect("in test")
+        };
+
+        let stale_after = Duration::seconds(30);
+        let mut staleness = MarketsStaleness::default();
+        staleness.market_updated(market_id, time_manager::now());
+
+        *seconds_offset.lock() = 20;
+        staleness.check(time_manager::now(), stale_after);
+        // middle price of USDT/BTC is 4
+        assert_eq!(convert(&staleness), Some(dec!(2)));
+
+        *seconds_offset.lock() = 31;
+        staleness.check(time_manager::now(), stale_after);
+        assert_eq!(convert(&staleness), None);
+
+        staleness.market_updated(market_id, time_manager::now());
+        assert_eq!(convert(&staleness), Some(dec!(2)));
+    }
 }
@@ -1,3 +1,4 @@
+use anyhow::Result;
 use mmb_domain::market::MarketId;
 use std::collections::HashMap;
 
@@ -5,6 +6,13 @@ use mmb_utils::{cancellation_token::CancellationToken, DateTime};
 
 use mmb_domain::order::snapshot::PriceByOrderSide;
 
+/// Last prices of a market saved not later than the requested time
+#[derive(Debug, Clone)]
+pub struct SavedPriceSource {
+    pub save_time: DateTime,
+    pub prices: PriceByOrderSide,
+}
+
 #[derive(Default)]
 pub struct PriceSourcesLoader {
     // TODO: fix when DatabaseManager will be added
@@ -23,7 +31,7 @@ impl PriceSourcesLoader {
         &self,
         _save_time: DateTime,
         _cancellation_token: CancellationToken,
-    ) -> Option<HashMap<MarketId, PriceByOrderSide>> {
+    ) -> Result<HashMap<MarketId, SavedPriceSource>> {
         //     const string sqlQuery =
         //         "SELECT a.* FROM public.\"PriceSources\" a " +
         //         "JOIN ( " +
@@ -41,6 +49,6 @@ impl PriceSourcesLoader {
         //             x => new PricesBySide(x.Ask, x.Bid),
         //             cancellationToken);
 
-        Some(HashMap::new())
+        Ok(HashMap::new())
     }
 }
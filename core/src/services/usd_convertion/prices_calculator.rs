@@ -13,13 +13,37 @@ use crate::{
     },
 };
 
+/// Which price of the order book top is used for converting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PriceSelection {
+    Bid,
+    Ask,
+    #[default]
+    Mid,
+    /// The worse side for the holder of converted amount: bid for selling base currency
+    /// and ask for buying it
+    Conservative,
+}
+
+impl PriceSelection {
+    pub fn select(&self, prices: &PriceByOrderSide, direction: &RebaseDirection) -> Option<Price> {
+        match (self, direction) {
+            (PriceSelection::Bid, _) => prices.top_bid,
+            (PriceSelection::Ask, _) => prices.top_ask,
+            (PriceSelection::Mid, _) => prices.mid_price(),
+            (PriceSelection::Conservative, RebaseDirection::ToQuote) => prices.top_bid,
+            (PriceSelection::Conservative, RebaseDirection::ToBase) => prices.top_ask,
+        }
+    }
+}
+
 #[cfg(test)]
 pub(crate) fn calculate(
     src_amount: Amount,
     price_source_chain: &PriceSourceChain,
     prices: &HashMap<MarketId, Price>,
 ) -> Price {
-    calculate_amount_for_chain(src_amount, price_source_chain, |market_id| {
+    calculate_amount_for_chain(src_amount, price_source_chain, |market_id, _| {
         prices.get(&market_id).cloned()
     })
     .expect("Invalid price cache")
@@ -28,13 +52,13 @@ pub(crate) fn calculate(
 fn calculate_amount_for_chain(
     src_amount: Amount,
     price_source_chain: &PriceSourceChain,
-    calculate_price: impl Fn(MarketId) -> Option<Price>,
+    calculate_price: impl Fn(MarketId, &RebaseDirection) -> Option<Price>,
 ) -> Option<Amount> {
     let mut rebase_price = dec!(1);
 
     for step in &price_source_chain.rebase_price_steps {
         let market_id = MarketId::new(step.exchange_id, step.symbol.currency_pair());
-        let calculated_price = (calculate_price)(market_id)?;
+        let calculated_price = (calculate_price)(market_id, &step.direction)?;
 
         match step.direction {
             RebaseDirection::ToQuote => rebase_price *= calculated_price,
@@ -44,15 +68,33 @@ fn calculate_amount_for_chain(
     Some(rebase_price * src_amount)
 }
 
+/// Convert amount by prices of current order books selected by `price_selection`.
+/// Return None if there is no snapshot for any step of the chain or a needed side of the snapshot is empty
+/// (e.g. right after subscription), so "no price yet" can't be mixed up with a zero amount
 pub(crate) fn convert_amount(
     src_amount: Amount,
     local_snapshot_service: &LocalSnapshotsService,
     price_source_chain: &PriceSourceChain,
+    price_selection: PriceSelection,
 ) -> Option<Amount> {
-    calculate_amount_for_chain(src_amount, price_source_chain, |market_id| {
-        local_snapshot_service
+    calculate_amount_for_chain(src_amount, price_source_chain, |market_id, direction| {
+        let top_prices = local_snapshot_service
             .get_snapshot(market_id)?
-            .calculate_middle_price(market_id)
+            .get_top_prices();
+        price_selection
+            .select(&top_prices, direction)
+            .filter(|price| !price.is_zero())
+    })
+}
+
+/// Convert amount by middle of top prices. Return None if top prices of any step of the chain are unknown
+pub(crate) fn convert_amount_by_top_prices(
+    src_amount: Amount,
+    price_source_chain: &PriceSourceChain,
+    get_top_prices: impl Fn(MarketId) -> Option<PriceByOrderSide>,
+) -> Option<Amount> {
+    calculate_amount_for_chain(src_amount, price_source_chain, |market_id, _| {
+        get_top_prices(market_id)?.mid_price()
     })
 }
 
@@ -61,20 +103,16 @@ pub fn convert_amount_in_past(
     price_cache: &HashMap<MarketId, PriceByOrderSide>,
     time_in_past: DateTime,
     price_source_chain: &PriceSourceChain,
+    price_selection: PriceSelection,
 ) -> Option<Amount> {
-    calculate_amount_for_chain(src_amount, price_source_chain, |market_id| {
-        let prices = match price_cache.get(&market_id) {
-            Some(prices) => prices,
+    calculate_amount_for_chain(src_amount, price_source_chain, |market_id, direction| {
+        match price_cache.get(&market_id) {
+            Some(prices) => price_selection.select(prices, direction),
             None => {
                 log::error!("Can't get price {:?} on time {}", market_id, time_in_past);
-                return None;
+                None
             }
-        };
-
-        let top_bid = prices.top_ask?;
-        let top_ask = prices.top_bid?;
-
-        Some((top_ask + top_bid) * dec!(0.5))
+        }
     })
 }
 
@@ -141,7 +179,8 @@ mod test {
         let price_source_chains = PriceSourceService::prepare_price_source_chains(
             &price_source_settings,
             Arc::new(converter),
-        );
+        )
+        .expect("in test");
 
         let price_source_chain = price_source_chains
             .into_iter()
@@ -172,8 +211,13 @@ mod test {
         let snapshot_service = LocalSnapshotsService::new(hashmap![market_id => snapshot]);
 
         let src_amount = dec!(10);
-        let price_now =
-            convert_amount(src_amount, &snapshot_service, &price_source_chain).expect("in test");
+        let price_now = convert_amount(
+            src_amount,
+            &snapshot_service,
+            &price_source_chain,
+            PriceSelection::Mid,
+        )
+        .expect("in test");
 
         assert_eq!(dec!(1) / (dec!(12) / dec!(2)) * src_amount, price_now);
     }
@@ -188,11 +232,55 @@ mod test {
         let snapshot_service = LocalSnapshotsService::new(hashmap![market_id => snapshot]);
 
         let src_amount = dec!(10);
-        let price_now = convert_amount(src_amount, &snapshot_service, &price_source_chain);
+        let price_now = convert_amount(
+            src_amount,
+            &snapshot_service,
+            &price_source_chain,
+            PriceSelection::Mid,
+        );
 
         assert!(price_now.is_none());
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn calculate_amount_now_by_price_selection() {
+        let (currency_pair, price_source_chain, _locker) = generate_one_step_setup();
+
+        let snapshot = order_book_data![
+            dec!(10) => dec!(1.2),
+            dec!(12) => dec!(4.3),
+            ;
+            dec!(1) => dec!(6),
+            dec!(2) => dec!(9),
+        ]
+        .to_orderbook_snapshot(Utc::now());
+
+        let market_id = MarketId::new(PriceSourceServiceTestBase::exchange_id(), currency_pair);
+
+        let snapshot_service = LocalSnapshotsService::new(hashmap![market_id => snapshot]);
+
+        let src_amount = dec!(10);
+        let convert = |price_selection| {
+            convert_amount(
+                src_amount,
+                &snapshot_service,
+                &price_source_chain,
+                price_selection,
+            )
+            .expect("in test")
+        };
+
+        // converting BTC to USDT is buying USDT/BTC, so dividing by price
+        let by_bid = convert(PriceSelection::Bid);
+        let by_ask = convert(PriceSelection::Ask);
+        let by_mid = convert(PriceSelection::Mid);
+        assert_eq!(by_bid, src_amount / dec!(2));
+        assert_eq!(by_ask, src_amount / dec!(10));
+        assert!(by_ask < by_mid && by_mid < by_bid);
+
+        assert_eq!(convert(PriceSelection::Conservative), by_ask);
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn calculate_amount_in_past_using_one_step_with_price() {
         let (currency_pair, price_source_chain, _locker) = generate_one_step_setup();
@@ -203,9 +291,14 @@ mod test {
         ];
 
         let src_amount = dec!(10);
-        let price_now =
-            convert_amount_in_past(src_amount, &price_cache, time_in_past, &price_source_chain)
-                .expect("in test");
+        let price_now = convert_amount_in_past(
+            src_amount,
+            &price_cache,
+            time_in_past,
+            &price_source_chain,
+            PriceSelection::Mid,
+        )
+        .expect("in test");
 
         assert_eq!(dec!(1) / (dec!(12) / dec!(2)) * src_amount, price_now);
     }
@@ -216,8 +309,13 @@ mod test {
         let time_in_past = Utc::now();
         let price_cache = HashMap::new();
         let src_amount = dec!(10);
-        let price_now =
-            convert_amount_in_past(src_amount, &price_cache, time_in_past, &price_source_chain);
+        let price_now = convert_amount_in_past(
+            src_amount,
+            &price_cache,
+            time_in_past,
+            &price_source_chain,
+            PriceSelection::Mid,
+        );
 
         assert!(price_now.is_none());
     }
@@ -298,7 +396,8 @@ mod test {
         let price_source_chains = PriceSourceService::prepare_price_source_chains(
             &price_source_settings,
             Arc::new(converter),
-        );
+        )
+        .expect("in test");
         let price_source_chain = price_source_chains
             .into_iter()
             .find(|chain| {
@@ -344,6 +443,45 @@ mod test {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn calculate_amount_now_using_two_step_when_one_side_of_book_is_empty() {
+        let (setup, _locker) = generate_two_step_setup();
+        let market_id_1 = MarketId::new(
+            PriceSourceServiceTestBase::exchange_id(),
+            setup.currency_pair_1,
+        );
+        let market_id_2 = MarketId::new(
+            PriceSourceServiceTestBase::exchange_id(),
+            setup.currency_pair_2,
+        );
+
+        let snapshot_1 = order_book_data![
+            dec!(10) => dec!(1.2),
+            ;
+            dec!(2) => dec!(9),
+        ]
+        .to_orderbook_snapshot(Utc::now());
+        let snapshot_2_without_bids = order_book_data![
+            dec!(7) => dec!(3),
+            ;
+        ]
+        .to_orderbook_snapshot(Utc::now());
+        let snapshot_service = LocalSnapshotsService::new(hashmap![
+            market_id_1 => snapshot_1,
+            market_id_2 => snapshot_2_without_bids
+        ]);
+
+        let src_amount = dec!(10);
+        let price_now = convert_amount(
+            src_amount,
+            &snapshot_service,
+            &setup.price_source_chain,
+            PriceSelection::Mid,
+        );
+
+        assert_eq!(price_now, None);
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     #[should_panic(expected = "Invalid price cache")]
     async fn calculate_amount_with_current_cached_prices_using_two_step_without_one_price() {
@@ -1,4 +1,4 @@
-use mmb_domain::market::ExchangeId;
+use mmb_domain::market::{CurrencyCode, ExchangeId, MarketId};
 use std::sync::Arc;
 
 use mmb_domain::exchanges::symbol::Symbol;
@@ -9,6 +9,15 @@ pub enum RebaseDirection {
     ToBase,
 }
 
+impl RebaseDirection {
+    pub fn opposite(&self) -> Self {
+        match self {
+            RebaseDirection::ToQuote => RebaseDirection::ToBase,
+            RebaseDirection::ToBase => RebaseDirection::ToQuote,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RebasePriceStep {
     pub exchange_id: ExchangeId,
@@ -24,4 +33,25 @@ impl RebasePriceStep {
             direction,
         }
     }
+
+    /// Currency which is converted by this step
+    pub fn source_currency_code(&self) -> CurrencyCode {
+        match self.direction {
+            RebaseDirection::ToQuote => self.symbol.base_currency_code(),
+            RebaseDirection::ToBase => self.symbol.quote_currency_code(),
+        }
+    }
+
+    /// Currency which is the result of conversion by this step
+    pub fn target_currency_code(&self) -> CurrencyCode {
+        match self.direction {
+            RebaseDirection::ToQuote => self.symbol.quote_currency_code(),
+            RebaseDirection::ToBase => self.symbol.base_currency_code(),
+        }
+    }
+
+    /// Market whose order book is used by this step
+    pub fn market_id(&self) -> MarketId {
+        MarketId::new(self.exchange_id, self.symbol.currency_pair())
+    }
 }
@@ -1,5 +1,6 @@
 use mmb_domain::market::{CurrencyCode, CurrencyPair, ExchangeAccountId};
-use mmb_domain::order::snapshot::Amount;
+use mmb_domain::order::snapshot::{Amount, OrderRole};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -22,6 +23,8 @@ pub struct AppSettings<StrategySettings: Clone> {
 pub struct CoreSettings {
     pub database: Option<DbSettings>,
     pub exchanges: Vec<ExchangeSettings>,
+    /// Log every change of virtual balances and reservations. Enabled if not specified
+    pub log_balance_changes: Option<bool>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
@@ -57,7 +60,51 @@ pub struct ExchangeSettings {
     pub is_reducing_market_data: Option<bool>,
     pub subscribe_to_market_data: bool,
     pub websocket_channels: Vec<String>,
+    /// Prefix of client order ids to distinguish bot orders from manual ones on exchange
+    pub client_order_id_prefix: Option<String>,
+    /// What to do with a fill which type is inconsistent with type of its order
+    #[serde(default)]
+    pub fill_type_mismatch_reaction: FillTypeMismatchReaction,
+    /// Role assumed for fills without commission, commission rate and known order role
+    /// (e.g. exchange settles commission separately). Such fills get zero commission.
+    /// If not specified these fills are treated as an error
+    pub commissionless_fill_role: Option<OrderRole>,
+    /// Rounding of commission converted to quote currency (e.g. commission paid in BNB)
+    #[serde(default)]
+    pub converted_commission_rounding: ConvertedCommissionRounding,
+    /// Amount residual which is treated as calculation error (e.g. when unreserving balance),
+    /// specified as multiple of amount tick. Default of symbol is used if not specified
+    pub amount_margin_error_in_ticks: Option<Decimal>,
     pub currency_pairs: Option<Vec<CurrencyPairSetting>>,
+    /// Kill switch for flapping websocket connection. Disabled if not specified
+    pub websocket_disconnects_limit: Option<WebsocketDisconnectsLimit>,
+}
+
+/// Reaction on a fill which type is inconsistent with type of its order,
+/// e.g. liquidation fill for `Limit` order
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum FillTypeMismatchReaction {
+    /// Log error and apply the fill
+    #[default]
+    Log,
+    /// Log error and skip the fill
+    Reject,
+}
+
+/// Rounding of commission converted to another currency by top of order book prices
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ConvertedCommissionRounding {
+    /// Keep full precision of conversion
+    #[default]
+    Disabled,
+    /// Round up to precision of the commission currency, so commission is never under-accounted
+    Up,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WebsocketDisconnectsLimit {
+    pub max_disconnects: usize,
+    pub period_secs: u64,
 }
 
 impl ExchangeSettings {
@@ -75,7 +122,13 @@ impl ExchangeSettings {
             is_margin_trading,
             request_trades: false,
             websocket_channels: vec![],
+            client_order_id_prefix: None,
+            fill_type_mismatch_reaction: FillTypeMismatchReaction::default(),
+            commissionless_fill_role: None,
+            converted_commission_rounding: ConvertedCommissionRounding::default(),
+            amount_margin_error_in_ticks: None,
             currency_pairs: None,
+            websocket_disconnects_limit: None,
             subscribe_to_market_data: true,
             is_reducing_market_data: None,
         }
@@ -91,7 +144,13 @@ impl Default for ExchangeSettings {
             is_margin_trading: false,
             request_trades: false,
             websocket_channels: vec![],
+            client_order_id_prefix: None,
+            fill_type_mismatch_reaction: FillTypeMismatchReaction::default(),
+            commissionless_fill_role: None,
+            converted_commission_rounding: ConvertedCommissionRounding::default(),
+            amount_margin_error_in_ticks: None,
             currency_pairs: None,
+            websocket_disconnects_limit: None,
             subscribe_to_market_data: true,
             is_reducing_market_data: None,
         }
@@ -119,6 +178,24 @@ impl CurrencyPriceSourceSettings {
     }
 }
 
+/// Settings of detecting price source markets which stopped receiving order book updates
+#[derive(Debug, Clone, Copy)]
+pub struct PriceSourceStalenessSettings {
+    /// Period of checking price source markets for staleness
+    pub check_interval: std::time::Duration,
+    /// Time without order book updates after which market is treated as stale
+    pub stale_after: chrono::Duration,
+}
+
+impl Default for PriceSourceStalenessSettings {
+    fn default() -> Self {
+        Self {
+            check_interval: std::time::Duration::from_secs(5),
+            stale_after: chrono::Duration::seconds(60),
+        }
+    }
+}
+
 pub struct ExchangeIdCurrencyPairSettings {
     pub exchange_account_id: ExchangeAccountId,
     pub currency_pair: CurrencyPair,
@@ -1,7 +1,10 @@
 use core::panic;
 use itertools::Itertools;
+use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering as AtomicOrdering};
+use std::sync::Arc;
 
 use mmb_database::impl_event;
 use mmb_utils::DateTime;
@@ -10,6 +13,7 @@ use rust_decimal_macros::dec;
 use serde::{de, Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
 
 use crate::market::{CurrencyCode, CurrencyPair, ExchangeAccountId};
 use crate::order::event::OrderEvent;
@@ -29,6 +33,8 @@ pub struct ExchangeBalance {
 pub struct ExchangeBalancesAndPositions {
     pub balances: Vec<ExchangeBalance>,
     pub positions: Option<Vec<DerivativePosition>>,
+    /// Time of the snapshot reported by exchange if it's available in the payload
+    pub event_time: Option<DateTime>,
 }
 
 impl Debug for ExchangeBalancesAndPositions {
@@ -41,19 +47,92 @@ impl Debug for ExchangeBalancesAndPositions {
         f.debug_struct("ExchangeBalancesAndPositions")
             .field("balances", &non_zero_balances)
             .field("positions", &self.positions)
+            .field("event_time", &self.event_time)
             .finish()
     }
 }
 
+/// Conversion of amounts between currencies for valuation, e.g. by current prices
+pub trait AmountConverter {
+    fn convert_amount(
+        &self,
+        from: CurrencyCode,
+        to: CurrencyCode,
+        amount: Amount,
+    ) -> Option<Amount>;
+}
+
+impl ExchangeBalancesAndPositions {
+    /// Total value of non-zero balances and derivative positions notionals in `currency_code`.
+    /// Notional of position is its amount in base currency of the position currency pair.
+    /// Returns `None` if some value can't be converted
+    pub fn net_balance_in(
+        &self,
+        currency_code: CurrencyCode,
+        converter: &dyn AmountConverter,
+    ) -> Option<Decimal> {
+        let balances = self
+            .balances
+            .iter()
+            .filter(|x| !x.balance.is_zero())
+            .map(|x| (x.currency_code, x.balance));
+        let positions = self
+            .positions
+            .iter()
+            .flatten()
+            .filter(|x| !x.position.is_zero())
+            .map(|x| (x.currency_pair.to_codes().base, x.position));
+
+        balances
+            .chain(positions)
+            .map(|(from, amount)| {
+                if from == currency_code {
+                    Some(amount)
+                } else {
+                    converter.convert_amount(from, currency_code, amount)
+                }
+            })
+            .sum()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BalanceUpdateEvent {
     pub exchange_account_id: ExchangeAccountId,
     pub balances_and_positions: ExchangeBalancesAndPositions,
+    pub event_time: DateTime,
+}
+
+/// Latest balance snapshot for each exchange account.
+/// Snapshots can arrive out of sequence (e.g. over websocket and REST fallback), so older ones are ignored
+#[derive(Debug, Clone, Default)]
+pub struct LatestBalanceUpdates {
+    by_exchange_account_id: HashMap<ExchangeAccountId, BalanceUpdateEvent>,
+}
+
+impl LatestBalanceUpdates {
+    /// Return true if `event` became the latest balance snapshot for its exchange account
+    pub fn apply(&mut self, event: BalanceUpdateEvent) -> bool {
+        match self.by_exchange_account_id.get(&event.exchange_account_id) {
+            Some(latest) if latest.event_time >= event.event_time => false,
+            _ => {
+                let _ = self
+                    .by_exchange_account_id
+                    .insert(event.exchange_account_id, event);
+                true
+            }
+        }
+    }
+
+    pub fn get(&self, exchange_account_id: ExchangeAccountId) -> Option<&BalanceUpdateEvent> {
+        self.by_exchange_account_id.get(&exchange_account_id)
+    }
 }
 
 pub const LIQUIDATION_PRICE_CURRENT_VERSION: u32 = 1;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "LiquidationPriceEventRecord")]
 #[non_exhaustive]
 pub struct LiquidationPriceEvent {
     pub version: u32,
@@ -86,7 +165,49 @@ impl LiquidationPriceEvent {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Eq)]
+/// Stored `LiquidationPriceEvent` of any version. Records of previous versions are upgraded on deserialization:
+/// * v0 records have no `version` and `entry_price` fields, `entry_price` is set to `liq_price`
+#[derive(Deserialize)]
+struct LiquidationPriceEventRecord {
+    #[serde(default)]
+    version: u32,
+    event_creation_time: DateTime,
+    exchange_account_id: ExchangeAccountId,
+    currency_pair: CurrencyPair,
+    liq_price: Price,
+    entry_price: Option<Price>,
+    side: OrderSide,
+}
+
+impl TryFrom<LiquidationPriceEventRecord> for LiquidationPriceEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(record: LiquidationPriceEventRecord) -> anyhow::Result<Self> {
+        let entry_price = match (record.version, record.entry_price) {
+            (_, Some(entry_price)) => entry_price,
+            (0, None) => record.liq_price,
+            (version, None) => {
+                anyhow::bail!(
+                    "entry_price is missing in LiquidationPriceEvent of version {version}"
+                )
+            }
+        };
+
+        Ok(LiquidationPriceEvent::new(
+            record.event_creation_time,
+            record.exchange_account_id,
+            record.currency_pair,
+            record.liq_price,
+            entry_price,
+            record.side,
+        ))
+    }
+}
+
+/// Trade ids of different formats are never equal, so trades from exchanges with numeric and string ids
+/// are treated as distinct. Mixed formats aren't really comparable, but total order is needed for sorting,
+/// so numbers are put before strings by order of variants
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum TradeId {
     Number(u64),
     String(Box<str>),
@@ -103,6 +224,17 @@ impl TradeId {
     fn make_string_variant(value: &Value) -> TradeId {
         TradeId::String(value.to_string().into_boxed_str())
     }
+
+    /// Check that trade id directly follows `other` in a numeric sequence of trade ids.
+    /// Return None if any of ids isn't a number
+    pub fn is_successor_of(&self, other: &TradeId) -> Option<bool> {
+        match (self, other) {
+            (TradeId::Number(this), TradeId::Number(other)) => {
+                Some(other.checked_add(1) == Some(*this))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl From<Value> for TradeId {
@@ -136,22 +268,6 @@ impl From<String> for TradeId {
     }
 }
 
-impl PartialEq for TradeId {
-    fn eq(&self, other: &TradeId) -> bool {
-        let panic_msg = "TradeId formats don't match";
-        match self {
-            TradeId::Number(this) => match other {
-                TradeId::Number(other) => this == other,
-                TradeId::String(_) => panic!("{panic_msg}"),
-            },
-            TradeId::String(this) => match other {
-                TradeId::Number(_) => panic!("{panic_msg}"),
-                TradeId::String(other) => this == other,
-            },
-        }
-    }
-}
-
 impl Display for TradeId {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
@@ -210,6 +326,21 @@ pub struct TradesEvent {
     pub receipt_time: DateTime,
 }
 
+impl TradesEvent {
+    /// Copy of the event without dust trades with quantity less than `min_quantity`
+    pub fn filtered(&self, min_quantity: Amount) -> TradesEvent {
+        TradesEvent {
+            trades: self
+                .trades
+                .iter()
+                .filter(|trade| trade.quantity >= min_quantity)
+                .cloned()
+                .collect(),
+            ..self.clone()
+        }
+    }
+}
+
 impl_event!(TradesEvent, "trades_events");
 
 #[derive(Debug, Clone)]
@@ -219,6 +350,7 @@ pub enum ExchangeEvent {
     BalanceUpdate(BalanceUpdateEvent),
     LiquidationPrice(LiquidationPriceEvent),
     Trades(TradesEvent),
+    Metrics(MetricsEvent),
 }
 
 pub struct ExchangeEvents {
@@ -233,14 +365,93 @@ impl ExchangeEvents {
     pub fn get_events_channel(&self) -> broadcast::Receiver<ExchangeEvent> {
         self.events_sender.subscribe()
     }
+
+    /// Subscribe to events with explicit reaction on lagging behind the channel
+    pub fn subscribe_with_policy(&self, policy: LagPolicy) -> ExchangeEventsReceiver {
+        ExchangeEventsReceiver {
+            receiver: self.events_sender.subscribe(),
+            policy,
+            dropped_events_count: 0,
+        }
+    }
+}
+
+/// Reaction of `ExchangeEventsReceiver` on missed events when receiver lags behind the channel.
+/// The oldest events are always overwritten by channel, policy only defines how it's reported
+#[derive(Debug, Clone)]
+pub enum LagPolicy {
+    /// Log dropped events and continue with the oldest available event
+    DropOldest,
+    /// Return `EventsReceiveError::Lagged` from `recv`
+    Error,
+    /// Add count of dropped events to shared metric and continue with the oldest available event
+    Count(Arc<AtomicU64>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventsReceiveError {
+    Lagged(u64),
+    Closed,
+}
+
+impl Display for EventsReceiveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EventsReceiveError::Lagged(count) => {
+                write!(f, "Events receiver lagged and missed {count} events")
+            }
+            EventsReceiveError::Closed => write!(f, "Events channel is closed"),
+        }
+    }
+}
+
+impl std::error::Error for EventsReceiveError {}
+
+pub struct ExchangeEventsReceiver {
+    receiver: broadcast::Receiver<ExchangeEvent>,
+    policy: LagPolicy,
+    dropped_events_count: u64,
+}
+
+impl ExchangeEventsReceiver {
+    pub async fn recv(&mut self) -> Result<ExchangeEvent, EventsReceiveError> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) => return Ok(event),
+                Err(RecvError::Closed) => return Err(EventsReceiveError::Closed),
+                Err(RecvError::Lagged(count)) => {
+                    self.dropped_events_count += count;
+                    match &self.policy {
+                        LagPolicy::DropOldest => {
+                            log::warn!("Events receiver lagged and dropped {count} events")
+                        }
+                        LagPolicy::Error => return Err(EventsReceiveError::Lagged(count)),
+                        LagPolicy::Count(metric) => {
+                            metric.fetch_add(count, AtomicOrdering::Relaxed);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Total count of events missed by this receiver
+    pub fn dropped_events_count(&self) -> u64 {
+        self.dropped_events_count
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Copy)]
 pub enum AllowedEventSourceType {
+    /// Events from any source are handled
     #[default]
     All,
+    /// Only events from fallback polling are handled (it's used for testing fallback)
     FallbackOnly,
+    /// Only events received via Rest or WebSocket are handled
     NonFallback,
+    /// Only events raised by bot itself via Rpc are handled (e.g. simulated fills)
+    RpcOnly,
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize, Hash)]
@@ -272,6 +483,47 @@ impl MetricsEvent {
             event_type: info.event_type,
         }
     }
+
+    /// Use offset between local and exchange server time from `source`
+    pub fn from_info(info: &MetricsEventInfoBase, source: &MetricsTimeSource) -> Self {
+        Self::new(info, source.local_time_offset())
+    }
+
+    pub fn latency(&self) -> MetricsTime {
+        self.latency
+    }
+
+    pub fn measure_time(&self) -> MetricsTime {
+        self.measure_time
+    }
+
+    pub fn event_type(&self) -> MetricsEventType {
+        self.event_type
+    }
+}
+
+/// Offset between local clock and exchange server time which should be added to latencies
+/// of events with exchange timestamps. It's updated periodically by measuring exchange server time
+#[derive(Debug, Default)]
+pub struct MetricsTimeSource {
+    local_time_offset: AtomicI64,
+}
+
+impl MetricsTimeSource {
+    pub fn new(local_time_offset: MetricsTime) -> Self {
+        Self {
+            local_time_offset: AtomicI64::new(local_time_offset),
+        }
+    }
+
+    pub fn local_time_offset(&self) -> MetricsTime {
+        self.local_time_offset.load(AtomicOrdering::SeqCst)
+    }
+
+    pub fn set_local_time_offset(&self, local_time_offset: MetricsTime) {
+        self.local_time_offset
+            .store(local_time_offset, AtomicOrdering::SeqCst)
+    }
 }
 
 #[derive(Debug, Copy, Clone, Serialize)]
@@ -282,6 +534,8 @@ pub enum MetricsEventType {
     TradeToMl,
     OrderFromCreateToFill,
     OrderLifeCycle(OrderStatus),
+    /// Time from sending order cancellation to receiving its confirmation
+    CancelAckLatency,
 }
 
 #[derive(Debug)]
@@ -332,3 +586,269 @@ impl MetricsEventInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+    use std::collections::HashSet;
+
+    struct FixedRatesConverter(Vec<(CurrencyCode, CurrencyCode, Price)>);
+
+    impl AmountConverter for FixedRatesConverter {
+        fn convert_amount(
+            &self,
+            from: CurrencyCode,
+            to: CurrencyCode,
+            amount: Amount,
+        ) -> Option<Amount> {
+            self.0
+                .iter()
+                .find(|(rate_from, rate_to, _)| *rate_from == from && *rate_to == to)
+                .map(|(_, _, rate)| amount * rate)
+        }
+    }
+
+    #[test]
+    fn net_balance_in_with_derivative_position() {
+        let usdt = CurrencyCode::from("usdt");
+        let btc = CurrencyCode::from("btc");
+        let eth = CurrencyCode::from("eth");
+
+        let balances_and_positions = ExchangeBalancesAndPositions {
+            balances: vec![
+                ExchangeBalance {
+                    currency_code: usdt,
+                    balance: dec!(100),
+                },
+                ExchangeBalance {
+                    currency_code: btc,
+                    balance: dec!(0.5),
+                },
+                ExchangeBalance {
+                    currency_code: eth,
+                    balance: dec!(0),
+                },
+            ],
+            positions: Some(vec![DerivativePosition::new(
+                CurrencyPair::from_codes(eth, usdt),
+                dec!(-2),
+                dec!(1500),
+                dec!(3000),
+                dec!(5),
+            )]),
+            event_time: None,
+        };
+        let converter =
+            FixedRatesConverter(vec![(btc, usdt, dec!(20000)), (eth, usdt, dec!(1000))]);
+
+        // 100 + 0.5 * 20000 - 2 * 1000
+        assert_eq!(
+            balances_and_positions.net_balance_in(usdt, &converter),
+            Some(dec!(8100))
+        );
+        assert_eq!(balances_and_positions.net_balance_in(btc, &converter), None);
+    }
+
+    #[test]
+    fn metrics_latency_includes_local_time_offset() {
+        let source = MetricsTimeSource::default();
+        let info = MetricsEventInfoBase::new(1_000, 1_500, MetricsEventType::TradeEvent);
+        assert_eq!(MetricsEvent::from_info(&info, &source).latency(), 500);
+
+        source.set_local_time_offset(200);
+        let metrics_event = MetricsEvent::from_info(&info, &source);
+        assert_eq!(metrics_event.latency(), 700);
+        assert_eq!(metrics_event.measure_time(), 1_500);
+    }
+
+    #[test]
+    fn latest_balance_updates_keep_newer_event() {
+        let btc = CurrencyCode::from("btc");
+        let exchange_account_id = ExchangeAccountId::new("Binance", 0);
+        let balance_update = |balance, event_time| BalanceUpdateEvent {
+            exchange_account_id,
+            balances_and_positions: ExchangeBalancesAndPositions {
+                balances: vec![ExchangeBalance {
+                    currency_code: btc,
+                    balance,
+                }],
+                positions: None,
+                event_time: Some(event_time),
+            },
+            event_time,
+        };
+
+        let now = chrono::Utc::now();
+        let older = balance_update(dec!(1), now - chrono::Duration::seconds(1));
+        let newer = balance_update(dec!(2), now);
+
+        let mut latest_balance_updates = LatestBalanceUpdates::default();
+        assert!(latest_balance_updates.apply(newer));
+        assert!(!latest_balance_updates.apply(older));
+
+        let latest = latest_balance_updates
+            .get(exchange_account_id)
+            .expect("in test");
+        assert_eq!(latest.event_time, now);
+        assert_eq!(latest.balances_and_positions.balances[0].balance, dec!(2));
+    }
+
+    #[test]
+    fn trades_event_filtered_drops_dust_trades() {
+        let trade = |id, quantity| Trade {
+            trade_id: TradeId::Number(id),
+            price: dec!(10),
+            quantity,
+            side: OrderSide::Buy,
+            transaction_time: chrono::Utc::now(),
+        };
+        let trades_event = TradesEvent {
+            exchange_account_id: ExchangeAccountId::new("Binance", 0),
+            currency_pair: CurrencyPair::from_codes("btc".into(), "usdt".into()),
+            trades: vec![trade(1, dec!(1)), trade(2, dec!(0.01)), trade(3, dec!(0.1))],
+            receipt_time: chrono::Utc::now(),
+        };
+
+        let filtered = trades_event.filtered(dec!(0.1));
+
+        let trade_ids = filtered
+            .trades
+            .iter()
+            .map(|trade| trade.trade_id.clone())
+            .collect_vec();
+        assert_eq!(trade_ids, vec![TradeId::Number(1), TradeId::Number(3)]);
+        assert_eq!(filtered.receipt_time, trades_event.receipt_time);
+        assert_eq!(filtered.currency_pair, trades_event.currency_pair);
+    }
+
+    fn liquidation_price_event() -> LiquidationPriceEvent {
+        LiquidationPriceEvent::new(
+            chrono::Utc::now(),
+            ExchangeAccountId::new("Bitmex", 0),
+            CurrencyPair::from_codes("xbt".into(), "usd".into()),
+            dec!(15000),
+            dec!(20000),
+            OrderSide::Buy,
+        )
+    }
+
+    #[test]
+    fn liquidation_price_event_serialization_round_trip() {
+        let event = liquidation_price_event();
+
+        let serialized = serde_json::to_string(&event).expect("in test");
+        let deserialized: LiquidationPriceEvent =
+            serde_json::from_str(&serialized).expect("in test");
+
+        assert_eq!(deserialized, event);
+    }
+
+    #[test]
+    fn liquidation_price_event_v0_entry_price_is_back_filled() {
+        let event = liquidation_price_event();
+        let mut v0_record = serde_json::to_value(&event).expect("in test");
+        let fields = v0_record.as_object_mut().expect("in test");
+        let _ = fields.remove("version");
+        let _ = fields.remove("entry_price");
+
+        let deserialized: LiquidationPriceEvent =
+            serde_json::from_value(v0_record).expect("in test");
+
+        assert_eq!(deserialized.version, LIQUIDATION_PRICE_CURRENT_VERSION);
+        assert_eq!(deserialized.liq_price, event.liq_price);
+        assert_eq!(deserialized.entry_price, event.liq_price);
+    }
+
+    #[test]
+    fn sort_numeric_trade_ids() {
+        let mut trade_ids = vec![
+            TradeId::Number(12),
+            TradeId::Number(3),
+            TradeId::Number(100),
+            TradeId::Number(4),
+        ];
+
+        trade_ids.sort();
+
+        assert_eq!(
+            trade_ids,
+            vec![
+                TradeId::Number(3),
+                TradeId::Number(4),
+                TradeId::Number(12),
+                TradeId::Number(100),
+            ]
+        );
+        assert_eq!(trade_ids[1].is_successor_of(&trade_ids[0]), Some(true));
+        assert_eq!(trade_ids[2].is_successor_of(&trade_ids[1]), Some(false));
+    }
+
+    #[test]
+    fn compare_mixed_trade_ids() {
+        let number = TradeId::Number(1);
+        let string = TradeId::String("a".into());
+
+        assert_eq!(number.partial_cmp(&string), Some(Ordering::Less));
+        assert_eq!(number.cmp(&string), Ordering::Less);
+        assert_eq!(string.is_successor_of(&number), None);
+    }
+
+    #[test]
+    fn trade_ids_of_different_formats_are_distinct() {
+        let number = TradeId::Number(1);
+        let string = TradeId::String("1".into());
+
+        assert_ne!(number, string);
+
+        let trade_ids = HashSet::from([number, string]);
+        assert_eq!(trade_ids.len(), 2);
+    }
+
+    fn balance_update_event() -> ExchangeEvent {
+        ExchangeEvent::BalanceUpdate(BalanceUpdateEvent {
+            exchange_account_id: ExchangeAccountId::new("test_exchange", 0),
+            balances_and_positions: ExchangeBalancesAndPositions {
+                balances: vec![],
+                positions: None,
+                event_time: None,
+            },
+            event_time: chrono::Utc::now(),
+        })
+    }
+
+    #[tokio::test]
+    async fn subscribe_with_policy_counts_dropped_events() {
+        let (events_sender, _) = broadcast::channel(2);
+        let exchange_events = ExchangeEvents::new(events_sender.clone());
+
+        let metric = Arc::new(AtomicU64::new(0));
+        let mut counting_receiver =
+            exchange_events.subscribe_with_policy(LagPolicy::Count(metric.clone()));
+        let mut failing_receiver = exchange_events.subscribe_with_policy(LagPolicy::Error);
+
+        for _ in 0..5 {
+            events_sender.send(balance_update_event()).expect("in test");
+        }
+
+        for _ in 0..2 {
+            assert!(counting_receiver.recv().await.is_ok());
+        }
+        assert_eq!(counting_receiver.dropped_events_count(), 3);
+        assert_eq!(metric.load(AtomicOrdering::Relaxed), 3);
+
+        assert_eq!(
+            failing_receiver.recv().await.err(),
+            Some(EventsReceiveError::Lagged(3))
+        );
+        assert!(failing_receiver.recv().await.is_ok());
+        assert_eq!(failing_receiver.dropped_events_count(), 3);
+
+        drop(events_sender);
+        drop(exchange_events);
+        assert_eq!(
+            counting_receiver.recv().await.err(),
+            Some(EventsReceiveError::Closed)
+        );
+    }
+}
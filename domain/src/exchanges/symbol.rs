@@ -1,9 +1,10 @@
+use std::fmt::{self, Display, Formatter};
 use std::hash::{Hash, Hasher};
 
 use crate::market::{powi, CurrencyCode, CurrencyId, CurrencyPair};
 use crate::order::snapshot::OrderSide;
 use crate::order::snapshot::{Amount, Price};
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use rust_decimal::Decimal;
 use rust_decimal::MathematicalOps;
 use rust_decimal_macros::dec;
@@ -53,6 +54,53 @@ impl Precision {
     }
 }
 
+pub const DEFAULT_AMOUNT_MARGIN_ERROR_IN_TICKS: Decimal = dec!(0.01);
+
+/// Reason why order can't be placed on exchange according to symbol constraints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderValidationError {
+    AmountLessThanMin { amount: Amount, min_amount: Amount },
+    AmountGreaterThanMax { amount: Amount, max_amount: Amount },
+    CostLessThanMin { cost: Amount, min_cost: Amount },
+    PriceNotAlignedToTick { price: Price, tick: Decimal },
+    AmountNotAlignedToTick { amount: Amount, tick: Decimal },
+}
+
+impl Display for OrderValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use OrderValidationError::*;
+
+        match self {
+            AmountLessThanMin { amount, min_amount } => {
+                write!(f, "Amount {amount} is less than min amount {min_amount}")
+            }
+            AmountGreaterThanMax { amount, max_amount } => {
+                write!(f, "Amount {amount} is greater than max amount {max_amount}")
+            }
+            CostLessThanMin { cost, min_cost } => {
+                write!(f, "Order cost {cost} is less than min cost {min_cost}")
+            }
+            PriceNotAlignedToTick { price, tick } => {
+                write!(f, "Price {price} is not aligned to price tick {tick}")
+            }
+            AmountNotAlignedToTick { amount, tick } => {
+                write!(f, "Amount {amount} is not aligned to amount tick {tick}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrderValidationError {}
+
+/// Kind of derivative contract which defines how position is valued
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum DerivativeKind {
+    /// Amount is specified in base currency and margin is held in quote currency (like BTCUSDT)
+    Linear,
+    /// Amount is specified in quote currency and margin is held in base currency (like BitMEX XBTUSD)
+    Inverse,
+}
+
 /// Metadata for a currency pair
 #[derive(Debug, Clone, Eq, Serialize)]
 pub struct Symbol {
@@ -73,9 +121,13 @@ pub struct Symbol {
     pub amount_currency_code: CurrencyCode,
     pub balance_currency_code: Option<CurrencyCode>,
     pub amount_multiplier: Decimal,
+    /// Kind of derivative contract, `None` for spot pairs
+    pub derivative_kind: Option<DerivativeKind>,
 
     pub price_precision: Precision,
     pub amount_precision: Precision,
+    /// Amount residual which is treated as calculation error, specified as multiple of amount tick
+    pub amount_margin_error_in_ticks: Decimal,
 }
 
 impl Symbol {
@@ -104,6 +156,12 @@ impl Symbol {
         price_precision: Precision,
         amount_precision: Precision,
     ) -> Self {
+        let derivative_kind = match is_derivative {
+            false => None,
+            true if amount_currency_code == base_currency_code => Some(DerivativeKind::Linear),
+            true => Some(DerivativeKind::Inverse),
+        };
+
         Self {
             is_derivative,
             base_currency_id,
@@ -118,8 +176,10 @@ impl Symbol {
             min_cost,
             balance_currency_code,
             amount_multiplier: dec!(1),
+            derivative_kind,
             price_precision,
             amount_precision,
+            amount_margin_error_in_ticks: DEFAULT_AMOUNT_MARGIN_ERROR_IN_TICKS,
         }
     }
 
@@ -144,6 +204,10 @@ impl Symbol {
         self.is_derivative
     }
 
+    pub fn is_inverse(&self) -> bool {
+        self.derivative_kind == Some(DerivativeKind::Inverse)
+    }
+
     pub fn price_round(&self, price: Price, round: Round) -> Price {
         match self.price_precision {
             Precision::ByTick { tick } => Self::round_by_tick(price, tick, round),
@@ -160,6 +224,22 @@ impl Symbol {
         }
     }
 
+    /// Rounding of order amount with explicit rounding mode, e.g. `Round::Floor` to not exceed available amount.
+    /// Unlike `amount_round` returns an error instead of panic when amount precision isn't set up properly
+    pub fn try_amount_round(&self, amount: Amount, round: Round) -> Result<Amount> {
+        match self.amount_precision {
+            Precision::ByTick { tick } if tick <= dec!(0) => bail!(
+                "Unable to round amount {amount} for {}: tick {tick} should be positive",
+                self.currency_pair()
+            ),
+            Precision::ByMantissa { precision: 0 } => bail!(
+                "Unable to round amount {amount} for {}: mantissa precision should be positive",
+                self.currency_pair()
+            ),
+            _ => Ok(self.amount_round(amount, round)),
+        }
+    }
+
     /// Rounding of order amount with specified precision
     pub fn amount_round_precision(
         &self,
@@ -331,6 +411,118 @@ impl Symbol {
         );
     }
 
+    /// Convert amount in base currency into number of contracts.
+    /// For inverse contracts (like XBTUSD) amount currency is the quote currency, so `price` is used for converting.
+    /// For linear contracts amount currency is the base currency and only `amount_multiplier` (contract size) matters
+    pub fn amount_to_contracts(&self, amount: Amount, price: Price) -> Decimal {
+        self.convert_amount_into_amount_currency_code(self.base_currency_code, amount, price)
+            / self.amount_multiplier
+    }
+
+    /// Convert number of contracts into amount in base currency. Inverse of `amount_to_contracts`
+    pub fn contracts_to_amount(&self, contracts: Decimal, price: Price) -> Amount {
+        self.convert_amount_from_amount_currency_code(
+            self.base_currency_code,
+            contracts * self.amount_multiplier,
+            price,
+        )
+    }
+
+    /// Margin needed for holding position of `amount` contracts with `leverage`
+    pub fn position_to_margin(&self, amount: Amount, leverage: Decimal) -> Amount {
+        amount / leverage * self.amount_multiplier
+    }
+
+    /// Position in contracts that can be held with `margin` and `leverage`. Inverse of `position_to_margin`
+    pub fn margin_to_position(&self, margin: Amount, leverage: Decimal) -> Amount {
+        margin * leverage / self.amount_multiplier
+    }
+
+    /// Check that price is within `min_price` and `max_price` limits of exchange.
+    /// Zero limit means that exchange doesn't restrict price from this side
+    pub fn validate_price(&self, price: Price) -> Result<()> {
+        if let Some(min_price) = self.min_price.filter(|x| !x.is_zero()) {
+            if price < min_price {
+                bail!(
+                    "Price {price} is less than min price {min_price} for {}",
+                    self.currency_pair()
+                );
+            }
+        }
+
+        if let Some(max_price) = self.max_price.filter(|x| !x.is_zero()) {
+            if price > max_price {
+                bail!(
+                    "Price {price} is greater than max price {max_price} for {}",
+                    self.currency_pair()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that order satisfies exchange constraints to not send order which will be rejected anyway.
+    /// Checks depending on price (min cost and price tick) are skipped if price isn't specified (e.g. for market orders)
+    pub fn validate_order(
+        &self,
+        price: Option<Price>,
+        amount: Amount,
+    ) -> Result<(), OrderValidationError> {
+        if let Some(min_amount) = self.min_amount {
+            if amount < min_amount {
+                return Err(OrderValidationError::AmountLessThanMin { amount, min_amount });
+            }
+        }
+
+        if let Some(max_amount) = self.max_amount.filter(|x| !x.is_zero()) {
+            if amount > max_amount {
+                return Err(OrderValidationError::AmountGreaterThanMax { amount, max_amount });
+            }
+        }
+
+        if !Self::is_aligned(amount, &self.amount_precision) {
+            return Err(OrderValidationError::AmountNotAlignedToTick {
+                amount,
+                tick: self.amount_precision.get_tick(),
+            });
+        }
+
+        if let Some(price) = price {
+            if !Self::is_aligned(price, &self.price_precision) {
+                return Err(OrderValidationError::PriceNotAlignedToTick {
+                    price,
+                    tick: self.price_precision.get_tick(),
+                });
+            }
+
+            if let Some(min_cost) = self.min_cost {
+                let cost = self.convert_amount_from_amount_currency_code(
+                    self.quote_currency_code,
+                    amount,
+                    price,
+                );
+                if cost < min_cost {
+                    return Err(OrderValidationError::CostLessThanMin { cost, min_cost });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Precision without positive tick or mantissa length isn't set up, so any value is aligned
+    fn is_aligned(value: Decimal, precision: &Precision) -> bool {
+        match *precision {
+            Precision::ByTick { tick } if tick <= dec!(0) => true,
+            Precision::ByTick { tick } => (value % tick).is_zero(),
+            Precision::ByMantissa { precision: 0 } => true,
+            Precision::ByMantissa { precision } => {
+                Self::round_by_mantissa(value, precision, Round::ToNearest) == value
+            }
+        }
+    }
+
     /// Calculate min order's amount constraint for placing order on exchange
     /// NOTE: `price` needed when `min_cost` specified. It's used only for Binance
     /// (for `MIN_NOTIONAL` constraint on order's amount) now.
@@ -380,6 +572,11 @@ impl Symbol {
             }
         }
     }
+
+    /// Max amount which is treated as calculation error instead of meaningful residual
+    pub fn get_amount_margin_error(&self) -> Amount {
+        self.get_amount_tick() * self.amount_margin_error_in_ticks
+    }
 }
 
 impl PartialEq for Symbol {
@@ -394,6 +591,12 @@ impl Hash for Symbol {
     }
 }
 
+/// Currency which a reservation of balance for the order with `side` on `symbol` uses.
+/// Allows to check the right balance before reservation
+pub fn reservation_currency_code(symbol: &Symbol, side: OrderSide) -> CurrencyCode {
+    symbol.get_trade_code(side, BeforeAfter::Before)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -521,6 +724,73 @@ mod test {
         Ok(())
     }
 
+    fn create_symbol_with_amount_precision(amount_precision: Precision) -> Symbol {
+        let base_code = CurrencyCode::new("PHB");
+        let quote_code = CurrencyCode::new("BTC");
+        Symbol::new(
+            false,
+            base_code.as_str().into(),
+            base_code,
+            quote_code.as_str().into(),
+            quote_code,
+            None,
+            None,
+            None,
+            None,
+            None,
+            base_code,
+            None,
+            Precision::ByTick { tick: dec!(0.1) },
+            amount_precision,
+        )
+    }
+
+    #[rstest]
+    #[case(dec!(1.2345), Round::ToNearest, dec!(1.235))]
+    #[case(dec!(1.2344), Round::ToNearest, dec!(1.234))]
+    #[case(dec!(1.2349), Round::Floor, dec!(1.234))]
+    #[case(dec!(1.2341), Round::Ceiling, dec!(1.235))]
+    #[case(dec!(1.234), Round::Floor, dec!(1.234))]
+    #[case(dec!(1.234), Round::Ceiling, dec!(1.234))]
+    fn try_amount_round_by_tick(
+        #[case] amount: Amount,
+        #[case] round_to: Round,
+        #[case] expected: Amount,
+    ) {
+        let symbol = create_symbol_with_amount_precision(Precision::ByTick { tick: dec!(0.001) });
+
+        let rounded = symbol.try_amount_round(amount, round_to).expect("in test");
+
+        assert_eq!(rounded, expected);
+    }
+
+    #[rstest]
+    #[case(dec!(0.0123456), Round::Floor, dec!(0.0123))]
+    #[case(dec!(0.0123456), Round::Ceiling, dec!(0.0124))]
+    #[case(dec!(0.0123456), Round::ToNearest, dec!(0.0123))]
+    fn try_amount_round_by_mantissa(
+        #[case] amount: Amount,
+        #[case] round_to: Round,
+        #[case] expected: Amount,
+    ) {
+        let symbol = create_symbol_with_amount_precision(Precision::ByMantissa { precision: 3 });
+
+        let rounded = symbol.try_amount_round(amount, round_to).expect("in test");
+
+        assert_eq!(rounded, expected);
+    }
+
+    #[rstest]
+    #[case(Precision::ByTick { tick: dec!(0) })]
+    #[case(Precision::ByMantissa { precision: 0 })]
+    fn try_amount_round_err_when_invalid_precision(#[case] amount_precision: Precision) {
+        let symbol = create_symbol_with_amount_precision(amount_precision);
+
+        let _ = symbol
+            .try_amount_round(dec!(1.2345), Round::Floor)
+            .expect_err("should be error for invalid amount precision");
+    }
+
     #[test]
     pub fn get_trade_code() {
         let base_currency = "PHB";
@@ -566,6 +836,41 @@ mod test {
         );
     }
 
+    #[rstest]
+    #[case::spot_buy(false, OrderSide::Buy, "BTC")]
+    #[case::spot_sell(false, OrderSide::Sell, "PHB")]
+    #[case::derivative_buy(true, OrderSide::Buy, "BTC")]
+    #[case::derivative_sell(true, OrderSide::Sell, "PHB")]
+    pub fn reservation_currency_code_by_side(
+        #[case] is_derivative: bool,
+        #[case] side: OrderSide,
+        #[case] expected: &str,
+    ) {
+        let base_code = CurrencyCode::new("PHB");
+        let quote_code = CurrencyCode::new("BTC");
+        let symbol = Symbol::new(
+            is_derivative,
+            base_code.as_str().into(),
+            base_code,
+            quote_code.as_str().into(),
+            quote_code,
+            None,
+            None,
+            None,
+            None,
+            None,
+            base_code,
+            None,
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0) },
+        );
+
+        assert_eq!(
+            reservation_currency_code(&symbol, side),
+            CurrencyCode::new(expected)
+        );
+    }
+
     mod get_min_amount {
         use crate::exchanges::symbol::{Precision, Symbol};
         use crate::market::CurrencyCode;
@@ -637,4 +942,202 @@ mod test {
                 .expect_err("should be error if min_amount not specified");
         }
     }
+
+    mod contracts {
+        use crate::exchanges::symbol::{DerivativeKind, Precision, Symbol};
+        use crate::market::CurrencyCode;
+        use rust_decimal_macros::dec;
+
+        fn create_derivative_symbol(
+            base_code: CurrencyCode,
+            quote_code: CurrencyCode,
+            amount_currency_code: CurrencyCode,
+        ) -> Symbol {
+            Symbol::new(
+                true,
+                base_code.as_str().into(),
+                base_code,
+                quote_code.as_str().into(),
+                quote_code,
+                None,
+                None,
+                None,
+                None,
+                None,
+                amount_currency_code,
+                None,
+                Precision::ByTick { tick: dec!(0.5) },
+                Precision::ByTick { tick: dec!(1) },
+            )
+        }
+
+        #[test]
+        pub fn inverse_contract() {
+            let base_code = CurrencyCode::new("XBT");
+            let quote_code = CurrencyCode::new("USD");
+            // XBTUSD contract is 1 USD
+            let symbol = create_derivative_symbol(base_code, quote_code, quote_code);
+            let price = dec!(20000);
+
+            assert_eq!(symbol.derivative_kind, Some(DerivativeKind::Inverse));
+
+            let contracts = symbol.amount_to_contracts(dec!(0.5), price);
+            assert_eq!(contracts, dec!(10000));
+
+            let amount = symbol.contracts_to_amount(contracts, price);
+            assert_eq!(amount, dec!(0.5));
+        }
+
+        #[test]
+        pub fn linear_contract() {
+            let base_code = CurrencyCode::new("BTC");
+            let quote_code = CurrencyCode::new("USDT");
+            let mut symbol = create_derivative_symbol(base_code, quote_code, base_code);
+            symbol.amount_multiplier = dec!(0.001);
+            let price = dec!(20000);
+
+            assert_eq!(symbol.derivative_kind, Some(DerivativeKind::Linear));
+
+            let contracts = symbol.amount_to_contracts(dec!(0.5), price);
+            assert_eq!(contracts, dec!(500));
+
+            let amount = symbol.contracts_to_amount(contracts, price);
+            assert_eq!(amount, dec!(0.5));
+        }
+    }
+
+    mod validate_price {
+        use crate::exchanges::symbol::{Precision, Symbol};
+        use crate::market::CurrencyCode;
+        use rust_decimal_macros::dec;
+
+        fn create_symbol_with_price_limits() -> Symbol {
+            let base_code = CurrencyCode::new("PHB");
+            let quote_code = CurrencyCode::new("BTC");
+            Symbol::new(
+                false,
+                base_code.as_str().into(),
+                base_code,
+                quote_code.as_str().into(),
+                quote_code,
+                Some(dec!(0.1)),
+                Some(dec!(100)),
+                None,
+                None,
+                None,
+                base_code,
+                None,
+                Precision::ByTick { tick: dec!(0.1) },
+                Precision::ByTick { tick: dec!(1) },
+            )
+        }
+
+        #[test]
+        pub fn err_when_price_less_than_min_price() {
+            let symbol = create_symbol_with_price_limits();
+
+            let _ = symbol
+                .validate_price(dec!(0.05))
+                .expect_err("should be error if price less than min price");
+        }
+
+        #[test]
+        pub fn ok_when_price_within_limits() {
+            let mut symbol = create_symbol_with_price_limits();
+            symbol
+                .validate_price(dec!(0.1))
+                .expect("price equal to min price is valid");
+
+            // zero max price means no limit
+            symbol.max_price = Some(dec!(0));
+            symbol
+                .validate_price(dec!(1000))
+                .expect("price isn't limited from above");
+        }
+    }
+
+    mod validate_order {
+        use crate::exchanges::symbol::{OrderValidationError, Precision, Symbol};
+        use crate::market::CurrencyCode;
+        use rust_decimal_macros::dec;
+
+        fn create_symbol_with_order_limits() -> Symbol {
+            let base_code = CurrencyCode::new("PHB");
+            let quote_code = CurrencyCode::new("BTC");
+            Symbol::new(
+                false,
+                base_code.as_str().into(),
+                base_code,
+                quote_code.as_str().into(),
+                quote_code,
+                None,
+                None,
+                Some(dec!(1)),
+                Some(dec!(1000)),
+                Some(dec!(10)),
+                base_code,
+                None,
+                Precision::ByTick { tick: dec!(0.1) },
+                Precision::ByTick { tick: dec!(0.5) },
+            )
+        }
+
+        #[test]
+        pub fn ok_when_order_satisfies_limits() {
+            let symbol = create_symbol_with_order_limits();
+
+            symbol
+                .validate_order(Some(dec!(2.5)), dec!(4))
+                .expect("order should be valid");
+            symbol
+                .validate_order(None, dec!(1))
+                .expect("min cost isn't checked without price");
+        }
+
+        #[test]
+        pub fn err_when_amount_less_than_min_amount() {
+            let symbol = create_symbol_with_order_limits();
+
+            assert_eq!(
+                symbol.validate_order(Some(dec!(100)), dec!(0.5)),
+                Err(OrderValidationError::AmountLessThanMin {
+                    amount: dec!(0.5),
+                    min_amount: dec!(1),
+                })
+            );
+        }
+
+        #[test]
+        pub fn err_when_cost_less_than_min_cost() {
+            let symbol = create_symbol_with_order_limits();
+
+            assert_eq!(
+                symbol.validate_order(Some(dec!(2.4)), dec!(4)),
+                Err(OrderValidationError::CostLessThanMin {
+                    cost: dec!(9.6),
+                    min_cost: dec!(10),
+                })
+            );
+        }
+
+        #[test]
+        pub fn err_when_not_aligned_to_tick() {
+            let symbol = create_symbol_with_order_limits();
+
+            assert_eq!(
+                symbol.validate_order(Some(dec!(2.5)), dec!(4.2)),
+                Err(OrderValidationError::AmountNotAlignedToTick {
+                    amount: dec!(4.2),
+                    tick: dec!(0.5),
+                })
+            );
+            assert_eq!(
+                symbol.validate_order(Some(dec!(2.55)), dec!(4)),
+                Err(OrderValidationError::PriceNotAlignedToTick {
+                    price: dec!(2.55),
+                    tick: dec!(0.1),
+                })
+            );
+        }
+    }
 }
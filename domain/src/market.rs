@@ -17,7 +17,38 @@ impl_table_type_raw!(CurrencyCode, 16, u16);
 impl_table_type_raw!(CurrencyPair, 16, u16);
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ExchangeIdParseError(String);
+pub enum ExchangeAccountIdParseError {
+    /// There is no account number separated by '_' on the tail
+    MissingAccountNumber,
+    NonNumericAccountNumber(String),
+    AccountNumberOutOfRange(String),
+    EmptyExchangeId,
+    /// 'ExchangeId' part contains characters other than 'A-Za-z0-9\-\.'
+    InvalidExchangeId(String),
+}
+
+impl Display for ExchangeAccountIdParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use ExchangeAccountIdParseError::*;
+
+        match self {
+            MissingAccountNumber => write!(f, "Missing account number separated by '_'"),
+            NonNumericAccountNumber(number_part) => {
+                write!(f, "Account number part is not numeric: '{number_part}'")
+            }
+            AccountNumberOutOfRange(number_part) => {
+                write!(f, "Account number part doesn't fit 'u8': '{number_part}'")
+            }
+            EmptyExchangeId => write!(f, "'ExchangeId' part is empty"),
+            InvalidExchangeId(exchange_id) => write!(
+                f,
+                "'ExchangeId' part can contains only 'A-Za-z0-9\\-\\.', but found '{exchange_id}'"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExchangeAccountIdParseError {}
 
 // unique user ID on the exchange
 #[derive(Clone, Copy, Eq, Hash, PartialEq)]
@@ -36,30 +67,52 @@ impl ExchangeAccountId {
             account_number,
         }
     }
+
+    /// Same as `new`, but checks that `exchange_id` is not empty and contains only 'A-Za-z0-9\-\.'
+    pub fn new_checked(
+        exchange_id: &str,
+        account_number: u8,
+    ) -> std::result::Result<Self, ExchangeAccountIdParseError> {
+        if exchange_id.is_empty() {
+            return Err(ExchangeAccountIdParseError::EmptyExchangeId);
+        }
+
+        if !exchange_id
+            .chars()
+            .all(|c| c.is_ascii_alphabetic() || c.is_ascii_digit() || c == '-' || c == '.')
+        {
+            return Err(ExchangeAccountIdParseError::InvalidExchangeId(
+                exchange_id.to_owned(),
+            ));
+        }
+
+        Ok(ExchangeAccountId::new(exchange_id, account_number))
+    }
 }
 
 impl FromStr for ExchangeAccountId {
-    type Err = ExchangeIdParseError;
+    type Err = ExchangeAccountIdParseError;
 
     fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
         let (exchange_id, number_part) = value
             .rsplit_once('_')
-            .ok_or_else(|| ExchangeIdParseError("not contains '_'".into()))?;
+            .ok_or(ExchangeAccountIdParseError::MissingAccountNumber)?;
 
-        if !exchange_id
-            .chars()
-            .all(|c| c.is_ascii_alphabetic() || c.is_ascii_digit() || c == '-' || c == '.')
-        {
-            return Err(ExchangeIdParseError(format!(
-                "'ExchangeId' part can contains only 'A-Za-z0-9\\-\\.', but found '{exchange_id}'"
-            )));
+        if number_part.is_empty() {
+            return Err(ExchangeAccountIdParseError::MissingAccountNumber);
         }
 
-        let number = number_part
-            .parse()
-            .map_err(|x| ExchangeIdParseError(format!("number part is not 'u8': {x}")))?;
+        if !number_part.chars().all(|c| c.is_ascii_digit()) {
+            return Err(ExchangeAccountIdParseError::NonNumericAccountNumber(
+                number_part.to_owned(),
+            ));
+        }
 
-        Ok(ExchangeAccountId::new(exchange_id, number))
+        let number = number_part.parse().map_err(|_| {
+            ExchangeAccountIdParseError::AccountNumberOutOfRange(number_part.to_owned())
+        })?;
+
+        ExchangeAccountId::new_checked(exchange_id, number)
     }
 }
 
@@ -193,6 +246,8 @@ pub enum ExchangeErrorType {
     ParsingError,
     PendingError(Duration),
     ServiceUnavailable,
+    /// Maker only order was rejected because it would be executed immediately as taker
+    PostOnlyRejected,
 }
 
 #[cfg(test)]
@@ -277,7 +332,7 @@ mod tests {
             let exchange_account_id = "123".parse::<ExchangeAccountId>();
             assert_eq!(
                 exchange_account_id,
-                Err(ExchangeIdParseError("not contains '_'".into()))
+                Err(ExchangeAccountIdParseError::MissingAccountNumber)
             )
         }
 
@@ -286,17 +341,71 @@ mod tests {
             let exchange_account_id = "Binance".parse::<ExchangeAccountId>();
             assert_eq!(
                 exchange_account_id,
-                Err(ExchangeIdParseError("not contains '_'".into()))
+                Err(ExchangeAccountIdParseError::MissingAccountNumber)
+            )
+        }
+
+        #[test]
+        pub fn failed_because_empty_number() {
+            let exchange_account_id = "Binance_".parse::<ExchangeAccountId>();
+            assert_eq!(
+                exchange_account_id,
+                Err(ExchangeAccountIdParseError::MissingAccountNumber)
+            )
+        }
+
+        #[test]
+        pub fn failed_because_non_numeric_number() {
+            let exchange_account_id = "Binance_-1".parse::<ExchangeAccountId>();
+            assert_eq!(
+                exchange_account_id,
+                Err(ExchangeAccountIdParseError::NonNumericAccountNumber(
+                    "-1".into()
+                ))
             )
         }
 
         #[test]
         pub fn failed_because_invalid_number() {
             let exchange_account_id = "binance_256".parse::<ExchangeAccountId>();
-            let expected = Err(ExchangeIdParseError(
-                "number part is not 'u8': number too large to fit in target type".into(),
-            ));
-            assert_eq!(exchange_account_id, expected)
+            assert_eq!(
+                exchange_account_id,
+                Err(ExchangeAccountIdParseError::AccountNumberOutOfRange(
+                    "256".into()
+                ))
+            )
+        }
+
+        #[test]
+        pub fn failed_because_empty_exchange_id() {
+            let exchange_account_id = "_0".parse::<ExchangeAccountId>();
+            assert_eq!(
+                exchange_account_id,
+                Err(ExchangeAccountIdParseError::EmptyExchangeId)
+            )
+        }
+
+        #[test]
+        pub fn failed_because_invalid_exchange_id() {
+            let exchange_account_id = "Binance test_0".parse::<ExchangeAccountId>();
+            assert_eq!(
+                exchange_account_id,
+                Err(ExchangeAccountIdParseError::InvalidExchangeId(
+                    "Binance test".into()
+                ))
+            )
+        }
+
+        #[test]
+        pub fn new_checked() {
+            assert_eq!(
+                ExchangeAccountId::new_checked("Binance", 1),
+                Ok(ExchangeAccountId::new("Binance", 1))
+            );
+            assert_eq!(
+                ExchangeAccountId::new_checked("", 1),
+                Err(ExchangeAccountIdParseError::EmptyExchangeId)
+            );
         }
     }
 
@@ -311,6 +420,30 @@ mod tests {
             assert_eq!(result, "Binance_1".to_string())
         }
     }
+
+    mod currency_pair {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        pub fn reversed() {
+            let currency_pair = CurrencyPair::from_codes("btc".into(), "usdt".into());
+
+            let reversed = currency_pair.reversed();
+
+            assert_eq!(reversed.as_str(), "usdt/btc");
+            assert_eq!(reversed.reversed(), currency_pair);
+        }
+
+        #[test]
+        pub fn contains() {
+            let currency_pair = CurrencyPair::from_codes("btc".into(), "usdt".into());
+
+            assert!(currency_pair.contains("btc".into()));
+            assert!(currency_pair.contains("usdt".into()));
+            assert!(!currency_pair.contains("eth".into()));
+        }
+    }
 }
 
 impl CurrencyCode {
@@ -355,6 +488,17 @@ impl CurrencyPair {
             quote: quote.into(),
         }
     }
+
+    /// Pair with swapped base and quote currencies, e.g. BTC/USDT for USDT/BTC
+    pub fn reversed(&self) -> CurrencyPair {
+        let codes = self.to_codes();
+        CurrencyPair::from_codes(codes.quote, codes.base)
+    }
+
+    /// Whether `code` is base or quote currency of the pair
+    pub fn contains(&self, code: CurrencyCode) -> bool {
+        self.to_codes().to_array().contains(&code)
+    }
 }
 
 pub fn powi(value: Decimal, degree: i8) -> Decimal {
@@ -11,6 +11,7 @@ pub enum OrderEventType {
     CreateOrderFailed,
     OrderFilled { cloned_order: Arc<OrderSnapshot> },
     OrderCompleted { cloned_order: Arc<OrderSnapshot> },
+    FilledAfterCancel { cloned_order: Arc<OrderSnapshot> },
     CancelOrderSucceeded,
     CancelOrderFailed,
 }
@@ -1,6 +1,7 @@
 use crate::events::{EventSourceType, TradeId};
 use crate::market::CurrencyCode;
-use crate::order::snapshot::{OrderFillRole, OrderSide};
+use crate::order::snapshot::{Amount, OrderFillRole, OrderSide};
+use mmb_database::impl_event;
 use mmb_utils::DateTime;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -21,6 +22,11 @@ impl OrderFillType {
         use OrderFillType::*;
         matches!(self, Liquidation | ClosePosition)
     }
+
+    /// Settlement fills (e.g. funding) only change balance and have no meaningful price
+    pub fn is_settlement(&self) -> bool {
+        matches!(self, OrderFillType::Funding)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +57,8 @@ pub struct OrderFill {
     side: Option<OrderSide>,
 }
 
+impl_event!(OrderFill, "order_fills");
+
 impl OrderFill {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -150,7 +158,84 @@ impl OrderFill {
         &self.client_order_fill_id
     }
 
+    /// Replaces commission and role by data received from more prioritized source of the same trade
+    pub fn update_metadata(
+        &mut self,
+        event_source_type: EventSourceType,
+        role: Option<OrderFillRole>,
+        commission_currency_code: Option<CurrencyCode>,
+        commission_amount: Option<Amount>,
+    ) {
+        self.event_source_type = Some(event_source_type);
+
+        if let Some(role) = role {
+            self.role = role;
+        }
+
+        if let Some(commission_amount) = commission_amount {
+            let commission_currency_code =
+                commission_currency_code.unwrap_or(self.commission_currency_code);
+            if self.converted_commission_currency_code == self.commission_currency_code {
+                self.converted_commission_currency_code = commission_currency_code;
+                self.converted_commission_amount = commission_amount;
+            }
+            self.commission_currency_code = commission_currency_code;
+            self.commission_amount = commission_amount;
+        }
+    }
+
     pub fn set_client_order_fill_id(&mut self, input: ClientOrderFillId) {
         self.client_order_fill_id = Some(input);
     }
+
+    /// Filled amount net of commission if the commission was paid in `base_currency_code`
+    pub fn net_filled_amount(&self, base_currency_code: CurrencyCode) -> Amount {
+        match self.commission_currency_code == base_currency_code {
+            true => self.amount - self.commission_amount,
+            false => self.amount,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rstest::rstest;
+    use rust_decimal_macros::dec;
+
+    #[rstest]
+    #[case::commission_in_base("ETH", dec!(9.99))]
+    #[case::commission_in_quote("BTC", dec!(10))]
+    fn net_filled_amount_by_commission_currency(
+        #[case] commission_currency_code: &str,
+        #[case] expected_net_filled_amount: Amount,
+    ) {
+        let commission_currency_code = CurrencyCode::new(commission_currency_code);
+        let fill = OrderFill::new(
+            Uuid::new_v4(),
+            None,
+            Utc::now(),
+            OrderFillType::UserTrade,
+            None,
+            dec!(0.2),
+            dec!(10),
+            dec!(2),
+            OrderFillRole::Taker,
+            commission_currency_code,
+            dec!(0.01),
+            dec!(0),
+            commission_currency_code,
+            dec!(0.01),
+            dec!(0.01),
+            false,
+            None,
+            Some(OrderSide::Buy),
+        );
+
+        assert_eq!(
+            fill.net_filled_amount("ETH".into()),
+            expected_net_filled_amount
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use crate::exchanges::symbol::Symbol;
+use crate::market::CurrencyCode;
 use crate::market::CurrencyPair;
 use crate::market::ExchangeAccountId;
 use crate::order::fill::OrderFill;
@@ -10,6 +12,7 @@ use dashmap::DashMap;
 use mmb_utils::DateTime;
 use parking_lot::RwLock;
 use std::borrow::{Borrow, BorrowMut};
+use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
 
@@ -92,6 +95,45 @@ impl OrderRef {
         self.header().order_type
     }
 
+    pub fn decision_price(&self) -> Option<Price> {
+        self.header().decision_price
+    }
+
+    /// Signed slippage of the fill against decision price of the order in quote currency.
+    /// Positive value means the fill is worse than decision price (buy above or sell below it).
+    /// Returns `None` if decision price wasn't specified on order creation.
+    pub fn realized_slippage(&self, fill: &OrderFill) -> Option<Amount> {
+        let decision_price = self.decision_price()?;
+        let price_diff = match self.side() {
+            OrderSide::Buy => fill.price() - decision_price,
+            OrderSide::Sell => decision_price - fill.price(),
+        };
+
+        Some(price_diff * fill.amount())
+    }
+
+    /// Price of the fill per unit of base currency adjusted by commission, so it's worse than the fill price
+    /// by the fee: higher for buy and lower for sell. Converted commission is used, so it's expected
+    /// to be in base or quote currency.
+    pub fn effective_price(&self, fill: &OrderFill) -> Price {
+        if fill.amount().is_zero() {
+            return fill.price();
+        }
+
+        let commission_in_quote =
+            if fill.converted_commission_currency_code() == self.currency_pair().to_codes().base {
+                fill.converted_commission_amount() * fill.price()
+            } else {
+                fill.converted_commission_amount()
+            };
+        let commission_per_unit = commission_in_quote / fill.amount();
+
+        match self.side() {
+            OrderSide::Buy => fill.price() + commission_per_unit,
+            OrderSide::Sell => fill.price() - commission_per_unit,
+        }
+    }
+
     /// Lock order for read and provide copy mutable properties or check some conditions
     pub fn fn_ref<T: 'static>(&self, f: impl FnOnce(&OrderMut) -> T) -> T {
         f(self.inner.data.read().borrow())
@@ -139,6 +181,26 @@ impl OrderRef {
     pub fn get_fills(&self) -> (Vec<OrderFill>, Amount) {
         self.fn_ref(|order| (order.fills.fills.clone(), order.fills.filled_amount))
     }
+
+    /// Total commission paid for all order fills by commission currency.
+    /// Fills usually share the same commission currency, but some exchanges can charge fee
+    /// in another currency (e.g. BNB on Binance)
+    pub fn total_commission(&self) -> HashMap<CurrencyCode, Amount> {
+        self.fn_ref(|order| {
+            let mut total_commission = HashMap::new();
+            for fill in &order.fills.fills {
+                *total_commission
+                    .entry(fill.commission_currency_code())
+                    .or_default() += fill.commission_amount();
+            }
+            total_commission
+        })
+    }
+
+    /// Average price of order fills calculated from fills cost. See `OrderFills::average_fill_price`
+    pub fn average_fill_price(&self, symbol: &Symbol) -> Option<Price> {
+        self.fn_ref(|order| order.fills.average_fill_price(symbol.is_derivative()))
+    }
 }
 
 #[derive(Debug)]
@@ -210,3 +272,161 @@ impl OrdersPool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventSourceType;
+    use crate::market::{CurrencyPair, ExchangeId};
+    use crate::order::fill::OrderFillType;
+    use crate::order::snapshot::{OrderFillRole, OrderOptions};
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn order_with_decision_price(side: OrderSide, decision_price: Option<Price>) -> OrderRef {
+        let header = OrderHeader::with_options(
+            ClientOrderId::unique_id(),
+            ExchangeAccountId::new(ExchangeId::new("Binance"), 0),
+            CurrencyPair::from_codes("btc".into(), "usdt".into()),
+            side,
+            dec!(1),
+            OrderOptions::limit(dec!(100)),
+            None,
+            None,
+            "test".to_owned(),
+        );
+        let header = match decision_price {
+            Some(decision_price) => header.with_decision_price(decision_price),
+            None => header,
+        };
+
+        OrdersPool::new().add_simple_initial(&header, Utc::now(), None)
+    }
+
+    fn fill(price: Price, amount: Amount) -> OrderFill {
+        fill_with_commission(price, amount, "usdt".into(), dec!(0))
+    }
+
+    fn fill_with_commission(
+        price: Price,
+        amount: Amount,
+        commission_currency_code: CurrencyCode,
+        commission_amount: Amount,
+    ) -> OrderFill {
+        OrderFill::new(
+            Uuid::new_v4(),
+            None,
+            Utc::now(),
+            OrderFillType::UserTrade,
+            None,
+            price,
+            amount,
+            price * amount,
+            OrderFillRole::Taker,
+            commission_currency_code,
+            commission_amount,
+            dec!(0),
+            commission_currency_code,
+            commission_amount,
+            commission_amount,
+            false,
+            Some(EventSourceType::WebSocket),
+            None,
+        )
+    }
+
+    #[test]
+    fn positive_slippage_for_buy_filled_above_decision_price() {
+        let order = order_with_decision_price(OrderSide::Buy, Some(dec!(100)));
+
+        let slippage = order.realized_slippage(&fill(dec!(101), dec!(2)));
+
+        assert_eq!(slippage, Some(dec!(2)));
+    }
+
+    #[test]
+    fn negative_slippage_for_sell_filled_above_decision_price() {
+        let order = order_with_decision_price(OrderSide::Sell, Some(dec!(100)));
+
+        let slippage = order.realized_slippage(&fill(dec!(101), dec!(2)));
+
+        assert_eq!(slippage, Some(dec!(-2)));
+    }
+
+    #[test]
+    fn no_slippage_without_decision_price() {
+        let order = order_with_decision_price(OrderSide::Buy, None);
+
+        let slippage = order.realized_slippage(&fill(dec!(101), dec!(2)));
+
+        assert_eq!(slippage, None);
+    }
+
+    #[test]
+    fn effective_price_of_buy_is_above_fill_price_by_commission() {
+        let order = order_with_decision_price(OrderSide::Buy, None);
+
+        let effective_price = order.effective_price(&fill_with_commission(
+            dec!(100),
+            dec!(2),
+            "usdt".into(),
+            dec!(0.2),
+        ));
+
+        assert!(effective_price > dec!(100));
+        assert_eq!(effective_price, dec!(100.1));
+    }
+
+    #[test]
+    fn effective_price_of_sell_with_commission_in_base_currency() {
+        let order = order_with_decision_price(OrderSide::Sell, None);
+
+        let effective_price = order.effective_price(&fill_with_commission(
+            dec!(100),
+            dec!(2),
+            "btc".into(),
+            dec!(0.002),
+        ));
+
+        assert_eq!(effective_price, dec!(99.9));
+    }
+
+    #[test]
+    fn total_commission_sums_fills_by_commission_currency() {
+        let order = order_with_decision_price(OrderSide::Buy, None);
+        order.fn_mut(|x| {
+            x.add_fill(fill_with_commission(
+                dec!(100),
+                dec!(0.5),
+                "usdt".into(),
+                dec!(0.05),
+            ));
+            x.add_fill(fill_with_commission(
+                dec!(101),
+                dec!(0.5),
+                "usdt".into(),
+                dec!(0.1),
+            ));
+        });
+
+        assert_eq!(
+            order.total_commission(),
+            HashMap::from([("usdt".into(), dec!(0.15))])
+        );
+
+        order.fn_mut(|x| {
+            x.add_fill(fill_with_commission(
+                dec!(101),
+                dec!(0.1),
+                "bnb".into(),
+                dec!(0.001),
+            ))
+        });
+
+        assert_eq!(
+            order.total_commission(),
+            HashMap::from([("usdt".into(), dec!(0.15)), ("bnb".into(), dec!(0.001))])
+        );
+    }
+}
@@ -111,12 +111,32 @@ pub enum OrderExecutionType {
     MakerOnly = 1,
 }
 
+/// How long order remains active on exchange
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone, Serialize, Deserialize, Hash)]
+pub enum OrderTimeInForce {
+    /// Good till canceled
+    #[default]
+    Gtc,
+    /// Immediate or cancel: part of order which can't be filled immediately is canceled
+    Ioc,
+    /// Fill or kill: order is canceled if it can't be filled immediately and entirely
+    Fok,
+    /// Good till time: order is canceled when specified time passes
+    Gtt(DateTime),
+}
+
 impl_str_id!(ClientOrderId);
 
 impl_from_for_str_id!(i64, ClientOrderId);
 impl_from_for_str_id!(u64, ClientOrderId);
 impl_from_for_str_id!(i32, ClientOrderId);
 
+impl ClientOrderId {
+    pub fn unique_id_with_prefix(prefix: &str) -> Self {
+        format!("{prefix}{}", Self::unique_id()).as_str().into()
+    }
+}
+
 impl From<&i32> for ClientOrderId {
     fn from(value: &i32) -> Self {
         Self::from(*value)
@@ -136,6 +156,61 @@ impl From<&i32> for ExchangeOrderId {
     }
 }
 
+/// Format of exchange order id, e.g. Binance uses integers and Bitmex uses UUIDs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExchangeOrderIdKind {
+    Numeric,
+    Uuid,
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmptyExchangeOrderIdError;
+
+impl Display for EmptyExchangeOrderIdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Exchange order id can't be empty")
+    }
+}
+
+impl std::error::Error for EmptyExchangeOrderIdError {}
+
+impl ExchangeOrderId {
+    /// Exchange order id received from exchange, which can't be empty
+    pub fn try_new(value: &str) -> Result<Self, EmptyExchangeOrderIdError> {
+        match value.is_empty() {
+            true => Err(EmptyExchangeOrderIdError),
+            false => Ok(value.into()),
+        }
+    }
+
+    /// Synthetic empty id for cases when the real one is unknown, e.g. in tests
+    pub fn placeholder() -> Self {
+        Self::new(SmallString::new())
+    }
+
+    pub fn is_placeholder(&self) -> bool {
+        self.is_empty()
+    }
+
+    /// Format of id, `None` for placeholder
+    pub fn kind(&self) -> Option<ExchangeOrderIdKind> {
+        if self.is_placeholder() {
+            return None;
+        }
+
+        let id = self.as_str();
+        let kind = if id.parse::<i64>().is_ok() || id.parse::<u64>().is_ok() {
+            ExchangeOrderIdKind::Numeric
+        } else if Uuid::parse_str(id).is_ok() {
+            ExchangeOrderIdKind::Uuid
+        } else {
+            ExchangeOrderIdKind::Other
+        };
+        Some(kind)
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize, Hash)]
 pub enum OrderStatus {
     Creating = 1,
@@ -201,6 +276,11 @@ impl UserOrder {
             execution_type: OrderExecutionType::MakerOnly,
         }
     }
+
+    /// Market order. Price isn't specified and order is filled by prices of order book
+    pub fn market() -> Self {
+        Self::Market
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -228,6 +308,11 @@ impl OrderOptions {
         Self::User(UserOrder::maker_only(price))
     }
 
+    /// Market order without price
+    pub fn market() -> Self {
+        Self::User(UserOrder::market())
+    }
+
     pub fn unknown(price: Option<Price>) -> Self {
         Self::Unknown { price }
     }
@@ -296,6 +381,19 @@ pub struct OrderHeader {
 
     pub signal_id: Option<String>,
     pub strategy_name: String,
+
+    /// Market price which was current when strategy decided to create the order.
+    /// Used for measuring execution quality of fills.
+    #[serde(default)]
+    pub decision_price: Option<Price>,
+
+    /// Order can only reduce current position, so exchange should reject it if it would open or flip the position.
+    /// Makes sense only for derivative markets
+    #[serde(default)]
+    pub reduce_only: bool,
+
+    #[serde(default)]
+    pub time_in_force: OrderTimeInForce,
 }
 
 impl OrderHeader {
@@ -348,9 +446,27 @@ impl OrderHeader {
             reservation_id,
             signal_id,
             strategy_name,
+            decision_price: None,
+            reduce_only: false,
+            time_in_force: OrderTimeInForce::default(),
         }
     }
 
+    pub fn with_decision_price(mut self, decision_price: Price) -> Self {
+        self.decision_price = Some(decision_price);
+        self
+    }
+
+    pub fn with_reduce_only(mut self) -> Self {
+        self.reduce_only = true;
+        self
+    }
+
+    pub fn with_time_in_force(mut self, time_in_force: OrderTimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
     pub fn market_account_id(&self) -> MarketAccountId {
         MarketAccountId {
             exchange_account_id: self.exchange_account_id,
@@ -448,6 +564,28 @@ impl OrderFills {
     pub fn last_fill_received_time(&self) -> Option<DateTime> {
         self.fills.last().map(|x| x.receive_time())
     }
+
+    /// Average price of fills calculated from their cost, so it doesn't depend on order price
+    /// and can be used for orders without price (e.g. `Market`).
+    /// Cost of derivative fill is `amount / price`, otherwise it's `amount * price`.
+    /// Returns `None` if there are no fills yet
+    pub fn average_fill_price(&self, is_derivative: bool) -> Option<Price> {
+        let (amount, cost) = self
+            .fills
+            .iter()
+            .fold((Amount::ZERO, Decimal::ZERO), |(amount, cost), fill| {
+                (amount + fill.amount(), cost + fill.cost())
+            });
+
+        if amount.is_zero() || cost.is_zero() {
+            return None;
+        }
+
+        Some(match is_derivative {
+            true => amount / cost,
+            false => cost / amount,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -462,6 +600,23 @@ pub struct OrderStatusHistory {
     status_changes: Vec<OrderStatusChange>,
 }
 
+impl OrderStatusHistory {
+    /// Last status change as (new status, time of previous status, time of new status).
+    /// Initial `Creating` status isn't stored in history, so `init_time` is used as its time
+    pub fn last_change_with_previous_time(
+        &self,
+        init_time: DateTime,
+    ) -> Option<(OrderStatus, DateTime, DateTime)> {
+        let (previous_changes, last_change) = match self.status_changes.as_slice() {
+            [previous_changes @ .., last_change] => (previous_changes, last_change),
+            [] => return None,
+        };
+
+        let previous_time = previous_changes.last().map_or(init_time, |x| x.time);
+        Some((last_change.status, previous_time, last_change.time))
+    }
+}
+
 /// Helping properties for trading engine internal use
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct SystemInternalOrderProps {
@@ -550,6 +705,19 @@ pub trait OrderInfoExtensionData: Any + DynClone + Send + Sync + Debug {
 
 clone_trait_object!(OrderInfoExtensionData);
 
+/// Unified reason of order rejection parsed from exchange specific messages
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderRejectReason {
+    InsufficientBalance,
+    PriceOutOfBounds,
+    MinNotional,
+    RateLimited,
+    /// Maker only order would be executed immediately as taker
+    PostOnlyRejected,
+    /// Message of exchange for rejection reasons which aren't recognized
+    Unknown(String),
+}
+
 // In some cases exchange doesn't send price, amount, average_fill_price and filled_amount values.
 // So it will be 0
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -566,6 +734,9 @@ pub struct OrderInfo {
     pub commission_currency_code: Option<String>,
     pub commission_rate: Option<Price>,
     pub commission_amount: Option<Amount>,
+    /// Reason of rejection if exchange rejected the order and reported why
+    #[serde(default)]
+    pub reject_reason: Option<OrderRejectReason>,
     pub extension_data: Option<Box<dyn OrderInfoExtensionData>>,
 }
 
@@ -598,9 +769,15 @@ impl OrderInfo {
             commission_currency_code,
             commission_rate,
             commission_amount,
+            reject_reason: None,
             extension_data: None,
         }
     }
+
+    pub fn with_reject_reason(mut self, reject_reason: OrderRejectReason) -> Self {
+        self.reject_reason = Some(reject_reason);
+        self
+    }
 }
 
 /// Mutable part of order
@@ -768,6 +945,26 @@ impl PriceByOrderSide {
     pub fn new(top_bid: Option<Price>, top_ask: Option<Price>) -> Self {
         Self { top_bid, top_ask }
     }
+
+    /// Middle of top bid and top ask. Returns `None` if any side is empty
+    pub fn mid_price(&self) -> Option<Price> {
+        Some((self.top_bid? + self.top_ask?) / Decimal::TWO)
+    }
+
+    /// Mid price weighted by sizes of top levels, so it leans to the side with the smaller size
+    /// which is more likely to be taken first.
+    /// Returns `None` if any side is empty or both sizes are zero
+    pub fn weighted_mid(&self, bid_size: Amount, ask_size: Amount) -> Option<Price> {
+        let top_bid = self.top_bid?;
+        let top_ask = self.top_ask?;
+
+        let total_size = bid_size + ask_size;
+        if total_size.is_zero() {
+            return None;
+        }
+
+        Some((top_bid * ask_size + top_ask * bid_size) / total_size)
+    }
 }
 
 impl Display for PriceByOrderSide {
@@ -792,3 +989,61 @@ fn set_status(
         time,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn mid_price_of_symmetric_book() {
+        let prices = PriceByOrderSide::new(Some(dec!(99)), Some(dec!(101)));
+
+        assert_eq!(prices.mid_price(), Some(dec!(100)));
+        assert_eq!(prices.weighted_mid(dec!(5), dec!(5)), Some(dec!(100)));
+    }
+
+    #[test]
+    fn weighted_mid_of_asymmetric_book() {
+        let prices = PriceByOrderSide::new(Some(dec!(99)), Some(dec!(101)));
+
+        // bigger bid size pushes price to the ask side
+        assert_eq!(prices.weighted_mid(dec!(3), dec!(1)), Some(dec!(100.5)));
+        assert_eq!(prices.weighted_mid(dec!(1), dec!(3)), Some(dec!(99.5)));
+        assert_eq!(prices.weighted_mid(dec!(0), dec!(0)), None);
+    }
+
+    #[test]
+    fn mid_price_of_one_sided_book() {
+        let prices = PriceByOrderSide::new(Some(dec!(99)), None);
+
+        assert_eq!(prices.mid_price(), None);
+        assert_eq!(prices.weighted_mid(dec!(1), dec!(1)), None);
+    }
+
+    #[rstest]
+    #[case::numeric("8589934592", ExchangeOrderIdKind::Numeric)]
+    #[case::negative_numeric("-42", ExchangeOrderIdKind::Numeric)]
+    #[case::uuid("0b7d6b4a-3b1c-4a3e-9c8f-2d7e1f5a6b90", ExchangeOrderIdKind::Uuid)]
+    #[case::other("order_1", ExchangeOrderIdKind::Other)]
+    fn exchange_order_id_kind(#[case] id: &str, #[case] expected_kind: ExchangeOrderIdKind) {
+        let exchange_order_id = ExchangeOrderId::try_new(id).expect("in test");
+
+        assert_eq!(exchange_order_id.kind(), Some(expected_kind));
+        assert!(!exchange_order_id.is_placeholder());
+    }
+
+    #[test]
+    fn empty_exchange_order_id_is_placeholder() {
+        assert_eq!(ExchangeOrderId::try_new(""), Err(EmptyExchangeOrderIdError));
+
+        let placeholder = ExchangeOrderId::placeholder();
+        assert!(placeholder.is_placeholder());
+        assert_eq!(placeholder.kind(), None);
+        assert_eq!(
+            ExchangeOrderId::from(42).kind(),
+            Some(ExchangeOrderIdKind::Numeric)
+        );
+    }
+}
@@ -34,6 +34,24 @@ impl DerivativePosition {
         }
     }
 
+    /// Unrealized PnL of position by `mark_price`.
+    /// For linear contract it's `(mark - entry) * size` in quote currency,
+    /// for inverse contract it's `size * (1 / entry - 1 / mark)` in base currency.
+    /// Negative position means short, so PnL is positive when mark price is below entry price
+    pub fn unrealized_pnl(&self, mark_price: Price, is_inverse: bool) -> Decimal {
+        match is_inverse {
+            false => self.position * (mark_price - self.average_entry_price),
+            true => {
+                if mark_price.is_zero() || self.average_entry_price.is_zero() {
+                    return Decimal::ZERO;
+                }
+
+                self.position
+                    * (Decimal::ONE / self.average_entry_price - Decimal::ONE / mark_price)
+            }
+        }
+    }
+
     pub fn get_side(&self) -> OrderSide {
         debug_assert!(!self.position.is_zero());
 
@@ -45,6 +63,17 @@ impl DerivativePosition {
     }
 }
 
+/// How far mark price can move against derivative position before the position is liquidated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiquidationDistance {
+    /// Adverse price move from mark price
+    pub price_distance: Price,
+    /// `price_distance` as percent of mark price
+    pub percent: Decimal,
+    /// Mark price at which position is expected to be liquidated
+    pub liquidation_price: Price,
+}
+
 #[derive(Debug)]
 pub struct ClosedPosition {
     pub exchange_order_id: ExchangeOrderId,
@@ -121,3 +150,37 @@ impl ActivePosition {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn position(position: Amount, average_entry_price: Price) -> DerivativePosition {
+        DerivativePosition::new(
+            CurrencyPair::from_codes("btc".into(), "usd".into()),
+            position,
+            average_entry_price,
+            dec!(0),
+            dec!(1),
+        )
+    }
+
+    #[test]
+    fn unrealized_pnl_of_long_linear_position() {
+        let position = position(dec!(2), dec!(100));
+
+        assert_eq!(position.unrealized_pnl(dec!(110), false), dec!(20));
+        assert_eq!(position.unrealized_pnl(dec!(95), false), dec!(-10));
+    }
+
+    #[test]
+    fn unrealized_pnl_of_short_inverse_position() {
+        let position = position(dec!(-1000), dec!(20000));
+
+        // -1000 * (1 / 20000 - 1 / 25000)
+        assert_eq!(position.unrealized_pnl(dec!(25000), true), dec!(-0.01));
+        // -1000 * (1 / 20000 - 1 / 16000)
+        assert_eq!(position.unrealized_pnl(dec!(16000), true), dec!(0.0125));
+    }
+}
@@ -0,0 +1,10 @@
+CREATE TABLE order_fills (
+    id bigint PRIMARY KEY GENERATED BY DEFAULT AS IDENTITY,
+    insert_time timestamp WITH TIME ZONE NOT NULL DEFAULT now(),
+    version int,
+    json jsonb NOT NULL
+);
+
+CREATE INDEX order_fills__insert_time_idx ON order_fills USING btree (insert_time);
+CREATE INDEX order_fills__receive_time_idx ON order_fills USING btree (((json ->> 'receive_time')::text));
+CREATE INDEX order_fills__trade_id_idx ON order_fills USING btree (((json ->> 'trade_id')::text));
@@ -130,6 +130,7 @@ impl ErrorHandler for ErrorHandlerBinance {
             | "Filter failure: PERCENT_PRICE"
             | "Quantity less than zero."
             | "Precision is over the maximum defined for this asset." => InvalidOrder,
+            "Order would immediately match and take." => PostOnlyRejected,
             msg if msg.contains("Too many requests;") => RateLimit,
             _ => Unknown,
         }
@@ -384,7 +385,8 @@ impl Binance {
         // TODO need special handler for OCO orders
 
         let exchange_order_id = json_response["i"].to_string();
-        let exchange_order_id = exchange_order_id.trim_matches('"');
+        let exchange_order_id = ExchangeOrderId::try_new(exchange_order_id.trim_matches('"'))
+            .with_context(|| format!("Unable to parse exchange order id from {msg_to_log}"))?;
         let execution_type = json_response["x"]
             .as_str()
             .ok_or_else(|| anyhow!("Unable to parse execution type"))?;
@@ -410,7 +412,7 @@ impl Binance {
                 "NEW" => {
                     (self.order_created_callback)(
                         client_order_id.into(),
-                        exchange_order_id.into(),
+                        exchange_order_id,
                         EventSourceType::WebSocket,
                     );
                 }
@@ -420,7 +422,7 @@ impl Binance {
                 "CANCELED" => {
                     (self.order_cancelled_callback)(
                         client_order_id.into(),
-                        exchange_order_id.into(),
+                        exchange_order_id,
                         EventSourceType::WebSocket,
                     );
                 }
@@ -434,7 +436,7 @@ impl Binance {
                 "GTX" => {
                     (self.order_cancelled_callback)(
                         client_order_id.into(),
-                        exchange_order_id.into(),
+                        exchange_order_id,
                         EventSourceType::WebSocket,
                     );
                 }
@@ -445,7 +447,7 @@ impl Binance {
                     &json_response,
                     execution_type,
                     client_order_id.into(),
-                    exchange_order_id.into(),
+                    exchange_order_id,
                     event_time,
                 )?;
 
@@ -547,8 +549,33 @@ impl Binance {
         let deserialized: OrderId = serde_json::from_str(&response.content)
             .map_err(|err| ExchangeError::parsing(format!("Unable to parse orderId: {err:?}")))?;
 
-        let order_id_str = deserialized.order_id.to_string().into();
-        Ok(ExchangeOrderId::new(order_id_str))
+        ExchangeOrderId::try_new(&deserialized.order_id.to_string())
+            .map_err(|err| ExchangeError::parsing(format!("Unable to parse orderId: {err}")))
+    }
+
+    pub(super) fn get_replacing_order_id(
+        &self,
+        response: &RestResponse,
+    ) -> Result<ExchangeOrderId, ExchangeError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct OrderId {
+            order_id: u64,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct CancelReplaceResponse {
+            new_order_response: OrderId,
+        }
+
+        let deserialized: CancelReplaceResponse =
+            serde_json::from_str(&response.content).map_err(|err| {
+                ExchangeError::parsing(format!("Unable to parse newOrderResponse: {err:?}"))
+            })?;
+
+        ExchangeOrderId::try_new(&deserialized.new_order_response.order_id.to_string())
+            .map_err(|err| ExchangeError::parsing(format!("Unable to parse orderId: {err}")))
     }
 
     pub(super) fn get_uri_path<'a>(
@@ -735,14 +762,15 @@ impl Binance {
             .await
     }
 
+    /// Balances with update time of account
     pub(super) fn parse_spot_balance(
         &self,
         response: &RestResponse,
-    ) -> Result<Vec<ExchangeBalance>> {
+    ) -> Result<(Vec<ExchangeBalance>, DateTime)> {
         let binance_account_info: BinanceSpotAccountInfo =
             serde_json::from_str(&response.content).context("Unable to parse account info")?;
 
-        Ok(binance_account_info
+        let balances = binance_account_info
             .balances
             .iter()
             .filter_map(|balance| {
@@ -752,17 +780,20 @@ impl Binance {
                         balance: balance.free,
                     })
             })
-            .collect_vec())
+            .collect_vec();
+
+        Ok((balances, u64_to_date_time(binance_account_info.update_time)))
     }
 
+    /// Balances with the latest update time of assets, which is None if no asset was updated
     pub(super) fn parse_derivative_balance(
         &self,
         response: &RestResponse,
-    ) -> Result<Vec<ExchangeBalance>> {
+    ) -> Result<(Vec<ExchangeBalance>, Option<DateTime>)> {
         let binance_account_info: BinanceDerivativeAccountInfo =
             serde_json::from_str(&response.content).context("Unable to parse account info")?;
 
-        Ok(binance_account_info
+        let balances = binance_account_info
             .assets
             .iter()
             .filter_map(|balance| {
@@ -772,7 +803,16 @@ impl Binance {
                         balance: balance.available_balance,
                     })
             })
-            .collect_vec())
+            .collect_vec();
+        let update_time = binance_account_info
+            .assets
+            .iter()
+            .map(|balance| balance.update_time)
+            .filter(|&update_time| update_time > 0)
+            .max()
+            .map(u64_to_date_time);
+
+        Ok((balances, update_time))
     }
 
     #[named]
@@ -802,16 +842,23 @@ impl Binance {
         &self,
         symbol: &Symbol,
         last_date_time: Option<DateTime>,
+        from_trade_id: Option<&TradeId>,
     ) -> Result<RestResponse, ExchangeError> {
         let specific_currency_pair = self.get_specific_currency_pair(symbol.currency_pair());
 
         let path = self.get_uri_path("/fapi/v1/userTrades", "/api/v3/myTrades");
         let mut builder = UriBuilder::from_path(path);
-        if let Some(last_date_time_value) = last_date_time {
-            builder.add_kv(
-                "startTime",
-                last_date_time_value.timestamp_millis().to_string(),
-            );
+        // startTime can't be combined with fromId
+        match from_trade_id {
+            Some(from_trade_id) => builder.add_kv("fromId", from_trade_id.number()),
+            None => {
+                if let Some(last_date_time_value) = last_date_time {
+                    builder.add_kv(
+                        "startTime",
+                        last_date_time_value.timestamp_millis().to_string(),
+                    );
+                }
+            }
         }
         builder.add_kv("symbol", specific_currency_pair);
         self.add_authentification(&mut builder);
@@ -892,11 +939,30 @@ impl Binance {
     ) -> Result<RestResponse, ExchangeError> {
         let header = order.header();
         let specific_currency_pair = self.get_specific_currency_pair(header.currency_pair);
-        let is_margin_trading = self.settings.is_margin_trading;
 
         let path = self.get_uri_path("/fapi/v1/order", "/api/v3/order");
         let mut builder = UriBuilder::from_path(path);
         builder.add_kv("symbol", specific_currency_pair);
+        self.add_order_params(&mut builder, header)?;
+
+        self.add_authentification(&mut builder);
+
+        let (uri, query) = builder.build_uri_and_query(self.hosts.rest_uri_host(), false);
+
+        let log_args = format!("Create order for {header:?}");
+        self.rest_client
+            .post(uri, Some(query), function_name!(), log_args)
+            .await
+    }
+
+    fn add_order_params(
+        &self,
+        builder: &mut UriBuilder,
+        header: &OrderHeader,
+    ) -> Result<(), ExchangeError> {
+        let is_margin_trading = self.settings.is_margin_trading;
+        let time_in_force = get_server_time_in_force(header.time_in_force);
+
         builder.add_kv("side", get_server_order_side(header.side));
         builder.add_kv("quantity", header.amount);
         builder.add_kv("newClientOrderId", &header.client_order_id);
@@ -910,7 +976,7 @@ impl Binance {
                     match execution_type {
                         OrderExecutionType::None => {
                             builder.add_kv("type", "LIMIT");
-                            builder.add_kv("timeInForce", "GTC");
+                            builder.add_kv("timeInForce", time_in_force);
                         }
                         OrderExecutionType::MakerOnly => builder.add_kv("type", "LIMIT_MAKER"),
                     }
@@ -944,7 +1010,7 @@ impl Binance {
                     builder.add_kv("price", price);
                     match *execution_type == OrderExecutionType::MakerOnly {
                         true => builder.add_kv("timeInForce", "GTX"),
-                        false => builder.add_kv("timeInForce", "GTC"),
+                        false => builder.add_kv("timeInForce", time_in_force),
                     }
                 }
                 UserOrder::Market => builder.add_kv("type", "MARKET"),
@@ -960,11 +1026,37 @@ impl Binance {
             _ => return Err(ExchangeError::unknown("Unexpected order type")),
         }
 
+        if is_margin_trading && header.reduce_only {
+            builder.add_kv("reduceOnly", "true");
+        }
+
+        Ok(())
+    }
+
+    #[named]
+    pub(super) async fn request_cancel_replace_order(
+        &self,
+        order: &OrderRef,
+        exchange_order_id: &ExchangeOrderId,
+        new_order: &OrderRef,
+    ) -> Result<RestResponse, ExchangeError> {
+        let new_header = new_order.header();
+        let specific_currency_pair = self.get_specific_currency_pair(new_header.currency_pair);
+
+        let mut builder = UriBuilder::from_path("/api/v3/order/cancelReplace");
+        builder.add_kv("symbol", specific_currency_pair);
+        builder.add_kv("cancelReplaceMode", "STOP_ON_FAILURE");
+        builder.add_kv("cancelOrderId", exchange_order_id);
+        self.add_order_params(&mut builder, new_header)?;
+
         self.add_authentification(&mut builder);
 
         let (uri, query) = builder.build_uri_and_query(self.hosts.rest_uri_host(), false);
 
-        let log_args = format!("Create order for {header:?}");
+        let log_args = format!(
+            "Cancel-replace order {} with {new_header:?}",
+            order.client_order_id()
+        );
         self.rest_client
             .post(uri, Some(query), function_name!(), log_args)
             .await
@@ -1147,6 +1239,16 @@ pub(super) fn get_server_order_side(side: OrderSide) -> &'static str {
     }
 }
 
+pub(super) fn get_server_time_in_force(time_in_force: OrderTimeInForce) -> &'static str {
+    match time_in_force {
+        OrderTimeInForce::Gtc => "GTC",
+        OrderTimeInForce::Ioc => "IOC",
+        OrderTimeInForce::Fok => "FOK",
+        // good-till-time orders aren't supported, so they are canceled by bot on expiry
+        OrderTimeInForce::Gtt(_) => "GTC",
+    }
+}
+
 pub(super) fn get_local_order_side(side: &str) -> OrderSide {
     match side {
         "BUY" => OrderSide::Buy,
@@ -1165,6 +1267,55 @@ fn get_local_order_status(status: &str) -> OrderStatus {
     }
 }
 
+/// Map rejection error of Binance to unified reason.
+/// Binance doesn't report rejection reason in order info, it's available only in error payload of rejected request
+pub fn get_order_reject_reason(code: i64, message: &str) -> OrderRejectReason {
+    // -1003 TOO_MANY_REQUESTS
+    // -1015 TOO_MANY_ORDERS
+    if matches!(code, -1003 | -1015) {
+        return OrderRejectReason::RateLimited;
+    }
+
+    match message {
+        "Account has insufficient balance for requested action." | "Margin is insufficient." => {
+            OrderRejectReason::InsufficientBalance
+        }
+        "Filter failure: PRICE_FILTER"
+        | "Filter failure: PERCENT_PRICE"
+        | "Filter failure: PERCENT_PRICE_BY_SIDE" => OrderRejectReason::PriceOutOfBounds,
+        "Filter failure: MIN_NOTIONAL" | "Filter failure: NOTIONAL" => {
+            OrderRejectReason::MinNotional
+        }
+        msg if msg.starts_with("Order's notional must be no smaller than") => {
+            OrderRejectReason::MinNotional
+        }
+        "Order would immediately match and take." => OrderRejectReason::PostOnlyRejected,
+        msg if msg.contains("Too many requests;") => OrderRejectReason::RateLimited,
+        msg => OrderRejectReason::Unknown(msg.to_owned()),
+    }
+}
+
+/// Parse Binance error payload like `{"code":-2010,"msg":"..."}` into unified rejection reason
+pub fn parse_order_reject_reason(content: &str) -> Result<OrderRejectReason> {
+    #[derive(Deserialize)]
+    struct Error<'a> {
+        msg: &'a str,
+        code: i64,
+    }
+
+    let error: Error = serde_json::from_str(content)
+        .with_context(|| format!("Unable to parse Binance error: {content}"))?;
+
+    Ok(get_order_reject_reason(error.code, error.msg))
+}
+
+/// Unified rejection reason of failed order request. Only errors reported by Binance have code
+pub(crate) fn get_error_reject_reason(error: &ExchangeError) -> Option<OrderRejectReason> {
+    error
+        .code
+        .map(|code| get_order_reject_reason(code, &error.message))
+}
+
 pub struct BinanceBuilder;
 
 impl ExchangeClientBuilder for BinanceBuilder {
@@ -1216,10 +1367,13 @@ impl ExchangeClientBuilder for BinanceBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use hyper::StatusCode;
     use mmb_core::exchanges::timeouts::requests_timeout_manager_factory::RequestsTimeoutManagerFactory;
     use mmb_core::lifecycle::launcher::EngineBuildConfig;
     use mmb_utils::cancellation_token::CancellationToken;
     use mmb_utils::hashmap;
+    use rstest::rstest;
+    use rust_decimal_macros::dec;
 
     pub(crate) fn get_timeout_manager(
         exchange_account_id: ExchangeAccountId,
@@ -1279,4 +1433,184 @@ mod tests {
 
         assert_eq!(signature_value, expected);
     }
+
+    #[rstest]
+    #[case::spot(false, "type=LIMIT_MAKER")]
+    #[case::margin(true, "timeInForce=GTX")]
+    fn maker_only_order_params(#[case] is_margin_trading: bool, #[case] expected_param: &str) {
+        let exchange_account_id: ExchangeAccountId = "Binance_0".parse().expect("in test");
+        let settings = ExchangeSettings::new_short(
+            exchange_account_id,
+            "api_key".into(),
+            "secret_key".into(),
+            is_margin_trading,
+        );
+
+        let (tx, _) = broadcast::channel(10);
+        let binance = Binance::new(
+            exchange_account_id,
+            settings,
+            tx,
+            AppLifetimeManager::new(CancellationToken::default()),
+            get_timeout_manager(exchange_account_id),
+            false,
+        );
+
+        let header = OrderHeader::with_options(
+            ClientOrderId::unique_id(),
+            exchange_account_id,
+            CurrencyPair::from_codes("eth".into(), "btc".into()),
+            OrderSide::Buy,
+            dec!(1),
+            OrderOptions::maker_only(dec!(0.05)),
+            None,
+            None,
+            "test".to_owned(),
+        );
+
+        let mut builder = UriBuilder::from_path("/test");
+        binance
+            .add_order_params(&mut builder, &header)
+            .expect("in test");
+
+        let query = String::from_utf8_lossy(builder.query()).into_owned();
+        assert!(
+            query.split('&').any(|param| param == expected_param),
+            "{query}"
+        );
+    }
+
+    #[rstest]
+    #[case::spot_ioc(false, OrderTimeInForce::Ioc, "timeInForce=IOC")]
+    #[case::spot_fok(false, OrderTimeInForce::Fok, "timeInForce=FOK")]
+    #[case::margin_ioc(true, OrderTimeInForce::Ioc, "timeInForce=IOC")]
+    #[case::gtt(false, OrderTimeInForce::Gtt(Utc::now()), "timeInForce=GTC")]
+    fn time_in_force_order_params(
+        #[case] is_margin_trading: bool,
+        #[case] time_in_force: OrderTimeInForce,
+        #[case] expected_param: &str,
+    ) {
+        let exchange_account_id: ExchangeAccountId = "Binance_0".parse().expect("in test");
+        let settings = ExchangeSettings::new_short(
+            exchange_account_id,
+            "api_key".into(),
+            "secret_key".into(),
+            is_margin_trading,
+        );
+
+        let (tx, _) = broadcast::channel(10);
+        let binance = Binance::new(
+            exchange_account_id,
+            settings,
+            tx,
+            AppLifetimeManager::new(CancellationToken::default()),
+            get_timeout_manager(exchange_account_id),
+            false,
+        );
+
+        let header = OrderHeader::with_options(
+            ClientOrderId::unique_id(),
+            exchange_account_id,
+            CurrencyPair::from_codes("eth".into(), "btc".into()),
+            OrderSide::Buy,
+            dec!(1),
+            OrderOptions::limit(dec!(0.05)),
+            None,
+            None,
+            "test".to_owned(),
+        )
+        .with_time_in_force(time_in_force);
+
+        let mut builder = UriBuilder::from_path("/test");
+        binance
+            .add_order_params(&mut builder, &header)
+            .expect("in test");
+
+        let query = String::from_utf8_lossy(builder.query()).into_owned();
+        assert!(
+            query.split('&').any(|param| param == expected_param),
+            "{query}"
+        );
+    }
+
+    #[rstest]
+    #[case(
+        r#"{"code":-2010,"msg":"Account has insufficient balance for requested action."}"#,
+        OrderRejectReason::InsufficientBalance
+    )]
+    #[case(
+        r#"{"code":-1013,"msg":"Filter failure: PERCENT_PRICE"}"#,
+        OrderRejectReason::PriceOutOfBounds
+    )]
+    #[case(
+        r#"{"code":-1013,"msg":"Filter failure: MIN_NOTIONAL"}"#,
+        OrderRejectReason::MinNotional
+    )]
+    #[case(
+        r#"{"code":-1015,"msg":"Too many new orders; current limit is 50 orders per 10 SECOND."}"#,
+        OrderRejectReason::RateLimited
+    )]
+    #[case(
+        r#"{"code":-2010,"msg":"Order would immediately match and take."}"#,
+        OrderRejectReason::PostOnlyRejected
+    )]
+    #[case(
+        r#"{"code":-1013,"msg":"Filter failure: LOT_SIZE"}"#,
+        OrderRejectReason::Unknown("Filter failure: LOT_SIZE".to_owned())
+    )]
+    fn map_order_reject_reason(#[case] content: &str, #[case] expected: OrderRejectReason) {
+        let reject_reason = parse_order_reject_reason(content).expect("in test");
+
+        assert_eq!(reject_reason, expected);
+    }
+
+    #[test]
+    fn spot_balance_event_time_from_payload() {
+        let exchange_account_id: ExchangeAccountId = "Binance_0".parse().expect("in test");
+        let settings = ExchangeSettings::new_short(
+            exchange_account_id,
+            "api_key".into(),
+            "secret_key".into(),
+            false,
+        );
+        let (tx, _) = broadcast::channel(10);
+        let binance = Binance::new(
+            exchange_account_id,
+            settings,
+            tx,
+            AppLifetimeManager::new(CancellationToken::default()),
+            get_timeout_manager(exchange_account_id),
+            false,
+        );
+
+        let response = RestResponse::new(
+            r#"{"updateTime":1668500000000,"balances":[{"asset":"BTC","free":"1.5","locked":"0"}]}"#
+                .to_owned(),
+            StatusCode::OK,
+        );
+
+        let (_, event_time) = binance.parse_spot_balance(&response).expect("in test");
+
+        assert_eq!(event_time, u64_to_date_time(1668500000000));
+    }
+
+    #[test]
+    fn reject_reason_of_order_request_error() {
+        let exchange_error = ExchangeError::new(
+            ExchangeErrorType::Unknown,
+            "Filter failure: MIN_NOTIONAL".to_owned(),
+            Some(-1013),
+        );
+        assert_eq!(
+            get_error_reject_reason(&exchange_error),
+            Some(OrderRejectReason::MinNotional)
+        );
+
+        let send_error = ExchangeError::new(
+            ExchangeErrorType::SendError,
+            "Connection reset".to_owned(),
+            None,
+        );
+        assert_eq!(get_error_reject_reason(&send_error), None);
+    }
 }
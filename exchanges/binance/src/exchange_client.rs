@@ -1,4 +1,4 @@
-use super::binance::Binance;
+use super::binance::{get_error_reject_reason, Binance};
 use crate::support::BinanceOrderInfo;
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
@@ -11,7 +11,7 @@ use mmb_core::exchanges::general::order::get_order_trades::OrderTrade;
 use mmb_core::exchanges::general::request_type::RequestType;
 use mmb_core::exchanges::rest_client::UriBuilder;
 use mmb_core::exchanges::traits::{ExchangeClient, ExchangeError, Support};
-use mmb_domain::events::{EventSourceType, ExchangeBalancesAndPositions};
+use mmb_domain::events::{EventSourceType, ExchangeBalancesAndPositions, TradeId};
 use mmb_domain::exchanges::symbol::Symbol;
 use mmb_domain::market::CurrencyPair;
 use mmb_domain::order::pool::OrderRef;
@@ -29,7 +29,13 @@ impl ExchangeClient for Binance {
                 Ok(order_id) => CreateOrderResult::succeed(&order_id, EventSourceType::Rest),
                 Err(error) => CreateOrderResult::failed(error, EventSourceType::Rest),
             },
-            Err(err) => CreateOrderResult::failed(err, EventSourceType::Rest),
+            Err(err) => {
+                let err = match get_error_reject_reason(&err) {
+                    Some(reject_reason) => err.with_reject_reason(reject_reason),
+                    None => err,
+                };
+                CreateOrderResult::failed(err, EventSourceType::Rest)
+            }
         }
     }
 
@@ -46,6 +52,29 @@ impl ExchangeClient for Binance {
         }
     }
 
+    fn supports_cancel_replace(&self) -> bool {
+        // cancelReplace endpoint is available only for spot trading
+        !self.settings.is_margin_trading
+    }
+
+    async fn cancel_replace_order(
+        &self,
+        order: &OrderRef,
+        exchange_order_id: &ExchangeOrderId,
+        new_order: &OrderRef,
+    ) -> CreateOrderResult {
+        match self
+            .request_cancel_replace_order(order, exchange_order_id, new_order)
+            .await
+        {
+            Ok(request_outcome) => match self.get_replacing_order_id(&request_outcome) {
+                Ok(order_id) => CreateOrderResult::succeed(&order_id, EventSourceType::Rest),
+                Err(error) => CreateOrderResult::failed(error, EventSourceType::Rest),
+            },
+            Err(err) => CreateOrderResult::failed(err, EventSourceType::Rest),
+        }
+    }
+
     #[named]
     async fn cancel_all_orders(&self, currency_pair: CurrencyPair) -> Result<()> {
         let specific_currency_pair = self.get_specific_currency_pair(currency_pair);
@@ -83,7 +112,11 @@ impl ExchangeClient for Binance {
     async fn get_order_info(&self, order: &OrderRef) -> Result<OrderInfo, ExchangeError> {
         match self.request_order_info(order).await {
             Ok(request_outcome) => Ok(self.parse_order_info(&request_outcome)),
-            Err(error) => Err(ExchangeError::parsing(error.to_string())),
+            Err(error) => {
+                let mut order_info_error = ExchangeError::parsing(error.to_string());
+                order_info_error.reject_reason = get_error_reject_reason(&error);
+                Err(order_info_error)
+            }
         }
     }
 
@@ -115,20 +148,24 @@ impl ExchangeClient for Binance {
             true => {
                 let (balance_response, position_response) =
                     tokio::join!(self.request_get_balance(), self.request_get_position());
+                let (balances, event_time) = self.parse_derivative_balance(&balance_response?)?;
                 ExchangeBalancesAndPositions {
-                    balances: self.parse_derivative_balance(&balance_response?)?,
+                    balances,
                     positions: Some(
                         self.get_active_positions(&position_response?)?
                             .map(|position| Ok::<_, anyhow::Error>(position?.derivative))
                             .try_collect()?,
                     ),
+                    event_time,
                 }
             }
             false => {
                 let balance_response = self.request_get_balance().await?;
+                let (balances, event_time) = self.parse_spot_balance(&balance_response)?;
                 ExchangeBalancesAndPositions {
-                    balances: self.parse_spot_balance(&balance_response)?,
+                    balances,
                     positions: None,
+                    event_time: Some(event_time),
                 }
             }
         })
@@ -140,7 +177,7 @@ impl ExchangeClient for Binance {
         last_date_time: Option<DateTime>,
     ) -> RequestResult<Vec<OrderTrade>> {
         // TODO Add metric UseTimeMetric(RequestType::GetMyTrades)
-        match self.request_my_trades(symbol, last_date_time).await {
+        match self.request_my_trades(symbol, last_date_time, None).await {
             Ok(response) => match self.parse_get_my_trades(&response, last_date_time) {
                 Ok(data) => RequestResult::Success(data),
                 Err(_) => RequestResult::Error(ExchangeError::unknown(&response.content)),
@@ -149,6 +186,24 @@ impl ExchangeClient for Binance {
         }
     }
 
+    async fn get_my_trades_from(
+        &self,
+        symbol: &Symbol,
+        from_datetime: DateTime,
+        from_trade_id: Option<&TradeId>,
+    ) -> RequestResult<Vec<OrderTrade>> {
+        match self
+            .request_my_trades(symbol, Some(from_datetime), from_trade_id)
+            .await
+        {
+            Ok(response) => match self.parse_get_my_trades(&response, None) {
+                Ok(data) => RequestResult::Success(data),
+                Err(_) => RequestResult::Error(ExchangeError::unknown(&response.content)),
+            },
+            Err(err) => RequestResult::Error(ExchangeError::parsing(err.to_string())),
+        }
+    }
+
     async fn build_all_symbols(&self) -> Result<Vec<Arc<Symbol>>> {
         let response = &self.request_all_symbols().await?;
         self.parse_all_symbols(response)
@@ -62,6 +62,8 @@ pub(crate) struct BinanceDerivativeAccountInfo<'a> {
 #[serde(bound(deserialize = "'de: 'a"))]
 pub(crate) struct BinanceSpotAccountInfo<'a> {
     pub(crate) balances: Vec<BinanceSpotBalances<'a>>,
+    #[serde(rename = "updateTime")]
+    pub(crate) update_time: u64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -84,12 +86,13 @@ pub(super) struct BinanceSpotBalances<'a> {
 /// available_balance: Decimal,         // available balance
 /// max_withdraw_amount: Decimal,       // maximum amount for transfer out
 /// margin_available: bool, // whether the asset can be used as margin in Multi-Assets mode
-/// update_time: Decimal,   // last update time
+/// update_time: u64,       // last update time
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct BinanceDerivativeBalances<'a> {
     pub(super) asset: &'a str,             // asset name
     pub(super) available_balance: Decimal, // available balance
+    pub(super) update_time: u64,           // last update time
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -36,8 +36,8 @@ use mmb_domain::market::{
 };
 use mmb_domain::order::pool::{OrderRef, OrdersPool};
 use mmb_domain::order::snapshot::{
-    ExchangeOrderId, ExternalOrder, OrderExecutionType, OrderInfo, OrderOptions, OrderRole,
-    OrderSide, OrderStatus, Price, UserOrder,
+    ExchangeOrderId, ExternalOrder, OrderExecutionType, OrderHeader, OrderInfo, OrderOptions,
+    OrderRejectReason, OrderRole, OrderSide, OrderStatus, OrderTimeInForce, Price, UserOrder,
 };
 use mmb_domain::position::{ActivePosition, ClosedPosition, DerivativePosition};
 use mmb_utils::DateTime;
@@ -164,6 +164,10 @@ impl RestHeaders for RestHeadersBitmex {
 }
 
 const EMPTY_RESPONSE_IS_OK: bool = false;
+/// Execution instruction of maker only order
+const PARTICIPATE_DO_NOT_INITIATE: &str = "ParticipateDoNotInitiate";
+/// Max count of orders which Bitmex returns by single GET /api/v1/order request
+pub(crate) const MAX_ORDERS_INFO_PER_REQUEST: usize = 500;
 
 pub struct Bitmex {
     pub(crate) settings: ExchangeSettings,
@@ -316,10 +320,24 @@ impl Bitmex {
 
         let mut builder = UriBuilder::from_path("/api/v1/order");
         builder.add_kv("symbol", specific_currency_pair);
+        Self::add_order_params(&mut builder, header)?;
+
+        let uri = builder.build_uri(self.hosts.rest_uri_host(), true);
+        let log_args = format!("Create order for {header:?}");
+        self.rest_client
+            .post(uri, None, function_name!(), log_args)
+            .await
+    }
+
+    fn add_order_params(
+        builder: &mut UriBuilder,
+        header: &OrderHeader,
+    ) -> Result<(), ExchangeError> {
         builder.add_kv("side", header.side);
         builder.add_kv("orderQty", header.amount);
         builder.add_kv("clOrdID", header.client_order_id.as_str());
 
+        let mut exec_instructions = Vec::new();
         match header.options {
             OrderOptions::User(user_order) => match user_order {
                 UserOrder::Limit {
@@ -329,7 +347,7 @@ impl Bitmex {
                     builder.add_kv("ordType", "Limit");
                     builder.add_kv("price", price);
                     if execution_type == OrderExecutionType::MakerOnly {
-                        builder.add_kv("execInst", "ParticipateDoNotInitiate");
+                        exec_instructions.push(PARTICIPATE_DO_NOT_INITIATE);
                     }
                 }
                 UserOrder::Market => builder.add_kv("ordType", "Market"),
@@ -357,11 +375,21 @@ impl Bitmex {
             _ => return Err(ExchangeError::unknown("Unexpected order type")),
         }
 
-        let uri = builder.build_uri(self.hosts.rest_uri_host(), true);
-        let log_args = format!("Create order for {header:?}");
-        self.rest_client
-            .post(uri, None, function_name!(), log_args)
-            .await
+        match header.time_in_force {
+            OrderTimeInForce::Ioc => builder.add_kv("timeInForce", "ImmediateOrCancel"),
+            OrderTimeInForce::Fok => builder.add_kv("timeInForce", "FillOrKill"),
+            // good-till-time orders aren't supported, so they are canceled by bot on expiry
+            OrderTimeInForce::Gtc | OrderTimeInForce::Gtt(_) => {}
+        }
+
+        if header.reduce_only {
+            exec_instructions.push("ReduceOnly");
+        }
+        if !exec_instructions.is_empty() {
+            builder.add_kv("execInst", exec_instructions.join(","));
+        }
+
+        Ok(())
     }
 
     pub(super) fn get_order_id(
@@ -372,12 +400,27 @@ impl Bitmex {
         struct OrderId<'a> {
             #[serde(rename = "orderID")]
             order_id: &'a str,
+            #[serde(rename = "ordStatus")]
+            status: Option<&'a str>,
+            text: Option<String>,
         }
 
         let deserialized: OrderId = serde_json::from_str(&response.content)
             .map_err(|err| ExchangeError::parsing(format!("Unable to parse orderId: {err:?}")))?;
 
-        Ok(ExchangeOrderId::from(deserialized.order_id))
+        // Bitmex cancels maker only order instead of rejection if it would be executed as taker
+        if let (Some("Canceled"), Some(text)) = (deserialized.status, deserialized.text) {
+            if text.contains(PARTICIPATE_DO_NOT_INITIATE) {
+                return Err(ExchangeError::new(
+                    ExchangeErrorType::PostOnlyRejected,
+                    text,
+                    None,
+                ));
+            }
+        }
+
+        ExchangeOrderId::try_new(deserialized.order_id)
+            .map_err(|err| ExchangeError::parsing(format!("Unable to parse orderID: {err}")))
     }
 
     #[named]
@@ -425,7 +468,7 @@ impl Bitmex {
             Some(amount) => amount,
             None => dec!(0),
         };
-        OrderInfo::new(
+        let order_info = OrderInfo::new(
             self.get_unified_currency_pair(&specific.specific_currency_pair)
                 .expect("Expected known currency pair"),
             specific.exchange_order_id.clone(),
@@ -440,7 +483,75 @@ impl Bitmex {
             None,
             None,
             None,
-        )
+        );
+
+        match Bitmex::get_order_info_reject_reason(specific) {
+            Some(reject_reason) => order_info.with_reject_reason(reject_reason),
+            None => order_info,
+        }
+    }
+
+    fn get_order_info_reject_reason(specific: &BitmexOrderInfo) -> Option<OrderRejectReason> {
+        if specific.status != "Rejected" && specific.status != "Canceled" {
+            return None;
+        }
+
+        // ordRejReason is often empty and the reason is placed in text field
+        let message = [&specific.reject_reason, &specific.text]
+            .into_iter()
+            .flatten()
+            .find(|message| !message.is_empty())?;
+
+        // maker only order is canceled instead of rejection, other cancellations aren't rejections
+        if specific.status == "Canceled" && !message.contains(PARTICIPATE_DO_NOT_INITIATE) {
+            return None;
+        }
+
+        Some(Bitmex::get_order_reject_reason(message))
+    }
+
+    /// Map rejection message of Bitmex to unified reason
+    pub fn get_order_reject_reason(message: &str) -> OrderRejectReason {
+        match message {
+            msg if msg.contains("Account has insufficient Available Balance") => {
+                OrderRejectReason::InsufficientBalance
+            }
+            msg if msg.contains("Rate limit exceeded") => OrderRejectReason::RateLimited,
+            msg if msg.contains(PARTICIPATE_DO_NOT_INITIATE) => OrderRejectReason::PostOnlyRejected,
+            msg if msg.contains("Order value is below the minimum") => {
+                OrderRejectReason::MinNotional
+            }
+            msg if msg.contains("Invalid price")
+                || msg.contains("Price is outside")
+                || msg.contains("Order price is above")
+                || msg.contains("Order price is below") =>
+            {
+                OrderRejectReason::PriceOutOfBounds
+            }
+            msg => OrderRejectReason::Unknown(msg.to_owned()),
+        }
+    }
+
+    /// Parse Bitmex error payload like `{"error":{"message":"...","name":"..."}}` into unified rejection reason
+    pub fn parse_order_reject_reason(content: &str) -> Result<OrderRejectReason> {
+        #[derive(Deserialize)]
+        struct BitmexError {
+            error: Message,
+        }
+        #[derive(Deserialize)]
+        struct Message {
+            message: String,
+        }
+
+        let bitmex_error: BitmexError = serde_json::from_str(content)
+            .with_context(|| format!("Unable to parse Bitmex error: {content}"))?;
+
+        Ok(Bitmex::get_order_reject_reason(&bitmex_error.error.message))
+    }
+
+    /// Unified rejection reason of failed order request if Bitmex reported the error payload
+    pub(crate) fn get_error_reject_reason(error: &ExchangeError) -> Option<OrderRejectReason> {
+        Bitmex::parse_order_reject_reason(&error.message).ok()
     }
 
     pub(super) fn get_unified_currency_pair(
@@ -503,6 +614,45 @@ impl Bitmex {
         Ok(self.specific_order_info_to_unified(order))
     }
 
+    #[named]
+    pub(super) async fn request_orders_info(
+        &self,
+        orders: &[OrderRef],
+    ) -> Result<RestResponse, ExchangeError> {
+        let client_order_ids = orders
+            .iter()
+            .map(|order| order.client_order_id())
+            .collect_vec();
+
+        let mut builder = UriBuilder::from_path("/api/v1/order");
+        builder.add_kv(
+            "filter",
+            format_args!(
+                "{}{}{}",
+                encode!(r#"{"clOrdID": ["#),
+                client_order_ids.iter().join(encode!(",")),
+                encode!("]}"),
+            ),
+        );
+        // response is limited to 100 orders by default, so orders should be chunked by MAX_ORDERS_INFO_PER_REQUEST
+        builder.add_kv("count", orders.len());
+
+        let uri = builder.build_uri(self.hosts.rest_uri_host(), true);
+        let log_args = format!("orders {}", client_order_ids.iter().join(", "));
+
+        self.rest_client.get(uri, function_name!(), log_args).await
+    }
+
+    pub(super) fn parse_orders_info(&self, response: &RestResponse) -> Result<Vec<OrderInfo>> {
+        let specific_orders: Vec<BitmexOrderInfo> = serde_json::from_str(&response.content)
+            .context("Unable to parse response content for get_orders_info request")?;
+
+        Ok(specific_orders
+            .iter()
+            .map(|order| self.specific_order_info_to_unified(order))
+            .collect())
+    }
+
     #[named]
     pub(super) async fn do_cancel_order(
         &self,
@@ -565,13 +715,14 @@ impl Bitmex {
         &self,
         symbol: &Symbol,
         last_date_time: Option<DateTime>,
+        is_reverse: bool,
     ) -> Result<RestResponse, ExchangeError> {
         let mut builder = UriBuilder::from_path("/api/v1/execution/tradeHistory");
         builder.add_kv(
             "symbol",
             self.get_specific_currency_pair(symbol.currency_pair()),
         );
-        builder.add_kv("reverse", true);
+        builder.add_kv("reverse", is_reverse);
         builder.add_kv("count", 100);
         if let Some(date_time) = last_date_time {
             builder.add_kv("startTime", date_time.to_rfc3339());
@@ -674,15 +825,20 @@ impl Bitmex {
             .await
     }
 
+    /// Balances with the latest update time of margins
     pub(super) fn parse_get_balance(
         &self,
         response: &RestResponse,
-    ) -> Result<Vec<ExchangeBalance>> {
+    ) -> Result<(Vec<ExchangeBalance>, Option<DateTime>)> {
         let raw_balances: Vec<BitmexBalanceInfo> =
             serde_json::from_str(&response.content).context("Failed to parse balance")?;
+        let update_time = raw_balances
+            .iter()
+            .map(|balance_info| balance_info.timestamp)
+            .max();
 
         let currency_rates = self.currency_balance_rates.lock();
-        raw_balances
+        let balances = raw_balances
             .into_iter()
             .map(|balance_info| {
                 let currency_code = balance_info.currency.into();
@@ -695,7 +851,9 @@ impl Bitmex {
                     balance: balance_info.balance * balance_rate,
                 })
             })
-            .try_collect()
+            .try_collect()?;
+
+        Ok((balances, update_time))
     }
 
     pub(super) fn parse_active_positions<'a>(
@@ -796,6 +954,7 @@ impl ExchangeClientBuilder for BitmexBuilder {
                     order_was_completed_error_for_cancellation: true,
                     supports_already_cancelled_order: true,
                     supports_stop_loss_order: true,
+                    supports_good_till_time: false,
                 },
                 OrderTradeOption {
                     supports_trade_time: true,
@@ -831,6 +990,8 @@ impl ExchangeClientBuilder for BitmexBuilder {
 mod tests {
     use super::*;
     use bstr::ByteSlice;
+    use mmb_domain::order::snapshot::ClientOrderId;
+    use rstest::rstest;
 
     #[test]
     fn generate_signature() {
@@ -857,4 +1018,142 @@ mod tests {
             "e2f422547eecb5b3cb29ade2127e21b858b235b386bfa45e1c1756eb3383919f"
         );
     }
+
+    #[rstest]
+    #[case(
+        r#"{"error":{"message":"Account has insufficient Available Balance, 2002386 XBt required","name":"ValidationError"}}"#,
+        OrderRejectReason::InsufficientBalance
+    )]
+    #[case(
+        r#"{"error":{"message":"Invalid price tickSize","name":"HTTPError"}}"#,
+        OrderRejectReason::PriceOutOfBounds
+    )]
+    #[case(
+        r#"{"error":{"message":"Order value is below the minimum. Please increase the quantity.","name":"ValidationError"}}"#,
+        OrderRejectReason::MinNotional
+    )]
+    #[case(
+        r#"{"error":{"message":"Rate limit exceeded, retry in 1 seconds.","name":"RateLimitError"}}"#,
+        OrderRejectReason::RateLimited
+    )]
+    #[case(
+        r#"{"error":{"message":"Canceled: Order had execInst of ParticipateDoNotInitiate","name":"HTTPError"}}"#,
+        OrderRejectReason::PostOnlyRejected
+    )]
+    #[case(
+        r#"{"error":{"message":"Invalid orderQty","name":"HTTPError"}}"#,
+        OrderRejectReason::Unknown("Invalid orderQty".to_owned())
+    )]
+    fn map_error_reject_reason(#[case] content: &str, #[case] expected: OrderRejectReason) {
+        let reject_reason = Bitmex::parse_order_reject_reason(content).expect("in test");
+
+        assert_eq!(reject_reason, expected);
+    }
+
+    #[test]
+    fn reject_reason_of_rejected_order_info() {
+        let content = r#"{
+            "symbol":"XBTUSD",
+            "orderID":"3f4a93b5-3c39-4e88-8d4d-e1f6e55b3bd0",
+            "clOrdID":"1234",
+            "price":20000,
+            "avgPx":null,
+            "orderQty":100,
+            "cumQty":0,
+            "ordStatus":"Rejected",
+            "side":"Buy",
+            "ordRejReason":"",
+            "text":"Account has insufficient Available Balance, 2002386 XBt required"
+        }"#;
+        let order_info: BitmexOrderInfo = serde_json::from_str(content).expect("in test");
+
+        assert_eq!(
+            Bitmex::get_order_info_reject_reason(&order_info),
+            Some(OrderRejectReason::InsufficientBalance)
+        );
+    }
+
+    #[test]
+    fn reject_reason_of_canceled_maker_only_order_info() {
+        let content = r#"{
+            "symbol":"XBTUSD",
+            "orderID":"3f4a93b5-3c39-4e88-8d4d-e1f6e55b3bd0",
+            "clOrdID":"1234",
+            "price":20000,
+            "avgPx":null,
+            "orderQty":100,
+            "cumQty":0,
+            "ordStatus":"Canceled",
+            "side":"Buy",
+            "ordRejReason":"",
+            "text":"Canceled: Order had execInst of ParticipateDoNotInitiate"
+        }"#;
+        let order_info: BitmexOrderInfo = serde_json::from_str(content).expect("in test");
+
+        assert_eq!(
+            Bitmex::get_order_info_reject_reason(&order_info),
+            Some(OrderRejectReason::PostOnlyRejected)
+        );
+    }
+
+    #[test]
+    fn reject_reason_of_order_request_error() {
+        let exchange_error = ExchangeError::new(
+            ExchangeErrorType::SendError,
+            r#"{"error":{"message":"Account has insufficient Available Balance, 0.1 XBt required","name":"ValidationError"}}"#.to_owned(),
+            None,
+        );
+        assert_eq!(
+            Bitmex::get_error_reject_reason(&exchange_error),
+            Some(OrderRejectReason::InsufficientBalance)
+        );
+
+        let timeout_error = ExchangeError::unknown("Request timed out");
+        assert_eq!(Bitmex::get_error_reject_reason(&timeout_error), None);
+    }
+
+    #[test]
+    fn maker_only_order_params() {
+        let header = OrderHeader::with_options(
+            ClientOrderId::unique_id(),
+            "Bitmex_0".parse().expect("in test"),
+            CurrencyPair::from_codes("xbt".into(), "usd".into()),
+            OrderSide::Buy,
+            dec!(100),
+            OrderOptions::maker_only(dec!(20000)),
+            None,
+            None,
+            "test".to_owned(),
+        );
+
+        let mut builder = UriBuilder::from_path("/api/v1/order");
+        Bitmex::add_order_params(&mut builder, &header).expect("in test");
+
+        let query = builder.query();
+        assert!(query.contains_str("ordType=Limit"));
+        assert!(query.contains_str("execInst=ParticipateDoNotInitiate"));
+    }
+
+    #[test]
+    fn immediate_or_cancel_order_params() {
+        let header = OrderHeader::with_options(
+            ClientOrderId::unique_id(),
+            "Bitmex_0".parse().expect("in test"),
+            CurrencyPair::from_codes("xbt".into(), "usd".into()),
+            OrderSide::Buy,
+            dec!(100),
+            OrderOptions::limit(dec!(20000)),
+            None,
+            None,
+            "test".to_owned(),
+        )
+        .with_time_in_force(OrderTimeInForce::Ioc);
+
+        let mut builder = UriBuilder::from_path("/api/v1/order");
+        Bitmex::add_order_params(&mut builder, &header).expect("in test");
+
+        let query = builder.query();
+        assert!(query.contains_str("ordType=Limit"));
+        assert!(query.contains_str("timeInForce=ImmediateOrCancel"));
+    }
 }
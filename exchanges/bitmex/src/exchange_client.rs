@@ -1,4 +1,4 @@
-use crate::bitmex::Bitmex;
+use crate::bitmex::{Bitmex, MAX_ORDERS_INFO_PER_REQUEST};
 use anyhow::{bail, Result};
 use async_trait::async_trait;
 use itertools::Itertools;
@@ -7,7 +7,7 @@ use mmb_core::exchanges::general::order::cancel::CancelOrderResult;
 use mmb_core::exchanges::general::order::create::CreateOrderResult;
 use mmb_core::exchanges::general::order::get_order_trades::OrderTrade;
 use mmb_core::exchanges::traits::{ExchangeClient, ExchangeError};
-use mmb_domain::events::{EventSourceType, ExchangeBalancesAndPositions};
+use mmb_domain::events::{EventSourceType, ExchangeBalancesAndPositions, TradeId};
 use mmb_domain::exchanges::symbol::Symbol;
 use mmb_domain::market::CurrencyPair;
 use mmb_domain::order::pool::OrderRef;
@@ -24,7 +24,13 @@ impl ExchangeClient for Bitmex {
                 Ok(order_id) => CreateOrderResult::succeed(&order_id, EventSourceType::Rest),
                 Err(error) => CreateOrderResult::failed(error, EventSourceType::Rest),
             },
-            Err(err) => CreateOrderResult::failed(err, EventSourceType::Rest),
+            Err(err) => {
+                let err = match Bitmex::get_error_reject_reason(&err) {
+                    Some(reject_reason) => err.with_reject_reason(reject_reason),
+                    None => err,
+                };
+                CreateOrderResult::failed(err, EventSourceType::Rest)
+            }
         }
     }
 
@@ -68,10 +74,39 @@ impl ExchangeClient for Bitmex {
             Ok(request_outcome) => self.parse_order_info(&request_outcome).map_err(|err| {
                 ExchangeError::parsing(format!("Unable to parse order info: {err:?}"))
             }),
-            Err(error) => Err(ExchangeError::unknown(
-                format!("Failed to get order info: {:?}", error).as_str(),
-            )),
+            Err(error) => {
+                let mut order_info_error = ExchangeError::unknown(
+                    format!("Failed to get order info: {:?}", error).as_str(),
+                );
+                order_info_error.reject_reason = Bitmex::get_error_reject_reason(&error);
+                Err(order_info_error)
+            }
+        }
+    }
+
+    fn supports_get_orders_info(&self) -> bool {
+        true
+    }
+
+    async fn get_orders_info(&self, orders: &[OrderRef]) -> Result<Vec<OrderInfo>, ExchangeError> {
+        let mut orders_info = Vec::with_capacity(orders.len());
+        for orders_chunk in orders.chunks(MAX_ORDERS_INFO_PER_REQUEST) {
+            let request_outcome =
+                self.request_orders_info(orders_chunk)
+                    .await
+                    .map_err(|error| {
+                        ExchangeError::unknown(
+                            format!("Failed to get orders info: {error:?}").as_str(),
+                        )
+                    })?;
+
+            let chunk_orders_info = self.parse_orders_info(&request_outcome).map_err(|err| {
+                ExchangeError::parsing(format!("Unable to parse orders info: {err:?}"))
+            })?;
+            orders_info.extend(chunk_orders_info);
         }
+
+        Ok(orders_info)
     }
 
     async fn close_position(
@@ -95,20 +130,24 @@ impl ExchangeClient for Bitmex {
             true => {
                 let (balance_response, position_response) =
                     tokio::join!(self.request_get_balance(), self.request_get_position());
+                let (balances, event_time) = self.parse_get_balance(&balance_response?)?;
                 ExchangeBalancesAndPositions {
-                    balances: self.parse_get_balance(&balance_response?)?,
+                    balances,
                     positions: Some(
                         self.parse_active_positions(&position_response?)?
                             .map(|position| Ok::<_, anyhow::Error>(position?.derivative))
                             .try_collect()?,
                     ),
+                    event_time,
                 }
             }
             false => {
                 let balance_response = self.request_get_balance().await?;
+                let (balances, event_time) = self.parse_get_balance(&balance_response)?;
                 ExchangeBalancesAndPositions {
-                    balances: self.parse_get_balance(&balance_response)?,
+                    balances,
                     positions: None,
+                    event_time,
                 }
             }
         })
@@ -119,7 +158,30 @@ impl ExchangeClient for Bitmex {
         symbol: &Symbol,
         last_date_time: Option<DateTime>,
     ) -> RequestResult<Vec<OrderTrade>> {
-        match self.request_my_trades(symbol, last_date_time).await {
+        match self.request_my_trades(symbol, last_date_time, true).await {
+            Ok(response) => match self.parse_my_trades(&response) {
+                Ok(data) => RequestResult::Success(data),
+                Err(err) => RequestResult::Error(ExchangeError::parsing(format!(
+                    "Unable to parse trades: {err:?}"
+                ))),
+            },
+            Err(err) => RequestResult::Error(ExchangeError::unknown(
+                format!("Failed to get trades: {err:?}").as_str(),
+            )),
+        }
+    }
+
+    /// Trade ids on Bitmex aren't sequential, so trades are always requested by time
+    async fn get_my_trades_from(
+        &self,
+        symbol: &Symbol,
+        from_datetime: DateTime,
+        _from_trade_id: Option<&TradeId>,
+    ) -> RequestResult<Vec<OrderTrade>> {
+        match self
+            .request_my_trades(symbol, Some(from_datetime), false)
+            .await
+        {
             Ok(response) => match self.parse_my_trades(&response) {
                 Ok(data) => RequestResult::Success(data),
                 Err(err) => RequestResult::Error(ExchangeError::parsing(format!(
@@ -272,6 +272,9 @@ pub(crate) struct BitmexOrderInfo<'a> {
     #[serde(rename = "ordStatus")]
     pub(crate) status: &'a str,
     pub(crate) side: OrderSide,
+    #[serde(rename = "ordRejReason")]
+    pub(crate) reject_reason: Option<String>,
+    pub(crate) text: Option<String>,
 }
 
 /// Bitmex Order Book description
@@ -462,6 +465,8 @@ pub(crate) struct BitmexBalanceInfo<'a> {
     pub(crate) currency: &'a str,
     #[serde(rename = "availableMargin")]
     pub(crate) balance: Decimal,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub(crate) timestamp: DateTime,
 }
 
 /// Bitmex position response description
@@ -168,6 +168,7 @@ impl ExchangeClient for InteractiveBrokers {
         Ok(ExchangeBalancesAndPositions {
             balances: self.get_balance_inner().await?,
             positions,
+            event_time: None,
         })
     }
 
@@ -151,6 +151,7 @@ impl ExchangeClient for Serum {
         Ok(ExchangeBalancesAndPositions {
             balances,
             positions: None,
+            event_time: None,
         })
     }
 
@@ -560,6 +560,7 @@ impl Serum {
                         commission_currency_code: None,
                         commission_rate: None,
                         commission_amount: None,
+                        reject_reason: None,
                         extension_data: Some(Box::new(SerumExtensionData {
                             owner: Some(market_info.owner_address),
                             actual_status: OrderStatus::Created,